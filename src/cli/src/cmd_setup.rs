@@ -1,4 +1,4 @@
-use clap::{arg, Arg, Command};
+use clap::{arg, Arg, ArgGroup, Command};
 
 pub const INIT: &str = "init";
 pub const CONFIG: &str = "config";
@@ -13,13 +13,20 @@ pub const RM: &str = "rm";
 pub const COMMIT: &str = "commit";
 pub const RESTORE: &str = "restore";
 pub const BRANCH: &str = "branch";
+pub const TAG: &str = "tag";
 pub const CHECKOUT: &str = "checkout";
 pub const MERGE: &str = "merge";
+pub const REVERT: &str = "revert";
+pub const RESET: &str = "reset";
+pub const CHERRY_PICK: &str = "cherry-pick";
+pub const STASH: &str = "stash";
 pub const CLONE: &str = "clone";
 pub const PUSH: &str = "push";
 pub const PULL: &str = "pull";
+pub const FETCH: &str = "fetch";
 pub const DIFF: &str = "diff";
 pub const MIGRATE: &str = "migrate";
+pub const GC: &str = "gc";
 pub const KVDB_INSPECT: &str = "kvdb-inspect";
 pub const READ_LINES: &str = "read-lines";
 
@@ -121,14 +128,59 @@ pub fn status() -> Command<'static> {
 }
 
 pub fn log() -> Command<'static> {
-    Command::new(LOG).about("See log of commits")
+    Command::new(LOG)
+        .about("See log of commits")
+        .arg(arg!([REVISION] "The branch, remote-tracking ref (ie: origin/main) or commit id to start from. Defaults to HEAD."))
+        .arg(
+            Arg::new("path")
+                .long("path")
+                .help("Only show the commits that changed this file, or anything under this directory.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("author")
+                .long("author")
+                .help("Only show the commits whose author name or email contains this.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("since")
+                .long("since")
+                .help("Only show the commits made on or after this date. Ex 2023-01-31 or 2023-01-31T12:00:00Z")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("until")
+                .long("until")
+                .help("Only show the commits made on or before this date. Ex 2023-01-31 or 2023-01-31T12:00:00Z")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("grep")
+                .long("grep")
+                .help("Only show the commits whose message contains this.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("max-count")
+                .long("max-count")
+                .short('n')
+                .help("Show at most this many commits.")
+                .takes_value(true),
+        )
 }
 
 pub fn df() -> Command<'static> {
     Command::new(DF)
         .about("View and transform data frames. Supported types: csv, tsv, ndjson, jsonl, parquet.")
-        .arg(arg!(<DF_SPEC> ... "The DataFrame you want to process. If in the schema subcommand the schema ref."))
+        .arg(arg!([DF_SPEC] ... "The DataFrame you want to process. If in the schema subcommand the schema ref."))
         .arg_required_else_help(true)
+        .arg(
+            Arg::new("sql")
+                .long("sql")
+                .help("Run a SQL query over versioned files, referenced as 'path@commit_or_branch'. Ex \"SELECT * FROM 'train.csv@main' AS train JOIN 'labels.csv@main' AS labels ON train.id = labels.id\"")
+                .takes_value(true),
+        )
         .arg(
             Arg::new("output")
                 .long("output")
@@ -147,7 +199,7 @@ pub fn df() -> Command<'static> {
             Arg::new("filter")
                 .long("filter")
                 .short('f')
-                .help("An filter the row data based on an expression. Supported Ops (=, !=, >, <, <= , >=) Supported dtypes (str,int,float)")
+                .help("Filter the row data based on an expression, for example \"label in [cat, dog] && (score > 0.5 || note is null)\". Supported Ops (==, !=, >, <, <=, >=, in, contains, starts_with, =~, is null, is not null) combined with && and || and grouped with parentheses")
                 .takes_value(true),
         )
         .arg(
@@ -267,6 +319,17 @@ pub fn schemas() -> Command<'static> {
                 .arg(Arg::new("HASH").help("Hash of the schema you want to name."))
                 .arg(Arg::new("NAME").help("Name of the schema.")),
         )
+        .subcommand(
+            Command::new("create_index")
+                .about("Index a field so equality filters on it look up rows instead of scanning the file")
+                .arg(arg!(<SCHEMA_REF> "Name or the hash of the schema."))
+                .arg(arg!(<FIELD> "Name of the field to index, must be a str or integer field.")),
+        )
+        .subcommand(
+            Command::new("list_indices")
+                .about("List the indexed fields of a schema")
+                .arg(arg!(<SCHEMA_REF> "Name or the hash of the schema.")),
+        )
         .subcommand(df())
 }
 
@@ -274,6 +337,13 @@ pub fn add() -> Command<'static> {
     Command::new(ADD)
         .about("Adds the specified files or directories")
         .arg(Arg::new("files").required(true).min_values(1))
+        .arg(
+            Arg::new("force")
+                .long("force")
+                .short('f')
+                .help("Add the files even if they match a pattern in an .oxenignore file")
+                .takes_value(false),
+        )
 }
 
 pub fn rm() -> Command<'static> {
@@ -342,10 +412,40 @@ pub fn branch() -> Command<'static> {
         )
 }
 
+pub fn tag() -> Command<'static> {
+    Command::new(TAG)
+        .about("Create, list and delete tags, names for a commit that never move")
+        .arg(Arg::new("name").help("Name of the tag to create, like v1.3-train"))
+        .arg(Arg::new("commit").help("Commit id or branch to tag. Defaults to HEAD."))
+        .arg(
+            Arg::new("message")
+                .long("message")
+                .short('m')
+                .help("What the tag is for")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("delete")
+                .long("delete")
+                .short('d')
+                .help("Remove the local tag, or the tag on --remote if you are an admin of it")
+                .conflicts_with("name")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("remote")
+                .long("remote")
+                .short('r')
+                .help("Delete the tag on this remote instead of the local one")
+                .requires("delete")
+                .takes_value(true),
+        )
+}
+
 pub fn checkout() -> Command<'static> {
     Command::new(CHECKOUT)
         .about("Checks out a branches in the repository")
-        .arg(Arg::new("name").help("Name of the branch or commit id to checkout"))
+        .arg(Arg::new("name").help("Name of the branch, tag or commit id to checkout"))
         .arg(
             Arg::new("create")
                 .long("create")
@@ -367,10 +467,114 @@ pub fn merge() -> Command<'static> {
         .about("Merges a branch into the current checked out branch.")
         .arg_required_else_help(true)
         .arg(arg!(<BRANCH> "The name of the branch you want to merge in."))
+        .arg(
+            Arg::new("primary-key")
+                .long("primary-key")
+                .short('k')
+                .help("Column used to match up rows when merging tabular data files.")
+                .takes_value(true),
+        )
+}
+
+pub fn revert() -> Command<'static> {
+    Command::new(REVERT)
+        .about("Creates a new commit that undoes the changes made in a commit.")
+        .arg_required_else_help(true)
+        .arg(arg!(<COMMIT> "The commit id, branch or tag to revert."))
+}
+
+pub fn reset() -> Command<'static> {
+    Command::new(RESET)
+        .about("Moves the current branch to a commit, and updates the staged files and working directory to match.")
+        .arg_required_else_help(true)
+        .arg(arg!(<REF> "The commit id, branch or tag to reset to."))
+        .arg(
+            Arg::new("soft")
+                .long("soft")
+                .help("Keep the working directory, and stage the changes since the commit.")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::new("mixed")
+                .long("mixed")
+                .help("Keep the working directory, but unstage everything. This is the default.")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::new("hard")
+                .long("hard")
+                .help("Unstage everything and overwrite the working directory with the commit. Local changes are lost.")
+                .takes_value(false),
+        )
+        .group(ArgGroup::new("mode").args(&["soft", "mixed", "hard"]))
+}
+
+pub fn cherry_pick() -> Command<'static> {
+    Command::new(CHERRY_PICK)
+        .about("Applies the changes made in a commit onto the current branch.")
+        .arg_required_else_help(true)
+        .arg(
+            Arg::new("COMMIT")
+                .help("The commit id, branch or tag to cherry-pick.")
+                .required_unless_present_any(["continue", "abort"]),
+        )
+        .arg(
+            Arg::new("continue")
+                .long("continue")
+                .help("Commit the cherry-pick once the conflicts are fixed and added.")
+                .conflicts_with_all(&["COMMIT", "abort"])
+                .takes_value(false),
+        )
+        .arg(
+            Arg::new("abort")
+                .long("abort")
+                .help("Give up on the cherry-pick and go back to where you were.")
+                .conflicts_with("COMMIT")
+                .takes_value(false),
+        )
+}
+
+fn stash_message() -> Arg<'static> {
+    Arg::new("message")
+        .long("message")
+        .short('m')
+        .help("Describe the stash, defaults to the branch and commit it was made on.")
+        .takes_value(true)
+}
+
+fn stash_index() -> Arg<'static> {
+    arg!([STASH] "The stash to use, as an index or stash@{n}. Defaults to the latest stash.")
+}
+
+pub fn stash() -> Command<'static> {
+    Command::new(STASH)
+        .about("Put aside the uncommitted changes to get a clean working directory, and bring them back later")
+        .subcommand(
+            Command::new("push")
+                .about("Stash the staged, modified, removed and untracked files. The default.")
+                .arg(stash_message()),
+        )
+        .subcommand(Command::new("list").about("List the stashes, latest first"))
+        .subcommand(
+            Command::new("pop")
+                .about("Apply a stash and drop it, unless there are conflicts")
+                .arg(stash_index()),
+        )
+        .subcommand(
+            Command::new("apply")
+                .about("Apply a stash and keep it")
+                .arg(stash_index()),
+        )
+        .subcommand(
+            Command::new("drop")
+                .about("Throw away a stash")
+                .arg(stash_index()),
+        )
+        .arg(stash_message())
 }
 
 pub fn clone() -> Command<'static> {
-    Command::new(CLONE)
+    with_transfer_args(Command::new(CLONE))
         .about("Clone a repository by its URL")
         .arg_required_else_help(true)
         .arg(arg!(<URL> "URL of the repository you want to clone"))
@@ -380,6 +584,38 @@ pub fn clone() -> Command<'static> {
                 .help("A shallow clone doesn't actually clone the data files, useful if you want to pull a specific branch instead.")
                 .takes_value(false),
         )
+        .arg(
+            Arg::new("include")
+                .long("include")
+                .help("Only clone the files matching these globs, ie: 'images/val/' 'annotations/val.csv'. Later pulls stay limited to them.")
+                .takes_value(true)
+                .multiple_values(true)
+                .multiple_occurrences(true),
+        )
+        .arg(
+            Arg::new("exclude")
+                .long("exclude")
+                .help("Do not clone the files matching these globs. Later pulls skip them as well.")
+                .takes_value(true)
+                .multiple_values(true)
+                .multiple_occurrences(true),
+        )
+}
+
+fn with_transfer_args(command: Command<'static>) -> Command<'static> {
+    command
+        .arg(
+            Arg::new("limit-rate")
+                .long("limit-rate")
+                .help("Cap the bandwidth of all the parallel uploads and downloads, ie: 20MB/s")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("report")
+                .long("report")
+                .help("Write a JSON report of the bytes, files, throughput and retries of the transfer to this file")
+                .takes_value(true),
+        )
 }
 
 pub fn inspect_kv_db() -> Command<'static> {
@@ -390,9 +626,9 @@ pub fn inspect_kv_db() -> Command<'static> {
 }
 
 pub fn push() -> Command<'static> {
-    Command::new(PUSH)
+    with_transfer_args(Command::new(PUSH))
         .about("Push the the files to the remote branch")
-        .arg(arg!(<REMOTE> "Remote you want to pull from"))
+        .arg(arg!([REMOTE] "Remote you want to push to").required_unless_present("all-remotes"))
         .arg(
             Arg::new("delete")
                 .long("delete")
@@ -400,21 +636,62 @@ pub fn push() -> Command<'static> {
                 .help("Remove the remote branch")
                 .takes_value(false),
         )
-        .arg(arg!(<BRANCH> "Branch name to pull"))
+        .arg(
+            Arg::new("force")
+                .long("force")
+                .short('f')
+                .help("Overwrite the remote branch even if it has commits you do not have")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::new("all-remotes")
+                .long("all-remotes")
+                .help("Push the branch to every remote, the only argument is then the branch")
+                .conflicts_with("delete")
+                .takes_value(false),
+        )
+        .arg(arg!([BRANCH] "Branch name to push").required_unless_present("all-remotes"))
 }
 
 pub fn pull() -> Command<'static> {
-    Command::new(PULL)
+    with_transfer_args(Command::new(PULL))
         .about("Pull the files up from a remote branch")
         .arg(arg!(<REMOTE> "Remote you want to pull from"))
         .arg(arg!(<BRANCH> "Branch name to pull"))
+        .arg(
+            Arg::new("paths")
+                .long("paths")
+                .help("Add these paths or globs to the files of a sparse clone, and pull them.")
+                .takes_value(true)
+                .multiple_values(true),
+        )
+}
+
+pub fn fetch() -> Command<'static> {
+    Command::new(FETCH)
+        .about("Download the commits from a remote without pulling any data or changing the working directory")
+        .arg(arg!([REMOTE] "Remote you want to fetch from. Defaults to origin."))
+        .arg(arg!([BRANCH] "Branch name to fetch. Defaults to all branches."))
 }
 
 pub fn diff() -> Command<'static> {
     Command::new(DIFF)
-        .about("Compare file from a commit history")
+        .about("Compare file from a commit history, or everything that changed between two commits with `oxen diff <base>..<head> [path]`")
         .arg(Arg::new("FILE_OR_COMMIT_ID").required(true))
         .arg(Arg::new("PATH").required(false))
+        .arg(
+            Arg::new("primary-key")
+                .long("primary-key")
+                .short('k')
+                .help("Column used to match up rows in tabular data files, so edited rows show up as modified.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("ignore-cols")
+                .long("ignore-cols")
+                .help("A comma separated set of columns to leave out of tabular diffs. Ex timestamp,annotator")
+                .takes_value(true),
+        )
 }
 
 pub fn migrate() -> Command<'static> {
@@ -430,6 +707,24 @@ pub fn migrate() -> Command<'static> {
         )
 }
 
+pub fn gc() -> Command<'static> {
+    Command::new(GC)
+        .about("Remove the commits and version files that no branch or HEAD can reach")
+        .arg(
+            Arg::new("dry-run")
+                .long("dry-run")
+                .short('n')
+                .help("Report what would be removed and the bytes reclaimed without removing anything")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::new("pack")
+                .long("pack")
+                .help("Fold the history dbs of old commits into a shared content addressed store")
+                .takes_value(false),
+        )
+}
+
 pub fn read_lines() -> Command<'static> {
     Command::new("read-lines")
         .about("Read a set of lines from a file without loading it all into memory")
@@ -2,15 +2,18 @@ use liboxen::api;
 use liboxen::command;
 use liboxen::config::UserConfig;
 use liboxen::df::df_opts::DFOpts;
+use liboxen::df::DFDiffOpts;
 use liboxen::error;
 use liboxen::error::OxenError;
 use liboxen::model::schema;
-use liboxen::model::LocalRepository;
-use liboxen::opts::RestoreOpts;
+use liboxen::model::{LocalRepository, SparseSpec};
+use liboxen::opts::{LogOpts, ResetMode, RestoreOpts, TransferOpts};
 use liboxen::util;
 
+use bytesize::ByteSize;
 use colored::Colorize;
 use std::env;
+use std::future::Future;
 use std::path::{Path, PathBuf};
 use time::format_description;
 
@@ -39,9 +42,35 @@ pub async fn init(path: &str) -> Result<(), OxenError> {
     Ok(())
 }
 
-pub async fn clone(url: &str, shallow: bool) -> Result<(), OxenError> {
+/// Runs a push, pull or clone within the rate limit, then prints and saves what it transferred
+async fn transfer<T, F>(opts: &TransferOpts, transfer: F) -> Result<T, OxenError>
+where
+    F: Future<Output = Result<T, OxenError>>,
+{
+    api::remote::transfer::limit_rate(opts.limit_rate);
+    api::remote::transfer::reset_stats();
+    let result = transfer.await;
+
+    let report = api::remote::transfer::report();
+    println!("{report}");
+    if let Some(path) = &opts.report_path {
+        report.save(path)?;
+    }
+    result
+}
+
+pub async fn clone(
+    url: &str,
+    shallow: bool,
+    spec: SparseSpec,
+    opts: &TransferOpts,
+) -> Result<(), OxenError> {
     let dst = std::env::current_dir()?;
-    command::clone(url, &dst, shallow).await?;
+    if spec.is_empty() {
+        transfer(opts, command::clone(url, &dst, shallow)).await?;
+    } else {
+        transfer(opts, command::clone_sparse(url, &dst, shallow, spec)).await?;
+    }
     Ok(())
 }
 
@@ -131,12 +160,16 @@ pub fn set_default_host(host: &str) -> Result<(), OxenError> {
     Ok(())
 }
 
-pub fn add(paths: Vec<PathBuf>) -> Result<(), OxenError> {
+pub fn add(paths: Vec<PathBuf>, force: bool) -> Result<(), OxenError> {
     let repo_dir = env::current_dir().unwrap();
     let repository = LocalRepository::from_dir(&repo_dir)?;
 
     for path in paths {
-        command::add(&repository, path)?;
+        if force {
+            command::add_force(&repository, path)?;
+        } else {
+            command::add(&repository, path)?;
+        }
     }
 
     Ok(())
@@ -162,37 +195,207 @@ pub fn restore(opts: RestoreOpts) -> Result<(), OxenError> {
     Ok(())
 }
 
-pub async fn push(remote: &str, branch: &str) -> Result<(), OxenError> {
+pub async fn push(
+    remote: &str,
+    branch: &str,
+    force: bool,
+    opts: &TransferOpts,
+) -> Result<(), OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let repository = LocalRepository::from_dir(&repo_dir)?;
+
+    if force {
+        transfer(
+            opts,
+            command::force_push_remote_branch(&repository, remote, branch),
+        )
+        .await?;
+    } else {
+        transfer(
+            opts,
+            command::push_remote_branch(&repository, remote, branch),
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+pub async fn push_all_remotes(
+    branch: &str,
+    force: bool,
+    opts: &TransferOpts,
+) -> Result<(), OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let repository = LocalRepository::from_dir(&repo_dir)?;
+
+    if force {
+        transfer(opts, command::force_push_all_remotes(&repository, branch)).await?;
+    } else {
+        transfer(opts, command::push_all_remotes(&repository, branch)).await?;
+    }
+    Ok(())
+}
+
+pub async fn pull(remote: &str, branch: &str, opts: &TransferOpts) -> Result<(), OxenError> {
     let repo_dir = env::current_dir().unwrap();
     let repository = LocalRepository::from_dir(&repo_dir)?;
 
-    command::push_remote_branch(&repository, remote, branch).await?;
+    transfer(
+        opts,
+        command::pull_remote_branch(&repository, remote, branch),
+    )
+    .await?;
+    Ok(())
+}
+
+pub async fn pull_paths(
+    remote: &str,
+    branch: &str,
+    paths: &[String],
+    opts: &TransferOpts,
+) -> Result<(), OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let mut repository = LocalRepository::from_dir(&repo_dir)?;
+
+    transfer(
+        opts,
+        command::pull_paths(&mut repository, remote, branch, paths),
+    )
+    .await?;
     Ok(())
 }
 
-pub async fn pull(remote: &str, branch: &str) -> Result<(), OxenError> {
+pub async fn fetch(remote: &str, branch: Option<&str>) -> Result<(), OxenError> {
     let repo_dir = env::current_dir().unwrap();
     let repository = LocalRepository::from_dir(&repo_dir)?;
 
-    command::pull_remote_branch(&repository, remote, branch).await?;
+    for branch in command::fetch(&repository, remote, branch).await? {
+        println!("{} -> {}", branch.name, branch.commit_id);
+    }
     Ok(())
 }
 
-pub fn diff(commit_id: Option<&str>, path: &str) -> Result<(), OxenError> {
+pub fn diff(commit_id: Option<&str>, path: &str, opts: &DFDiffOpts) -> Result<(), OxenError> {
     let repo_dir = env::current_dir().unwrap();
     let repository = LocalRepository::from_dir(&repo_dir)?;
     let path = Path::new(path);
 
-    let result = command::diff(&repository, commit_id, path)?;
+    let result = command::diff(&repository, commit_id, path, opts)?;
     println!("{result}");
     Ok(())
 }
 
-pub fn merge(branch: &str) -> Result<(), OxenError> {
+pub fn diff_commits(range: &str, path: Option<&str>, opts: &DFDiffOpts) -> Result<(), OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let repository = LocalRepository::from_dir(&repo_dir)?;
+    let (base, head) = util::resource::parse_commit_range(range)?;
+
+    // Paths are relative to the repo root, so make them relative if we are in a sub directory
+    let path = match path {
+        Some(path) => Some(util::fs::path_relative_to_dir(
+            &repo_dir.join(path),
+            &repository.path,
+        )?),
+        None => None,
+    };
+
+    let diff = command::diff_commits(&repository, base, head, path.as_deref(), opts)?;
+    diff.print_stdout();
+    Ok(())
+}
+
+pub fn merge(branch: &str, primary_key: Option<&str>) -> Result<(), OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let repository = LocalRepository::from_dir(&repo_dir)?;
+
+    match primary_key {
+        Some(primary_key) => command::merge_with_primary_key(&repository, branch, primary_key)?,
+        None => command::merge(&repository, branch)?,
+    };
+    Ok(())
+}
+
+pub fn revert(commit: &str) -> Result<(), OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let repository = LocalRepository::from_dir(&repo_dir)?;
+
+    command::revert(&repository, commit)?;
+    Ok(())
+}
+
+pub fn reset(commit: &str, mode: ResetMode) -> Result<(), OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let repository = LocalRepository::from_dir(&repo_dir)?;
+
+    let commit = command::reset(&repository, commit, mode)?;
+    println!("HEAD is now at {} {}", commit.id, commit.message);
+    Ok(())
+}
+
+pub fn cherry_pick(commit: &str) -> Result<(), OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let repository = LocalRepository::from_dir(&repo_dir)?;
+
+    command::cherry_pick(&repository, commit)?;
+    Ok(())
+}
+
+pub fn cherry_pick_continue() -> Result<(), OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let repository = LocalRepository::from_dir(&repo_dir)?;
+
+    command::cherry_pick_continue(&repository)?;
+    Ok(())
+}
+
+pub fn cherry_pick_abort() -> Result<(), OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let repository = LocalRepository::from_dir(&repo_dir)?;
+
+    command::cherry_pick_abort(&repository)?;
+    println!("Cherry-pick aborted");
+    Ok(())
+}
+
+pub fn stash_push(message: Option<&str>) -> Result<(), OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let repository = LocalRepository::from_dir(&repo_dir)?;
+
+    command::stash_push(&repository, message)?;
+    Ok(())
+}
+
+pub fn stash_list() -> Result<(), OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let repository = LocalRepository::from_dir(&repo_dir)?;
+
+    for (i, stash) in command::stash_list(&repository)?.iter().enumerate() {
+        println!("stash@{{{i}}}: {}", stash.message);
+    }
+    Ok(())
+}
+
+pub fn stash_pop(index: usize) -> Result<(), OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let repository = LocalRepository::from_dir(&repo_dir)?;
+
+    command::stash_pop(&repository, index)?;
+    Ok(())
+}
+
+pub fn stash_apply(index: usize) -> Result<(), OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let repository = LocalRepository::from_dir(&repo_dir)?;
+
+    command::stash_apply(&repository, index)?;
+    Ok(())
+}
+
+pub fn stash_drop(index: usize) -> Result<(), OxenError> {
     let repo_dir = env::current_dir().unwrap();
     let repository = LocalRepository::from_dir(&repo_dir)?;
 
-    command::merge(&repository, branch)?;
+    command::stash_drop(&repository, index)?;
     Ok(())
 }
 
@@ -223,7 +426,7 @@ pub fn commit(args: Vec<&std::ffi::OsStr>) -> Result<(), OxenError> {
     }
 }
 
-pub fn log_commits() -> Result<(), OxenError> {
+pub fn log_commits(opts: &LogOpts) -> Result<(), OxenError> {
     let repo_dir = env::current_dir().unwrap();
     let repository = LocalRepository::from_dir(&repo_dir)?;
 
@@ -232,7 +435,8 @@ pub fn log_commits() -> Result<(), OxenError> {
         "[weekday], [day] [month repr:long] [year] [hour]:[minute]:[second] [offset_hour sign:mandatory]",
     ).unwrap();
 
-    for commit in command::log(&repository)? {
+    let commits = command::log_with_opts(&repository, opts)?;
+    for commit in commits {
         let commit_id_str = format!("commit {}", commit.id).yellow();
         println!("{commit_id_str}\n");
         println!("Author: {}", commit.author);
@@ -274,6 +478,13 @@ pub fn df<P: AsRef<Path>>(input: P, opts: DFOpts) -> Result<(), OxenError> {
     Ok(())
 }
 
+pub fn df_sql(sql: &str, opts: DFOpts) -> Result<(), OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let repository = LocalRepository::from_dir(&repo_dir)?;
+    command::df_sql(&repository, sql, opts)?;
+    Ok(())
+}
+
 pub fn df_schema<P: AsRef<Path>>(input: P, flatten: bool) -> Result<(), OxenError> {
     let result = command::df_schema(input, flatten)?;
     println!("{result}");
@@ -318,6 +529,52 @@ pub fn schema_name(schema_ref: &str, val: &str) -> Result<(), OxenError> {
     Ok(())
 }
 
+pub fn schema_create_index(schema_ref: &str, field: &str) -> Result<(), OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let repository = LocalRepository::from_dir(&repo_dir)?;
+
+    let field = command::schema_create_index(&repository, schema_ref, field)?;
+    println!("Indexed field {}", field.name);
+
+    Ok(())
+}
+
+pub fn gc(dry_run: bool) -> Result<(), OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let repository = LocalRepository::from_dir(&repo_dir)?;
+
+    let stats = command::gc(&repository, dry_run)?;
+    let verb = if dry_run { "Would remove" } else { "Removed" };
+    println!(
+        "{} {} commits, {} version dirs, {} chunks and {} pack objects, reclaiming {}",
+        verb,
+        stats.num_commits,
+        stats.num_version_dirs,
+        stats.num_chunks,
+        stats.num_pack_objects,
+        ByteSize::b(stats.num_bytes)
+    );
+
+    Ok(())
+}
+
+pub fn gc_pack() -> Result<(), OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let repository = LocalRepository::from_dir(&repo_dir)?;
+
+    let stats = command::gc_pack(&repository)?;
+    println!(
+        "Packed {} dbs from {} commits into {} new objects, {} -> {}",
+        stats.num_dbs,
+        stats.num_commits,
+        stats.num_new_objects,
+        ByteSize::b(stats.bytes_before),
+        ByteSize::b(stats.bytes_after)
+    );
+
+    Ok(())
+}
+
 pub fn schema_list_indices(schema_ref: &str) -> Result<(), OxenError> {
     let repo_dir = env::current_dir().unwrap();
     let repository = LocalRepository::from_dir(&repo_dir)?;
@@ -392,6 +649,42 @@ pub fn force_delete_branch(name: &str) -> Result<(), OxenError> {
     Ok(())
 }
 
+pub fn create_tag(name: &str, commit: Option<&str>, message: &str) -> Result<(), OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let repository = LocalRepository::from_dir(&repo_dir)?;
+    let tag = command::create_tag(&repository, name, commit, message)?;
+    println!("Tagged {} -> {}", tag.name, tag.commit_id);
+    Ok(())
+}
+
+pub fn list_tags() -> Result<(), OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let repository = LocalRepository::from_dir(&repo_dir)?;
+    for tag in command::list_tags(&repository)? {
+        println!("{}\t{}\t{}", tag.name, tag.commit_id, tag.message);
+    }
+    Ok(())
+}
+
+pub fn delete_tag(name: &str) -> Result<(), OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let repository = LocalRepository::from_dir(&repo_dir)?;
+    let tag = command::delete_tag(&repository, name)?;
+    println!("Deleted tag {} (was {})", tag.name, tag.commit_id);
+    Ok(())
+}
+
+pub async fn delete_remote_tag(remote_name: &str, name: &str) -> Result<(), OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let repository = LocalRepository::from_dir(&repo_dir)?;
+    let tag = command::delete_remote_tag(&repository, remote_name, name).await?;
+    println!(
+        "Deleted tag {} on {} (was {})",
+        tag.name, remote_name, tag.commit_id
+    );
+    Ok(())
+}
+
 pub fn checkout(name: &str) -> Result<(), OxenError> {
     let repo_dir = env::current_dir().unwrap();
     let repository = LocalRepository::from_dir(&repo_dir)?;
@@ -1,7 +1,13 @@
 use clap::ArgMatches;
-use liboxen::model::LocalRepository;
+use liboxen::api;
+use liboxen::df::DFDiffOpts;
+use liboxen::error::OxenError;
+use liboxen::model::{LocalRepository, SparseSpec};
 use liboxen::util;
-use liboxen::{command, opts::RestoreOpts};
+use liboxen::{
+    command,
+    opts::{LogOpts, ResetMode, RestoreOpts, TransferOpts},
+};
 use std::path::{Path, PathBuf};
 
 use crate::dispatch;
@@ -129,15 +135,37 @@ pub fn status(sub_matches: &ArgMatches) {
     }
 }
 
-pub fn log() {
-    match dispatch::log_commits() {
-        Ok(_) => {}
-        Err(err) => {
-            eprintln!("{err}")
-        }
+pub fn log(sub_matches: &ArgMatches) {
+    let result = log_opts(sub_matches).and_then(|opts| dispatch::log_commits(&opts));
+    if let Err(err) = result {
+        eprintln!("{err}")
     }
 }
 
+fn log_opts(sub_matches: &ArgMatches) -> Result<LogOpts, OxenError> {
+    let max_count = match sub_matches.value_of("max-count") {
+        Some(n) => Some(n.parse::<usize>().map_err(|_| {
+            OxenError::basic_str(format!("Max count must be a valid integer: {n}"))
+        })?),
+        None => None,
+    };
+    Ok(LogOpts {
+        revision: sub_matches.value_of("REVISION").map(String::from),
+        path: sub_matches.value_of("path").map(PathBuf::from),
+        author: sub_matches.value_of("author").map(String::from),
+        since: sub_matches
+            .value_of("since")
+            .map(LogOpts::parse_since)
+            .transpose()?,
+        until: sub_matches
+            .value_of("until")
+            .map(LogOpts::parse_until)
+            .transpose()?,
+        grep: sub_matches.value_of("grep").map(String::from),
+        max_count,
+    })
+}
+
 fn parse_df_sub_matches(sub_matches: &ArgMatches) -> liboxen::df::DFOpts {
     let vstack: Option<Vec<PathBuf>> = if let Some(vstack) = sub_matches.values_of("vstack") {
         let values: Vec<PathBuf> = vstack.map(std::path::PathBuf::from).collect();
@@ -177,7 +205,24 @@ fn parse_df_sub_matches(sub_matches: &ArgMatches) -> liboxen::df::DFOpts {
 }
 
 pub fn df(sub_matches: &ArgMatches) {
-    let path = sub_matches.value_of("DF_SPEC").expect("required");
+    if let Some(sql) = sub_matches.value_of("sql") {
+        let opts = parse_df_sub_matches(sub_matches);
+        match dispatch::df_sql(sql, opts) {
+            Ok(_) => {}
+            Err(err) => {
+                eprintln!("{err}")
+            }
+        }
+        return;
+    }
+
+    let path = match sub_matches.value_of("DF_SPEC") {
+        Some(path) => path,
+        None => {
+            eprintln!("Must supply a DataFrame to process or a --sql query");
+            return;
+        }
+    };
     if sub_matches.is_present("schema") || sub_matches.is_present("schema_flat") {
         match dispatch::df_schema(path, sub_matches.is_present("schema_flat")) {
             Ok(_) => {}
@@ -228,6 +273,25 @@ pub fn schemas(sub_matches: &ArgMatches) {
                     }
                 }
             }
+            ("create_index", sub_matches) => {
+                let schema_ref = sub_matches.value_of("SCHEMA_REF").expect("required");
+                let field = sub_matches.value_of("FIELD").expect("required");
+                match dispatch::schema_create_index(schema_ref, field) {
+                    Ok(_) => {}
+                    Err(err) => {
+                        eprintln!("{err}")
+                    }
+                }
+            }
+            ("list_indices", sub_matches) => {
+                let schema_ref = sub_matches.value_of("SCHEMA_REF").expect("required");
+                match dispatch::schema_list_indices(schema_ref) {
+                    Ok(_) => {}
+                    Err(err) => {
+                        eprintln!("{err}")
+                    }
+                }
+            }
             (cmd, _) => {
                 eprintln!("Unknown subcommand {cmd}")
             }
@@ -248,8 +312,9 @@ pub fn add(sub_matches: &ArgMatches) {
         .expect("Must supply files")
         .map(PathBuf::from)
         .collect();
+    let force = sub_matches.is_present("force");
 
-    match dispatch::add(paths) {
+    match dispatch::add(paths, force) {
         Ok(_) => {}
         Err(err) => {
             eprintln!("{err}")
@@ -331,6 +396,26 @@ pub async fn branch(sub_matches: &ArgMatches) {
     }
 }
 
+pub async fn tag(sub_matches: &ArgMatches) {
+    if let Some(name) = sub_matches.value_of("delete") {
+        let result = match sub_matches.value_of("remote") {
+            Some(remote_name) => dispatch::delete_remote_tag(remote_name, name).await,
+            None => dispatch::delete_tag(name),
+        };
+        if let Err(err) = result {
+            eprintln!("{err}")
+        }
+    } else if let Some(name) = sub_matches.value_of("name") {
+        let commit = sub_matches.value_of("commit");
+        let message = sub_matches.value_of("message").unwrap_or("");
+        if let Err(err) = dispatch::create_tag(name, commit, message) {
+            eprintln!("{err}")
+        }
+    } else if let Err(err) = dispatch::list_tags() {
+        eprintln!("{err}")
+    }
+}
+
 pub fn checkout(sub_matches: &ArgMatches) {
     if sub_matches.is_present("create") {
         let name = sub_matches.value_of("create").expect("required");
@@ -356,7 +441,8 @@ pub fn merge(sub_matches: &ArgMatches) {
     let branch = sub_matches
         .value_of("BRANCH")
         .unwrap_or(DEFAULT_BRANCH_NAME);
-    match dispatch::merge(branch) {
+    let primary_key = sub_matches.value_of("primary-key");
+    match dispatch::merge(branch, primary_key) {
         Ok(_) => {}
         Err(err) => {
             eprintln!("{err}")
@@ -364,6 +450,81 @@ pub fn merge(sub_matches: &ArgMatches) {
     }
 }
 
+pub fn revert(sub_matches: &ArgMatches) {
+    let commit = sub_matches.value_of("COMMIT").expect("required");
+    if let Err(err) = dispatch::revert(commit) {
+        eprintln!("{err}")
+    }
+}
+
+pub fn reset(sub_matches: &ArgMatches) {
+    let commit = sub_matches.value_of("REF").expect("required");
+    let mode = if sub_matches.is_present("soft") {
+        ResetMode::Soft
+    } else if sub_matches.is_present("hard") {
+        ResetMode::Hard
+    } else {
+        ResetMode::Mixed
+    };
+    if let Err(err) = dispatch::reset(commit, mode) {
+        eprintln!("{err}")
+    }
+}
+
+pub fn cherry_pick(sub_matches: &ArgMatches) {
+    let result = if sub_matches.is_present("continue") {
+        dispatch::cherry_pick_continue()
+    } else if sub_matches.is_present("abort") {
+        dispatch::cherry_pick_abort()
+    } else {
+        let commit = sub_matches.value_of("COMMIT").expect("required");
+        dispatch::cherry_pick(commit)
+    };
+    if let Err(err) = result {
+        eprintln!("{err}")
+    }
+}
+
+pub fn stash(sub_matches: &ArgMatches) {
+    let result = match sub_matches.subcommand() {
+        Some(("push", sub_matches)) => dispatch::stash_push(sub_matches.value_of("message")),
+        Some(("list", _)) => dispatch::stash_list(),
+        Some(("pop", sub_matches)) => stash_index(sub_matches).and_then(dispatch::stash_pop),
+        Some(("apply", sub_matches)) => stash_index(sub_matches).and_then(dispatch::stash_apply),
+        Some(("drop", sub_matches)) => stash_index(sub_matches).and_then(dispatch::stash_drop),
+        Some((command, _)) => Err(OxenError::basic_str(format!(
+            "Invalid subcommand: {command}"
+        ))),
+        None => dispatch::stash_push(sub_matches.value_of("message")),
+    };
+    if let Err(err) = result {
+        eprintln!("{err}")
+    }
+}
+
+// Accepts both `1` and `stash@{1}`
+fn stash_index(sub_matches: &ArgMatches) -> Result<usize, OxenError> {
+    let value = sub_matches.value_of("STASH").unwrap_or("0");
+    let index = value
+        .strip_prefix("stash@{")
+        .and_then(|v| v.strip_suffix('}'))
+        .unwrap_or(value);
+    index
+        .parse::<usize>()
+        .map_err(|_| OxenError::basic_str(format!("Invalid stash: {value}")))
+}
+
+fn transfer_opts(sub_matches: &ArgMatches) -> Result<TransferOpts, OxenError> {
+    let limit_rate = match sub_matches.value_of("limit-rate") {
+        Some(rate) => Some(api::remote::transfer::parse_rate(rate)?),
+        None => None,
+    };
+    Ok(TransferOpts {
+        limit_rate,
+        report_path: sub_matches.value_of("report").map(PathBuf::from),
+    })
+}
+
 pub async fn push(sub_matches: &ArgMatches) {
     let remote = sub_matches
         .value_of("REMOTE")
@@ -371,11 +532,32 @@ pub async fn push(sub_matches: &ArgMatches) {
     let branch = sub_matches
         .value_of("BRANCH")
         .unwrap_or(DEFAULT_BRANCH_NAME);
+    let opts = match transfer_opts(sub_matches) {
+        Ok(opts) => opts,
+        Err(err) => {
+            eprintln!("{err}");
+            return;
+        }
+    };
 
-    if sub_matches.is_present("delete") {
+    if sub_matches.is_present("all-remotes") {
+        // The remote is not given, so a single argument is the branch
+        let branch = sub_matches
+            .value_of("BRANCH")
+            .or_else(|| sub_matches.value_of("REMOTE"))
+            .unwrap_or(DEFAULT_BRANCH_NAME);
+        let force = sub_matches.is_present("force");
+        match dispatch::push_all_remotes(branch, force, &opts).await {
+            Ok(_) => {}
+            Err(err) => {
+                eprintln!("{err}")
+            }
+        }
+    } else if sub_matches.is_present("delete") {
         println!("Delete remote branch {remote}/{branch}");
     } else {
-        match dispatch::push(remote, branch).await {
+        let force = sub_matches.is_present("force");
+        match dispatch::push(remote, branch, force, &opts).await {
             Ok(_) => {}
             Err(err) => {
                 eprintln!("{err}")
@@ -391,7 +573,34 @@ pub async fn pull(sub_matches: &ArgMatches) {
     let branch = sub_matches
         .value_of("BRANCH")
         .unwrap_or(DEFAULT_BRANCH_NAME);
-    match dispatch::pull(remote, branch).await {
+    let opts = match transfer_opts(sub_matches) {
+        Ok(opts) => opts,
+        Err(err) => {
+            eprintln!("{err}");
+            return;
+        }
+    };
+    let result = match sub_matches.values_of("paths") {
+        Some(paths) => {
+            let paths: Vec<String> = paths.map(String::from).collect();
+            dispatch::pull_paths(remote, branch, &paths, &opts).await
+        }
+        None => dispatch::pull(remote, branch, &opts).await,
+    };
+    match result {
+        Ok(_) => {}
+        Err(err) => {
+            eprintln!("{err}")
+        }
+    }
+}
+
+pub async fn fetch(sub_matches: &ArgMatches) {
+    let remote = sub_matches
+        .value_of("REMOTE")
+        .unwrap_or(DEFAULT_REMOTE_NAME);
+    let branch = sub_matches.value_of("BRANCH");
+    match dispatch::fetch(remote, branch).await {
         Ok(_) => {}
         Err(err) => {
             eprintln!("{err}")
@@ -403,15 +612,29 @@ pub fn diff(sub_matches: &ArgMatches) {
     // First arg is optional
     let file_or_commit_id = sub_matches.value_of("FILE_OR_COMMIT_ID").expect("required");
     let path = sub_matches.value_of("PATH");
-    if let Some(path) = path {
-        match dispatch::diff(Some(file_or_commit_id), path) {
+    let opts = DFDiffOpts {
+        primary_key: sub_matches.value_of("primary-key").map(String::from),
+        ignore_columns: sub_matches
+            .value_of("ignore-cols")
+            .map(|cols| cols.split(',').map(|c| String::from(c.trim())).collect())
+            .unwrap_or_default(),
+    };
+    if file_or_commit_id.contains("..") {
+        match dispatch::diff_commits(file_or_commit_id, path, &opts) {
+            Ok(_) => {}
+            Err(err) => {
+                eprintln!("{err}")
+            }
+        }
+    } else if let Some(path) = path {
+        match dispatch::diff(Some(file_or_commit_id), path, &opts) {
             Ok(_) => {}
             Err(err) => {
                 eprintln!("{err}")
             }
         }
     } else {
-        match dispatch::diff(None, file_or_commit_id) {
+        match dispatch::diff(None, file_or_commit_id, &opts) {
             Ok(_) => {}
             Err(err) => {
                 eprintln!("{err}")
@@ -423,7 +646,23 @@ pub fn diff(sub_matches: &ArgMatches) {
 pub async fn clone(sub_matches: &ArgMatches) {
     let url = sub_matches.value_of("URL").expect("required");
     let shallow = sub_matches.is_present("shallow");
-    match dispatch::clone(url, shallow).await {
+    let include: Vec<String> = sub_matches
+        .values_of("include")
+        .map(|values| values.map(String::from).collect())
+        .unwrap_or_default();
+    let exclude: Vec<String> = sub_matches
+        .values_of("exclude")
+        .map(|values| values.map(String::from).collect())
+        .unwrap_or_default();
+    let spec = SparseSpec::new(include, exclude);
+    let opts = match transfer_opts(sub_matches) {
+        Ok(opts) => opts,
+        Err(err) => {
+            println!("Err: {err}");
+            return;
+        }
+    };
+    match dispatch::clone(url, shallow, spec, &opts).await {
         Ok(_) => {}
         Err(err) => {
             println!("Err: {err}")
@@ -471,6 +710,26 @@ pub fn migrate(sub_matches: &ArgMatches) {
     }
 }
 
+pub fn gc(sub_matches: &ArgMatches) {
+    let dry_run = sub_matches.is_present("dry-run");
+    match dispatch::gc(dry_run) {
+        Ok(_) => {}
+        Err(err) => {
+            eprintln!("{err}");
+            return;
+        }
+    }
+
+    if sub_matches.is_present("pack") && !dry_run {
+        match dispatch::gc_pack() {
+            Ok(_) => {}
+            Err(err) => {
+                eprintln!("{err}")
+            }
+        }
+    }
+}
+
 pub fn kvdb_inspect(sub_matches: &ArgMatches) {
     let path_str = sub_matches.value_of("PATH").expect("required");
     let path = Path::new(path_str);
@@ -1,6 +1,8 @@
+pub mod branch_policies;
 pub mod branches;
 pub mod commits;
 pub mod entries;
 pub mod namespaces;
 pub mod repositories;
 pub mod schemas;
+pub mod tags;
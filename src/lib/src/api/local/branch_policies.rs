@@ -0,0 +1,189 @@
+use crate::compute::cachers::content_validator;
+use crate::compute::commit_cacher;
+use crate::compute::commit_cacher::CacherStatusType;
+use crate::constants::BRANCH_POLICY_FILE;
+use crate::error::OxenError;
+use crate::index::CommitReader;
+use crate::model::{BranchPolicy, LocalRepository, ProtectedBranch};
+use crate::util;
+
+use std::path::PathBuf;
+
+fn policy_path(repo: &LocalRepository) -> PathBuf {
+    util::fs::oxen_hidden_dir(&repo.path).join(BRANCH_POLICY_FILE)
+}
+
+/// Branch policy of the repository, no branches are protected until one is saved
+pub fn get(repo: &LocalRepository) -> Result<BranchPolicy, OxenError> {
+    let path = policy_path(repo);
+    if !path.exists() {
+        return Ok(BranchPolicy::default());
+    }
+
+    let contents = util::fs::read_from_path(&path)?;
+    let policy: BranchPolicy = toml::from_str(&contents)?;
+    Ok(policy)
+}
+
+pub fn save(repo: &LocalRepository, policy: &BranchPolicy) -> Result<(), OxenError> {
+    let toml = toml::to_string(policy)?;
+    util::fs::write_to_path(&policy_path(repo), &toml)?;
+    Ok(())
+}
+
+/// Adds or replaces the protection for its pattern
+pub fn protect(
+    repo: &LocalRepository,
+    protection: ProtectedBranch,
+) -> Result<BranchPolicy, OxenError> {
+    // Catch bad globs before they are saved and lock up every push
+    protection.matches("")?;
+
+    let mut policy = get(repo)?;
+    policy.protect(protection);
+    save(repo, &policy)?;
+    Ok(policy)
+}
+
+/// Removes the protection for a pattern, returns None if it was not protected
+pub fn unprotect(
+    repo: &LocalRepository,
+    pattern: &str,
+) -> Result<Option<ProtectedBranch>, OxenError> {
+    let mut policy = get(repo)?;
+    let removed = policy.unprotect(pattern);
+    if removed.is_some() {
+        save(repo, &policy)?;
+    }
+    Ok(removed)
+}
+
+/// Returns why the policy does not allow deleting the branch, or None if it is allowed
+pub fn delete_violation(
+    repo: &LocalRepository,
+    branch_name: &str,
+) -> Result<Option<String>, OxenError> {
+    let policy = get(repo)?;
+    for protection in policy.protections_for(branch_name)? {
+        if protection.prevent_deletion {
+            return Ok(Some(format!(
+                "Branch '{}' is protected by '{}' and cannot be deleted",
+                branch_name, protection.pattern
+            )));
+        }
+    }
+    Ok(None)
+}
+
+/// Returns why the policy does not allow moving the branch to the commit, or None if it is allowed
+pub fn update_violation(
+    repo: &LocalRepository,
+    branch_name: &str,
+    commit_id: &str,
+    force: bool,
+) -> Result<Option<String>, OxenError> {
+    let policy = get(repo)?;
+    for protection in policy.protections_for(branch_name)? {
+        if force && protection.prevent_force_push {
+            return Ok(Some(format!(
+                "Branch '{}' is protected by '{}' and cannot be force pushed",
+                branch_name, protection.pattern
+            )));
+        }
+
+        if protection.require_validation && !is_validated(repo, commit_id)? {
+            return Ok(Some(format!(
+                "Branch '{}' is protected by '{}' and only accepts commits that passed validation, commit {} has not",
+                branch_name, protection.pattern, commit_id
+            )));
+        }
+    }
+    Ok(None)
+}
+
+fn is_validated(repo: &LocalRepository, commit_id: &str) -> Result<bool, OxenError> {
+    let commit_reader = CommitReader::new(repo)?;
+    let commit = match commit_reader.get_commit_by_id(commit_id)? {
+        Some(commit) => commit,
+        None => return Ok(false),
+    };
+
+    match commit_cacher::get_status(repo, &commit)? {
+        Some(CacherStatusType::Success) => {
+            Ok(content_validator::is_valid(repo, &commit).unwrap_or(false))
+        }
+        _ => Ok(false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api;
+    use crate::command;
+    use crate::compute::commit_cacher;
+    use crate::constants::DEFAULT_BRANCH_NAME;
+    use crate::error::OxenError;
+    use crate::model::ProtectedBranch;
+    use crate::test;
+
+    #[test]
+    fn test_branch_policies_save_and_check() -> Result<(), OxenError> {
+        test::run_training_data_repo_test_fully_committed(|repo| {
+            let commit = command::head_commit(&repo)?;
+
+            // Nothing is protected by default
+            assert!(api::local::branch_policies::get(&repo)?
+                .protected_branches
+                .is_empty());
+            assert!(
+                api::local::branch_policies::delete_violation(&repo, DEFAULT_BRANCH_NAME)?
+                    .is_none()
+            );
+
+            let mut protection = ProtectedBranch::new(DEFAULT_BRANCH_NAME);
+            protection.require_validation = true;
+            api::local::branch_policies::protect(&repo, protection)?;
+            let policy = api::local::branch_policies::get(&repo)?;
+            assert_eq!(policy.protected_branches.len(), 1);
+
+            assert!(
+                api::local::branch_policies::delete_violation(&repo, DEFAULT_BRANCH_NAME)?
+                    .is_some()
+            );
+            assert!(api::local::branch_policies::delete_violation(&repo, "other")?.is_none());
+            assert!(api::local::branch_policies::update_violation(
+                &repo,
+                DEFAULT_BRANCH_NAME,
+                &commit.id,
+                true
+            )?
+            .is_some());
+
+            // Not validated yet
+            assert!(api::local::branch_policies::update_violation(
+                &repo,
+                DEFAULT_BRANCH_NAME,
+                &commit.id,
+                false
+            )?
+            .is_some());
+
+            commit_cacher::run_all(&repo, &commit)?;
+            assert!(api::local::branch_policies::update_violation(
+                &repo,
+                DEFAULT_BRANCH_NAME,
+                &commit.id,
+                false
+            )?
+            .is_none());
+
+            assert!(api::local::branch_policies::unprotect(&repo, DEFAULT_BRANCH_NAME)?.is_some());
+            assert!(
+                api::local::branch_policies::delete_violation(&repo, DEFAULT_BRANCH_NAME)?
+                    .is_none()
+            );
+
+            Ok(())
+        })
+    }
+}
@@ -1,8 +1,16 @@
+use lazy_static::lazy_static;
+use std::sync::Mutex;
+
 use crate::command;
 use crate::error::OxenError;
 use crate::index::{CommitReader, RefReader, RefWriter};
 use crate::model::{Branch, LocalRepository};
 
+lazy_static! {
+    // Pushes check what a branch points at before moving it, so the check and the move happen together
+    static ref BRANCH_UPDATE_LOCK: Mutex<()> = Mutex::new(());
+}
+
 pub fn list(repo: &LocalRepository) -> Result<Vec<Branch>, OxenError> {
     command::list_branches(repo)
 }
@@ -13,6 +21,7 @@ pub fn get_by_name(repo: &LocalRepository, name: &str) -> Result<Option<Branch>,
 }
 
 pub fn update(repo: &LocalRepository, name: &str, commit_id: &str) -> Result<Branch, OxenError> {
+    let _lock = BRANCH_UPDATE_LOCK.lock().unwrap();
     let referencer = RefReader::new(repo)?;
     match referencer.get_branch_by_name(name)? {
         Some(branch) => {
@@ -27,6 +36,53 @@ pub fn update(repo: &LocalRepository, name: &str, commit_id: &str) -> Result<Bra
     }
 }
 
+/// Moves the branch to `commit_id` only if it still points at `expected_commit_id`, which is None if the branch
+/// should not exist yet. Returns None and leaves the branch alone if another update moved it first.
+pub fn compare_and_update(
+    repo: &LocalRepository,
+    name: &str,
+    expected_commit_id: Option<&str>,
+    commit_id: &str,
+) -> Result<Option<Branch>, OxenError> {
+    let _lock = BRANCH_UPDATE_LOCK.lock().unwrap();
+    let current = RefReader::new(repo)?.get_commit_id_for_branch(name)?;
+    if current.as_deref() != expected_commit_id {
+        log::debug!(
+            "compare_and_update branch {} moved from {:?} to {:?}",
+            name,
+            expected_commit_id,
+            current
+        );
+        return Ok(None);
+    }
+
+    match current {
+        Some(_) => {
+            let ref_writer = RefWriter::new(repo)?;
+            ref_writer.set_branch_commit_id(name, commit_id)?;
+            Ok(Some(Branch {
+                name: name.to_string(),
+                commit_id: commit_id.to_string(),
+                is_head: false,
+            }))
+        }
+        None => command::create_branch(repo, name, commit_id).map(Some),
+    }
+}
+
+/// Check if moving a branch from `branch_commit_id` to `commit_id` keeps all of its current history.
+/// Setting a branch that does not exist yet is always a fast forward.
+pub fn is_fast_forward(
+    repo: &LocalRepository,
+    branch_commit_id: Option<&str>,
+    commit_id: &str,
+) -> Result<bool, OxenError> {
+    match branch_commit_id {
+        Some(branch_commit_id) => CommitReader::new(repo)?.is_ancestor(branch_commit_id, commit_id),
+        None => Ok(true),
+    }
+}
+
 pub fn create(repo: &LocalRepository, name: &str) -> Result<Branch, OxenError> {
     command::create_branch_from_head(repo, name)
 }
@@ -83,3 +139,70 @@ pub fn force_delete(repo: &LocalRepository, name: &str) -> Result<(), OxenError>
     let ref_writer = RefWriter::new(repo)?;
     ref_writer.delete_branch(name)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::api;
+    use crate::command;
+    use crate::constants::DEFAULT_BRANCH_NAME;
+    use crate::error::OxenError;
+    use crate::test;
+    use crate::util;
+
+    #[test]
+    fn test_branches_compare_and_update() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let path = repo.path.join("hello.txt");
+            util::fs::write_to_path(&path, "hello")?;
+            command::add(&repo, &path)?;
+            let first = command::commit(&repo, "first")?.unwrap();
+            util::fs::write_to_path(&path, "second")?;
+            command::add(&repo, &path)?;
+            let second = command::commit(&repo, "second")?.unwrap();
+
+            assert!(api::local::branches::is_fast_forward(
+                &repo,
+                Some(&first.id),
+                &second.id
+            )?);
+            assert!(!api::local::branches::is_fast_forward(
+                &repo,
+                Some(&second.id),
+                &first.id
+            )?);
+            assert!(api::local::branches::is_fast_forward(
+                &repo, None, &first.id
+            )?);
+
+            // Someone else already moved the branch since we looked at it
+            let branch = api::local::branches::compare_and_update(
+                &repo,
+                DEFAULT_BRANCH_NAME,
+                Some(&first.id),
+                &first.id,
+            )?;
+            assert!(branch.is_none());
+            let branch = api::local::branches::get_by_name(&repo, DEFAULT_BRANCH_NAME)?.unwrap();
+            assert_eq!(branch.commit_id, second.id);
+
+            let branch = api::local::branches::compare_and_update(
+                &repo,
+                DEFAULT_BRANCH_NAME,
+                Some(&second.id),
+                &first.id,
+            )?;
+            assert_eq!(branch.unwrap().commit_id, first.id);
+
+            // A new branch only gets created if nobody beat us to it
+            let branch =
+                api::local::branches::compare_and_update(&repo, "feature", None, &second.id)?;
+            assert_eq!(branch.unwrap().commit_id, second.id);
+            assert!(
+                api::local::branches::compare_and_update(&repo, "feature", None, &first.id)?
+                    .is_none()
+            );
+
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,138 @@
+use crate::config::UserConfig;
+use crate::error::OxenError;
+use crate::index::{CommitReader, RefReader, RefWriter};
+use crate::model::{LocalRepository, Tag};
+
+use lazy_static::lazy_static;
+use std::sync::Mutex;
+use time::OffsetDateTime;
+
+lazy_static! {
+    // Tags never move, so checking that one is free and writing it happen together
+    static ref TAG_UPDATE_LOCK: Mutex<()> = Mutex::new(());
+}
+
+pub fn list(repo: &LocalRepository) -> Result<Vec<Tag>, OxenError> {
+    let ref_reader = RefReader::new(repo)?;
+    ref_reader.list_tags()
+}
+
+pub fn get_by_name(repo: &LocalRepository, name: &str) -> Result<Option<Tag>, OxenError> {
+    let ref_reader = RefReader::new(repo)?;
+    ref_reader.get_tag_by_name(name)
+}
+
+/// Tags the commit as the current user
+pub fn create(
+    repo: &LocalRepository,
+    name: &str,
+    commit_id: &str,
+    message: &str,
+) -> Result<Tag, OxenError> {
+    let cfg = UserConfig::get()?;
+    let tag = Tag {
+        name: String::from(name),
+        commit_id: String::from(commit_id),
+        tagger: cfg.name,
+        email: cfg.email,
+        message: String::from(message),
+        timestamp: OffsetDateTime::now_utc(),
+    };
+
+    if save_if_absent(repo, &tag)?.is_some() {
+        let err = format!("Tag '{name}' already exists");
+        return Err(OxenError::basic_str(err));
+    }
+    Ok(tag)
+}
+
+/// Stores a tag made elsewhere, like one that was pushed or pulled. Saving a tag we already have for the
+/// same commit returns the one we have, saving it for another commit is an error since tags never move.
+pub fn save(repo: &LocalRepository, tag: &Tag) -> Result<Tag, OxenError> {
+    match save_if_absent(repo, tag)? {
+        None => Ok(tag.clone()),
+        Some(existing) if existing.commit_id == tag.commit_id => Ok(existing),
+        Some(_) => Err(OxenError::tag_already_exists(&tag.name)),
+    }
+}
+
+/// Saves the tag unless there already is one with its name, in which case that one is returned and
+/// nothing is written
+pub fn save_if_absent(repo: &LocalRepository, tag: &Tag) -> Result<Option<Tag>, OxenError> {
+    Tag::validate_name(&tag.name)?;
+    let _lock = TAG_UPDATE_LOCK.lock().unwrap();
+    if let Some(existing) = get_by_name(repo, &tag.name)? {
+        return Ok(Some(existing));
+    }
+
+    let ref_reader = RefReader::new(repo)?;
+    if ref_reader.has_branch(&tag.name) {
+        let err = format!("A branch named '{}' already exists", tag.name);
+        return Err(OxenError::basic_str(err));
+    }
+
+    let commit_reader = CommitReader::new(repo)?;
+    if !commit_reader.commit_id_exists(&tag.commit_id) {
+        return Err(OxenError::commit_id_does_not_exist(&tag.commit_id));
+    }
+
+    let ref_writer = RefWriter::new(repo)?;
+    ref_writer.create_tag(tag)?;
+    Ok(None)
+}
+
+/// Removes the tag, returns None if it did not exist
+pub fn delete(repo: &LocalRepository, name: &str) -> Result<Option<Tag>, OxenError> {
+    let _lock = TAG_UPDATE_LOCK.lock().unwrap();
+    let tag = get_by_name(repo, name)?;
+    if tag.is_some() {
+        let ref_writer = RefWriter::new(repo)?;
+        ref_writer.delete_tag(name)?;
+    }
+    Ok(tag)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api;
+    use crate::command;
+    use crate::error::OxenError;
+    use crate::model::Tag;
+    use crate::test;
+
+    #[test]
+    fn test_api_local_tags_create_list_delete() -> Result<(), OxenError> {
+        test::run_training_data_repo_test_fully_committed(|repo| {
+            let head = command::head_commit(&repo)?;
+            let tag = api::local::tags::create(&repo, "v1.3-train", &head.id, "Train split")?;
+            assert_eq!(tag.commit_id, head.id);
+            assert_eq!(tag.message, "Train split");
+
+            // Creating it again is an error, even for the same commit
+            assert!(api::local::tags::create(&repo, "v1.3-train", &head.id, "Again").is_err());
+
+            // Saving the same tag is fine, moving it is not
+            api::local::tags::save(&repo, &tag)?;
+            let moved = Tag {
+                commit_id: String::from("not-a-commit"),
+                ..tag.clone()
+            };
+            assert!(api::local::tags::save(&repo, &moved).is_err());
+            assert_eq!(
+                api::local::tags::save_if_absent(&repo, &moved)?,
+                Some(tag.clone())
+            );
+
+            // Tags need a commit we have and a name that is not a branch
+            assert!(api::local::tags::create(&repo, "v2", "not-a-commit", "").is_err());
+            assert!(api::local::tags::create(&repo, "main", &head.id, "").is_err());
+
+            assert_eq!(api::local::tags::list(&repo)?, vec![tag.clone()]);
+            assert_eq!(api::local::tags::delete(&repo, "v1.3-train")?, Some(tag));
+            assert_eq!(api::local::tags::delete(&repo, "v1.3-train")?, None);
+            assert!(api::local::tags::list(&repo)?.is_empty());
+
+            Ok(())
+        })
+    }
+}
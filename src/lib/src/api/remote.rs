@@ -1,7 +1,10 @@
 pub mod branches;
+pub mod chunks;
 pub mod client;
 pub mod commits;
 pub mod dir;
 pub mod entries;
 pub mod repositories;
+pub mod tags;
+pub mod transfer;
 pub mod version;
@@ -13,7 +13,7 @@ pub async fn get_by_name(
     let uri = format!("/branches/{branch_name}");
     let url = api::endpoint::url_from_repo(repository, &uri)?;
 
-    let client = client::new_for_url(&url)?;
+    let client = repository.client_pool.get_for_url(&url)?;
     if let Ok(res) = client.get(&url).send().await {
         let status = res.status();
         if 404 == status {
@@ -46,7 +46,7 @@ pub async fn create_or_get(repository: &RemoteRepository, name: &str) -> Result<
 
     let params = serde_json::to_string(&json!({ "name": name }))?;
 
-    let client = client::new_for_url(&url)?;
+    let client = repository.client_pool.get_for_url(&url)?;
     if let Ok(res) = client.post(&url).body(params).send().await {
         let body = client::parse_json_body(&url, res).await?;
         let response: Result<BranchResponse, serde_json::Error> = serde_json::from_str(&body);
@@ -70,7 +70,7 @@ pub async fn create_or_get(repository: &RemoteRepository, name: &str) -> Result<
 pub async fn list(repository: &RemoteRepository) -> Result<Vec<Branch>, OxenError> {
     let url = api::endpoint::url_from_repo(repository, "/branches")?;
 
-    let client = client::new_for_url(&url)?;
+    let client = repository.client_pool.get_for_url(&url)?;
     if let Ok(res) = client.get(&url).send().await {
         let body = client::parse_json_body(&url, res).await?;
         let response: Result<ListBranchesResponse, serde_json::Error> = serde_json::from_str(&body);
@@ -92,18 +92,37 @@ pub async fn list(repository: &RemoteRepository) -> Result<Vec<Branch>, OxenErro
     }
 }
 
+/// Move the remote branch to the commit, the server rejects it if the commit does not descend from the branch head
 pub async fn update(
     repository: &RemoteRepository,
     branch_name: &str,
     commit: &Commit,
+) -> Result<Branch, OxenError> {
+    p_update(repository, branch_name, commit, false).await
+}
+
+/// Move the remote branch to the commit even if that drops commits from the branch history
+pub async fn force_update(
+    repository: &RemoteRepository,
+    branch_name: &str,
+    commit: &Commit,
+) -> Result<Branch, OxenError> {
+    p_update(repository, branch_name, commit, true).await
+}
+
+async fn p_update(
+    repository: &RemoteRepository,
+    branch_name: &str,
+    commit: &Commit,
+    force: bool,
 ) -> Result<Branch, OxenError> {
     let uri = format!("/branches/{branch_name}");
     let url = api::endpoint::url_from_repo(repository, &uri)?;
-    log::debug!("remote::branches::update url: {}", url);
+    log::debug!("remote::branches::update url: {} force: {}", url, force);
 
-    let params = serde_json::to_string(&json!({ "commit_id": commit.id }))?;
+    let params = serde_json::to_string(&json!({ "commit_id": commit.id, "force": force }))?;
 
-    let client = client::new_for_url(&url)?;
+    let client = repository.client_pool.get_for_url(&url)?;
     if let Ok(res) = client.put(&url).body(params).send().await {
         let body = client::parse_json_body(&url, res).await?;
         let response: Result<BranchResponse, serde_json::Error> = serde_json::from_str(&body);
@@ -123,6 +142,7 @@ pub async fn update(
         Err(OxenError::basic_str(&msg))
     }
 }
+
 pub async fn delete(
     repository: &RemoteRepository,
     branch_name: &str,
@@ -131,7 +151,7 @@ pub async fn delete(
     let url = api::endpoint::url_from_repo(repository, &uri)?;
     log::debug!("Deleting branch: {}", url);
 
-    let client = client::new_for_url(&url)?;
+    let client = repository.client_pool.get_for_url(&url)?;
     if let Ok(res) = client.delete(&url).send().await {
         let body = client::parse_json_body(&url, res).await?;
         let response: Result<StatusMessage, serde_json::Error> = serde_json::from_str(&body);
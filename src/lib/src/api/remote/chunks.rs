@@ -0,0 +1,165 @@
+use crate::api;
+use crate::api::remote::{client, transfer};
+use crate::error::OxenError;
+use crate::model::{ChunkManifest, RemoteRepository};
+use crate::view::{ChunkHashes, ChunkHashesResponse, ChunkManifestResponse, StatusMessage};
+
+use reqwest::Method;
+
+/// Asks the remote which of the chunks it does not have yet
+pub async fn list_missing(
+    remote_repo: &RemoteRepository,
+    hashes: &[String],
+) -> Result<Vec<String>, OxenError> {
+    let url = api::endpoint::url_from_repo(remote_repo, "/chunks/missing")?;
+    let params = serde_json::to_string(&ChunkHashes {
+        hashes: hashes.to_vec(),
+    })?;
+
+    let client = remote_repo.client_pool.get_for_url(&url)?;
+    match client.post(&url).body(params).send().await {
+        Ok(res) => {
+            let body = client::parse_json_body(&url, res).await?;
+            let response: Result<ChunkHashesResponse, serde_json::Error> =
+                serde_json::from_str(&body);
+            match response {
+                Ok(response) => Ok(response.hashes),
+                Err(err) => Err(OxenError::basic_str(format!(
+                    "api::chunks::list_missing error parsing response from {url}\n\nErr {err:?} \n\n{body}"
+                ))),
+            }
+        }
+        Err(err) => {
+            let err = format!("api::chunks::list_missing Request failed: {url}\nErr {err:?}");
+            Err(OxenError::basic_str(err))
+        }
+    }
+}
+
+pub async fn upload_with_retry(
+    remote_repo: &RemoteRepository,
+    hash: &str,
+    data: &[u8],
+) -> Result<(), OxenError> {
+    let description = format!("Upload chunk {hash}");
+    // Chunks are stored by their hash, so sending one again is harmless
+    client::with_retry_for_key(&description, hash, || upload(remote_repo, hash, data)).await
+}
+
+pub async fn upload(
+    remote_repo: &RemoteRepository,
+    hash: &str,
+    data: &[u8],
+) -> Result<(), OxenError> {
+    let uri = format!("/chunks/{hash}");
+    let url = api::endpoint::url_from_repo(remote_repo, &uri)?;
+
+    let client = remote_repo.client_pool.get_for_url(&url)?;
+    let body = transfer::upload_body(data.to_vec());
+    match client.post(&url).body(body).send().await {
+        Ok(res) => {
+            let body = client::parse_json_body(&url, res).await?;
+            let response: Result<StatusMessage, serde_json::Error> = serde_json::from_str(&body);
+            match response {
+                Ok(_) => Ok(()),
+                Err(err) => Err(OxenError::basic_str(format!(
+                    "api::chunks::upload error parsing response from {url}\n\nErr {err:?} \n\n{body}"
+                ))),
+            }
+        }
+        Err(err) => {
+            let err = format!("api::chunks::upload Request failed: {url}\nErr {err:?}");
+            Err(OxenError::basic_str(err))
+        }
+    }
+}
+
+pub async fn download_with_retry(
+    remote_repo: &RemoteRepository,
+    hash: &str,
+) -> Result<Vec<u8>, OxenError> {
+    let description = format!("Download chunk {hash}");
+    client::with_retry(&description, &Method::GET, || download(remote_repo, hash)).await
+}
+
+pub async fn download(remote_repo: &RemoteRepository, hash: &str) -> Result<Vec<u8>, OxenError> {
+    let uri = format!("/chunks/{hash}");
+    let url = api::endpoint::url_from_repo(remote_repo, &uri)?;
+
+    let client = remote_repo.client_pool.get_for_url(&url)?;
+    match client.get(&url).send().await {
+        Ok(res) => {
+            let status = res.status();
+            if reqwest::StatusCode::OK == status {
+                transfer::body_bytes(res).await
+            } else {
+                let err = format!("api::chunks::download Err request failed [{status}] {url}");
+                Err(OxenError::basic_str(err))
+            }
+        }
+        Err(err) => {
+            let err = format!("api::chunks::download Request failed: {url}\nErr {err:?}");
+            Err(OxenError::basic_str(err))
+        }
+    }
+}
+
+/// Gets the chunk manifest for a versioned file, the remote chunks the file if it has not yet
+pub async fn get_manifest(
+    remote_repo: &RemoteRepository,
+    hash: &str,
+) -> Result<Option<ChunkManifest>, OxenError> {
+    let uri = format!("/manifests/{hash}");
+    let url = api::endpoint::url_from_repo(remote_repo, &uri)?;
+
+    let client = remote_repo.client_pool.get_for_url(&url)?;
+    match client.get(&url).send().await {
+        Ok(res) => {
+            if 404 == res.status() {
+                return Ok(None);
+            }
+
+            let body = client::parse_json_body(&url, res).await?;
+            let response: Result<ChunkManifestResponse, serde_json::Error> =
+                serde_json::from_str(&body);
+            match response {
+                Ok(response) => Ok(Some(response.manifest)),
+                Err(err) => Err(OxenError::basic_str(format!(
+                    "api::chunks::get_manifest error parsing response from {url}\n\nErr {err:?} \n\n{body}"
+                ))),
+            }
+        }
+        Err(err) => {
+            let err = format!("api::chunks::get_manifest Request failed: {url}\nErr {err:?}");
+            Err(OxenError::basic_str(err))
+        }
+    }
+}
+
+/// Sends the manifest after all the chunks are uploaded, so the remote can assemble the version
+pub async fn post_manifest(
+    remote_repo: &RemoteRepository,
+    manifest: &ChunkManifest,
+) -> Result<(), OxenError> {
+    let url = api::endpoint::url_from_repo(remote_repo, "/manifests")?;
+    let params = serde_json::to_string(manifest)?;
+
+    let client = remote_repo.client_pool.get_for_url(&url)?;
+    match client.post(&url).body(params).send().await {
+        Ok(res) => {
+            let body = client::parse_json_body(&url, res).await?;
+            let response: Result<ChunkManifestResponse, serde_json::Error> =
+                serde_json::from_str(&body);
+            match response {
+                Ok(_) => Ok(()),
+                Err(err) => Err(OxenError::basic_str(format!(
+                    "api::chunks::post_manifest error parsing response from {url}\n\nErr {err:?} \n\n{body}"
+                ))),
+            }
+        }
+        Err(err) => {
+            let err = format!("api::chunks::post_manifest Request failed: {url}\nErr {err:?}");
+            Err(OxenError::basic_str(err))
+        }
+    }
+}
@@ -1,13 +1,51 @@
+use crate::api::remote::transfer;
+use crate::config::{TransferConfig, UserConfig};
 use crate::error::OxenError;
 use crate::view::http;
-use crate::{config::UserConfig, view::OxenResponse};
+use crate::view::OxenResponse;
 
 pub use reqwest::Url;
-use reqwest::{header, Client, ClientBuilder, IntoUrl};
+use reqwest::{header, Client, ClientBuilder, IntoUrl, Method};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 const USER_AGENT: &str = "Oxen";
 
+/// One client per host for the requests to a remote repository. Clones of a client share its connection
+/// pool, so requests reuse kept alive connections. The pool goes away with the remote repository that
+/// holds it, along with the connections, which are driven by tasks on the runtime that opened them.
+#[derive(Debug, Clone, Default)]
+pub struct ClientPool {
+    clients: Arc<Mutex<HashMap<String, Client>>>,
+}
+
+impl ClientPool {
+    pub fn new() -> ClientPool {
+        ClientPool::default()
+    }
+
+    pub fn get_for_url<U: IntoUrl>(&self, url: U) -> Result<Client, OxenError> {
+        let host = get_host_from_url(url)?;
+        self.get_for_host(host)
+    }
+
+    /// Returns the client for the host, creating it on first use
+    pub fn get_for_host<S: AsRef<str>>(&self, host: S) -> Result<Client, OxenError> {
+        let host = host.as_ref();
+        let mut clients = self.clients.lock().unwrap();
+        if let Some(client) = clients.get(host) {
+            return Ok(client.clone());
+        }
+
+        let client = new_for_host(host)?;
+        clients.insert(String::from(host), client.clone());
+        Ok(client)
+    }
+}
+
 fn get_host_from_url<U: IntoUrl>(url: U) -> Result<String, OxenError> {
     let parsed_url = url.into_url()?;
     let mut host_str = parsed_url.host_str().unwrap_or_default().to_string();
@@ -17,15 +55,27 @@ fn get_host_from_url<U: IntoUrl>(url: U) -> Result<String, OxenError> {
     Ok(host_str)
 }
 
-// TODO: we probably want to create a pool of clients instead of constructing a
-// new one for each request so we can take advantage of keep-alive
+/// The `[transfer]` settings of the user config, or the defaults if there is no config
+pub fn transfer_config() -> TransferConfig {
+    match UserConfig::get() {
+        Ok(config) => config.transfer,
+        Err(_) => TransferConfig::default(),
+    }
+}
+
+/// Number of requests to keep in flight at once when pushing or pulling
+pub fn concurrency() -> usize {
+    transfer_config().concurrency()
+}
+
 pub fn new_for_url<U: IntoUrl>(url: U) -> Result<Client, OxenError> {
     let host = get_host_from_url(url)?;
     new_for_host(host)
 }
 
+/// Builds a client for the host, use a `ClientPool` to share one between requests
 pub fn new_for_host<S: AsRef<str>>(host: S) -> Result<Client, OxenError> {
-    match builder_for_host(host.as_ref())?.build() {
+    match builder_for_host(host)?.build() {
         Ok(client) => Ok(client),
         Err(reqwest_err) => Err(OxenError::HTTP(reqwest_err)),
     }
@@ -61,7 +111,91 @@ pub fn builder_for_host<S: AsRef<str>>(host: S) -> Result<ClientBuilder, OxenErr
 }
 
 fn builder() -> ClientBuilder {
-    Client::builder().user_agent(format!("{USER_AGENT}/{VERSION}"))
+    let config = transfer_config();
+    let mut builder = Client::builder()
+        .user_agent(format!("{USER_AGENT}/{VERSION}"))
+        .connect_timeout(Duration::from_secs(config.connect_timeout_secs))
+        .pool_idle_timeout(Duration::from_secs(config.pool_idle_timeout_secs))
+        .pool_max_idle_per_host(config.concurrency())
+        .tcp_keepalive(Duration::from_secs(60))
+        .http2_adaptive_window(true);
+
+    if let Some(timeout_secs) = config.timeout_secs {
+        builder = builder.timeout(Duration::from_secs(timeout_secs));
+    }
+
+    if config.http2_prior_knowledge {
+        builder = builder.http2_prior_knowledge();
+    }
+
+    builder
+}
+
+/// Whether a request that failed part way can be sent again without doing its work twice
+pub fn is_idempotent(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::PUT)
+}
+
+/// Runs the request until it succeeds, backing off exponentially between tries. Gives up after
+/// `max_retries` retries, authentication errors are returned right away since retrying will not help.
+/// Only GET, HEAD and PUT requests are retried, anything else is tried once.
+pub async fn with_retry<T, F, Fut>(
+    description: &str,
+    method: &Method,
+    request: F,
+) -> Result<T, OxenError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, OxenError>>,
+{
+    retry(description, is_idempotent(method), request).await
+}
+
+/// Like `with_retry` for a POST that writes its body under `key`, such as a chunk stored by its hash.
+/// Sending it again writes the same data to the same place, so it is retried like a PUT.
+pub async fn with_retry_for_key<T, F, Fut>(
+    description: &str,
+    key: &str,
+    request: F,
+) -> Result<T, OxenError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, OxenError>>,
+{
+    log::debug!("{} is retried by its key {}", description, key);
+    retry(description, true, request).await
+}
+
+async fn retry<T, F, Fut>(
+    description: &str,
+    can_retry: bool,
+    mut request: F,
+) -> Result<T, OxenError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, OxenError>>,
+{
+    let config = transfer_config();
+    let mut num_tries = 0;
+    loop {
+        match request().await {
+            Ok(val) => return Ok(val),
+            Err(OxenError::Authentication(err)) => return Err(OxenError::Authentication(err)),
+            Err(err) if !can_retry => return Err(err),
+            Err(err) => {
+                num_tries += 1;
+                if num_tries > config.max_retries {
+                    let err = format!("{description} failed after {num_tries} tries: {err}");
+                    return Err(OxenError::basic_str(err));
+                }
+
+                transfer::add_retry();
+                let backoff = config.backoff(num_tries);
+                log::warn!("{} failed, retrying in {:?}: {}", description, backoff, err);
+                tokio::time::sleep(backoff).await;
+            }
+        }
+    }
 }
 
 /// Performs an extra parse to validate that the response is success
@@ -111,3 +245,107 @@ fn parse_status_and_message(
         status => Err(OxenError::basic_str(format!("Unknown status [{status}]"))),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use reqwest::Method;
+
+    use crate::api::remote::client;
+    use crate::error::OxenError;
+
+    #[tokio::test]
+    async fn test_client_with_retry_until_success() -> Result<(), OxenError> {
+        let mut num_tries = 0;
+        let val = client::with_retry("Flaky request", &Method::GET, || {
+            num_tries += 1;
+            let num_tries = num_tries;
+            async move {
+                if num_tries < 2 {
+                    Err(OxenError::basic_str("connection reset"))
+                } else {
+                    Ok(num_tries)
+                }
+            }
+        })
+        .await?;
+        assert_eq!(val, 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_client_with_retry_does_not_retry_authentication() -> Result<(), OxenError> {
+        let mut num_tries = 0;
+        let result: Result<(), OxenError> =
+            client::with_retry("Unauthorized request", &Method::GET, || {
+                num_tries += 1;
+                async { Err(OxenError::authentication("unauthorized")) }
+            })
+            .await;
+        assert!(matches!(result, Err(OxenError::Authentication(_))));
+        assert_eq!(num_tries, 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_client_with_retry_does_not_retry_post() -> Result<(), OxenError> {
+        let mut num_tries = 0;
+        let result: Result<(), OxenError> = client::with_retry("Post", &Method::POST, || {
+            num_tries += 1;
+            async { Err(OxenError::basic_str("connection reset")) }
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(num_tries, 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_client_with_retry_for_key_retries_post() -> Result<(), OxenError> {
+        let mut num_tries = 0;
+        let val = client::with_retry_for_key("Upload chunk", "abc123", || {
+            num_tries += 1;
+            let num_tries = num_tries;
+            async move {
+                if num_tries < 2 {
+                    Err(OxenError::basic_str("connection reset"))
+                } else {
+                    Ok(num_tries)
+                }
+            }
+        })
+        .await?;
+        assert_eq!(val, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_client_pool_is_shared_between_threads() -> Result<(), OxenError> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .enable_all()
+            .build()?;
+        let pool = client::ClientPool::new();
+
+        // Tasks on any worker thread get the client of the pool they were handed
+        runtime.block_on(async {
+            let tasks: Vec<_> = (0..4)
+                .map(|_| {
+                    let pool = pool.clone();
+                    tokio::spawn(async move { pool.get_for_host("localhost:3000").map(|_| ()) })
+                })
+                .collect();
+            for task in tasks {
+                task.await.unwrap()?;
+            }
+            Ok::<(), OxenError>(())
+        })?;
+        assert_eq!(pool.clients.lock().unwrap().len(), 1);
+
+        pool.get_for_url("http://localhost:3001/api/repos")?;
+        assert_eq!(pool.clients.lock().unwrap().len(), 2);
+
+        // Another pool, like the one of another remote repository, has its own clients
+        assert!(client::ClientPool::new().clients.lock().unwrap().is_empty());
+        Ok(())
+    }
+}
@@ -1,13 +1,17 @@
-use crate::api::remote::client;
+use crate::api::remote::{client, transfer};
 use crate::constants::HISTORY_DIR;
 use crate::error::OxenError;
+use crate::index::HistoryPack;
 use crate::model::commit::CommitWithSize;
-use crate::model::{Commit, LocalRepository, RemoteRepository};
+use crate::model::{Commit, CommitEntry, LocalRepository, RemoteRepository, UploadSession};
 use crate::util;
-use crate::util::hasher::hash_buffer;
+use crate::util::hasher::{hash_buffer, hash_str};
 use crate::{api, constants};
 // use crate::util::ReadProgress;
-use crate::view::{CommitParentsResponse, CommitResponse, IsValidStatusMessage, StatusMessage};
+use crate::view::{
+    CommitParentsResponse, CommitResponse, IsValidStatusMessage, StatusMessage,
+    UploadSessionResponse,
+};
 
 use std::path::Path;
 use std::str;
@@ -19,13 +23,37 @@ use async_tar::Archive;
 use bytesize::ByteSize;
 use flate2::write::GzEncoder;
 use flate2::Compression;
-use futures_util::TryStreamExt;
 use indicatif::ProgressBar;
 
+// Uploads of a tarball or chunk time out after this long unless the user config sets a timeout
+const UPLOAD_TIMEOUT_SECS: u64 = 120;
+
+/// Rate limited uploads wait on each other for longer than an upload normally takes,
+/// so they only time out if the user config sets a timeout
+fn upload_timeout() -> Option<time::Duration> {
+    match client::transfer_config().timeout_secs {
+        Some(timeout_secs) => Some(time::Duration::from_secs(timeout_secs)),
+        None if transfer::is_limited() => None,
+        None => Some(time::Duration::from_secs(UPLOAD_TIMEOUT_SECS)),
+    }
+}
+
 pub struct ChunkParams {
     pub chunk_num: usize,
     pub total_chunks: usize,
     pub total_size: usize,
+    pub content_hash: String,
+}
+
+/// Identifies an upload by the commit and the entries it carries, or the history dbs of the commit if there are
+/// no entries. Unlike the hash of the data, it stays the same when an interrupted push rebuilds the tarball.
+pub fn upload_id(commit: &Commit, entries: &[CommitEntry]) -> String {
+    let mut lines: Vec<String> = entries
+        .iter()
+        .map(|entry| format!("{}:{}", entry.path.to_string_lossy(), entry.hash))
+        .collect();
+    lines.sort();
+    hash_str(format!("{}\n{}", commit.id, lines.join("\n")))
 }
 
 pub async fn get_by_id(
@@ -36,7 +64,7 @@ pub async fn get_by_id(
     let url = api::endpoint::url_from_repo(repository, &uri)?;
     log::debug!("remote::commits::get_by_id {}", url);
 
-    let client = client::new_for_url(&url)?;
+    let client = repository.client_pool.get_for_url(&url)?;
     if let Ok(res) = client.get(&url).send().await {
         if res.status() == 404 {
             return Ok(None);
@@ -64,7 +92,7 @@ pub async fn commit_is_synced(
     let url = api::endpoint::url_from_repo(remote_repo, &uri)?;
     log::debug!("commit_is_synced checking URL: {}", url);
 
-    let client = client::new_for_url(&url)?;
+    let client = remote_repo.client_pool.get_for_url(&url)?;
     if let Ok(res) = client.get(&url).send().await {
         log::debug!("commit_is_synced Got response [{}]", res.status());
         if res.status() == 404 {
@@ -96,15 +124,12 @@ pub async fn download_commit_db_by_id(
     let uri = format!("/commits/{commit_id}/commit_db");
     let url = api::endpoint::url_from_repo(remote_repo, &uri)?;
 
-    let client = client::new_for_url(&url)?;
+    let client = remote_repo.client_pool.get_for_url(&url)?;
     if let Ok(res) = client.get(url).send().await {
         // Unpack tarball to our hidden dir
         let hidden_dir = util::fs::oxen_hidden_dir(&local_repo.path);
 
-        let reader = res
-            .bytes_stream()
-            .map_err(|e| futures::io::Error::new(futures::io::ErrorKind::Other, e))
-            .into_async_read();
+        let reader = transfer::body_reader(res);
         let decoder = GzipDecoder::new(futures::io::BufReader::new(reader));
         let archive = Archive::new(decoder);
         archive.unpack(hidden_dir).await?;
@@ -124,7 +149,7 @@ pub async fn get_remote_parent(
     let uri = format!("/commits/{commit_id}/parents");
     let url = api::endpoint::url_from_repo(remote_repo, &uri)?;
 
-    let client = client::new_for_url(&url)?;
+    let client = remote_repo.client_pool.get_for_url(&url)?;
     if let Ok(res) = client.get(&url).send().await {
         let body = client::parse_json_body(&url, res).await?;
         let response: Result<CommitParentsResponse, serde_json::Error> =
@@ -148,7 +173,7 @@ pub async fn post_push_complete(
     let url = api::endpoint::url_from_repo(remote_repo, &uri)?;
     log::debug!("post_push_complete: {}", url);
 
-    let client = client::new_for_url(&url)?;
+    let client = remote_repo.client_pool.get_for_url(&url)?;
     if let Ok(res) = client.post(&url).send().await {
         let body = client::parse_json_body(&url, res).await?;
         let response: Result<StatusMessage, serde_json::Error> = serde_json::from_str(&body);
@@ -169,6 +194,11 @@ pub async fn post_commit_to_server(
     commit: &Commit,
     unsynced_entries_size: u64,
 ) -> Result<(), OxenError> {
+    // The server expects the history dbs on disk, not in our pack
+    if HistoryPack::is_packed(local_repo, &commit.id) {
+        HistoryPack::new(local_repo).unpack_commit(&commit.id)?;
+    }
+
     // Compute the size of the commit
     let commit_history_dir = util::fs::oxen_hidden_dir(&local_repo.path)
         .join(HISTORY_DIR)
@@ -206,7 +236,17 @@ pub async fn post_commit_to_server(
 
     let is_compressed = true;
     let filename = None;
-    post_data_to_server(remote_repo, commit, buffer, is_compressed, &filename, bar).await
+    let upload_id = upload_id(commit, &[]);
+    post_data_to_server(
+        remote_repo,
+        commit,
+        &upload_id,
+        buffer,
+        is_compressed,
+        &filename,
+        bar,
+    )
+    .await
 }
 
 async fn create_commit_obj_on_server(
@@ -217,7 +257,7 @@ async fn create_commit_obj_on_server(
     let body = serde_json::to_string(&commit).unwrap();
     log::debug!("create_commit_obj_on_server {}\n{}", url, body);
 
-    let client = client::new_for_url(&url)?;
+    let client = remote_repo.client_pool.get_for_url(&url)?;
     if let Ok(res) = client
         .post(&url)
         .body(reqwest::Body::from(body))
@@ -240,9 +280,12 @@ async fn create_commit_obj_on_server(
     }
 }
 
+/// Sends data to be unpacked into the remote's hidden dir, in resumable chunks if it is large.
+/// `upload_id` is what the server tracks the chunks by, see `upload_id`.
 pub async fn post_data_to_server(
     remote_repo: &RemoteRepository,
     commit: &Commit,
+    upload_id: &str,
     buffer: Vec<u8>,
     is_compressed: bool,
     filename: &Option<String>,
@@ -253,8 +296,8 @@ pub async fn post_data_to_server(
         upload_data_to_server_in_chunks(
             remote_repo,
             commit,
+            upload_id,
             &buffer,
-            chunk_size,
             is_compressed,
             filename,
             bar,
@@ -272,27 +315,11 @@ pub async fn upload_single_tarball_to_server_with_retry(
     buffer: &[u8],
     bar: Arc<ProgressBar>,
 ) -> Result<(), OxenError> {
-    let mut total_tries = 0;
-    while total_tries < constants::NUM_HTTP_RETRIES {
-        match upload_single_tarball_to_server(remote_repo, commit, buffer, bar.to_owned()).await {
-            Ok(_) => {
-                return Ok(());
-            }
-            Err(err) => {
-                total_tries += 1;
-                // Exponentially back off
-                let sleep_time = total_tries * total_tries;
-                log::debug!(
-                    "upload_single_tarball_to_server_with_retry upload failed sleeping {}: {:?}",
-                    sleep_time,
-                    err
-                );
-                std::thread::sleep(std::time::Duration::from_secs(sleep_time));
-            }
-        }
-    }
-
-    Err(OxenError::basic_str("Upload retry failed."))
+    client::with_retry_for_key("Upload tarball", &commit.id, || {
+        upload_single_tarball_to_server(remote_repo, commit, buffer, bar.to_owned())
+    })
+    .await?;
+    Ok(())
 }
 
 async fn upload_single_tarball_to_server(
@@ -304,12 +331,16 @@ async fn upload_single_tarball_to_server(
     let uri = format!("/commits/{}/data", commit.id);
     let url = api::endpoint::url_from_repo(remote_repo, &uri)?;
 
-    let client = client::builder_for_url(&url)?
-        .timeout(time::Duration::from_secs(120))
-        .build()?;
+    let client = remote_repo.client_pool.get_for_url(&url)?;
 
     let size = buffer.len() as u64;
-    match client.post(&url).body(buffer.to_owned()).send().await {
+    let mut request = client
+        .post(&url)
+        .body(transfer::upload_body(buffer.to_owned()));
+    if let Some(timeout) = upload_timeout() {
+        request = request.timeout(timeout);
+    }
+    match request.send().await {
         Ok(res) => {
             let body = client::parse_json_body(&url, res).await?;
 
@@ -335,8 +366,8 @@ async fn upload_single_tarball_to_server(
 async fn upload_data_to_server_in_chunks(
     remote_repo: &RemoteRepository,
     commit: &Commit,
+    hash: &str,
     buffer: &[u8],
-    chunk_size: usize,
     is_compressed: bool,
     filename: &Option<String>,
     bar: Arc<ProgressBar>,
@@ -346,15 +377,42 @@ async fn upload_data_to_server_in_chunks(
         "upload_data_to_server_in_chunks chunking data {} ...",
         total_size
     );
-    let chunks: Vec<&[u8]> = buffer.chunks(chunk_size).collect();
-    let hash = hash_buffer(buffer);
+    let chunks: Vec<&[u8]> = buffer.chunks(constants::AVG_CHUNK_SIZE as usize).collect();
+    let content_hash = hash_buffer(buffer);
     log::debug!(
         "upload_data_to_server_in_chunks got {} chunks from {}",
         chunks.len(),
         ByteSize::b(total_size as u64)
     );
 
+    // If a previous push was interrupted part way through, only send what the server is missing,
+    // as long as the data came out the same this time
+    let upload = UploadSession::new(
+        hash,
+        &commit.id,
+        Some(&content_hash),
+        chunks.len(),
+        total_size,
+    );
+    let missing_chunks = match get_upload_session(remote_repo, hash).await? {
+        Some(session) if session.has_same_contents(&upload) => {
+            log::debug!(
+                "upload_data_to_server_in_chunks resuming upload {} with {} of {} chunks",
+                hash,
+                session.received_chunks.len(),
+                session.total_chunks
+            );
+            session.missing_chunks()
+        }
+        _ => (0..chunks.len()).collect(),
+    };
+
     for (i, chunk) in chunks.iter().enumerate() {
+        if missing_chunks.binary_search(&i).is_err() {
+            bar.inc(chunk.len() as u64);
+            continue;
+        }
+
         log::debug!(
             "upload_data_to_server_in_chunks uploading chunk {} of size {}",
             i,
@@ -365,28 +423,55 @@ async fn upload_data_to_server_in_chunks(
             chunk_num: i,
             total_chunks: chunks.len(),
             total_size,
+            content_hash: content_hash.to_owned(),
         };
-        match upload_data_chunk_to_server_with_retry(
+        upload_data_chunk_to_server_with_retry(
             remote_repo,
             commit,
             chunk,
-            &hash,
+            hash,
             &params,
             is_compressed,
             filename,
         )
-        .await
-        {
-            Ok(_) => {
-                log::debug!("Success uploading chunk!")
+        .await?;
+        log::debug!("Success uploading chunk!");
+        bar.inc(chunk.len() as u64)
+    }
+    Ok(())
+}
+
+/// The pieces of an upload the server has received so far, None if it has not seen the upload
+pub async fn get_upload_session(
+    remote_repo: &RemoteRepository,
+    hash: &str,
+) -> Result<Option<UploadSession>, OxenError> {
+    let uri = format!("/uploads/{hash}");
+    let url = api::endpoint::url_from_repo(remote_repo, &uri)?;
+    log::debug!("get_upload_session {}", url);
+
+    let client = remote_repo.client_pool.get_for_url(&url)?;
+    match client.get(&url).send().await {
+        Ok(res) => {
+            if res.status() == 404 {
+                return Ok(None);
             }
-            Err(err) => {
-                log::error!("Err uploading chunk: {}", err)
+
+            let body = client::parse_json_body(&url, res).await?;
+            let response: Result<UploadSessionResponse, serde_json::Error> =
+                serde_json::from_str(&body);
+            match response {
+                Ok(response) => Ok(Some(response.session)),
+                Err(err) => Err(OxenError::basic_str(format!(
+                    "get_upload_session() Could not deserialize response [{err}]\n{body}"
+                ))),
             }
         }
-        bar.inc(chunk.len() as u64)
+        Err(err) => {
+            let err = format!("get_upload_session() Request failed: {err}");
+            Err(OxenError::basic_str(err))
+        }
     }
-    Ok(())
 }
 
 pub async fn upload_data_chunk_to_server_with_retry(
@@ -398,9 +483,10 @@ pub async fn upload_data_chunk_to_server_with_retry(
     is_compressed: bool,
     filename: &Option<String>,
 ) -> Result<(), OxenError> {
-    let mut total_tries = 0;
-    while total_tries < constants::NUM_HTTP_RETRIES {
-        match upload_data_chunk_to_server(
+    let description = format!("Upload chunk {} of {}", params.chunk_num, hash);
+    let key = format!("{}/{}", hash, params.chunk_num);
+    client::with_retry_for_key(&description, &key, || {
+        upload_data_chunk_to_server(
             remote_repo,
             commit,
             chunk,
@@ -409,26 +495,9 @@ pub async fn upload_data_chunk_to_server_with_retry(
             is_compressed,
             filename,
         )
-        .await
-        {
-            Ok(_) => {
-                return Ok(());
-            }
-            Err(err) => {
-                total_tries += 1;
-                // Exponentially back off
-                let sleep_time = total_tries * total_tries;
-                log::debug!(
-                    "upload_data_chunk_to_server_with_retry upload failed sleeping {}: {:?}",
-                    sleep_time,
-                    err
-                );
-                std::thread::sleep(std::time::Duration::from_secs(sleep_time));
-            }
-        }
-    }
-
-    Err(OxenError::basic_str("Upload chunk retry failed."))
+    })
+    .await?;
+    Ok(())
 }
 
 async fn upload_data_chunk_to_server(
@@ -454,8 +523,8 @@ async fn upload_data_chunk_to_server(
     };
 
     let uri = format!(
-        "/commits/{}/upload_chunk?chunk_num={}&total_size={}&hash={}&total_chunks={}&is_compressed={}{}",
-        commit.id, params.chunk_num, params.total_size, hash, params.total_chunks, is_compressed, maybe_filename
+        "/commits/{}/upload_chunk?chunk_num={}&total_size={}&hash={}&content_hash={}&total_chunks={}&is_compressed={}{}",
+        commit.id, params.chunk_num, params.total_size, hash, params.content_hash, params.total_chunks, is_compressed, maybe_filename
     );
     let url = api::endpoint::url_from_repo(remote_repo, &uri)?;
     let total_size = chunk.len() as u64;
@@ -465,11 +534,15 @@ async fn upload_data_chunk_to_server(
         url
     );
 
-    let client = client::builder_for_url(&url)?
-        .timeout(time::Duration::from_secs(120))
-        .build()?;
+    let client = remote_repo.client_pool.get_for_url(&url)?;
 
-    match client.post(&url).body(chunk.to_owned()).send().await {
+    let mut request = client
+        .post(&url)
+        .body(transfer::upload_body(chunk.to_owned()));
+    if let Some(timeout) = upload_timeout() {
+        request = request.timeout(timeout);
+    }
+    match request.send().await {
         Ok(res) => {
             let body = client::parse_json_body(&url, res).await?;
 
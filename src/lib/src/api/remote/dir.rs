@@ -14,7 +14,7 @@ pub async fn list_dir(
     let uri = format!("/dir/{commit_or_branch}/{path}?page={page}&page_size={page_size}");
     let url = api::endpoint::url_from_repo(remote_repo, &uri)?;
 
-    let client = client::new_for_url(&url)?;
+    let client = remote_repo.client_pool.get_for_url(&url)?;
     if let Ok(res) = client.get(&url).send().await {
         let body = client::parse_json_body(&url, res).await?;
         // log::debug!("list_page got body: {}", body);
@@ -1,11 +1,12 @@
-use crate::api::remote::client;
+use crate::api;
+use crate::api::remote::{client, transfer};
 use crate::constants::AVG_CHUNK_SIZE;
 use crate::error::OxenError;
+use crate::index::ChunkStore;
 use crate::model::{CommitEntry, LocalRepository, RemoteEntry, RemoteRepository};
 use crate::util;
-use crate::{api, constants};
 // use crate::util::ReadProgress;
-use crate::view::RemoteEntryResponse;
+use crate::view::{CommitEntries, CommitEntriesResponse, RemoteEntryResponse};
 
 // use flate2::read::GzDecoder;
 use async_compression::futures::bufread::GzipDecoder;
@@ -13,8 +14,8 @@ use async_std::prelude::*;
 use async_tar::Archive;
 use flate2::write::GzEncoder;
 use flate2::Compression;
-use futures_util::TryStreamExt;
 use indicatif::ProgressBar;
+use reqwest::Method;
 use std::fs;
 use std::io::prelude::*;
 use std::io::Cursor;
@@ -27,21 +28,23 @@ pub async fn create(
     remote_repo: &RemoteRepository,
     entry: &CommitEntry,
 ) -> Result<RemoteEntry, OxenError> {
-    let fullpath = util::fs::version_path(local_repo, entry);
+    // Held until the request is done, in case it was rebuilt from chunks
+    let version_file = ChunkStore::new(local_repo).version_file(entry)?;
+    let fullpath = version_file.path();
     log::debug!("Creating remote entry: {:?} -> {:?}", entry.path, fullpath);
 
     if !fullpath.exists() {
         return Err(OxenError::file_does_not_exist(fullpath));
     }
 
-    let file = tokio::fs::File::open(&fullpath).await?;
+    let file = tokio::fs::File::open(fullpath).await?;
     let stream = FramedRead::new(file, BytesCodec::new());
     let body = reqwest::Body::wrap_stream(stream);
 
     let uri = format!("/entries?{}", entry.to_uri_encoded());
     let url = api::endpoint::url_from_repo(remote_repo, &uri)?;
     log::debug!("create entry: {}", url);
-    let client = client::new_for_url(&url)?;
+    let client = remote_repo.client_pool.get_for_url(&url)?;
     match client.post(&url).body(body).send().await {
         Ok(res) => {
             let body = client::parse_json_body(&url, res).await?;
@@ -62,6 +65,37 @@ pub async fn create(
     }
 }
 
+/// Asks the remote which of the entries it does not have the versions of yet, so a resumed push only sends those
+pub async fn list_missing_versions(
+    remote_repo: &RemoteRepository,
+    entries: &[CommitEntry],
+) -> Result<Vec<CommitEntry>, OxenError> {
+    let url = api::endpoint::url_from_repo(remote_repo, "/versions/missing")?;
+    let params = serde_json::to_string(&CommitEntries {
+        entries: entries.to_vec(),
+    })?;
+
+    let client = remote_repo.client_pool.get_for_url(&url)?;
+    match client.post(&url).body(params).send().await {
+        Ok(res) => {
+            let body = client::parse_json_body(&url, res).await?;
+            let response: Result<CommitEntriesResponse, serde_json::Error> =
+                serde_json::from_str(&body);
+            match response {
+                Ok(response) => Ok(response.entries),
+                Err(err) => Err(OxenError::basic_str(format!(
+                    "api::entries::list_missing_versions error parsing response from {url}\n\nErr {err:?} \n\n{body}"
+                ))),
+            }
+        }
+        Err(err) => {
+            let err =
+                format!("api::entries::list_missing_versions Request failed: {url}\nErr {err:?}");
+            Err(OxenError::basic_str(err))
+        }
+    }
+}
+
 pub async fn download_entries(
     local_repo: &LocalRepository,
     remote_repo: &RemoteRepository,
@@ -71,14 +105,11 @@ pub async fn download_entries(
 ) -> Result<(), OxenError> {
     let uri = format!("/commits/{commit_id}/download_entries?page={page}&page_size={page_size}");
     let url = api::endpoint::url_from_repo(remote_repo, &uri)?;
-    let client = client::new_for_url(&url)?;
+    let client = remote_repo.client_pool.get_for_url(&url)?;
     if let Ok(res) = client.get(&url).send().await {
         let status = res.status();
         if reqwest::StatusCode::OK == status {
-            let reader = res
-                .bytes_stream()
-                .map_err(|e| futures::io::Error::new(futures::io::ErrorKind::Other, e))
-                .into_async_read();
+            let reader = transfer::body_reader(res);
             let decoder = GzipDecoder::new(futures::io::BufReader::new(reader));
             let archive = Archive::new(decoder);
             archive.unpack(&local_repo.path).await?;
@@ -174,22 +205,11 @@ pub async fn download_large_entry(
         }
     }
 
-    // Copy to version path
-    let version_path = util::fs::version_path(local_repo, entry);
-    log::debug!("Copying to version path {:?}", version_path);
-    if let Some(parent) = version_path.parent() {
-        if !parent.exists() {
-            log::debug!("Creating parent {:?}", parent);
-            let err = format!("Could not create version dir path {parent:?}");
-            std::fs::create_dir_all(parent).expect(&err);
-        }
-    }
-    match std::fs::copy(&full_path, &version_path) {
-        Ok(_) => {}
-        Err(err) => {
-            let err = format!("Could not copy file {full_path:?} to {version_path:?}: {err}");
-            return Err(OxenError::basic_str(err));
-        }
+    // Store in the versions dir, as chunks if it is large
+    log::debug!("Storing version of {:?}", full_path);
+    if let Err(err) = ChunkStore::new(local_repo).put_version_file(entry, &full_path) {
+        let err = format!("Could not store version of {full_path:?}: {err}");
+        return Err(OxenError::basic_str(err));
     }
 
     Ok(())
@@ -202,22 +222,13 @@ async fn try_download_entry_chunk(
     chunk_start: u64,
     chunk_size: u64,
 ) -> Result<(), OxenError> {
-    let mut try_num = 0;
-    while try_num < constants::NUM_HTTP_RETRIES {
-        match download_entry_chunk(remote_repo, entry, dest, chunk_start, chunk_size).await {
-            Ok(_) => {
-                log::debug!("Downloaded chunk {:?}", dest);
-                return Ok(());
-            }
-            Err(err) => {
-                log::error!("Error trying to download chunk: {}", err);
-                try_num += 1;
-                let sleep_time = try_num * try_num;
-                std::thread::sleep(std::time::Duration::from_secs(sleep_time));
-            }
-        }
-    }
-    Err(OxenError::basic_str("Retry download chunk failed"))
+    let description = format!("Download chunk of {:?}", entry.path);
+    client::with_retry(&description, &Method::GET, || {
+        download_entry_chunk(remote_repo, entry, dest, chunk_start, chunk_size)
+    })
+    .await?;
+    log::debug!("Downloaded chunk {:?}", dest);
+    Ok(())
 }
 
 /// Downloads a chunk of a file
@@ -240,7 +251,7 @@ async fn download_entry_chunk(
 
     log::debug!("download_entry_chunk {}", url);
 
-    let client = client::new_for_url(&url)?;
+    let client = remote_repo.client_pool.get_for_url(&url)?;
     let response = client.get(&url).send().await?;
 
     if let Some(parent) = dest.parent() {
@@ -254,7 +265,7 @@ async fn download_entry_chunk(
     if reqwest::StatusCode::OK == status {
         // Copy to file
         let mut dest = { fs::File::create(dest)? };
-        let mut content = Cursor::new(response.bytes().await?);
+        let mut content = Cursor::new(transfer::body_bytes(response).await?);
         std::io::copy(&mut content, &mut dest)?;
         Ok(())
     } else {
@@ -268,33 +279,11 @@ pub async fn download_data_from_version_paths(
     remote_repo: &RemoteRepository,
     content_ids: &[String],
 ) -> Result<u64, OxenError> {
-    let total_retries = constants::NUM_HTTP_RETRIES;
-    let mut num_retries = 0;
-
-    while num_retries < total_retries {
-        match try_download_data_from_version_paths(local_repo, remote_repo, content_ids).await {
-            Ok(val) => return Ok(val),
-            Err(OxenError::Authentication(val)) => return Err(OxenError::Authentication(val)),
-            Err(err) => {
-                num_retries += 1;
-                // Exponentially back off
-                let sleep_time = num_retries * num_retries;
-                log::warn!(
-                    "Could not download content {:?} sleeping {}",
-                    err,
-                    sleep_time
-                );
-                std::thread::sleep(std::time::Duration::from_secs(sleep_time));
-            }
-        }
-    }
-
-    let err = format!(
-        "Err: Failed to download {} files after {} retries",
-        content_ids.len(),
-        total_retries
-    );
-    Err(OxenError::basic_str(err))
+    let description = format!("Download {} files", content_ids.len());
+    client::with_retry(&description, &Method::GET, || {
+        try_download_data_from_version_paths(local_repo, remote_repo, content_ids)
+    })
+    .await
 }
 
 pub async fn try_download_data_from_version_paths(
@@ -310,7 +299,7 @@ pub async fn try_download_data_from_version_paths(
     let body = encoder.finish()?;
     let url = api::endpoint::url_from_repo(remote_repo, "/versions")?;
 
-    let client = client::new_for_url(&url)?;
+    let client = remote_repo.client_pool.get_for_url(&url)?;
     if let Ok(res) = client.get(&url).body(body).send().await {
         if reqwest::StatusCode::UNAUTHORIZED == res.status() {
             let err = "Err: unauthorized request to download data".to_string();
@@ -318,10 +307,7 @@ pub async fn try_download_data_from_version_paths(
             return Err(OxenError::authentication(err));
         }
 
-        let reader = res
-            .bytes_stream()
-            .map_err(|e| futures::io::Error::new(futures::io::ErrorKind::Other, e))
-            .into_async_read();
+        let reader = transfer::body_reader(res);
         let decoder = GzipDecoder::new(futures::io::BufReader::new(reader));
         let archive = Archive::new(decoder);
 
@@ -385,20 +371,11 @@ pub async fn download_entry(
     if reqwest::StatusCode::OK == status {
         // Copy to working dir
         let mut dest = { fs::File::create(&fpath)? };
-        let mut content = Cursor::new(response.bytes().await?);
+        let mut content = Cursor::new(transfer::body_bytes(response).await?);
         std::io::copy(&mut content, &mut dest)?;
 
-        // Copy to versions dir
-        let version_path = util::fs::version_path(repository, entry);
-
-        if let Some(parent) = version_path.parent() {
-            if !parent.exists() {
-                log::debug!("Create version parent dir {:?}", parent);
-                std::fs::create_dir_all(parent)?;
-            }
-        }
-
-        std::fs::copy(fpath, version_path)?;
+        // Store in the versions dir, as chunks if it is large
+        ChunkStore::new(repository).put_version_file(entry, &fpath)?;
     } else {
         let err = format!("Could not download entry status: {status}");
         return Err(OxenError::basic_str(err));
@@ -82,7 +82,7 @@ pub async fn delete(repository: &RemoteRepository) -> Result<StatusMessage, Oxen
     let url = api::endpoint::url_from_repo(repository, "")?;
     log::debug!("Deleting repository: {}", url);
 
-    let client = client::new_for_url(&url)?;
+    let client = repository.client_pool.get_for_url(&url)?;
     if let Ok(res) = client.delete(&url).send().await {
         let body = client::parse_json_body(&url, res).await?;
         let response: Result<StatusMessage, serde_json::Error> = serde_json::from_str(&body);
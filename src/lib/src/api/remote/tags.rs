@@ -0,0 +1,113 @@
+use crate::api;
+use crate::api::remote::client;
+use crate::error::OxenError;
+use crate::model::{RemoteRepository, Tag};
+use crate::view::{ListTagsResponse, TagResponse};
+
+pub async fn get_by_name(
+    repository: &RemoteRepository,
+    tag_name: &str,
+) -> Result<Option<Tag>, OxenError> {
+    let uri = format!("/tags/{tag_name}");
+    let url = api::endpoint::url_from_repo(repository, &uri)?;
+
+    let client = repository.client_pool.get_for_url(&url)?;
+    if let Ok(res) = client.get(&url).send().await {
+        if 404 == res.status() {
+            return Ok(None);
+        }
+
+        let body = client::parse_json_body(&url, res).await?;
+        let response: Result<TagResponse, serde_json::Error> = serde_json::from_str(&body);
+        match response {
+            Ok(j_res) => Ok(Some(j_res.tag)),
+            Err(err) => {
+                log::debug!(
+                    "remote::tags::get_by_name() Could not deserialize response [{}] {}",
+                    err,
+                    body
+                );
+                Ok(None)
+            }
+        }
+    } else {
+        let err = "Failed to get tag";
+        log::error!("remote::tags::get_by_name() err: {}", err);
+        Err(OxenError::basic_str(err))
+    }
+}
+
+pub async fn list(repository: &RemoteRepository) -> Result<Vec<Tag>, OxenError> {
+    let url = api::endpoint::url_from_repo(repository, "/tags")?;
+
+    let client = repository.client_pool.get_for_url(&url)?;
+    if let Ok(res) = client.get(&url).send().await {
+        let body = client::parse_json_body(&url, res).await?;
+        let response: Result<ListTagsResponse, serde_json::Error> = serde_json::from_str(&body);
+        match response {
+            Ok(j_res) => Ok(j_res.tags),
+            Err(err) => {
+                log::debug!(
+                    "remote::tags::list() Could not deserialize response [{}] {}",
+                    err,
+                    body
+                );
+                Err(OxenError::basic_str("Could not list remote tags"))
+            }
+        }
+    } else {
+        let err = "Failed to list tags";
+        log::error!("remote::tags::list() err: {}", err);
+        Err(OxenError::basic_str(err))
+    }
+}
+
+/// Creates the tag on the remote, the commit must have been pushed first. The server accepts the same tag twice
+/// but rejects a tag that already exists for another commit.
+pub async fn create(repository: &RemoteRepository, tag: &Tag) -> Result<Tag, OxenError> {
+    let url = api::endpoint::url_from_repo(repository, "/tags")?;
+    log::debug!("remote::tags::create {} -> {}", url, tag.name);
+
+    let params = serde_json::to_string(tag)?;
+
+    let client = repository.client_pool.get_for_url(&url)?;
+    if let Ok(res) = client.post(&url).body(params).send().await {
+        let body = client::parse_json_body(&url, res).await?;
+        let response: Result<TagResponse, serde_json::Error> = serde_json::from_str(&body);
+        match response {
+            Ok(response) => Ok(response.tag),
+            Err(err) => {
+                let err = format!(
+                    "Could not create tag [{}]: {}\n{}",
+                    repository.name, err, body
+                );
+                Err(OxenError::basic_str(err))
+            }
+        }
+    } else {
+        let msg = format!("Could not create tag {}", tag.name);
+        log::error!("remote::tags::create() {}", msg);
+        Err(OxenError::basic_str(&msg))
+    }
+}
+
+/// Tags are immutable on the server, deleting one needs admin access to the repository
+pub async fn force_delete(repository: &RemoteRepository, tag_name: &str) -> Result<Tag, OxenError> {
+    let uri = format!("/tags/{tag_name}?force=true");
+    let url = api::endpoint::url_from_repo(repository, &uri)?;
+    log::debug!("Deleting tag: {}", url);
+
+    let client = repository.client_pool.get_for_url(&url)?;
+    if let Ok(res) = client.delete(&url).send().await {
+        let body = client::parse_json_body(&url, res).await?;
+        let response: Result<TagResponse, serde_json::Error> = serde_json::from_str(&body);
+        match response {
+            Ok(response) => Ok(response.tag),
+            Err(_) => Err(OxenError::basic_str(format!(
+                "could not delete tag \n\n{body}"
+            ))),
+        }
+    } else {
+        Err(OxenError::basic_str("api::tags::delete() Request failed"))
+    }
+}
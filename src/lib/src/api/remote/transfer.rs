@@ -0,0 +1,286 @@
+//! # Transfer
+//!
+//! Bandwidth limit and stats shared by all the workers of a push, pull or clone.
+//!
+//! Every upload waits for its turn in the rate limiter before it is sent, and every download is
+//! counted as it arrives, so the limit holds across the parallel chunk and tarball workers.
+
+use bytes::Bytes;
+use futures::io::AsyncRead;
+use futures::{Stream, StreamExt, TryStreamExt};
+use lazy_static::lazy_static;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::error::OxenError;
+use crate::model::TransferReport;
+
+// Bodies are throttled in pieces of this size so a large upload does not go out in one burst
+const PIECE_SIZE: usize = 64 * 1024;
+
+lazy_static! {
+    static ref LIMITER: RateLimiter = RateLimiter::new(None);
+    static ref STATS: TransferStats = TransferStats::new();
+}
+
+/// Spaces out transfers so that on average no more than `bytes_per_sec` go over the wire
+pub struct RateLimiter {
+    // The limit, and when the bytes already let through will have gone at that rate
+    state: Mutex<(Option<u64>, Instant)>,
+}
+
+impl RateLimiter {
+    pub fn new(bytes_per_sec: Option<u64>) -> RateLimiter {
+        RateLimiter {
+            state: Mutex::new((bytes_per_sec, Instant::now())),
+        }
+    }
+
+    pub fn rate(&self) -> Option<u64> {
+        self.state.lock().unwrap().0
+    }
+
+    pub fn set_rate(&self, bytes_per_sec: Option<u64>) {
+        *self.state.lock().unwrap() = (bytes_per_sec, Instant::now());
+    }
+
+    /// Books the bytes and returns how long to wait until they would have gone at the rate
+    pub fn reserve(&self, num_bytes: u64) -> Duration {
+        let mut state = self.state.lock().unwrap();
+        let bytes_per_sec = match state.0 {
+            Some(bytes_per_sec) if bytes_per_sec > 0 => bytes_per_sec,
+            _ => return Duration::ZERO,
+        };
+
+        // Time that went unused is not saved up, or a burst could go over the limit
+        let now = Instant::now();
+        let start = state.1.max(now);
+        state.1 = start + Duration::from_secs_f64(num_bytes as f64 / bytes_per_sec as f64);
+        state.1 - now
+    }
+
+    pub async fn throttle(&self, num_bytes: u64) {
+        let wait = self.reserve(num_bytes);
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// Running totals of a transfer
+pub struct TransferStats {
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+    files: AtomicU64,
+    retries: AtomicU64,
+    started_at: Mutex<Instant>,
+}
+
+impl TransferStats {
+    pub fn new() -> TransferStats {
+        TransferStats {
+            bytes_sent: AtomicU64::new(0),
+            bytes_received: AtomicU64::new(0),
+            files: AtomicU64::new(0),
+            retries: AtomicU64::new(0),
+            started_at: Mutex::new(Instant::now()),
+        }
+    }
+
+    pub fn reset(&self) {
+        self.bytes_sent.store(0, Ordering::SeqCst);
+        self.bytes_received.store(0, Ordering::SeqCst);
+        self.files.store(0, Ordering::SeqCst);
+        self.retries.store(0, Ordering::SeqCst);
+        *self.started_at.lock().unwrap() = Instant::now();
+    }
+
+    pub fn add_sent(&self, num_bytes: u64) {
+        self.bytes_sent.fetch_add(num_bytes, Ordering::SeqCst);
+    }
+
+    pub fn add_received(&self, num_bytes: u64) {
+        self.bytes_received.fetch_add(num_bytes, Ordering::SeqCst);
+    }
+
+    pub fn add_files(&self, num_files: u64) {
+        self.files.fetch_add(num_files, Ordering::SeqCst);
+    }
+
+    pub fn add_retry(&self) {
+        self.retries.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub fn report(&self, limit_rate: Option<u64>) -> TransferReport {
+        let bytes_sent = self.bytes_sent.load(Ordering::SeqCst);
+        let bytes_received = self.bytes_received.load(Ordering::SeqCst);
+        let elapsed_secs = self.started_at.lock().unwrap().elapsed().as_secs_f64();
+        let throughput_bytes_per_sec = if elapsed_secs > 0.0 {
+            ((bytes_sent + bytes_received) as f64 / elapsed_secs) as u64
+        } else {
+            0
+        };
+
+        TransferReport {
+            bytes_sent,
+            bytes_received,
+            files: self.files.load(Ordering::SeqCst),
+            retries: self.retries.load(Ordering::SeqCst),
+            elapsed_secs,
+            throughput_bytes_per_sec,
+            limit_rate_bytes_per_sec: limit_rate,
+        }
+    }
+}
+
+impl Default for TransferStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parses a rate like `20MB/s`, `512KiB` or `1000`, in bytes per second
+pub fn parse_rate<S: AsRef<str>>(rate: S) -> Result<u64, OxenError> {
+    let rate = rate.as_ref().trim();
+    let size = rate.strip_suffix("/s").unwrap_or(rate).trim();
+    match size.parse::<bytesize::ByteSize>() {
+        Ok(size) if size.as_u64() > 0 => Ok(size.as_u64()),
+        _ => Err(OxenError::basic_str(format!(
+            "Invalid rate '{rate}', expected a size per second like 20MB/s"
+        ))),
+    }
+}
+
+/// Limits all the transfers of this process to the rate, or lifts the limit with None
+pub fn limit_rate(bytes_per_sec: Option<u64>) {
+    LIMITER.set_rate(bytes_per_sec);
+}
+
+/// Starts counting a new transfer
+pub fn reset_stats() {
+    STATS.reset();
+}
+
+/// Totals since the stats were last reset
+pub fn report() -> TransferReport {
+    STATS.report(LIMITER.rate())
+}
+
+pub fn is_limited() -> bool {
+    LIMITER.rate().is_some()
+}
+
+/// Waits until the bytes can be sent within the rate limit and counts them
+pub async fn sent(num_bytes: u64) {
+    LIMITER.throttle(num_bytes).await;
+    STATS.add_sent(num_bytes);
+}
+
+/// Counts bytes that arrived, and waits if they went over the rate limit
+pub async fn received(num_bytes: u64) {
+    STATS.add_received(num_bytes);
+    LIMITER.throttle(num_bytes).await;
+}
+
+pub fn add_files(num_files: u64) {
+    STATS.add_files(num_files);
+}
+
+pub fn add_retry() {
+    STATS.add_retry();
+}
+
+/// A request body that is counted and throttled as it is sent
+pub fn upload_body(data: Vec<u8>) -> reqwest::Body {
+    let data = Bytes::from(data);
+    let pieces: Vec<Bytes> = (0..data.len())
+        .step_by(PIECE_SIZE)
+        .map(|start| data.slice(start..(start + PIECE_SIZE).min(data.len())))
+        .collect();
+    let stream = futures::stream::iter(pieces).then(|piece| async move {
+        sent(piece.len() as u64).await;
+        Ok::<Bytes, std::io::Error>(piece)
+    });
+    reqwest::Body::wrap_stream(stream)
+}
+
+/// Reads the body of a response, counting and throttling it as it streams in
+pub fn body_reader(res: reqwest::Response) -> impl AsyncRead + Unpin + Send {
+    let stream: Pin<Box<dyn Stream<Item = Result<Bytes, reqwest::Error>> + Send>> =
+        Box::pin(res.bytes_stream().then(|chunk| async move {
+            if let Ok(bytes) = &chunk {
+                received(bytes.len() as u64).await;
+            }
+            chunk
+        }));
+    stream.map_err(futures::io::Error::other).into_async_read()
+}
+
+/// Reads the whole body of a response, counting and throttling it as it streams in
+pub async fn body_bytes(res: reqwest::Response) -> Result<Vec<u8>, OxenError> {
+    let mut body: Vec<u8> = Vec::new();
+    let mut stream = res.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        received(chunk.len() as u64).await;
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::remote::transfer::{self, RateLimiter, TransferStats};
+    use crate::error::OxenError;
+    use std::time::Duration;
+
+    #[test]
+    fn test_transfer_parse_rate() -> Result<(), OxenError> {
+        assert_eq!(transfer::parse_rate("20MB/s")?, 20_000_000);
+        assert_eq!(transfer::parse_rate("512KiB")?, 512 * 1024);
+        assert_eq!(transfer::parse_rate("1000")?, 1000);
+        assert!(transfer::parse_rate("fast").is_err());
+        assert!(transfer::parse_rate("0MB/s").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_transfer_rate_limiter_spaces_out_bytes() {
+        let limiter = RateLimiter::new(Some(1000));
+        // 500 bytes take half a second at 1000 bytes per second
+        let wait = limiter.reserve(500);
+        assert!(wait > Duration::from_millis(400) && wait <= Duration::from_millis(500));
+
+        // Later bytes wait for the ones before them
+        let wait = limiter.reserve(1000);
+        assert!(wait > Duration::from_millis(1400) && wait <= Duration::from_millis(1500));
+        let wait = limiter.reserve(1000);
+        assert!(wait > Duration::from_millis(2400) && wait <= Duration::from_millis(2500));
+
+        limiter.set_rate(None);
+        assert_eq!(limiter.reserve(1_000_000), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_transfer_stats_report() {
+        let stats = TransferStats::new();
+        stats.add_sent(1000);
+        stats.add_received(24);
+        stats.add_files(2);
+        stats.add_retry();
+
+        let report = stats.report(Some(1000));
+        assert_eq!(report.bytes_sent, 1000);
+        assert_eq!(report.bytes_received, 24);
+        assert_eq!(report.files, 2);
+        assert_eq!(report.retries, 1);
+        assert_eq!(report.limit_rate_bytes_per_sec, Some(1000));
+
+        stats.reset();
+        let report = stats.report(None);
+        assert_eq!(report.bytes_sent, 0);
+        assert_eq!(report.files, 0);
+    }
+}
@@ -6,23 +6,27 @@
 use crate::api;
 use crate::compute;
 use crate::constants;
-use crate::df::{df_opts::DFOpts, tabular};
+use crate::df::{self, df_opts::DFOpts, tabular, DFDiffOpts};
 use crate::error::OxenError;
 use crate::index::SchemaIndexReader;
-use crate::index::{self, differ};
+use crate::index::{self, differ, schema_field_indexer};
 use crate::index::{
-    CommitDirReader, CommitReader, CommitWriter, EntryIndexer, MergeConflictReader, Merger,
-    RefReader, RefWriter, Stager,
+    ChunkStore, CommitDirReader, CommitReader, CommitWriter, EntryIndexer, GarbageCollector,
+    HistoryPack, MergeConflictReader, Merger, RefReader, RefWriter, Reverter, Stager, Stasher,
 };
 use crate::model::schema;
 use crate::model::Schema;
-use crate::model::{Branch, Commit, LocalRepository, RemoteBranch, RemoteRepository, StagedData};
+use crate::model::{
+    Branch, Commit, LocalRepository, MergeConflict, Remote, RemoteBranch, RemoteRepository,
+    SparseSpec, StagedData, Stash, Tag, TreeDiff,
+};
 
-use crate::opts::RestoreOpts;
+use crate::opts::{LogOpts, ResetMode, RestoreOpts};
 use crate::util;
 use crate::util::resource;
 
 use bytevec::ByteDecodable;
+use polars::prelude::{DataFrame, IntoLazy};
 use rocksdb::{IteratorMode, LogLevel, Options, DB};
 use std::path::Path;
 use std::str;
@@ -189,6 +193,15 @@ pub fn add<P: AsRef<Path>>(repo: &LocalRepository, path: P) -> Result<(), OxenEr
     Ok(())
 }
 
+/// # Stage a path even if it matches a pattern in an .oxenignore file
+pub fn add_force<P: AsRef<Path>>(repo: &LocalRepository, path: P) -> Result<(), OxenError> {
+    let stager = Stager::new_with_merge(repo)?;
+    let commit = head_commit(repo)?;
+    let reader = CommitDirReader::new(repo, &commit)?;
+    stager.add_force(path.as_ref(), &reader)?;
+    Ok(())
+}
+
 /// Removes the path from disk then adds it to the removed index
 pub fn rm<P: AsRef<Path>>(repo: &LocalRepository, path: P) -> Result<(), OxenError> {
     let path = path.as_ref();
@@ -217,6 +230,20 @@ pub fn df<P: AsRef<Path>>(input: P, opts: DFOpts) -> Result<(), OxenError> {
     Ok(())
 }
 
+/// Run a SQL query over versioned tabular files, see `df::sql` for how tables are referenced
+pub fn df_sql(repo: &LocalRepository, sql: &str, opts: DFOpts) -> Result<DataFrame, OxenError> {
+    let df = df::sql::query(repo, sql)?;
+    let mut df = tabular::transform_df(df.lazy(), opts.clone())?;
+    println!("{df}");
+
+    if let Some(output) = opts.output {
+        println!("Writing {output:?}");
+        tabular::write_df(&mut df, output)?;
+    }
+
+    Ok(df)
+}
+
 pub fn df_schema<P: AsRef<Path>>(input: P, flatten: bool) -> Result<String, OxenError> {
     tabular::schema_to_string(input, flatten)
 }
@@ -285,6 +312,20 @@ pub fn schema_list_indices(
     }
 }
 
+/// Index a field of a schema in the HEAD commit, so equality filters on it can look up rows instead of scanning
+pub fn schema_create_index(
+    repo: &LocalRepository,
+    schema_ref: &str,
+    field: &str,
+) -> Result<schema::Field, OxenError> {
+    let head_commit = head_commit(repo)?;
+    if let Some(schema) = schema_get(repo, Some(&head_commit.id), schema_ref)? {
+        schema_field_indexer::create_index(repo, &head_commit, &schema, field)
+    } else {
+        Err(OxenError::schema_does_not_exist(schema_ref))
+    }
+}
+
 /// # Restore a removed file that was committed
 ///
 /// ```
@@ -326,6 +367,32 @@ pub fn restore(repo: &LocalRepository, opts: RestoreOpts) -> Result<(), OxenErro
     index::restore(repo, opts)
 }
 
+/// # Revert a commit
+/// Creates a new commit on top of HEAD that undoes the changes made in the commit, branch or tag.
+/// Tabular files that were edited again since are reverted row by row.
+pub fn revert(repo: &LocalRepository, commit_or_ref: &str) -> Result<Commit, OxenError> {
+    let commit = resource::maybe_get_commit(repo, commit_or_ref)?
+        .ok_or_else(|| OxenError::commit_id_does_not_exist(commit_or_ref))?;
+    let reverter = Reverter::new(repo)?;
+    let revert_commit = reverter.revert(&commit)?;
+    println!("Reverted {} -> {}", commit.id, revert_commit.id);
+    Ok(revert_commit)
+}
+
+/// # Reset the current branch to a commit, branch or tag
+/// Moves the branch pointer, or HEAD if it is detached. `--soft` keeps the changes staged,
+/// `--mixed` keeps them on disk unstaged, and `--hard` overwrites the working tree.
+pub fn reset(
+    repo: &LocalRepository,
+    commit_or_ref: &str,
+    mode: ResetMode,
+) -> Result<Commit, OxenError> {
+    let commit = resource::maybe_get_commit(repo, commit_or_ref)?
+        .ok_or_else(|| OxenError::commit_id_does_not_exist(commit_or_ref))?;
+    index::reset(repo, &commit, mode)?;
+    Ok(commit)
+}
+
 /// # Commit the staged files in the repo
 ///
 /// ```
@@ -421,7 +488,7 @@ pub fn log_commit_or_branch_history(
     commit_or_branch: &str,
 ) -> Result<Vec<Commit>, OxenError> {
     let committer = CommitReader::new(repo)?;
-    let commit_id = match get_branch_commit_id(repo, commit_or_branch)? {
+    let commit_id = match resource::maybe_get_commit_id_from_branch_name(repo, commit_or_branch)? {
         Some(branch_commit_id) => branch_commit_id,
         None => String::from(commit_or_branch),
     };
@@ -435,6 +502,17 @@ pub fn log_commit_or_branch_history(
     }
 }
 
+/// # Get the history filtered by path, author, date or message
+/// Starts from the revision in the opts or HEAD. With a path, only the commits that changed that file, or
+/// anything under that directory, are returned.
+pub fn log_with_opts(repo: &LocalRepository, opts: &LogOpts) -> Result<Vec<Commit>, OxenError> {
+    let commits = match &opts.revision {
+        Some(revision) => log_commit_or_branch_history(repo, revision)?,
+        None => log(repo)?,
+    };
+    index::log_filter::filter_commits(repo, commits, opts)
+}
+
 /// # Create a new branch from the head commit
 /// This creates a new pointer to the current commit with a name,
 /// it does not switch you to this branch, you still must call `checkout_branch`
@@ -496,7 +574,50 @@ pub fn force_delete_branch(repo: &LocalRepository, name: &str) -> Result<(), Oxe
     api::local::branches::force_delete(repo, name)
 }
 
-/// # Checkout a branch or commit id
+/// # Create a tag
+/// Tags the commit id or branch, or HEAD if None, with a name that will always point at that commit
+pub fn create_tag(
+    repo: &LocalRepository,
+    name: &str,
+    commit_or_branch: Option<&str>,
+    message: &str,
+) -> Result<Tag, OxenError> {
+    let commit = match commit_or_branch {
+        Some(commit_or_branch) => resource::maybe_get_commit(repo, commit_or_branch)?
+            .ok_or_else(|| OxenError::local_commit_or_branch_not_found(commit_or_branch))?,
+        None => head_commit(repo)?,
+    };
+    api::local::tags::create(repo, name, &commit.id, message)
+}
+
+/// # List tags
+pub fn list_tags(repo: &LocalRepository) -> Result<Vec<Tag>, OxenError> {
+    api::local::tags::list(repo)
+}
+
+/// # Delete a local tag
+/// Tags on remotes stay until an admin deletes them with `delete_remote_tag`
+pub fn delete_tag(repo: &LocalRepository, name: &str) -> Result<Tag, OxenError> {
+    api::local::tags::delete(repo, name)?.ok_or_else(|| OxenError::tag_not_found(name))
+}
+
+/// # Delete a tag on a remote
+/// Tags are immutable on the server, so this needs admin access to the remote repository
+pub async fn delete_remote_tag(
+    repo: &LocalRepository,
+    remote: &str,
+    name: &str,
+) -> Result<Tag, OxenError> {
+    let remote = repo
+        .get_remote(remote)
+        .ok_or_else(OxenError::remote_not_set)?;
+    let remote_repo = api::remote::repositories::get_by_remote(&remote)
+        .await?
+        .ok_or_else(|| OxenError::remote_repo_not_found(&remote.url))?;
+    api::remote::tags::force_delete(&remote_repo, name).await
+}
+
+/// # Checkout a branch, tag or commit id
 /// This switches HEAD to point to the branch name or commit id,
 /// it also updates all the local files to be from the commit that this branch references
 pub fn checkout<S: AsRef<str>>(repo: &LocalRepository, value: S) -> Result<(), OxenError> {
@@ -511,6 +632,16 @@ pub fn checkout<S: AsRef<str>>(repo: &LocalRepository, value: S) -> Result<(), O
         println!("Checkout branch: {value}");
         set_working_branch(repo, value)?;
         set_head(repo, value)?;
+    } else if let Some(tag) = api::local::tags::get_by_name(repo, value)? {
+        // Tags check out their commit, the same as checking out the commit id
+        if already_on_commit(repo, &tag.commit_id) {
+            eprintln!("Tag already checked out {value}");
+            return Ok(());
+        }
+
+        println!("Checkout tag: {} -> {}", tag.name, tag.commit_id);
+        set_working_commit_id(repo, &tag.commit_id)?;
+        set_head(repo, &tag.commit_id)?;
     } else {
         // If we are already on the commit, do nothing
         if already_on_commit(repo, value) {
@@ -568,9 +699,10 @@ pub fn checkout_combine<P: AsRef<Path>>(repo: &LocalRepository, path: P) -> Resu
         .find(|c| c.merge_entry.path == path.as_ref())
     {
         if util::fs::is_tabular(&conflict.head_entry.path) {
-            let df_head_path = util::fs::version_path(repo, &conflict.head_entry);
+            let store = ChunkStore::new(repo);
+            let df_head_path = store.version_file(&conflict.head_entry)?;
             let df_head = tabular::read_df(df_head_path, DFOpts::empty())?;
-            let df_merge_path = util::fs::version_path(repo, &conflict.merge_entry);
+            let df_merge_path = store.version_file(&conflict.merge_entry)?;
             let df_merge = tabular::read_df(df_merge_path, DFOpts::empty())?;
 
             log::debug!("GOT DF HEAD {}", df_head);
@@ -618,13 +750,6 @@ fn set_head(repo: &LocalRepository, value: &str) -> Result<(), OxenError> {
     Ok(())
 }
 
-fn get_branch_commit_id(repo: &LocalRepository, name: &str) -> Result<Option<String>, OxenError> {
-    match RefReader::new(repo) {
-        Ok(ref_reader) => ref_reader.get_commit_id_for_branch(name),
-        _ => Err(OxenError::basic_str("Could not read reference for repo.")),
-    }
-}
-
 fn branch_exists(repo: &LocalRepository, name: &str) -> bool {
     match RefReader::new(repo) {
         Ok(ref_reader) => ref_reader.has_branch(name),
@@ -683,10 +808,30 @@ pub fn merge<S: AsRef<str>>(
     repo: &LocalRepository,
     branch_name: S,
 ) -> Result<Option<Commit>, OxenError> {
-    let branch_name = branch_name.as_ref();
+    p_merge(repo, branch_name.as_ref(), None)
+}
+
+/// # Merge a branch into the current branch, matching rows of tabular files by a primary key column
+/// Rows edited on only one side merge cleanly, rows edited differently on both sides are recorded as conflicts
+pub fn merge_with_primary_key<S: AsRef<str>, K: AsRef<str>>(
+    repo: &LocalRepository,
+    branch_name: S,
+    primary_key: K,
+) -> Result<Option<Commit>, OxenError> {
+    p_merge(repo, branch_name.as_ref(), Some(primary_key.as_ref()))
+}
+
+fn p_merge(
+    repo: &LocalRepository,
+    branch_name: &str,
+    primary_key: Option<&str>,
+) -> Result<Option<Commit>, OxenError> {
     if branch_exists(repo, branch_name) {
         if let Some(branch) = current_branch(repo)? {
-            let merger = Merger::new(repo)?;
+            let merger = match primary_key {
+                Some(primary_key) => Merger::new_with_primary_key(repo, primary_key)?,
+                None => Merger::new(repo)?,
+            };
             if let Some(commit) = merger.merge(branch_name)? {
                 println!(
                     "Successfully merged `{}` into `{}`",
@@ -708,6 +853,117 @@ pub fn merge<S: AsRef<str>>(
     }
 }
 
+/// # Cherry-pick a commit onto the current branch
+/// Applies the changes between a commit, branch or tag and its parent onto HEAD with a three way merge.
+/// If there are conflicts, it stops and shows them in the `status` command, fix them and `cherry_pick_continue`
+pub fn cherry_pick(
+    repo: &LocalRepository,
+    commit_or_ref: &str,
+) -> Result<Option<Commit>, OxenError> {
+    let commit = resource::maybe_get_commit(repo, commit_or_ref)?
+        .ok_or_else(|| OxenError::commit_id_does_not_exist(commit_or_ref))?;
+    if cherry_pick_in_progress(repo) {
+        return Err(OxenError::basic_str(
+            "A cherry-pick is already in progress, finish it with `oxen cherry-pick --continue` or `oxen cherry-pick --abort`",
+        ));
+    }
+
+    let status = status(repo)?;
+    if status.has_added_entries()
+        || status.has_modified_entries()
+        || status.has_removed_entries()
+        || status.has_merge_conflicts()
+    {
+        return Err(OxenError::basic_str(
+            "Cannot cherry-pick with local changes, commit or restore them first",
+        ));
+    }
+
+    let merger = Merger::new(repo)?;
+    if let Some(cherry_pick_commit) = merger.cherry_pick(&commit)? {
+        println!("Cherry-picked {} -> {}", commit.id, cherry_pick_commit.id);
+        Ok(Some(cherry_pick_commit))
+    } else {
+        eprintln!("Cherry-pick stopped on conflicts; fix them, `oxen add` them and run `oxen cherry-pick --continue`, or `oxen cherry-pick --abort`.");
+        Ok(None)
+    }
+}
+
+/// # Finish a cherry-pick that stopped on conflicts, once they are fixed and added
+pub fn cherry_pick_continue(repo: &LocalRepository) -> Result<Commit, OxenError> {
+    let merger = Merger::new(repo)?;
+    let commit = merger.continue_cherry_pick()?;
+    println!("Cherry-picked -> {}", commit.id);
+    Ok(commit)
+}
+
+/// # Give up on a cherry-pick that stopped on conflicts, putting back HEAD and the working directory
+pub fn cherry_pick_abort(repo: &LocalRepository) -> Result<(), OxenError> {
+    if !cherry_pick_in_progress(repo) {
+        return Err(OxenError::basic_str("No cherry-pick in progress"));
+    }
+
+    let head = head_commit(repo)?;
+    index::reset(repo, &head, ResetMode::Hard)
+}
+
+fn cherry_pick_in_progress(repo: &LocalRepository) -> bool {
+    util::fs::oxen_hidden_dir(&repo.path)
+        .join(constants::CHERRY_PICK_HEAD_FILE)
+        .exists()
+}
+
+/// # Stash the uncommitted changes
+/// Saves the staged, modified, removed and untracked files so they can be applied later, and resets the
+/// working directory back to HEAD. The message defaults to the branch and HEAD commit it was stashed from.
+pub fn stash_push(repo: &LocalRepository, message: Option<&str>) -> Result<Stash, OxenError> {
+    let stasher = Stasher::new(repo)?;
+    let stash = stasher.push(message)?;
+    println!("Saved working directory to stash@{{0}}: {}", stash.message);
+    Ok(stash)
+}
+
+/// # List the stashes, newest first
+pub fn stash_list(repo: &LocalRepository) -> Result<Vec<Stash>, OxenError> {
+    let stasher = Stasher::new(repo)?;
+    stasher.list()
+}
+
+/// # Apply a stash onto HEAD and keep it
+/// Returns the conflicts if HEAD changed the same files, which show up in the `status` command to be fixed
+pub fn stash_apply(repo: &LocalRepository, index: usize) -> Result<Vec<MergeConflict>, OxenError> {
+    let stasher = Stasher::new(repo)?;
+    let conflicts = stasher.apply(index)?;
+    print_stash_conflicts(index, &conflicts);
+    Ok(conflicts)
+}
+
+/// # Apply a stash onto HEAD and drop it
+/// If there are conflicts the stash is kept so it is not lost, drop it once they are fixed
+pub fn stash_pop(repo: &LocalRepository, index: usize) -> Result<Vec<MergeConflict>, OxenError> {
+    let stasher = Stasher::new(repo)?;
+    let conflicts = stasher.pop(index)?;
+    if conflicts.is_empty() {
+        println!("Dropped stash@{{{index}}}");
+    }
+    print_stash_conflicts(index, &conflicts);
+    Ok(conflicts)
+}
+
+/// # Throw away a stash
+pub fn stash_drop(repo: &LocalRepository, index: usize) -> Result<Stash, OxenError> {
+    let stasher = Stasher::new(repo)?;
+    let stash = stasher.remove(index)?;
+    println!("Dropped stash@{{{index}}} ({})", stash.commit_id);
+    Ok(stash)
+}
+
+fn print_stash_conflicts(index: usize, conflicts: &[MergeConflict]) {
+    if !conflicts.is_empty() {
+        eprintln!("Applying stash@{{{index}}} had {} conflicts; fix them and `oxen add` them, the stash was kept.", conflicts.len());
+    }
+}
+
 /// # List local branches
 pub fn list_branches(repo: &LocalRepository) -> Result<Vec<Branch>, OxenError> {
     let ref_reader = RefReader::new(repo)?;
@@ -839,6 +1095,107 @@ pub async fn push_remote_branch(
     indexer.push(&rb).await
 }
 
+/// Push to a specific remote, overwriting the remote branch even if it has commits we do not have
+pub async fn force_push_remote_branch(
+    repo: &LocalRepository,
+    remote: &str,
+    branch: &str,
+) -> Result<RemoteRepository, OxenError> {
+    let indexer = EntryIndexer::new(repo)?;
+    let rb = RemoteBranch {
+        remote: String::from(remote),
+        branch: String::from(branch),
+    };
+    indexer.force_push(&rb).await
+}
+
+/// Push the branch to every remote of the repository. All of them are tried before the ones that
+/// failed are reported in the error.
+pub async fn push_all_remotes(
+    repo: &LocalRepository,
+    branch: &str,
+) -> Result<Vec<RemoteRepository>, OxenError> {
+    p_push_all_remotes(repo, branch, false).await
+}
+
+/// Push the branch to every remote of the repository, overwriting remote branches that have commits we do not have
+pub async fn force_push_all_remotes(
+    repo: &LocalRepository,
+    branch: &str,
+) -> Result<Vec<RemoteRepository>, OxenError> {
+    p_push_all_remotes(repo, branch, true).await
+}
+
+async fn p_push_all_remotes(
+    repo: &LocalRepository,
+    branch: &str,
+    force: bool,
+) -> Result<Vec<RemoteRepository>, OxenError> {
+    if repo.remotes.is_empty() {
+        return Err(OxenError::remote_not_set());
+    }
+
+    let mut pushed: Vec<RemoteRepository> = vec![];
+    let mut errors: Vec<String> = vec![];
+    for remote in repo.remotes.iter() {
+        println!("🐂 pushing {} to {}", branch, remote.name);
+        let result = if force {
+            force_push_remote_branch(repo, &remote.name, branch).await
+        } else {
+            push_remote_branch(repo, &remote.name, branch).await
+        };
+        match result {
+            Ok(remote_repo) => pushed.push(remote_repo),
+            Err(err) => errors.push(format!("{}: {}", remote.name, err)),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(pushed)
+    } else {
+        let err = format!(
+            "Failed to push {} to {} of {} remotes\n{}",
+            branch,
+            errors.len(),
+            repo.remotes.len(),
+            errors.join("\n")
+        );
+        Err(OxenError::basic_str(err))
+    }
+}
+
+/// Copy the remote repository at `url` into `dst/<repo name>`, creating it on the first run and updating
+/// it after. Every branch keeps the commit id it has on the remote, so the copy can serve as a read replica.
+pub async fn mirror(url: &str, dst: &Path) -> Result<Vec<Branch>, OxenError> {
+    let remote = Remote {
+        name: String::from(constants::DEFAULT_REMOTE_NAME),
+        url: String::from(url),
+    };
+    let remote_repo = match api::remote::repositories::get_by_remote(&remote).await? {
+        Some(remote_repo) => remote_repo,
+        None => return Err(OxenError::remote_repo_not_found(url)),
+    };
+
+    let repo_path = dst.join(&remote_repo.name);
+    let repo = if util::fs::oxen_hidden_dir(&repo_path).exists() {
+        let repo = LocalRepository::from_dir(&repo_path)?;
+        match repo.get_remote(&remote.name) {
+            Some(origin) if origin.url == url => repo,
+            _ => {
+                let err = format!("Repository {repo_path:?} exists and is not a mirror of {url}");
+                return Err(OxenError::basic_str(err));
+            }
+        }
+    } else {
+        let repo = LocalRepository::create_from_remote(&remote_repo, &repo_path, None)?;
+        RefWriter::new(&repo)?.set_head(constants::DEFAULT_BRANCH_NAME);
+        repo
+    };
+
+    let indexer = EntryIndexer::new(&repo)?;
+    indexer.mirror(&remote.name).await
+}
+
 /// Clone a repo from a url to a directory
 pub async fn clone(url: &str, dst: &Path, shallow: bool) -> Result<LocalRepository, OxenError> {
     match LocalRepository::clone_remote(url, dst, shallow).await {
@@ -848,6 +1205,32 @@ pub async fn clone(url: &str, dst: &Path, shallow: bool) -> Result<LocalReposito
     }
 }
 
+/// Clone a repository, only pulling the entries that match the `include` and `exclude` globs of `spec`.
+/// The spec is saved in the repository config so later pulls stay sparse.
+pub async fn clone_sparse(
+    url: &str,
+    dst: &Path,
+    shallow: bool,
+    spec: SparseSpec,
+) -> Result<LocalRepository, OxenError> {
+    match LocalRepository::clone_remote_sparse(url, dst, shallow, Some(spec)).await {
+        Ok(Some(repo)) => Ok(repo),
+        Ok(None) => Err(OxenError::remote_repo_not_found(url)),
+        Err(err) => Err(err),
+    }
+}
+
+/// Fetch the commits of `branch` on `remote`, or of all its branches, without pulling any data.
+/// Records remote-tracking refs like `origin/main` that `log` and `diff` can use.
+pub async fn fetch(
+    repo: &LocalRepository,
+    remote: &str,
+    branch: Option<&str>,
+) -> Result<Vec<Branch>, OxenError> {
+    let indexer = EntryIndexer::new(repo)?;
+    indexer.fetch(remote, branch).await
+}
+
 /// Pull a repository's data from origin/main
 pub async fn pull(repo: &LocalRepository) -> Result<(), OxenError> {
     let indexer = EntryIndexer::new(repo)?;
@@ -861,9 +1244,29 @@ pub fn diff(
     repo: &LocalRepository,
     commit_id_or_branch: Option<&str>,
     path: &Path,
+    opts: &DFDiffOpts,
 ) -> Result<String, OxenError> {
     let commit = resource::get_commit_or_head(repo, commit_id_or_branch)?;
-    differ::diff(repo, Some(&commit.id), path)
+    differ::diff(repo, Some(&commit.id), path, opts)
+}
+
+/// # Compare two commits or branches
+/// Lists the files added, removed and modified going from `base_ref` to `head_ref`, optionally only under `path_filter`.
+/// If `path_filter` is a single modified file, the line or row level diff of that file is included.
+pub fn diff_commits<S: AsRef<str>, T: AsRef<str>>(
+    repo: &LocalRepository,
+    base_ref: S,
+    head_ref: T,
+    path_filter: Option<&Path>,
+    opts: &DFDiffOpts,
+) -> Result<TreeDiff, OxenError> {
+    let base_ref = base_ref.as_ref();
+    let head_ref = head_ref.as_ref();
+    let base = resource::maybe_get_commit(repo, base_ref)?
+        .ok_or_else(|| OxenError::local_commit_or_branch_not_found(base_ref))?;
+    let head = resource::maybe_get_commit(repo, head_ref)?
+        .ok_or_else(|| OxenError::local_commit_or_branch_not_found(head_ref))?;
+    differ::diff_commits(repo, &base, &head, path_filter, opts)
 }
 
 /// Pull a specific origin and branch
@@ -881,6 +1284,35 @@ pub async fn pull_remote_branch(
     Ok(())
 }
 
+/// Add `paths` to the sparse spec of the repository, save it, and pull the entries that now match
+pub async fn pull_paths(
+    repo: &mut LocalRepository,
+    remote: &str,
+    branch: &str,
+    paths: &[String],
+) -> Result<(), OxenError> {
+    let mut spec = repo.sparse.clone().unwrap_or_default();
+    spec.add_includes(paths);
+    repo.sparse = Some(spec);
+    repo.save_default()?;
+
+    pull_remote_branch(repo, remote, branch).await
+}
+
+/// Remove the commits, version files and history dbs that no branch, HEAD or merge can reach.
+/// With `dry_run` nothing is removed and the stats report what would be.
+pub fn gc(
+    repo: &LocalRepository,
+    dry_run: bool,
+) -> Result<index::garbage_collector::GCStats, OxenError> {
+    GarbageCollector::new(repo).collect(dry_run)
+}
+
+/// Fold the history dbs of commits that are not branch heads, HEAD or part of a merge into the shared pack store
+pub fn gc_pack(repo: &LocalRepository) -> Result<index::history_pack::PackStats, OxenError> {
+    HistoryPack::new(repo).pack()
+}
+
 /// Run the computation cache on all repositories within a directory
 pub fn migrate_all_repos(path: &Path) -> Result<(), OxenError> {
     let namespaces = api::local::repositories::list_namespaces(path)?;
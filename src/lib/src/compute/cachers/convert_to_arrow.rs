@@ -1,12 +1,13 @@
 use crate::df::{tabular, DFOpts};
 use crate::error::OxenError;
-use crate::index::CommitDirReader;
+use crate::index::{ChunkStore, CommitDirReader};
 use crate::model::{Commit, LocalRepository};
 use crate::util;
 
 pub fn convert_to_arrow(repo: &LocalRepository, commit: &Commit) -> Result<(), OxenError> {
     log::debug!("running convert_to_arrow");
     let commit_entry_reader = CommitDirReader::new(repo, commit)?;
+    let store = ChunkStore::new(repo);
 
     for entry in commit_entry_reader.list_entries()? {
         let version_path = util::fs::version_path(repo, &entry);
@@ -14,7 +15,7 @@ pub fn convert_to_arrow(repo: &LocalRepository, commit: &Commit) -> Result<(), O
         let is_already_arrow = util::fs::has_ext(&version_path, "arrow");
         if util::fs::is_tabular(&version_path) && !arrow_path.exists() && !is_already_arrow {
             log::debug!("convert_to_arrow converting {:?}", entry.path);
-            let mut df = tabular::read_df(version_path, DFOpts::empty())?;
+            let mut df = tabular::read_df(store.version_file(&entry)?, DFOpts::empty())?;
             tabular::write_df(&mut df, &arrow_path)?;
             log::debug!("convert_to_arrow wrote {:?}", arrow_path);
         }
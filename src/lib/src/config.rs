@@ -1,5 +1,7 @@
 pub mod endpoint;
+pub mod transfer_config;
 pub mod user_config;
 
+pub use crate::config::transfer_config::TransferConfig;
 pub use crate::config::user_config::UserConfig;
 pub use crate::config::user_config::USER_CONFIG_FILENAME;
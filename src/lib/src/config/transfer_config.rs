@@ -0,0 +1,120 @@
+use crate::constants;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+fn default_connect_timeout_secs() -> u64 {
+    30
+}
+
+fn default_pool_idle_timeout_secs() -> u64 {
+    90
+}
+
+fn default_max_retries() -> u64 {
+    constants::NUM_HTTP_RETRIES
+}
+
+fn default_retry_backoff_ms() -> u64 {
+    1000
+}
+
+fn default_max_retry_backoff_secs() -> u64 {
+    60
+}
+
+/// How push, pull and clone talk to a remote, the `[transfer]` table of the user config
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TransferConfig {
+    /// Number of requests in flight at once, defaults to the number of cpus
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<usize>,
+    /// Give up on a request that has not finished after this long, no limit by default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    #[serde(default = "default_connect_timeout_secs")]
+    pub connect_timeout_secs: u64,
+    /// How long an unused connection is kept open to be reused
+    #[serde(default = "default_pool_idle_timeout_secs")]
+    pub pool_idle_timeout_secs: u64,
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u64,
+    /// Wait before the first retry, it doubles after every failed try
+    #[serde(default = "default_retry_backoff_ms")]
+    pub retry_backoff_ms: u64,
+    #[serde(default = "default_max_retry_backoff_secs")]
+    pub max_retry_backoff_secs: u64,
+    /// Speak HTTP/2 without negotiating it first, for servers that only accept h2c.
+    /// Over https HTTP/2 is negotiated automatically.
+    #[serde(default)]
+    pub http2_prior_knowledge: bool,
+}
+
+impl Default for TransferConfig {
+    fn default() -> TransferConfig {
+        TransferConfig {
+            concurrency: None,
+            timeout_secs: None,
+            connect_timeout_secs: default_connect_timeout_secs(),
+            pool_idle_timeout_secs: default_pool_idle_timeout_secs(),
+            max_retries: default_max_retries(),
+            retry_backoff_ms: default_retry_backoff_ms(),
+            max_retry_backoff_secs: default_max_retry_backoff_secs(),
+            http2_prior_knowledge: false,
+        }
+    }
+}
+
+impl TransferConfig {
+    pub fn is_default(&self) -> bool {
+        *self == TransferConfig::default()
+    }
+
+    pub fn concurrency(&self) -> usize {
+        match self.concurrency {
+            Some(concurrency) if concurrency > 0 => concurrency,
+            _ => num_cpus::get(),
+        }
+    }
+
+    /// How long to wait after the `num_tries`th failed try before trying again
+    pub fn backoff(&self, num_tries: u64) -> Duration {
+        let exponent = num_tries.saturating_sub(1).min(16) as u32;
+        let backoff =
+            Duration::from_millis(self.retry_backoff_ms.saturating_mul(2u64.pow(exponent)));
+        backoff.min(Duration::from_secs(self.max_retry_backoff_secs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::TransferConfig;
+    use crate::constants;
+    use std::time::Duration;
+
+    #[test]
+    fn test_transfer_config_defaults_when_missing() {
+        let config: TransferConfig = toml::from_str("concurrency = 4").unwrap();
+        assert_eq!(config.concurrency(), 4);
+        assert_eq!(config.max_retries, constants::NUM_HTTP_RETRIES);
+        assert!(config.timeout_secs.is_none());
+        assert!(!config.is_default());
+
+        let config: TransferConfig = toml::from_str("").unwrap();
+        assert!(config.is_default());
+        assert!(config.concurrency() > 0);
+    }
+
+    #[test]
+    fn test_transfer_config_backoff_doubles_up_to_max() {
+        let config = TransferConfig {
+            retry_backoff_ms: 500,
+            max_retry_backoff_secs: 3,
+            ..TransferConfig::default()
+        };
+        assert_eq!(config.backoff(1), Duration::from_millis(500));
+        assert_eq!(config.backoff(2), Duration::from_secs(1));
+        assert_eq!(config.backoff(3), Duration::from_secs(2));
+        assert_eq!(config.backoff(4), Duration::from_secs(3));
+        assert_eq!(config.backoff(40), Duration::from_secs(3));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::config::TransferConfig;
 use crate::error::OxenError;
 use crate::model::User;
 use crate::util;
@@ -19,6 +20,8 @@ pub struct UserConfig {
     pub email: String,
     pub default_host: Option<String>,
     host_configs: Vec<HostConfig>,
+    #[serde(default, skip_serializing_if = "TransferConfig::is_default")]
+    pub transfer: TransferConfig,
 }
 
 impl UserConfig {
@@ -33,6 +36,7 @@ impl UserConfig {
             email: user.email.to_owned(),
             default_host: Some(String::from("hub.oxen.ai")),
             host_configs: Vec::new(),
+            transfer: TransferConfig::default(),
         }
     }
 
@@ -42,6 +46,7 @@ impl UserConfig {
             email: String::from(""),
             default_host: Some(String::from("hub.oxen.ai")),
             host_configs: Vec::new(),
+            transfer: TransferConfig::default(),
         }
     }
 
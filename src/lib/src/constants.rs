@@ -3,10 +3,18 @@
 pub const OXEN_HIDDEN_DIR: &str = ".oxen";
 /// Config file for the repository
 pub const REPO_CONFIG_FILENAME: &str = "config.toml";
+/// .oxenignore holds gitignore style patterns of paths to skip when adding
+pub const OXEN_IGNORE_FILE: &str = ".oxenignore";
 /// HEAD file holds onto where the head commit is (commit_id or branch name)
 pub const HEAD_FILE: &str = "HEAD";
 /// refs/ is a key,val store of branch names to commit ids
 pub const REFS_DIR: &str = "refs";
+/// remote_refs/ is a key,val store of remote-tracking refs like origin/main to the commit ids we last fetched
+pub const REMOTE_REFS_DIR: &str = "remote_refs";
+/// tags/ is a key,val store of tag names to annotated tags, they never move once created
+pub const TAGS_DIR: &str = "tags";
+/// stash/ is a key,val store of the stashes of uncommitted work, keyed by when they were made
+pub const STASH_DIR: &str = "stash";
 /// history/ dir is a list of directories named after commit ids
 pub const HISTORY_DIR: &str = "history";
 /// commits/ is a key-value database of commit ids to commit objects
@@ -27,6 +35,26 @@ pub const INDICES_DIR: &str = "indices";
 pub const FIELDS_DIR: &str = "fields";
 /// versions/ is where all the versions are stored so that we can use to quickly swap between versions of the file
 pub const VERSIONS_DIR: &str = "versions";
+/// versions/chunks/ holds the content defined chunks that large files are split into
+pub const CHUNKS_DIR: &str = "chunks";
+/// versions/chunk_index/ is a key-value database of chunk hashes to chunk sizes
+pub const CHUNK_INDEX_DIR: &str = "chunk_index";
+/// MANIFEST lists the chunks a chunked version file is rebuilt from
+pub const CHUNK_MANIFEST_FILE: &str = "MANIFEST";
+/// packs/ is where the history dbs of old commits are folded into a content addressed store
+pub const PACKS_DIR: &str = "packs";
+/// packs/objects/ holds the dumped dbs, named by the hash of their contents
+pub const PACK_OBJECTS_DIR: &str = "objects";
+/// packs/commits/ holds a manifest per packed commit of db paths to object hashes
+pub const PACK_COMMITS_DIR: &str = "commits";
+/// tmp/ holds data that is still being transferred
+pub const TMP_DIR: &str = "tmp";
+/// tmp/chunked/ holds the pieces of uploads that are too large for a single request
+pub const CHUNKED_UPLOADS_DIR: &str = "chunked";
+/// SESSION tracks which pieces of a chunked upload have arrived
+pub const UPLOAD_SESSION_FILE: &str = "SESSION";
+/// branch_policy.toml lists the branches a server protects from deletion and force pushes
+pub const BRANCH_POLICY_FILE: &str = "branch_policy.toml";
 /// merge/ is where any merge conflicts are stored so that we can get rid of them
 pub const MERGE_DIR: &str = "merge";
 /// data.arrow
@@ -35,6 +63,8 @@ pub const DATA_ARROW_FILE: &str = "data.arrow";
 /// if we have merge conflicts we write to MERGE_HEAD and ORIG_HEAD to keep track of the parents
 pub const MERGE_HEAD_FILE: &str = "MERGE_HEAD";
 pub const ORIG_HEAD_FILE: &str = "ORIG_HEAD";
+/// if a cherry-pick has conflicts we write the commit being picked to CHERRY_PICK_HEAD so we can continue it
+pub const CHERRY_PICK_HEAD_FILE: &str = "CHERRY_PICK_HEAD";
 
 // Precomputed vals
 pub const HASH_FILE: &str = "HASH";
@@ -60,6 +90,10 @@ pub const FILE_ROW_NUM_COL_NAME: &str = "_file_row_num";
 pub const AVG_CHUNK_SIZE: u64 = 1024 * 1024 * 4;
 // Retry and back off of requests N times
 pub const NUM_HTTP_RETRIES: u64 = 6;
+// Unfinished chunked uploads are kept for a day so the client can resume them
+pub const DEFAULT_UPLOAD_TTL_SECS: u64 = 60 * 60 * 24;
+// gc and pack leave anything written in the last hour alone, it may belong to a push or commit in progress
+pub const DEFAULT_GC_GRACE_PERIOD_SECS: u64 = 60 * 60;
 // Pagination page size
 pub const DEFAULT_PAGE_SIZE: usize = 10;
 pub const DEFAULT_PAGE_NUM: usize = 1;
@@ -1,6 +1,10 @@
 pub mod agg;
 pub mod df_opts;
+pub mod diff;
 pub mod filter;
+pub mod merge;
+pub mod sql;
 pub mod tabular;
 
 pub use crate::df::df_opts::DFOpts;
+pub use crate::df::diff::DFDiffOpts;
@@ -0,0 +1,267 @@
+//! Row and column level diff of two versions of a data frame
+//!
+//! With a primary key, rows are lined up across versions by key, so an edited row is reported as modified along with
+//! the before and after value of every cell that changed. Without a key rows are lined up by their `_row_hash`, and
+//! an edit looks like a removed row plus an added row.
+
+use polars::prelude::*;
+
+use crate::df::merge::KeyedRows;
+use crate::df::tabular;
+use crate::error::OxenError;
+use crate::model::schema::Field;
+use crate::model::{DataFrameDiff, ModifiedCell, ModifiedRow, Schema};
+
+const DIFF_ERROR: &str = "Could not diff data frames";
+
+#[derive(Clone, Debug, Default)]
+pub struct DFDiffOpts {
+    /// Column that uniquely identifies a row in both versions
+    pub primary_key: Option<String>,
+    /// Columns left out of the diff entirely, for example timestamps that change on every write
+    pub ignore_columns: Vec<String>,
+}
+
+impl DFDiffOpts {
+    pub fn empty() -> DFDiffOpts {
+        DFDiffOpts::default()
+    }
+
+    pub fn from_primary_key<S: AsRef<str>>(primary_key: S) -> DFDiffOpts {
+        DFDiffOpts {
+            primary_key: Some(String::from(primary_key.as_ref())),
+            ignore_columns: vec![],
+        }
+    }
+}
+
+/// Diffs `head` against `base`. If the schemas differ we only report the added and removed columns, since rows
+/// cannot be lined up across them.
+pub fn compare(
+    base: DataFrame,
+    head: DataFrame,
+    opts: &DFDiffOpts,
+) -> Result<DataFrameDiff, OxenError> {
+    let base = drop_columns(base, &opts.ignore_columns);
+    let head = drop_columns(head, &opts.ignore_columns);
+
+    let base_schema = Schema::from_polars(&base.schema());
+    let head_schema = Schema::from_polars(&head.schema());
+    if base_schema.hash != head_schema.hash {
+        log::debug!("df::diff::compare schemas differ, comparing columns");
+        return Ok(compare_columns(&base, &head, &base_schema, &head_schema));
+    }
+
+    compare_rows(base, head, opts.primary_key.as_deref())
+}
+
+fn drop_columns(df: DataFrame, columns: &[String]) -> DataFrame {
+    let keep: Vec<&str> = df
+        .get_column_names()
+        .into_iter()
+        .filter(|name| !columns.iter().any(|c| c == name))
+        .collect();
+    df.select(keep).expect(DIFF_ERROR)
+}
+
+fn compare_columns(
+    base: &DataFrame,
+    head: &DataFrame,
+    base_schema: &Schema,
+    head_schema: &Schema,
+) -> DataFrameDiff {
+    let added_fields = head_schema.added_fields(base_schema);
+    let removed_fields = head_schema.removed_fields(base_schema);
+
+    DataFrameDiff {
+        added_rows: None,
+        removed_rows: None,
+        modified_rows: vec![],
+        added_cols: select_fields(head, added_fields),
+        removed_cols: select_fields(base, removed_fields),
+    }
+}
+
+fn select_fields(df: &DataFrame, fields: Vec<Field>) -> Option<DataFrame> {
+    if fields.is_empty() {
+        return None;
+    }
+    let names: Vec<String> = fields.into_iter().map(|f| f.name).collect();
+    Some(df.select(names).expect(DIFF_ERROR))
+}
+
+fn compare_rows(
+    base: DataFrame,
+    head: DataFrame,
+    primary_key: Option<&str>,
+) -> Result<DataFrameDiff, OxenError> {
+    let base = KeyedRows::new(base, primary_key)?;
+    let head = KeyedRows::new(head, primary_key)?;
+
+    let mut added_indices: Vec<u32> = vec![];
+    let mut modified_rows: Vec<ModifiedRow> = vec![];
+    for (i, key) in head.keys.iter().enumerate() {
+        match base.indices.get(key) {
+            // Without a key, rows with the same hash are identical so there is nothing to compare
+            Some(&b) if base.hashes[b] != head.hashes[i] => {
+                modified_rows.push(modified_row(key, &base, b, &head, i, primary_key));
+            }
+            Some(_) => {}
+            None => added_indices.push(i as u32),
+        }
+    }
+
+    let removed_indices: Vec<u32> = base
+        .keys
+        .iter()
+        .enumerate()
+        .filter(|(_, key)| !head.indices.contains_key(*key))
+        .map(|(i, _)| i as u32)
+        .collect();
+
+    log::debug!(
+        "df::diff::compare_rows added {} removed {} modified {}",
+        added_indices.len(),
+        removed_indices.len(),
+        modified_rows.len()
+    );
+
+    let added_rows = tabular::take(head.df.clone().lazy(), added_indices)?;
+    let removed_rows = tabular::take(base.df.clone().lazy(), removed_indices)?;
+
+    Ok(DataFrameDiff {
+        added_rows: if added_rows.height() > 0 {
+            Some(added_rows)
+        } else {
+            None
+        },
+        removed_rows: if removed_rows.height() > 0 {
+            Some(removed_rows)
+        } else {
+            None
+        },
+        modified_rows,
+        added_cols: None,
+        removed_cols: None,
+    })
+}
+
+fn modified_row(
+    key: &str,
+    base: &KeyedRows,
+    base_idx: usize,
+    head: &KeyedRows,
+    head_idx: usize,
+    primary_key: Option<&str>,
+) -> ModifiedRow {
+    let before = base.row(Some(base_idx)).unwrap_or_default();
+    let after = head.row(Some(head_idx)).unwrap_or_default();
+
+    let cells = head
+        .df
+        .get_column_names()
+        .into_iter()
+        .zip(before.into_iter().zip(after))
+        .filter(|(column, (before, after))| Some(*column) != primary_key && before != after)
+        .map(|(column, (before, after))| ModifiedCell {
+            column: String::from(column),
+            before,
+            after,
+        })
+        .collect();
+
+    ModifiedRow {
+        key: String::from(key),
+        cells,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::df::diff::{self, DFDiffOpts};
+    use crate::error::OxenError;
+    use crate::model::ModifiedCell;
+    use polars::prelude::*;
+
+    fn labels_df(ids: &[i64], labels: &[&str], scores: &[f64]) -> DataFrame {
+        df!(
+            "id" => ids,
+            "label" => labels,
+            "score" => scores,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_compare_modified_rows_with_primary_key() -> Result<(), OxenError> {
+        let base = labels_df(&[1, 2, 3], &["cat", "dog", "fish"], &[0.1, 0.2, 0.3]);
+        let head = labels_df(&[1, 2, 4], &["lion", "dog", "human"], &[0.1, 0.5, 0.4]);
+
+        let diff = diff::compare(base, head, &DFDiffOpts::from_primary_key("id"))?;
+
+        assert_eq!(diff.added_rows.unwrap().height(), 1);
+        assert_eq!(diff.removed_rows.unwrap().height(), 1);
+        assert_eq!(diff.modified_rows.len(), 2);
+
+        let lion = &diff.modified_rows[0];
+        assert_eq!(lion.key, "1");
+        assert_eq!(
+            lion.cells,
+            vec![ModifiedCell {
+                column: String::from("label"),
+                before: String::from("cat"),
+                after: String::from("lion"),
+            }]
+        );
+
+        let dog = &diff.modified_rows[1];
+        assert_eq!(dog.key, "2");
+        assert_eq!(dog.cells.len(), 1);
+        assert_eq!(dog.cells[0].column, "score");
+        assert_eq!(dog.cells[0].before, "0.2");
+        assert_eq!(dog.cells[0].after, "0.5");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_compare_ignore_columns() -> Result<(), OxenError> {
+        let base = labels_df(&[1, 2], &["cat", "dog"], &[0.1, 0.2]);
+        let head = labels_df(&[1, 2], &["cat", "dog"], &[0.9, 0.8]);
+
+        let opts = DFDiffOpts {
+            primary_key: Some(String::from("id")),
+            ignore_columns: vec![String::from("score")],
+        };
+        let diff = diff::compare(base, head, &opts)?;
+        assert!(diff.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_compare_without_primary_key_edit_is_add_and_remove() -> Result<(), OxenError> {
+        let base = labels_df(&[1, 2], &["cat", "dog"], &[0.1, 0.2]);
+        let head = labels_df(&[1, 2], &["lion", "dog"], &[0.1, 0.2]);
+
+        let diff = diff::compare(base, head, &DFDiffOpts::empty())?;
+        assert!(diff.modified_rows.is_empty());
+        assert_eq!(diff.added_rows.unwrap().height(), 1);
+        assert_eq!(diff.removed_rows.unwrap().height(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_compare_columns_changed() -> Result<(), OxenError> {
+        let base = labels_df(&[1, 2], &["cat", "dog"], &[0.1, 0.2]);
+        let head = df!("id" => &[1, 2], "label" => &["cat", "dog"], "width" => &[10, 20]).unwrap();
+
+        let diff = diff::compare(base, head, &DFDiffOpts::empty())?;
+        assert!(diff.added_rows.is_none());
+        assert_eq!(diff.added_cols.unwrap().get_column_names(), vec!["width"]);
+        assert_eq!(diff.removed_cols.unwrap().get_column_names(), vec!["score"]);
+
+        Ok(())
+    }
+}
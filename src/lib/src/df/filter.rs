@@ -1,6 +1,11 @@
+//! # Filters
+//! label == person && (min_x > 0.5 || label in ['cat', 'dog']) && file starts_with 'train/' && note is not null
+//!
+//! `&&` binds tighter than `||`, and parentheses group sub expressions.
 use crate::error::OxenError;
 
-use crate::util;
+use regex::Regex;
+use std::fmt;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum DFLogicalOp {
@@ -9,14 +14,6 @@ pub enum DFLogicalOp {
 }
 
 impl DFLogicalOp {
-    pub fn from_str_op(s: &str) -> DFLogicalOp {
-        match s {
-            "&&" => DFLogicalOp::AND,
-            "||" => DFLogicalOp::OR,
-            _ => panic!("Unknown DFLogicalOp"),
-        }
-    }
-
     pub fn as_str(&self) -> &'static str {
         match self {
             DFLogicalOp::AND => "&&",
@@ -33,18 +30,28 @@ pub enum DFFilterOp {
     GTE,
     LTE,
     NEQ,
+    In,
+    Contains,
+    StartsWith,
+    Regex,
+    IsNull,
+    IsNotNull,
 }
 
 impl DFFilterOp {
-    pub fn from_str_op(s: &str) -> DFFilterOp {
+    pub fn from_str_op(s: &str) -> Result<DFFilterOp, OxenError> {
         match s {
-            "==" => DFFilterOp::EQ,
-            "<" => DFFilterOp::LT,
-            ">" => DFFilterOp::GT,
-            "<=" => DFFilterOp::LTE,
-            ">=" => DFFilterOp::GTE,
-            "!=" => DFFilterOp::NEQ,
-            _ => panic!("Unknown DFFilterOp"),
+            "==" => Ok(DFFilterOp::EQ),
+            "<" => Ok(DFFilterOp::LT),
+            ">" => Ok(DFFilterOp::GT),
+            "<=" => Ok(DFFilterOp::LTE),
+            ">=" => Ok(DFFilterOp::GTE),
+            "!=" => Ok(DFFilterOp::NEQ),
+            "in" => Ok(DFFilterOp::In),
+            "contains" => Ok(DFFilterOp::Contains),
+            "starts_with" => Ok(DFFilterOp::StartsWith),
+            "=~" => Ok(DFFilterOp::Regex),
+            _ => Err(OxenError::basic_str(format!("Unknown filter op: {s:?}"))),
         }
     }
 
@@ -56,126 +63,409 @@ impl DFFilterOp {
             DFFilterOp::LTE => "<=",
             DFFilterOp::GTE => ">=",
             DFFilterOp::NEQ => "!=",
+            DFFilterOp::In => "in",
+            DFFilterOp::Contains => "contains",
+            DFFilterOp::StartsWith => "starts_with",
+            DFFilterOp::Regex => "=~",
+            DFFilterOp::IsNull => "is null",
+            DFFilterOp::IsNotNull => "is not null",
+        }
+    }
+}
+
+/// Values are typed by how they are written, and converted to the column type when the filter is applied
+#[derive(Clone, Debug, PartialEq)]
+pub enum DFFilterLiteral {
+    Str(String),
+    // Kept as written so it can be read as either an int or a float depending on the column
+    Number(String),
+    Bool(bool),
+    // YYYY-MM-DD
+    Date(String),
+    List(Vec<DFFilterLiteral>),
+}
+
+impl fmt::Display for DFFilterLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DFFilterLiteral::Str(val) => write!(f, "{val}"),
+            DFFilterLiteral::Number(val) => write!(f, "{val}"),
+            DFFilterLiteral::Bool(val) => write!(f, "{val}"),
+            DFFilterLiteral::Date(val) => write!(f, "{val}"),
+            DFFilterLiteral::List(vals) => {
+                let vals: Vec<String> = vals.iter().map(|v| v.to_string()).collect();
+                write!(f, "[{}]", vals.join(", "))
+            }
         }
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct DFFilterVal {
     pub op: DFFilterOp,
     pub field: String,
-    pub value: String,
-}
-
-#[derive(Clone, Debug)]
-pub struct DFFilterExp {
-    // logical ops are all the ops we want to chain "&&","||"
-    pub logical_ops: Vec<DFLogicalOp>,
-    // vals are all the sub expressions like "label != person"
-    pub vals: Vec<DFFilterVal>,
-}
-
-// Scan query to find the first logical op if there is one
-fn find_next_logical_op(
-    query: &str,
-    logical_ops: &Vec<DFLogicalOp>,
-) -> Option<(DFLogicalOp, usize)> {
-    let mut logical_op: Option<(DFLogicalOp, usize)> = None;
-    let mut min_idx = query.len();
-    for op in logical_ops {
-        if let Some(idx) = query.find(op.as_str()) {
-            if idx < min_idx {
-                logical_op = Some((op.to_owned(), idx));
-                min_idx = idx;
+    // None for the ops that do not take a value, like `is null`
+    pub value: Option<DFFilterLiteral>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DFFilterExp {
+    Logical(DFLogicalOp, Box<DFFilterExp>, Box<DFFilterExp>),
+    // Sub expressions like "label != person"
+    Val(DFFilterVal),
+}
+
+impl DFFilterExp {
+    /// The `field == value` predicates every matching row must satisfy, so they can be answered by a field index
+    pub fn required_eqs(&self) -> Vec<(String, String)> {
+        match self {
+            DFFilterExp::Val(DFFilterVal {
+                op: DFFilterOp::EQ,
+                field,
+                value: Some(value),
+            }) if !matches!(value, DFFilterLiteral::List(_)) => {
+                vec![(field.to_owned(), value.to_string())]
+            }
+            DFFilterExp::Logical(DFLogicalOp::AND, lhs, rhs) => {
+                let mut eqs = lhs.required_eqs();
+                eqs.extend(rhs.required_eqs());
+                eqs
             }
+            _ => vec![],
         }
     }
-    logical_op
 }
 
-/// Can parse an expression such as "pred_label == person && is_correct == true"
+/// Can parse an expression such as "pred_label == person && (is_correct == true || score > 0.5)"
 pub fn parse(query: Option<String>) -> Result<Option<DFFilterExp>, OxenError> {
-    if let Some(mut filter) = query {
-        if filter.is_empty() {
+    if let Some(filter) = query {
+        if filter.trim().is_empty() {
             return Err(OxenError::parse_error(filter));
         }
 
-        // 1) Iterate over string finding DFLogicalOps and collecting the sub expressions
-        let mut found_ops: Vec<DFLogicalOp> = vec![];
-        let mut sub_exprs: Vec<String> = vec![];
-        let candidate_ops = vec![DFLogicalOp::AND, DFLogicalOp::OR];
-
-        while let Some((op, idx)) = find_next_logical_op(&filter, &candidate_ops) {
-            // split at idx
-            let op_str = op.as_str();
-            let (sub_expr, remaining) = filter.split_at(idx);
-            let (_, remaining) = remaining.split_at(op_str.len());
-
-            // add sub_expr to eval later
-            sub_exprs.push(sub_expr.trim().to_string());
-
-            // add op
-            found_ops.push(op);
-            filter = remaining.to_string();
-        }
-
-        // push remaining
-        sub_exprs.push(filter.trim().to_string());
-
-        // 2) Parse each sub expression
-        // Order in which we check matters because some ops are substrings of others, put the longest ones first
-        let filter_ops = vec![
-            DFFilterOp::NEQ,
-            DFFilterOp::GTE,
-            DFFilterOp::LTE,
-            DFFilterOp::EQ,
-            DFFilterOp::GT,
-            DFFilterOp::LT,
-        ];
-
-        let mut filter_vals: Vec<DFFilterVal> = vec![];
-        for sub_expr in sub_exprs {
-            for op in filter_ops.iter() {
-                if sub_expr.contains(op.as_str()) {
-                    let split = util::str::split_and_trim(&sub_expr, op.as_str());
-                    let val = DFFilterVal {
-                        op: op.clone(),
-                        field: split[0].to_owned(),
-                        value: split[1].to_owned(),
-                    };
-                    filter_vals.push(val);
-                    break;
-                }
+        let mut parser = FilterParser::new(&filter);
+        let exp = parser.parse_or()?;
+        parser.skip_whitespace();
+        if !parser.is_done() {
+            return Err(parser.error("Unexpected input, expected && or ||"));
+        }
+        return Ok(Some(exp));
+    }
+    Ok(None)
+}
+
+struct FilterParser<'a> {
+    query: &'a str,
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl<'a> FilterParser<'a> {
+    fn new(query: &'a str) -> FilterParser<'a> {
+        FilterParser {
+            query,
+            chars: query.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    fn error<S: AsRef<str>>(&self, msg: S) -> OxenError {
+        OxenError::filter_parse_error(self.query, self.pos, msg)
+    }
+
+    fn is_done(&self) -> bool {
+        self.pos >= self.chars.len()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().map(|c| c.is_whitespace()).unwrap_or(false) {
+            self.pos += 1;
+        }
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        s.chars()
+            .enumerate()
+            .all(|(i, c)| self.chars.get(self.pos + i) == Some(&c))
+    }
+
+    // Consumes `s` if it is next in the input
+    fn consume(&mut self, s: &str) -> bool {
+        if self.starts_with(s) {
+            self.pos += s.chars().count();
+            true
+        } else {
+            false
+        }
+    }
+
+    // Consumes a keyword like `in` or `is` only if it is a whole word
+    fn consume_keyword(&mut self, keyword: &str) -> bool {
+        let start = self.pos;
+        let word = self.read_word();
+        if word == keyword {
+            true
+        } else {
+            self.pos = start;
+            false
+        }
+    }
+
+    fn read_word(&mut self) -> String {
+        let start = self.pos;
+        while self.peek().map(is_field_char).unwrap_or(false) {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    // or := and ( '||' and )*
+    fn parse_or(&mut self) -> Result<DFFilterExp, OxenError> {
+        let mut exp = self.parse_and()?;
+        loop {
+            self.skip_whitespace();
+            if !self.consume(DFLogicalOp::OR.as_str()) {
+                return Ok(exp);
             }
+            let rhs = self.parse_and()?;
+            exp = DFFilterExp::Logical(DFLogicalOp::OR, Box::new(exp), Box::new(rhs));
         }
+    }
 
-        return Ok(Some(DFFilterExp {
-            logical_ops: found_ops,
-            vals: filter_vals,
-        }));
+    // and := term ( '&&' term )*
+    fn parse_and(&mut self) -> Result<DFFilterExp, OxenError> {
+        let mut exp = self.parse_term()?;
+        loop {
+            self.skip_whitespace();
+            if !self.consume(DFLogicalOp::AND.as_str()) {
+                return Ok(exp);
+            }
+            let rhs = self.parse_term()?;
+            exp = DFFilterExp::Logical(DFLogicalOp::AND, Box::new(exp), Box::new(rhs));
+        }
+    }
+
+    // term := '(' or ')' | predicate
+    fn parse_term(&mut self) -> Result<DFFilterExp, OxenError> {
+        self.skip_whitespace();
+        if self.consume("(") {
+            let exp = self.parse_or()?;
+            self.skip_whitespace();
+            if !self.consume(")") {
+                return Err(self.error("Expected closing parenthesis"));
+            }
+            return Ok(exp);
+        }
+        Ok(DFFilterExp::Val(self.parse_predicate()?))
+    }
+
+    // predicate := field op value | field 'is' ['not'] 'null'
+    fn parse_predicate(&mut self) -> Result<DFFilterVal, OxenError> {
+        let field = self.parse_field()?;
+        self.skip_whitespace();
+
+        if self.consume_keyword("is") {
+            self.skip_whitespace();
+            let op = if self.consume_keyword("not") {
+                self.skip_whitespace();
+                DFFilterOp::IsNotNull
+            } else {
+                DFFilterOp::IsNull
+            };
+            if !self.consume_keyword("null") {
+                return Err(self.error("Expected null"));
+            }
+            return Ok(DFFilterVal {
+                op,
+                field,
+                value: None,
+            });
+        }
+
+        let op = self.parse_op()?;
+        self.skip_whitespace();
+        let value_pos = self.pos;
+        let value = match op {
+            DFFilterOp::In => self.parse_list()?,
+            _ => self.parse_literal()?,
+        };
+
+        if op == DFFilterOp::Regex {
+            if let Err(err) = Regex::new(&value.to_string()) {
+                self.pos = value_pos;
+                return Err(self.error(format!("Invalid regex: {err}")));
+            }
+        }
+
+        Ok(DFFilterVal {
+            op,
+            field,
+            value: Some(value),
+        })
+    }
+
+    fn parse_field(&mut self) -> Result<String, OxenError> {
+        if let Some(quote) = self.peek().filter(|c| is_quote(*c)) {
+            return self.parse_quoted(quote);
+        }
+
+        let field = self.read_word();
+        if field.is_empty() {
+            return Err(self.error("Expected a column name"));
+        }
+        Ok(field)
+    }
+
+    fn parse_op(&mut self) -> Result<DFFilterOp, OxenError> {
+        // Longest first, since some ops are prefixes of others
+        let symbol_ops = ["==", "!=", ">=", "<=", "=~", ">", "<"];
+        for op in symbol_ops {
+            if self.consume(op) {
+                return DFFilterOp::from_str_op(op);
+            }
+        }
+
+        let start = self.pos;
+        let word = self.read_word();
+        match DFFilterOp::from_str_op(&word) {
+            Ok(op) => Ok(op),
+            Err(_) => {
+                self.pos = start;
+                Err(self.error(
+                    "Expected an operator: ==, !=, >, <, >=, <=, =~, in, contains, starts_with or is",
+                ))
+            }
+        }
+    }
+
+    fn parse_list(&mut self) -> Result<DFFilterLiteral, OxenError> {
+        if !self.consume("[") {
+            return Err(self.error("Expected a list like [a, b]"));
+        }
+
+        let mut vals: Vec<DFFilterLiteral> = vec![];
+        loop {
+            self.skip_whitespace();
+            if self.consume("]") {
+                return Ok(DFFilterLiteral::List(vals));
+            }
+            if !vals.is_empty() && !self.consume(",") {
+                return Err(self.error("Expected , or ] in list"));
+            }
+            self.skip_whitespace();
+            vals.push(self.parse_scalar(|c| c == ',' || c == ']')?);
+        }
+    }
+
+    fn parse_literal(&mut self) -> Result<DFFilterLiteral, OxenError> {
+        self.parse_scalar(|c| c == ')')
+    }
+
+    // Quoted values are always strings, anything else is typed by what it looks like
+    fn parse_scalar(&mut self, is_end: fn(char) -> bool) -> Result<DFFilterLiteral, OxenError> {
+        if let Some(quote) = self.peek().filter(|c| is_quote(*c)) {
+            return Ok(DFFilterLiteral::Str(self.parse_quoted(quote)?));
+        }
+
+        // Unquoted values run until the next logical op so "label == person walking" still works
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if is_end(c) || self.starts_with("&&") || self.starts_with("||") {
+                break;
+            }
+            self.pos += 1;
+        }
+        let raw: String = self.chars[start..self.pos].iter().collect();
+        let raw = raw.trim();
+        if raw.is_empty() {
+            return Err(self.error("Expected a value"));
+        }
+        Ok(typed_literal(raw))
+    }
+
+    fn parse_quoted(&mut self, quote: char) -> Result<String, OxenError> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut val = String::new();
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            if c == quote {
+                return Ok(val);
+            }
+            val.push(c);
+        }
+        self.pos = start;
+        Err(self.error("Unterminated quote"))
+    }
+}
+
+fn is_quote(c: char) -> bool {
+    c == '\'' || c == '"'
+}
+
+fn is_field_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '.' || c == '-' || c == '/'
+}
+
+fn is_date(s: &str) -> bool {
+    let parts: Vec<&str> = s.split('-').collect();
+    parts.len() == 3
+        && parts[0].len() == 4
+        && parts[1].len() == 2
+        && parts[2].len() == 2
+        && parts.iter().all(|p| p.chars().all(|c| c.is_ascii_digit()))
+}
+
+fn typed_literal(raw: &str) -> DFFilterLiteral {
+    if raw == "true" || raw == "false" {
+        DFFilterLiteral::Bool(raw == "true")
+    } else if raw.parse::<f64>().is_ok() {
+        DFFilterLiteral::Number(String::from(raw))
+    } else if is_date(raw) {
+        DFFilterLiteral::Date(String::from(raw))
+    } else {
+        DFFilterLiteral::Str(String::from(raw))
     }
-    Ok(None)
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
-        df::filter::{parse, DFFilterOp, DFLogicalOp},
+        df::filter::{parse, DFFilterExp, DFFilterLiteral, DFFilterOp, DFFilterVal, DFLogicalOp},
         error::OxenError,
     };
 
+    fn val(field: &str, op: DFFilterOp, value: Option<DFFilterLiteral>) -> DFFilterExp {
+        DFFilterExp::Val(DFFilterVal {
+            op,
+            field: String::from(field),
+            value,
+        })
+    }
+
+    fn logical(op: DFLogicalOp, lhs: DFFilterExp, rhs: DFFilterExp) -> DFFilterExp {
+        DFFilterExp::Logical(op, Box::new(lhs), Box::new(rhs))
+    }
+
+    fn str_lit(s: &str) -> Option<DFFilterLiteral> {
+        Some(DFFilterLiteral::Str(String::from(s)))
+    }
+
+    fn num_lit(s: &str) -> Option<DFFilterLiteral> {
+        Some(DFFilterLiteral::Number(String::from(s)))
+    }
+
     #[test]
     fn test_parse_filter_single_expr() -> Result<(), OxenError> {
         let query = Some("label == person".to_string());
 
-        let opt = parse(query)?.unwrap();
+        let exp = parse(query)?.unwrap();
 
-        assert_eq!(opt.logical_ops.len(), 0);
-
-        assert_eq!(opt.vals.len(), 1);
-        assert_eq!(opt.vals[0].field, "label");
-        assert_eq!(opt.vals[0].op, DFFilterOp::EQ);
-        assert_eq!(opt.vals[0].value, "person");
+        assert_eq!(exp, val("label", DFFilterOp::EQ, str_lit("person")));
         Ok(())
     }
 
@@ -183,18 +473,20 @@ mod tests {
     fn test_parse_filter_two_logical_op_expression() -> Result<(), OxenError> {
         let query = Some("label == person && is_true == false".to_string());
 
-        let opt = parse(query)?.unwrap();
-
-        assert_eq!(opt.logical_ops.len(), 1);
-        assert_eq!(opt.logical_ops[0], DFLogicalOp::AND);
-
-        assert_eq!(opt.vals.len(), 2);
-        assert_eq!(opt.vals[0].field, "label");
-        assert_eq!(opt.vals[0].op, DFFilterOp::EQ);
-        assert_eq!(opt.vals[0].value, "person");
-        assert_eq!(opt.vals[1].field, "is_true");
-        assert_eq!(opt.vals[1].op, DFFilterOp::EQ);
-        assert_eq!(opt.vals[1].value, "false");
+        let exp = parse(query)?.unwrap();
+
+        assert_eq!(
+            exp,
+            logical(
+                DFLogicalOp::AND,
+                val("label", DFFilterOp::EQ, str_lit("person")),
+                val(
+                    "is_true",
+                    DFFilterOp::EQ,
+                    Some(DFFilterLiteral::Bool(false))
+                ),
+            )
+        );
         Ok(())
     }
 
@@ -202,22 +494,162 @@ mod tests {
     fn test_parse_filter_three_logical_op_expression() -> Result<(), OxenError> {
         let query = Some("label == person && min_x > 0 || max_x >= 1.0".to_string());
 
-        let opt = parse(query)?.unwrap();
-
-        assert_eq!(opt.logical_ops.len(), 2);
-        assert_eq!(opt.logical_ops[0], DFLogicalOp::AND);
-        assert_eq!(opt.logical_ops[1], DFLogicalOp::OR);
-
-        assert_eq!(opt.vals.len(), 3);
-        assert_eq!(opt.vals[0].field, "label");
-        assert_eq!(opt.vals[0].op, DFFilterOp::EQ);
-        assert_eq!(opt.vals[0].value, "person");
-        assert_eq!(opt.vals[1].field, "min_x");
-        assert_eq!(opt.vals[1].op, DFFilterOp::GT);
-        assert_eq!(opt.vals[1].value, "0");
-        assert_eq!(opt.vals[2].field, "max_x");
-        assert_eq!(opt.vals[2].op, DFFilterOp::GTE);
-        assert_eq!(opt.vals[2].value, "1.0");
+        let exp = parse(query)?.unwrap();
+
+        // && binds tighter than ||
+        assert_eq!(
+            exp,
+            logical(
+                DFLogicalOp::OR,
+                logical(
+                    DFLogicalOp::AND,
+                    val("label", DFFilterOp::EQ, str_lit("person")),
+                    val("min_x", DFFilterOp::GT, num_lit("0")),
+                ),
+                val("max_x", DFFilterOp::GTE, num_lit("1.0")),
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_filter_precedence_or_before_and() -> Result<(), OxenError> {
+        let query = Some("a == 1 || b == 2 && c == 3".to_string());
+
+        let exp = parse(query)?.unwrap();
+
+        assert_eq!(
+            exp,
+            logical(
+                DFLogicalOp::OR,
+                val("a", DFFilterOp::EQ, num_lit("1")),
+                logical(
+                    DFLogicalOp::AND,
+                    val("b", DFFilterOp::EQ, num_lit("2")),
+                    val("c", DFFilterOp::EQ, num_lit("3")),
+                ),
+            )
+        );
         Ok(())
     }
+
+    #[test]
+    fn test_parse_filter_nested_parens() -> Result<(), OxenError> {
+        let query = Some("(a == 1 || (b == 2)) && c != 'x y'".to_string());
+
+        let exp = parse(query)?.unwrap();
+
+        assert_eq!(
+            exp,
+            logical(
+                DFLogicalOp::AND,
+                logical(
+                    DFLogicalOp::OR,
+                    val("a", DFFilterOp::EQ, num_lit("1")),
+                    val("b", DFFilterOp::EQ, num_lit("2")),
+                ),
+                val("c", DFFilterOp::NEQ, str_lit("x y")),
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_filter_keyword_ops() -> Result<(), OxenError> {
+        let exp = parse(Some("label in [cat, 'dog', 3]".to_string()))?.unwrap();
+        assert_eq!(
+            exp,
+            val(
+                "label",
+                DFFilterOp::In,
+                Some(DFFilterLiteral::List(vec![
+                    DFFilterLiteral::Str(String::from("cat")),
+                    DFFilterLiteral::Str(String::from("dog")),
+                    DFFilterLiteral::Number(String::from("3")),
+                ]))
+            )
+        );
+
+        let exp = parse(Some(
+            "file starts_with 'train/' && label contains at".to_string(),
+        ))?
+        .unwrap();
+        assert_eq!(
+            exp,
+            logical(
+                DFLogicalOp::AND,
+                val("file", DFFilterOp::StartsWith, str_lit("train/")),
+                val("label", DFFilterOp::Contains, str_lit("at")),
+            )
+        );
+
+        let exp = parse(Some("file =~ '^train/.*\\.jpg$'".to_string()))?.unwrap();
+        assert_eq!(
+            exp,
+            val("file", DFFilterOp::Regex, str_lit("^train/.*\\.jpg$"))
+        );
+
+        let exp = parse(Some("note is null || note is not null".to_string()))?.unwrap();
+        assert_eq!(
+            exp,
+            logical(
+                DFLogicalOp::OR,
+                val("note", DFFilterOp::IsNull, None),
+                val("note", DFFilterOp::IsNotNull, None),
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_filter_typed_literals() -> Result<(), OxenError> {
+        let exp = parse(Some("created >= 2022-01-31".to_string()))?.unwrap();
+        assert_eq!(
+            exp,
+            val(
+                "created",
+                DFFilterOp::GTE,
+                Some(DFFilterLiteral::Date(String::from("2022-01-31")))
+            )
+        );
+
+        // Quoted values are always strings
+        let exp = parse(Some("is_true == 'true'".to_string()))?.unwrap();
+        assert_eq!(exp, val("is_true", DFFilterOp::EQ, str_lit("true")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_filter_required_eqs() -> Result<(), OxenError> {
+        let exp = parse(Some("label == cat && (id == 1 && x > 2)".to_string()))?.unwrap();
+        assert_eq!(
+            exp.required_eqs(),
+            vec![
+                (String::from("label"), String::from("cat")),
+                (String::from("id"), String::from("1")),
+            ]
+        );
+
+        // Either side of an || can match, so neither is required
+        let exp = parse(Some("label == cat || id == 1".to_string()))?.unwrap();
+        assert!(exp.required_eqs().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_filter_errors_have_position() {
+        let err = parse(Some("label == cat && (x > 1".to_string())).unwrap_err();
+        assert!(err.to_string().contains("position 22"));
+
+        let err = parse(Some("label ~~ cat".to_string())).unwrap_err();
+        assert!(err.to_string().contains("position 6"));
+
+        let err = parse(Some("label == 'cat".to_string())).unwrap_err();
+        assert!(err.to_string().contains("position 9"));
+
+        let err = parse(Some("label =~ '(unclosed'".to_string())).unwrap_err();
+        assert!(err.to_string().contains("Invalid regex"));
+
+        assert!(parse(Some("label == cat )".to_string())).is_err());
+    }
 }
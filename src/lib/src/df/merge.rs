@@ -0,0 +1,473 @@
+//! Row level three way merge of tabular data
+//!
+//! Rows are matched across the LCA, HEAD and MERGE versions of a data frame either by a primary key column,
+//! or by their `_row_hash` if there is no key. With a key we can tell that a row was edited, without one an edit
+//! looks like a remove plus an add. So without a key, a row of the LCA that is gone from both sides is removed,
+//! unless each side added a different row in its place, which is a conflict.
+
+use polars::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+use crate::constants;
+use crate::df::tabular;
+use crate::error::OxenError;
+use crate::model::{RowConflict, Schema};
+
+const MERGE_POS_COL_NAME: &str = "_merge_pos";
+const MERGE_ERROR: &str = "Could not combine merged rows";
+
+pub struct RowMerge {
+    /// The merged data frame, conflicting rows keep the HEAD version
+    pub df: DataFrame,
+    pub conflicts: Vec<RowConflict>,
+}
+
+impl RowMerge {
+    pub fn has_conflicts(&self) -> bool {
+        !self.conflicts.is_empty()
+    }
+}
+
+/// A data frame with the key and hash of each row, and a lookup from key to row index
+pub struct KeyedRows {
+    pub df: DataFrame,
+    pub keys: Vec<String>,
+    pub hashes: Vec<String>,
+    pub indices: HashMap<String, usize>,
+}
+
+impl KeyedRows {
+    pub fn new(df: DataFrame, primary_key: Option<&str>) -> Result<KeyedRows, OxenError> {
+        let hashed = tabular::df_hash_rows(df.clone())?;
+        let hashes: Vec<String> = hashed
+            .column(constants::ROW_HASH_COL_NAME)
+            .unwrap()
+            .utf8()
+            .unwrap()
+            .into_iter()
+            .map(|v| v.unwrap_or_default().to_string())
+            .collect();
+
+        let keys: Vec<String> = match primary_key {
+            Some(key) => {
+                let column = df.column(key).map_err(|_| {
+                    OxenError::basic_str(format!("Primary key column not found: {key}"))
+                })?;
+                let column = column.cast(&polars::prelude::DataType::Utf8).map_err(|_| {
+                    OxenError::basic_str(format!("Could not use column as primary key: {key}"))
+                })?;
+                column
+                    .utf8()
+                    .unwrap()
+                    .into_iter()
+                    .map(|v| v.unwrap_or_default().to_string())
+                    .collect()
+            }
+            None => hashes.clone(),
+        };
+
+        let mut indices: HashMap<String, usize> = HashMap::new();
+        for (i, key) in keys.iter().enumerate() {
+            // Identical rows share a hash, but a primary key has to be unique to line rows up
+            let is_duplicate = indices.insert(key.to_owned(), i).is_some();
+            if let (true, Some(primary_key)) = (is_duplicate, primary_key) {
+                return Err(OxenError::basic_str(format!(
+                    "Primary key {primary_key:?} is not unique, found duplicate value {key:?}"
+                )));
+            }
+        }
+
+        Ok(KeyedRows {
+            df,
+            keys,
+            hashes,
+            indices,
+        })
+    }
+
+    /// The values of a row as strings, None if there is no row at `idx`
+    pub fn row(&self, idx: Option<usize>) -> Option<Vec<String>> {
+        let row = self.df.get_row(idx?).ok()?;
+        Some(
+            row.0
+                .iter()
+                .map(|val| match val {
+                    AnyValue::Null => String::from(""),
+                    AnyValue::Utf8(val) => val.to_string(),
+                    val => val.to_string(),
+                })
+                .collect(),
+        )
+    }
+
+    fn conflict(
+        key: &str,
+        lca: (&KeyedRows, Option<usize>),
+        head: (&KeyedRows, Option<usize>),
+        merge: (&KeyedRows, Option<usize>),
+    ) -> RowConflict {
+        RowConflict {
+            key: key.to_owned(),
+            lca_row: lca.0.row(lca.1),
+            head_row: head.0.row(head.1),
+            merge_row: merge.0.row(merge.1),
+        }
+    }
+}
+
+/// Merges the rows of HEAD and MERGE given their common ancestor. Rows added, removed or edited on only one side
+/// are merged, rows changed differently on both sides are returned as conflicts.
+/// Returns None if the schemas differ, since we cannot line up rows across them.
+pub fn three_way_merge(
+    lca: DataFrame,
+    head: DataFrame,
+    merge: DataFrame,
+    primary_key: Option<&str>,
+) -> Result<Option<RowMerge>, OxenError> {
+    let schema_hash = Schema::from_polars(&head.schema()).hash;
+    if Schema::from_polars(&lca.schema()).hash != schema_hash
+        || Schema::from_polars(&merge.schema()).hash != schema_hash
+    {
+        log::debug!("df::merge::three_way_merge schemas differ, cannot merge rows");
+        return Ok(None);
+    }
+
+    let lca = KeyedRows::new(lca, primary_key)?;
+    let head = KeyedRows::new(head, primary_key)?;
+    let merge = KeyedRows::new(merge, primary_key)?;
+
+    // Keep track of where each row we take ends up, so HEAD order is preserved and MERGE additions go at the end
+    let mut head_take: (Vec<u32>, Vec<u32>) = (vec![], vec![]);
+    let mut merge_take: (Vec<u32>, Vec<u32>) = (vec![], vec![]);
+    let mut conflicts: Vec<RowConflict> = vec![];
+    let mut pos: u32 = 0;
+
+    for (i, key) in head.keys.iter().enumerate() {
+        let head_hash = &head.hashes[i];
+        match (lca.indices.get(key), merge.indices.get(key)) {
+            (Some(&l), Some(&m)) => {
+                let lca_hash = &lca.hashes[l];
+                let merge_hash = &merge.hashes[m];
+                if head_hash == lca_hash {
+                    // Only MERGE could have edited it
+                    merge_take.0.push(m as u32);
+                    merge_take.1.push(pos);
+                } else {
+                    if merge_hash != lca_hash && merge_hash != head_hash {
+                        conflicts.push(KeyedRows::conflict(
+                            key,
+                            (&lca, Some(l)),
+                            (&head, Some(i)),
+                            (&merge, Some(m)),
+                        ));
+                    }
+                    head_take.0.push(i as u32);
+                    head_take.1.push(pos);
+                }
+            }
+            (Some(&l), None) => {
+                // Removed on MERGE, which conflicts if HEAD edited it
+                if head_hash == &lca.hashes[l] {
+                    continue;
+                }
+                conflicts.push(KeyedRows::conflict(
+                    key,
+                    (&lca, Some(l)),
+                    (&head, Some(i)),
+                    (&merge, None),
+                ));
+                head_take.0.push(i as u32);
+                head_take.1.push(pos);
+            }
+            (None, Some(&m)) => {
+                // Added on both sides, fine as long as they added the same thing
+                if head_hash != &merge.hashes[m] {
+                    conflicts.push(KeyedRows::conflict(
+                        key,
+                        (&lca, None),
+                        (&head, Some(i)),
+                        (&merge, Some(m)),
+                    ));
+                }
+                head_take.0.push(i as u32);
+                head_take.1.push(pos);
+            }
+            (None, None) => {
+                // Added on HEAD
+                head_take.0.push(i as u32);
+                head_take.1.push(pos);
+            }
+        }
+        pos += 1;
+    }
+
+    // Without a key, both sides editing a row shows up as both removing it, and adding a row where it was.
+    // Identical edits already line up by their hash. Different ones conflict and keep the HEAD row.
+    let mut conflicting_merge_rows: HashSet<usize> = HashSet::new();
+    if primary_key.is_none() {
+        let head_replacements = replacements(&lca, &head);
+        let merge_replacements = replacements(&lca, &merge);
+        for (l, key) in lca.keys.iter().enumerate() {
+            if head.indices.contains_key(key) || merge.indices.contains_key(key) {
+                continue;
+            }
+
+            if let (Some(&h), Some(&m)) = (head_replacements.get(&l), merge_replacements.get(&l)) {
+                if head.hashes[h] != merge.hashes[m] {
+                    conflicts.push(KeyedRows::conflict(
+                        key,
+                        (&lca, Some(l)),
+                        (&head, Some(h)),
+                        (&merge, Some(m)),
+                    ));
+                    conflicting_merge_rows.insert(m);
+                }
+            }
+        }
+    }
+
+    for (i, key) in merge.keys.iter().enumerate() {
+        if head.indices.contains_key(key) || conflicting_merge_rows.contains(&i) {
+            continue;
+        }
+
+        match lca.indices.get(key) {
+            Some(&l) => {
+                // Removed on HEAD, which conflicts if MERGE edited it
+                if merge.hashes[i] != lca.hashes[l] {
+                    conflicts.push(KeyedRows::conflict(
+                        key,
+                        (&lca, Some(l)),
+                        (&head, None),
+                        (&merge, Some(i)),
+                    ));
+                }
+            }
+            None => {
+                // Added on MERGE
+                merge_take.0.push(i as u32);
+                merge_take.1.push(pos);
+                pos += 1;
+            }
+        }
+    }
+
+    log::debug!(
+        "df::merge::three_way_merge took {} rows from HEAD, {} rows from MERGE, {} conflicts",
+        head_take.0.len(),
+        merge_take.0.len(),
+        conflicts.len()
+    );
+
+    let head_rows = take_with_positions(&head.df, head_take)?;
+    let merge_rows = take_with_positions(&merge.df, merge_take)?;
+    let df = head_rows
+        .vstack(&merge_rows)
+        .and_then(|df| df.sort([MERGE_POS_COL_NAME], false))
+        .and_then(|df| df.drop(MERGE_POS_COL_NAME))
+        .map_err(merge_error)?;
+
+    Ok(Some(RowMerge { df, conflicts }))
+}
+
+/// Pairs the LCA rows a side removed with the rows it added in their place, as LCA index to side index.
+/// Rows are in the same place if they come after the same LCA row the side kept.
+fn replacements(lca: &KeyedRows, side: &KeyedRows) -> HashMap<usize, usize> {
+    let mut removed: HashMap<Option<usize>, Vec<usize>> = HashMap::new();
+    let mut anchor: Option<usize> = None;
+    for (l, key) in lca.keys.iter().enumerate() {
+        if side.indices.contains_key(key) {
+            anchor = Some(l);
+        } else {
+            removed.entry(anchor).or_default().push(l);
+        }
+    }
+
+    let mut added: HashMap<Option<usize>, Vec<usize>> = HashMap::new();
+    let mut anchor: Option<usize> = None;
+    for (i, key) in side.keys.iter().enumerate() {
+        match lca.indices.get(key) {
+            Some(&l) => anchor = Some(l),
+            None => added.entry(anchor).or_default().push(i),
+        }
+    }
+
+    let mut pairs: HashMap<usize, usize> = HashMap::new();
+    for (anchor, removed) in removed.iter() {
+        if let Some(added) = added.get(anchor) {
+            pairs.extend(removed.iter().copied().zip(added.iter().copied()));
+        }
+    }
+    pairs
+}
+
+fn take_with_positions(
+    df: &DataFrame,
+    (indices, positions): (Vec<u32>, Vec<u32>),
+) -> Result<DataFrame, OxenError> {
+    let mut rows = tabular::take(df.clone().lazy(), indices)?;
+    rows.with_column(Series::new(MERGE_POS_COL_NAME, positions))
+        .map_err(merge_error)?;
+    Ok(rows)
+}
+
+fn merge_error(err: PolarsError) -> OxenError {
+    OxenError::basic_str(format!("{MERGE_ERROR}: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::df::merge;
+    use crate::error::OxenError;
+    use polars::prelude::*;
+
+    fn labels_df(ids: &[i64], labels: &[&str]) -> DataFrame {
+        df!(
+            "id" => ids,
+            "label" => labels,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_merge_rows_edited_on_different_sides() -> Result<(), OxenError> {
+        let lca = labels_df(&[1, 2, 3], &["cat", "dog", "fish"]);
+        // HEAD edits row 1 and adds row 4
+        let head = labels_df(&[1, 2, 3, 4], &["lion", "dog", "fish", "human"]);
+        // MERGE edits row 2 and removes row 3
+        let merge = labels_df(&[1, 2], &["cat", "wolf"]);
+
+        let result = merge::three_way_merge(lca, head, merge, Some("id"))?.unwrap();
+        assert!(!result.has_conflicts());
+
+        let expected = labels_df(&[1, 2, 4], &["lion", "wolf", "human"]);
+        assert!(result.df.frame_equal(&expected));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_rows_edited_on_both_sides_conflicts() -> Result<(), OxenError> {
+        let lca = labels_df(&[1, 2], &["cat", "dog"]);
+        let head = labels_df(&[1, 2], &["lion", "dog"]);
+        let merge = labels_df(&[1, 2, 3], &["tiger", "dog", "bird"]);
+
+        let result = merge::three_way_merge(lca, head, merge, Some("id"))?.unwrap();
+        assert_eq!(result.conflicts.len(), 1);
+        let conflict = &result.conflicts[0];
+        assert_eq!(conflict.key, "1");
+        assert_eq!(
+            conflict.lca_row,
+            Some(vec!["1".to_string(), "cat".to_string()])
+        );
+        assert_eq!(
+            conflict.head_row,
+            Some(vec!["1".to_string(), "lion".to_string()])
+        );
+        assert_eq!(
+            conflict.merge_row,
+            Some(vec!["1".to_string(), "tiger".to_string()])
+        );
+
+        // Conflicting rows keep HEAD, the rest still merges
+        let expected = labels_df(&[1, 2, 3], &["lion", "dog", "bird"]);
+        assert!(result.df.frame_equal(&expected));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_rows_without_primary_key() -> Result<(), OxenError> {
+        let lca = labels_df(&[1, 2], &["cat", "dog"]);
+        let head = labels_df(&[1, 2, 3], &["cat", "dog", "fish"]);
+        let merge = labels_df(&[2, 4], &["dog", "human"]);
+
+        let result = merge::three_way_merge(lca, head, merge, None)?.unwrap();
+        assert!(!result.has_conflicts());
+
+        let expected = labels_df(&[2, 3, 4], &["dog", "fish", "human"]);
+        assert!(result.df.frame_equal(&expected));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_rows_without_primary_key_edited_on_both_sides_conflicts() -> Result<(), OxenError>
+    {
+        let lca = labels_df(&[1, 2], &["cat", "dog"]);
+        let head = labels_df(&[1, 2], &["lion", "dog"]);
+        let merge = labels_df(&[1, 2], &["tiger", "dog"]);
+
+        let result = merge::three_way_merge(lca, head, merge, None)?.unwrap();
+        assert_eq!(result.conflicts.len(), 1);
+        let conflict = &result.conflicts[0];
+        assert_eq!(
+            conflict.lca_row,
+            Some(vec!["1".to_string(), "cat".to_string()])
+        );
+        assert_eq!(
+            conflict.head_row,
+            Some(vec!["1".to_string(), "lion".to_string()])
+        );
+        assert_eq!(
+            conflict.merge_row,
+            Some(vec!["1".to_string(), "tiger".to_string()])
+        );
+
+        // The conflicting row keeps HEAD
+        let expected = labels_df(&[1, 2], &["lion", "dog"]);
+        assert!(result.df.frame_equal(&expected));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_rows_without_primary_key_removed_on_both_sides() -> Result<(), OxenError> {
+        let lca = labels_df(&[1, 2, 3], &["cat", "dog", "fish"]);
+        let head = labels_df(&[1, 3], &["cat", "fish"]);
+        let merge = labels_df(&[1, 3, 4], &["cat", "fish", "human"]);
+
+        let result = merge::three_way_merge(lca, head, merge, None)?.unwrap();
+        assert!(!result.has_conflicts());
+
+        let expected = labels_df(&[1, 3, 4], &["cat", "fish", "human"]);
+        assert!(result.df.frame_equal(&expected));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_rows_without_primary_key_same_edit_on_both_sides() -> Result<(), OxenError> {
+        let lca = labels_df(&[1, 2, 3], &["cat", "dog", "fish"]);
+        let head = labels_df(&[1, 2, 3], &["cat", "wolf", "fish"]);
+        let merge = labels_df(&[1, 2, 3, 4], &["cat", "wolf", "fish", "human"]);
+
+        let result = merge::three_way_merge(lca, head, merge, None)?.unwrap();
+        assert!(!result.has_conflicts());
+
+        let expected = labels_df(&[1, 2, 3, 4], &["cat", "wolf", "fish", "human"]);
+        assert!(result.df.frame_equal(&expected));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_rows_schema_changed() -> Result<(), OxenError> {
+        let lca = labels_df(&[1], &["cat"]);
+        let head = labels_df(&[1], &["dog"]);
+        let merge = df!("id" => &[1], "name" => &["cat"]).unwrap();
+
+        assert!(merge::three_way_merge(lca, head, merge, None)?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_rows_duplicate_primary_key() {
+        let lca = labels_df(&[1, 1], &["cat", "dog"]);
+        let head = labels_df(&[1], &["cat"]);
+        let merge = labels_df(&[1], &["cat"]);
+
+        assert!(merge::three_way_merge(lca, head, merge, Some("id")).is_err());
+    }
+}
@@ -0,0 +1,371 @@
+//! SQL queries over versioned tabular files
+//!
+//! Tables are quoted `'path@commit_or_branch'` references, optionally followed by an alias. The `@commit_or_branch`
+//! can be left off to query the HEAD commit.
+//!
+//! ```text
+//! SELECT * FROM 'train.csv@main' AS train JOIN 'labels.csv@a1b2c3' AS labels ON train.id = labels.id
+//! ```
+//!
+//! Each reference is resolved with `util::resource::parse_resource`, lazily scanned from the version store, and
+//! registered with a polars `SQLContext` under its alias. Tables without an alias are named after their file stem.
+
+use polars::prelude::*;
+use polars::sql::SQLContext;
+use std::path::{Path, PathBuf};
+
+use crate::api;
+use crate::df::tabular;
+use crate::error::OxenError;
+use crate::index::{ChunkStore, VersionFile};
+use crate::model::LocalRepository;
+use crate::util;
+
+// Words that can follow a table reference, so are never an alias
+const CLAUSE_KEYWORDS: [&str; 15] = [
+    "ON", "WHERE", "JOIN", "INNER", "LEFT", "RIGHT", "FULL", "OUTER", "CROSS", "GROUP", "ORDER",
+    "LIMIT", "HAVING", "UNION", "USING",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SqlTable {
+    /// The identifier the table is registered as in the query
+    pub name: String,
+    pub path: PathBuf,
+    pub commit_or_branch: Option<String>,
+}
+
+impl SqlTable {
+    fn from_reference(name: String, reference: &str) -> SqlTable {
+        match reference.rsplit_once('@') {
+            Some((path, commit_or_branch)) => SqlTable {
+                name,
+                path: PathBuf::from(path),
+                commit_or_branch: Some(String::from(commit_or_branch)),
+            },
+            None => SqlTable {
+                name,
+                path: PathBuf::from(reference),
+                commit_or_branch: None,
+            },
+        }
+    }
+}
+
+/// Runs a query against the versioned files it references
+pub fn query(repo: &LocalRepository, sql: &str) -> Result<DataFrame, OxenError> {
+    let (sql, tables) = parse_tables(sql)?;
+    log::debug!(
+        "df::sql::query rewrote query to {} with tables {:?}",
+        sql,
+        tables
+    );
+
+    // The frames are scanned lazily, so the version files have to stay around until the query ran
+    let mut version_files: Vec<VersionFile> = vec![];
+    let mut frames: Vec<(String, LazyFrame)> = vec![];
+    for table in tables.iter() {
+        let (version_file, frame) = scan_table(repo, table)?;
+        version_files.push(version_file);
+        frames.push((table.name.to_owned(), frame));
+    }
+
+    // polars only allows one context per thread, so make sure it is dropped before we return
+    let mut context = SQLContext::try_new().map_err(|err| OxenError::basic_str(err.to_string()))?;
+    for (name, frame) in frames {
+        context.register(&name, frame);
+    }
+    let result = context
+        .execute(&sql)
+        .and_then(|df| df.collect())
+        .map_err(|err| OxenError::basic_str(format!("Invalid SQL query: {err}")));
+    drop(context);
+    drop(version_files);
+    result
+}
+
+fn scan_table(
+    repo: &LocalRepository,
+    table: &SqlTable,
+) -> Result<(VersionFile, LazyFrame), OxenError> {
+    let commit_or_branch = match &table.commit_or_branch {
+        Some(commit_or_branch) => commit_or_branch.to_owned(),
+        None => api::local::commits::get_head_commit(repo)?.id,
+    };
+
+    let resource = Path::new(&commit_or_branch).join(&table.path);
+    let (commit_id, _, path) = util::resource::parse_resource(repo, &resource)?
+        .ok_or_else(|| OxenError::local_commit_or_branch_not_found(&commit_or_branch))?;
+    let commit = api::local::commits::get_by_id(repo, &commit_id)?
+        .ok_or_else(|| OxenError::local_commit_or_branch_not_found(&commit_or_branch))?;
+    let entry = api::local::entries::get_entry_for_commit(repo, &commit, &path)?
+        .ok_or_else(|| OxenError::file_does_not_exist_in_commit(&path, &commit_id))?;
+
+    let version_file = ChunkStore::new(repo).version_file(&entry)?;
+    let frame = tabular::scan_df(&version_file)?;
+    Ok((version_file, frame))
+}
+
+/// Pulls the quoted table references out of a query, replacing each with the name it will be registered under
+pub fn parse_tables(sql: &str) -> Result<(String, Vec<SqlTable>), OxenError> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut rewritten = String::new();
+    let mut tables: Vec<SqlTable> = vec![];
+    let mut prev_word = String::new();
+
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\'' || c == '"' {
+            let end = (i + 1..chars.len())
+                .find(|j| chars[*j] == c)
+                .ok_or_else(|| {
+                    OxenError::basic_str(format!("Unterminated quote at position {i} in query"))
+                })?;
+            let literal: String = chars[i + 1..end].iter().collect();
+
+            let is_table =
+                prev_word.eq_ignore_ascii_case("FROM") || prev_word.eq_ignore_ascii_case("JOIN");
+            if !is_table {
+                rewritten.extend(&chars[i..=end]);
+                prev_word.clear();
+                i = end + 1;
+                continue;
+            }
+
+            let (alias, next) = parse_alias(&chars, end + 1)?;
+            let name = add_table(&mut tables, &literal, alias)?;
+            rewritten.push_str(&name);
+            prev_word.clear();
+            i = next;
+        } else if is_word_char(c) {
+            let word = read_word(&chars, i);
+            i += word.len();
+            rewritten.push_str(&word);
+            prev_word = word;
+        } else {
+            if !c.is_whitespace() {
+                prev_word.clear();
+            }
+            rewritten.push(c);
+            i += 1;
+        }
+    }
+
+    Ok((rewritten, tables))
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn read_word(chars: &[char], start: usize) -> String {
+    chars[start..]
+        .iter()
+        .take_while(|c| is_word_char(**c))
+        .collect()
+}
+
+// Returns the alias after a table reference if there is one, and where to continue parsing from
+fn parse_alias(chars: &[char], start: usize) -> Result<(Option<String>, usize), OxenError> {
+    let skip_whitespace = |i: usize| {
+        (i..chars.len())
+            .find(|j| !chars[*j].is_whitespace())
+            .unwrap_or(chars.len())
+    };
+
+    let word_start = skip_whitespace(start);
+    let word = read_word(chars, word_start);
+    if word.eq_ignore_ascii_case("AS") {
+        let alias_start = skip_whitespace(word_start + word.len());
+        let alias = read_word(chars, alias_start);
+        if alias.is_empty() {
+            return Err(OxenError::basic_str(format!(
+                "Expected a table alias after AS at position {alias_start} in query"
+            )));
+        }
+        Ok((Some(alias.clone()), alias_start + alias.len()))
+    } else if word.is_empty() || CLAUSE_KEYWORDS.iter().any(|k| word.eq_ignore_ascii_case(k)) {
+        Ok((None, start))
+    } else {
+        Ok((Some(word.clone()), word_start + word.len()))
+    }
+}
+
+fn add_table(
+    tables: &mut Vec<SqlTable>,
+    reference: &str,
+    alias: Option<String>,
+) -> Result<String, OxenError> {
+    let table = SqlTable::from_reference(String::new(), reference);
+    if let Some(existing) = tables
+        .iter()
+        .find(|t| t.path == table.path && t.commit_or_branch == table.commit_or_branch)
+    {
+        if alias.is_none() || alias.as_ref() == Some(&existing.name) {
+            return Ok(existing.name.to_owned());
+        }
+    }
+
+    let name = match alias {
+        Some(alias) => {
+            if tables.iter().any(|t| t.name == alias) {
+                return Err(OxenError::basic_str(format!(
+                    "Table alias {alias} is used more than once"
+                )));
+            }
+            alias
+        }
+        None => {
+            let base = default_table_name(&table.path);
+            let mut name = base.clone();
+            let mut n = 2;
+            while tables.iter().any(|t| t.name == name) {
+                name = format!("{base}_{n}");
+                n += 1;
+            }
+            name
+        }
+    };
+
+    tables.push(SqlTable {
+        name: name.clone(),
+        ..table
+    });
+    Ok(name)
+}
+
+fn default_table_name(path: &Path) -> String {
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("table");
+    let name: String = stem
+        .chars()
+        .map(|c| if is_word_char(c) { c } else { '_' })
+        .collect();
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("t_{name}")
+    } else {
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::command;
+    use crate::df::sql::{self, SqlTable};
+    use crate::error::OxenError;
+    use crate::test;
+    use crate::util;
+
+    use polars::prelude::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_parse_tables_with_aliases() -> Result<(), OxenError> {
+        let (query, tables) = sql::parse_tables(
+            "SELECT * FROM 'data/train.csv@main' AS train JOIN \"labels.csv@a1b2\" labels ON train.id = labels.id WHERE label = 'cat'",
+        )?;
+
+        assert_eq!(
+            query,
+            "SELECT * FROM train JOIN labels ON train.id = labels.id WHERE label = 'cat'"
+        );
+        assert_eq!(
+            tables,
+            vec![
+                SqlTable {
+                    name: String::from("train"),
+                    path: PathBuf::from("data/train.csv"),
+                    commit_or_branch: Some(String::from("main")),
+                },
+                SqlTable {
+                    name: String::from("labels"),
+                    path: PathBuf::from("labels.csv"),
+                    commit_or_branch: Some(String::from("a1b2")),
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_tables_default_names() -> Result<(), OxenError> {
+        let (query, tables) = sql::parse_tables(
+            "select * from 'labels.csv@main' join 'labels.csv@dev' on id = id where x > 1",
+        )?;
+
+        assert_eq!(
+            query,
+            "select * from labels join labels_2 on id = id where x > 1"
+        );
+        assert_eq!(tables.len(), 2);
+        assert_eq!(tables[1].commit_or_branch, Some(String::from("dev")));
+
+        // No commit means HEAD
+        let (_, tables) = sql::parse_tables("SELECT * FROM '2022-labels.csv'")?;
+        assert_eq!(tables[0].name, "t_2022_labels");
+        assert_eq!(tables[0].commit_or_branch, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_tables_unterminated_quote() {
+        let result = sql::parse_tables("SELECT * FROM 'labels.csv@main");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_query_join_across_commits() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let labels = repo.path.join("labels.csv");
+            let images = repo.path.join("images.csv");
+            util::fs::write_to_path(&labels, "id,label\n1,cat\n2,dog\n3,fish\n")?;
+            util::fs::write_to_path(&images, "id,file\n1,cat.jpg\n2,dog.jpg\n3,fish.jpg\n")?;
+            command::add(&repo, &repo.path)?;
+            let first = command::commit(&repo, "Adding data")?.unwrap();
+
+            util::fs::write_to_path(&labels, "id,label\n1,lion\n2,dog\n3,fish\n")?;
+            command::add(&repo, &labels)?;
+            command::commit(&repo, "Relabel")?;
+
+            let query = format!(
+                "SELECT file, label FROM 'images.csv' JOIN 'labels.csv@{}' ON id = id WHERE label = 'cat'",
+                first.id
+            );
+            let df = sql::query(&repo, &query)?;
+            assert_eq!(df.height(), 1);
+            assert_eq!(
+                df.column("file").unwrap().get(0).unwrap(),
+                AnyValue::Utf8("cat.jpg")
+            );
+
+            // HEAD has the new label
+            let df = sql::query(
+                &repo,
+                "SELECT * FROM 'labels.csv@main' WHERE label = 'lion'",
+            )?;
+            assert_eq!(df.height(), 1);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_query_unknown_file() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let labels = repo.path.join("labels.csv");
+            util::fs::write_to_path(&labels, "id,label\n1,cat\n")?;
+            command::add(&repo, &labels)?;
+            command::commit(&repo, "Adding labels")?;
+
+            let result = sql::query(&repo, "SELECT * FROM 'missing.csv@main'");
+            assert!(result.is_err());
+
+            let result = sql::query(&repo, "SELECT missing_col FROM 'labels.csv@main'");
+            assert!(result.is_err());
+
+            Ok(())
+        })
+    }
+}
@@ -17,7 +17,7 @@ use std::path::Path;
 
 use super::{
     agg::{DFAggFn, DFAggFnType, DFAggregation},
-    filter::{DFFilterExp, DFFilterOp, DFFilterVal},
+    filter::{DFFilterExp, DFFilterLiteral, DFFilterOp, DFFilterVal},
 };
 
 const DEFAULT_INFER_SCHEMA_LEN: usize = 10000;
@@ -201,58 +201,138 @@ fn val_from_str_and_dtype<'a>(s: &'a str, dtype: &polars::prelude::DataType) ->
     }
 }
 
-fn val_from_df_and_filter<'a>(df: &'a LazyFrame, filter: &'a DFFilterVal) -> AnyValue<'a> {
-    if let Some(value) = df
-        .schema()
-        .expect("Unable to get schema from data frame")
-        .iter_fields()
-        .find(|f| f.name == filter.field)
-    {
-        val_from_str_and_dtype(&filter.value, value.data_type())
-    } else {
-        log::error!("Unknown field {:?}", filter.field);
-        AnyValue::Null
+fn filter_field_dtype(
+    schema: &polars::prelude::Schema,
+    field: &str,
+) -> Result<polars::prelude::DataType, OxenError> {
+    schema
+        .get(field)
+        .cloned()
+        .ok_or_else(|| OxenError::basic_str(format!("Unknown column in filter: {field:?}")))
+}
+
+// Dates are compared as YYYY-MM-DD text, which sorts the same as the dates themselves
+fn filter_col(field: &str, dtype: &polars::prelude::DataType) -> Expr {
+    match dtype {
+        polars::prelude::DataType::Date | polars::prelude::DataType::Datetime(_, _) => {
+            col(field).cast(polars::prelude::DataType::Utf8)
+        }
+        _ => col(field),
     }
 }
 
-fn lit_from_any(value: &AnyValue) -> Expr {
-    match value {
-        AnyValue::Boolean(val) => lit(*val),
-        AnyValue::Float64(val) => lit(*val),
-        AnyValue::Float32(val) => lit(*val),
-        AnyValue::Int64(val) => lit(*val),
-        AnyValue::Int32(val) => lit(*val),
-        AnyValue::Utf8(val) => lit(*val),
-        val => panic!("Unknown data type for [{}] to create literal", val),
+fn filter_lit(
+    field: &str,
+    dtype: &polars::prelude::DataType,
+    value: &DFFilterLiteral,
+) -> Result<Expr, OxenError> {
+    let text = value.to_string();
+    let mismatch = || {
+        OxenError::basic_str(format!(
+            "Cannot compare column {field:?} of type {dtype} to {text:?}"
+        ))
+    };
+
+    match (dtype, value) {
+        (_, DFFilterLiteral::List(_)) => Err(OxenError::basic_str(format!(
+            "Only the in operator takes a list, column {field:?}"
+        ))),
+        (polars::prelude::DataType::Utf8, _)
+        | (polars::prelude::DataType::Date, _)
+        | (polars::prelude::DataType::Datetime(_, _), _) => Ok(lit(text)),
+        (polars::prelude::DataType::Boolean, _) => {
+            text.parse::<bool>().map(lit).map_err(|_| mismatch())
+        }
+        (polars::prelude::DataType::Float32, _) | (polars::prelude::DataType::Float64, _) => {
+            text.parse::<f64>().map(lit).map_err(|_| mismatch())
+        }
+        (dtype, _) if dtype.is_numeric() => text.parse::<i64>().map(lit).map_err(|_| mismatch()),
+        (polars::prelude::DataType::Null, _) => Ok(lit(NULL)),
+        _ => Err(OxenError::basic_str(format!(
+            "Cannot filter column {field:?} of type {dtype}"
+        ))),
     }
 }
 
-fn filter_from_val(df: &LazyFrame, filter: &DFFilterVal) -> Expr {
-    let val = val_from_df_and_filter(df, filter);
-    let val = lit_from_any(&val);
+fn filter_from_val(
+    schema: &polars::prelude::Schema,
+    filter: &DFFilterVal,
+) -> Result<Expr, OxenError> {
+    let field = &filter.field;
+    let dtype = filter_field_dtype(schema, field)?;
+    let column = filter_col(field, &dtype);
+
+    let value = match (&filter.op, &filter.value) {
+        (DFFilterOp::IsNull, _) => return Ok(col(field).is_null()),
+        (DFFilterOp::IsNotNull, _) => return Ok(col(field).is_not_null()),
+        (op, None) => {
+            return Err(OxenError::basic_str(format!(
+                "Filter op {} on {field:?} requires a value",
+                op.as_str()
+            )))
+        }
+        (_, Some(value)) => value,
+    };
+
     match filter.op {
-        DFFilterOp::EQ => col(&filter.field).eq(val),
-        DFFilterOp::GT => col(&filter.field).gt(val),
-        DFFilterOp::LT => col(&filter.field).lt(val),
-        DFFilterOp::GTE => col(&filter.field).gt_eq(val),
-        DFFilterOp::LTE => col(&filter.field).lt_eq(val),
-        DFFilterOp::NEQ => col(&filter.field).neq(val),
+        DFFilterOp::In => {
+            let vals = match value {
+                DFFilterLiteral::List(vals) => vals,
+                _ => {
+                    return Err(OxenError::basic_str(
+                        "The in operator takes a list like [a, b]",
+                    ))
+                }
+            };
+            // Equivalent to is_in, which our polars build does not include
+            let mut expr = lit(false);
+            for val in vals {
+                expr = expr.or(column.clone().eq(filter_lit(field, &dtype, val)?));
+            }
+            Ok(expr)
+        }
+        DFFilterOp::Contains | DFFilterOp::StartsWith | DFFilterOp::Regex => {
+            if dtype != polars::prelude::DataType::Utf8 {
+                return Err(OxenError::basic_str(format!(
+                    "Filter op {} requires a string column, {field:?} is {dtype}",
+                    filter.op.as_str()
+                )));
+            }
+            let text = value.to_string();
+            match filter.op {
+                DFFilterOp::Contains => Ok(column.str().contains_literal(text)),
+                DFFilterOp::StartsWith => Ok(column.str().starts_with(text)),
+                _ => Ok(column.str().contains(text)),
+            }
+        }
+        DFFilterOp::EQ => Ok(column.eq(filter_lit(field, &dtype, value)?)),
+        DFFilterOp::GT => Ok(column.gt(filter_lit(field, &dtype, value)?)),
+        DFFilterOp::LT => Ok(column.lt(filter_lit(field, &dtype, value)?)),
+        DFFilterOp::GTE => Ok(column.gt_eq(filter_lit(field, &dtype, value)?)),
+        DFFilterOp::LTE => Ok(column.lt_eq(filter_lit(field, &dtype, value)?)),
+        DFFilterOp::NEQ => Ok(column.neq(filter_lit(field, &dtype, value)?)),
+        DFFilterOp::IsNull | DFFilterOp::IsNotNull => unreachable!(),
     }
 }
 
-fn filter_df(df: LazyFrame, filter: &DFFilterExp) -> Result<LazyFrame, OxenError> {
-    log::debug!("Got filter: {:?}", filter);
-    let mut vals = filter.vals.iter();
-    let mut expr: Expr = filter_from_val(&df, vals.next().unwrap());
-    for op in &filter.logical_ops {
-        let chain_expr: Expr = filter_from_val(&df, vals.next().unwrap());
-
-        match op {
-            DFLogicalOp::AND => expr = expr.and(chain_expr),
-            DFLogicalOp::OR => expr = expr.or(chain_expr),
+fn filter_exp(schema: &polars::prelude::Schema, filter: &DFFilterExp) -> Result<Expr, OxenError> {
+    match filter {
+        DFFilterExp::Val(val) => filter_from_val(schema, val),
+        DFFilterExp::Logical(op, lhs, rhs) => {
+            let lhs = filter_exp(schema, lhs)?;
+            let rhs = filter_exp(schema, rhs)?;
+            match op {
+                DFLogicalOp::AND => Ok(lhs.and(rhs)),
+                DFLogicalOp::OR => Ok(lhs.or(rhs)),
+            }
         }
     }
+}
 
+fn filter_df(df: LazyFrame, filter: &DFFilterExp) -> Result<LazyFrame, OxenError> {
+    log::debug!("Got filter: {:?}", filter);
+    let schema = df.schema().expect("Unable to get schema from data frame");
+    let expr = filter_exp(&schema, filter)?;
     Ok(df.filter(expr))
 }
 
@@ -306,9 +386,38 @@ fn unique_df(df: LazyFrame, columns: Vec<String>) -> Result<LazyFrame, OxenError
     Ok(df.unique(Some(columns), UniqueKeepStrategy::First))
 }
 
-pub fn transform_df(mut df: LazyFrame, opts: DFOpts) -> Result<DataFrame, OxenError> {
+pub fn transform_df(df: LazyFrame, opts: DFOpts) -> Result<DataFrame, OxenError> {
+    transform_df_with_index(df, opts, |_, _| Ok(None))
+}
+
+/// Same as `transform_df`, but `lookup(field, value)` can return the rows where `field == value` from a field index,
+/// which are taken before the filter is applied
+pub fn transform_df_with_index<F>(
+    mut df: LazyFrame,
+    opts: DFOpts,
+    lookup: F,
+) -> Result<DataFrame, OxenError>
+where
+    F: Fn(&str, &str) -> Result<Option<Vec<u32>>, OxenError>,
+{
     log::debug!("Got transform ops {:?}", opts);
 
+    let filter = opts.get_filter()?;
+    if let Some(filter) = &filter {
+        for (field, value) in filter.required_eqs() {
+            if let Some(indices) = lookup(&field, &value)? {
+                log::debug!(
+                    "Taking {} indexed rows for {} == {}",
+                    indices.len(),
+                    field,
+                    value
+                );
+                df = take(df, indices)?.lazy();
+                break;
+            }
+        }
+    }
+
     if let Some(vstack) = &opts.vstack {
         log::debug!("Got files to stack {:?}", vstack);
         for path in vstack.iter() {
@@ -338,15 +447,8 @@ pub fn transform_df(mut df: LazyFrame, opts: DFOpts) -> Result<DataFrame, OxenEr
         }
     }
 
-    match opts.get_filter() {
-        Ok(filter) => {
-            if let Some(filter) = filter {
-                df = filter_df(df, &filter)?;
-            }
-        }
-        Err(err) => {
-            log::error!("Could not parse filter: {err}");
-        }
+    if let Some(filter) = filter {
+        df = filter_df(df, &filter)?;
     }
 
     if let Some(columns) = opts.unique_columns() {
@@ -807,6 +909,70 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_filter_typed_and_string_ops() -> Result<(), OxenError> {
+        let df = df!(
+            "image" => &["train/0000.jpg", "train/0001.jpg", "test/0002.jpg", "test/0003.png"],
+            "label" => &[Some("cat"), Some("dog"), None, Some("catfish")],
+            "score" => &[0.1, 0.9, 0.5, 0.7],
+            "width" => &[10, 20, 30, 40],
+        )
+        .unwrap();
+
+        let run = |query: &str| -> Result<usize, OxenError> {
+            let filter = filter::parse(Some(query.to_string()))?.unwrap();
+            Ok(tabular::filter_df(df.clone().lazy(), &filter)?
+                .collect()
+                .unwrap()
+                .height())
+        };
+
+        assert_eq!(run("score > 0.6 && width >= 40")?, 1);
+        assert_eq!(run("label in [cat, dog]")?, 2);
+        assert_eq!(run("label contains cat")?, 2);
+        assert_eq!(run("image starts_with 'train/' || label is null")?, 3);
+        assert_eq!(run("image =~ '\\.png$'")?, 1);
+        assert_eq!(
+            run("label is not null && (width == 10 || score == 0.7)")?,
+            2
+        );
+
+        // Values that do not match the column type and unknown columns are errors, not panics
+        assert!(run("width > cat").is_err());
+        assert!(run("missing == 1").is_err());
+        assert!(run("width contains 1").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_transform_df_with_index_takes_indexed_rows() -> Result<(), OxenError> {
+        let df = df!(
+            "id" => &[1, 2, 3, 4],
+            "label" => &["cat", "dog", "cat", "cat"],
+        )
+        .unwrap();
+
+        // Pretend the index only knows about the first two cats, to check the rows come from the index
+        let lookup = |field: &str, value: &str| {
+            if field == "label" && value == "cat" {
+                Ok(Some(vec![0, 2]))
+            } else {
+                Ok(None)
+            }
+        };
+        let opts = DFOpts::from_filter_query("label == cat && id > 1");
+        let filtered_df = tabular::transform_df_with_index(df.clone().lazy(), opts, lookup)?;
+        assert_eq!(filtered_df.height(), 1);
+
+        // Without an index every row is scanned
+        let opts = DFOpts::from_filter_query("label == cat && id > 1");
+        let filtered_df = tabular::transform_df(df.lazy(), opts)?;
+        assert_eq!(filtered_df.height(), 2);
+
+        Ok(())
+    }
+
     #[test]
     fn test_unique_single_field() -> Result<(), OxenError> {
         let fields = "label";
@@ -104,6 +104,27 @@ impl OxenError {
         OxenError::basic_str(err)
     }
 
+    pub fn remote_branch_not_fast_forward<T: AsRef<str>>(name: T) -> OxenError {
+        let err = format!(
+            "Remote branch '{}' has commits you do not have locally. Fetch and merge first, or push with --force to overwrite them.",
+            name.as_ref()
+        );
+        OxenError::basic_str(err)
+    }
+
+    pub fn tag_not_found<T: AsRef<str>>(name: T) -> OxenError {
+        let err = format!("Tag '{}' not found", name.as_ref());
+        OxenError::basic_str(err)
+    }
+
+    pub fn tag_already_exists<T: AsRef<str>>(name: T) -> OxenError {
+        let err = format!(
+            "Tag '{}' already exists for another commit, tags cannot be moved. Delete it first if you are sure.",
+            name.as_ref()
+        );
+        OxenError::basic_str(err)
+    }
+
     pub fn commit_db_corrupted<T: AsRef<str>>(commit_id: T) -> OxenError {
         let err = format!(
             "Commit db corrupted, could not find commit: {}",
@@ -144,6 +165,28 @@ impl OxenError {
         OxenError::basic_str(err)
     }
 
+    pub fn path_is_ignored<T: AsRef<Path>>(path: T) -> OxenError {
+        let err = format!(
+            "Path is ignored by an .oxenignore file: {:?}\n\nUse `oxen add --force` if you really want to add it.",
+            path.as_ref()
+        );
+        OxenError::basic_str(err)
+    }
+
+    pub fn chunk_does_not_exist<T: AsRef<str>>(hash: T) -> OxenError {
+        let err = format!("Chunk does not exist: {}", hash.as_ref());
+        OxenError::basic_str(err)
+    }
+
+    pub fn chunk_hash_mismatch<T: AsRef<str>, U: AsRef<str>>(expected: T, actual: U) -> OxenError {
+        let err = format!(
+            "Chunked content is corrupt, expected hash {} but got {}",
+            expected.as_ref(),
+            actual.as_ref()
+        );
+        OxenError::basic_str(err)
+    }
+
     pub fn could_not_convert_path_to_str<T: AsRef<Path>>(path: T) -> OxenError {
         let err = format!("File has no name: {:?}", path.as_ref());
         OxenError::basic_str(err)
@@ -180,6 +223,21 @@ impl OxenError {
         OxenError::basic_str(err)
     }
 
+    pub fn filter_parse_error<S: AsRef<str>, T: AsRef<str>>(
+        query: S,
+        position: usize,
+        msg: T,
+    ) -> OxenError {
+        let err = format!(
+            "Could not parse filter at position {}: {}\n  {}\n  {}^",
+            position,
+            msg.as_ref(),
+            query.as_ref(),
+            " ".repeat(position)
+        );
+        OxenError::basic_str(err)
+    }
+
     pub fn unknown_agg_fn<S: AsRef<str>>(name: S) -> OxenError {
         let err = format!("Unknown aggregation function: {:?}", name.as_ref());
         OxenError::basic_str(err)
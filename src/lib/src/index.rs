@@ -1,3 +1,4 @@
+pub mod chunk_store;
 pub mod commit_db_reader;
 pub mod commit_dir_entry_reader;
 pub mod commit_dir_entry_writer;
@@ -8,13 +9,19 @@ pub mod commit_validator;
 pub mod commit_writer;
 pub mod differ;
 pub mod entry_indexer;
+pub mod garbage_collector;
+pub mod history_pack;
+pub mod log_filter;
 pub mod merge_conflict_db_reader;
 pub mod merge_conflict_reader;
 pub mod merger;
 pub mod ref_db_reader;
 pub mod ref_reader;
 pub mod ref_writer;
+pub mod reset;
 pub mod restore;
+pub mod reverter;
+pub mod schema_field_indexer;
 pub mod schema_field_val_index;
 pub mod schema_index_reader;
 pub mod schema_index_writer;
@@ -22,7 +29,10 @@ pub mod schema_reader;
 pub mod schema_writer;
 pub mod staged_dir_entry_db;
 pub mod stager;
+pub mod stasher;
+pub mod upload_session_store;
 
+pub use crate::index::chunk_store::{ChunkStore, VersionFile};
 pub use crate::index::commit_db_reader::CommitDBReader;
 pub use crate::index::commit_dir_entry_reader::CommitDirEntryReader;
 pub use crate::index::commit_dir_entry_writer::CommitDirEntryWriter;
@@ -31,6 +41,8 @@ pub use crate::index::commit_entry_writer::CommitEntryWriter;
 pub use crate::index::commit_reader::CommitReader;
 pub use crate::index::commit_writer::CommitWriter;
 pub use crate::index::entry_indexer::EntryIndexer;
+pub use crate::index::garbage_collector::GarbageCollector;
+pub use crate::index::history_pack::HistoryPack;
 
 pub use crate::index::merge_conflict_db_reader::MergeConflictDBReader;
 pub use crate::index::merge_conflict_reader::MergeConflictReader;
@@ -38,7 +50,9 @@ pub use crate::index::merger::Merger;
 pub use crate::index::ref_db_reader::RefDBReader;
 pub use crate::index::ref_reader::RefReader;
 pub use crate::index::ref_writer::RefWriter;
+pub use crate::index::reset::reset;
 pub use crate::index::restore::restore;
+pub use crate::index::reverter::Reverter;
 pub use crate::index::schema_field_val_index::SchemaFieldValIndex;
 pub use crate::index::schema_index_reader::SchemaIndexReader;
 pub use crate::index::schema_index_writer::SchemaIndexWriter;
@@ -46,3 +60,5 @@ pub use crate::index::schema_reader::SchemaReader;
 pub use crate::index::schema_writer::SchemaWriter;
 pub use crate::index::staged_dir_entry_db::StagedDirEntryDB;
 pub use crate::index::stager::Stager;
+pub use crate::index::stasher::Stasher;
+pub use crate::index::upload_session_store::UploadSessionStore;
@@ -0,0 +1,663 @@
+//! # ChunkStore
+//!
+//! Content defined chunk storage for large versioned files.
+//!
+//! Files bigger than `AVG_CHUNK_SIZE` are split with FastCDC so that an edit in the middle
+//! of a file only changes the chunks around the edit. Chunks live once in
+//! `.oxen/versions/chunks/` keyed by their hash, a RocksDB index tracks which chunks we have,
+//! and each version dir gets a `MANIFEST` listing the chunks to rebuild the file from.
+//!
+
+use fastcdc::v2020::StreamCDC;
+use lazy_static::lazy_static;
+use rocksdb::{DBWithThreadMode, MultiThreaded};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError, Weak};
+use xxhash_rust::xxh3::Xxh3;
+
+use crate::constants::{
+    AVG_CHUNK_SIZE, CHUNKS_DIR, CHUNK_INDEX_DIR, CHUNK_MANIFEST_FILE, HASH_FILE, TMP_DIR,
+    VERSIONS_DIR,
+};
+use crate::db;
+use crate::db::str_val_db;
+use crate::error::OxenError;
+use crate::model::{Chunk, ChunkManifest, CommitEntry, LocalRepository};
+use crate::util;
+
+type ChunkIndex = DBWithThreadMode<MultiThreaded>;
+
+lazy_static! {
+    // RocksDB only allows a single open handle per process, so the stores of a repository share it while
+    // any of them is around. Opening and closing take turns on the lock.
+    static ref CHUNK_INDEXES: Mutex<HashMap<PathBuf, Weak<ChunkIndex>>> = Mutex::new(HashMap::new());
+}
+
+pub struct ChunkStore {
+    repository: LocalRepository,
+    avg_chunk_size: u64,
+    index: OnceLock<Arc<ChunkIndex>>,
+}
+
+/// The full contents of a version on disk, for readers that need a path. A version that is only stored as
+/// chunks gets rebuilt into a tmp file, which is removed again once this is dropped.
+#[derive(Debug)]
+pub struct VersionFile {
+    path: PathBuf,
+    is_tmp: bool,
+}
+
+impl VersionFile {
+    /// A file that is already on disk and is left alone when dropped
+    pub fn existing<P: AsRef<Path>>(path: P) -> VersionFile {
+        VersionFile {
+            path: path.as_ref().to_path_buf(),
+            is_tmp: false,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl AsRef<Path> for VersionFile {
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for VersionFile {
+    fn drop(&mut self) {
+        if self.is_tmp {
+            if let Err(err) = std::fs::remove_file(&self.path) {
+                log::error!("Could not remove tmp version file {:?}: {}", self.path, err);
+            }
+        }
+    }
+}
+
+impl ChunkStore {
+    pub fn new(repository: &LocalRepository) -> ChunkStore {
+        ChunkStore::with_avg_chunk_size(repository, AVG_CHUNK_SIZE)
+    }
+
+    pub fn with_avg_chunk_size(repository: &LocalRepository, avg_chunk_size: u64) -> ChunkStore {
+        ChunkStore {
+            repository: repository.clone(),
+            avg_chunk_size,
+            index: OnceLock::new(),
+        }
+    }
+
+    /// Chunk and version hashes come from the remote and get split into dirs, so they have to be hex
+    pub fn validate_hash(hash: &str) -> Result<(), OxenError> {
+        if hash.len() < 3 || hash.len() > 32 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(OxenError::basic_str(format!("Invalid hash '{hash}'")));
+        }
+        Ok(())
+    }
+
+    /// Files larger than the average chunk size get stored as chunks instead of whole copies
+    pub fn should_chunk(&self, num_bytes: u64) -> bool {
+        num_bytes > self.avg_chunk_size
+    }
+
+    pub fn chunks_dir(path: &Path) -> PathBuf {
+        util::fs::oxen_hidden_dir(path)
+            .join(VERSIONS_DIR)
+            .join(CHUNKS_DIR)
+    }
+
+    pub fn index_db_dir(path: &Path) -> PathBuf {
+        util::fs::oxen_hidden_dir(path)
+            .join(VERSIONS_DIR)
+            .join(CHUNK_INDEX_DIR)
+    }
+
+    pub fn chunk_path(&self, hash: &str) -> Result<PathBuf, OxenError> {
+        ChunkStore::validate_hash(hash)?;
+        Ok(ChunkStore::chunks_dir(&self.repository.path)
+            .join(&hash[..2])
+            .join(&hash[2..]))
+    }
+
+    pub fn manifest_path(&self, hash: &str) -> Result<PathBuf, OxenError> {
+        ChunkStore::validate_hash(hash)?;
+        Ok(
+            util::fs::version_dir_from_hash(&self.repository, hash.to_string())
+                .join(CHUNK_MANIFEST_FILE),
+        )
+    }
+
+    pub fn has_manifest(&self, hash: &str) -> bool {
+        match self.manifest_path(hash) {
+            Ok(path) => path.exists(),
+            Err(_) => false,
+        }
+    }
+
+    pub fn get_manifest(&self, hash: &str) -> Result<Option<ChunkManifest>, OxenError> {
+        let path = self.manifest_path(hash)?;
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let contents = util::fs::read_from_path(&path)?;
+        Ok(Some(serde_json::from_str(&contents)?))
+    }
+
+    /// Saves the manifest once all of its chunks are present and add up to the file hash
+    pub fn put_manifest(&self, manifest: &ChunkManifest) -> Result<(), OxenError> {
+        let missing = self.missing_chunks(&manifest.chunk_hashes())?;
+        if let Some(hash) = missing.first() {
+            return Err(OxenError::chunk_does_not_exist(hash));
+        }
+
+        let hash = self.hash_manifest_contents(manifest)?;
+        if hash != manifest.hash {
+            return Err(OxenError::chunk_hash_mismatch(&manifest.hash, hash));
+        }
+
+        self.write_manifest(manifest)
+    }
+
+    fn write_manifest(&self, manifest: &ChunkManifest) -> Result<(), OxenError> {
+        let path = self.manifest_path(&manifest.hash)?;
+        let version_dir = path.parent().unwrap();
+        if !version_dir.exists() {
+            std::fs::create_dir_all(version_dir)?;
+        }
+
+        let contents = serde_json::to_string(manifest)?;
+        util::fs::write_to_path(&path, &contents)?;
+        // Cache the hash so validating a commit does not have to read the chunks again
+        util::fs::write_to_path(&version_dir.join(HASH_FILE), &manifest.hash)?;
+        Ok(())
+    }
+
+    pub fn has_chunk(&self, hash: &str) -> bool {
+        match self.missing_chunks(&[hash.to_string()]) {
+            Ok(missing) => missing.is_empty(),
+            Err(_) => false,
+        }
+    }
+
+    /// Filters the hashes down to the chunks we do not have yet, keeping their order
+    pub fn missing_chunks(&self, hashes: &[String]) -> Result<Vec<String>, OxenError> {
+        let paths = hashes
+            .iter()
+            .map(|hash| self.chunk_path(hash))
+            .collect::<Result<Vec<PathBuf>, OxenError>>()?;
+
+        let db_path = ChunkStore::index_db_dir(&self.repository.path);
+        if !db_path.exists() {
+            return Ok(hashes.to_vec());
+        }
+
+        let db = self.index()?;
+        Ok(hashes
+            .iter()
+            .zip(paths)
+            .filter(|(hash, path)| !str_val_db::has_key(db, hash.as_str()) || !path.exists())
+            .map(|(hash, _)| hash.to_owned())
+            .collect())
+    }
+
+    pub fn read_chunk(&self, hash: &str) -> Result<Vec<u8>, OxenError> {
+        let path = self.chunk_path(hash)?;
+        if !path.exists() {
+            return Err(OxenError::chunk_does_not_exist(hash));
+        }
+        Ok(std::fs::read(path)?)
+    }
+
+    /// Stores a chunk we received, making sure the data matches the hash it was sent with
+    pub fn put_chunk(&self, hash: &str, data: &[u8]) -> Result<(), OxenError> {
+        let actual = util::hasher::hash_buffer(data);
+        if actual != hash {
+            return Err(OxenError::chunk_hash_mismatch(hash, actual));
+        }
+
+        self.write_chunk_file(hash, data)?;
+        str_val_db::put(self.index()?, hash, &(data.len() as u64))
+    }
+
+    fn write_chunk_file(&self, hash: &str, data: &[u8]) -> Result<(), OxenError> {
+        let path = self.chunk_path(hash)?;
+        if path.exists() {
+            return Ok(());
+        }
+
+        let parent = path.parent().unwrap();
+        if !parent.exists() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        // Write to a tmp file first so a half written chunk is never picked up by another reader
+        let tmp_path = parent.join(format!("{}.{}", &hash[2..], uuid::Uuid::new_v4()));
+        std::fs::write(&tmp_path, data)?;
+        std::fs::rename(&tmp_path, &path)?;
+        Ok(())
+    }
+
+    /// Splits a file into content defined chunks, stores the new ones and writes the manifest
+    pub fn chunk_file<P: AsRef<Path>>(
+        &self,
+        hash: &str,
+        path: P,
+    ) -> Result<ChunkManifest, OxenError> {
+        let path = path.as_ref();
+        log::debug!("ChunkStore::chunk_file {:?} -> {}", path, hash);
+
+        let avg_size = self.avg_chunk_size as u32;
+        let reader = BufReader::new(File::open(path)?);
+        let chunker = StreamCDC::new(reader, avg_size / 4, avg_size, avg_size * 4);
+
+        let mut hasher = Xxh3::new();
+        let mut chunks: Vec<Chunk> = vec![];
+        for result in chunker {
+            let data = match result {
+                Ok(data) => data,
+                Err(err) => {
+                    let err = format!("Could not chunk file {path:?}: {err}");
+                    return Err(OxenError::basic_str(err));
+                }
+            };
+
+            hasher.update(&data.data);
+            let chunk_hash = util::hasher::hash_buffer(&data.data);
+            self.write_chunk_file(&chunk_hash, &data.data)?;
+            chunks.push(Chunk {
+                hash: chunk_hash,
+                offset: data.offset,
+                num_bytes: data.length as u64,
+            });
+        }
+
+        let actual = format!("{:x}", hasher.digest128());
+        if actual != hash {
+            return Err(OxenError::chunk_hash_mismatch(hash, actual));
+        }
+
+        let db = self.index()?;
+        for chunk in chunks.iter() {
+            str_val_db::put(db, &chunk.hash, &chunk.num_bytes)?;
+        }
+
+        let manifest = ChunkManifest {
+            hash: hash.to_string(),
+            num_bytes: chunks.iter().map(|c| c.num_bytes).sum(),
+            chunks,
+        };
+        self.write_manifest(&manifest)?;
+        Ok(manifest)
+    }
+
+    /// Hashes the file a manifest describes without writing it out
+    pub fn hash_manifest_contents(&self, manifest: &ChunkManifest) -> Result<String, OxenError> {
+        let mut hasher = Xxh3::new();
+        for chunk in manifest.chunks.iter() {
+            hasher.update(&self.read_chunk(&chunk.hash)?);
+        }
+        Ok(format!("{:x}", hasher.digest128()))
+    }
+
+    /// Rebuilds the file a manifest describes at `dst`
+    pub fn restore_to<P: AsRef<Path>>(
+        &self,
+        manifest: &ChunkManifest,
+        dst: P,
+    ) -> Result<(), OxenError> {
+        let dst = dst.as_ref();
+        if let Some(parent) = dst.parent() {
+            if !parent.exists() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+
+        let mut writer = BufWriter::new(File::create(dst)?);
+        for chunk in manifest.chunks.iter() {
+            writer.write_all(&self.read_chunk(&chunk.hash)?)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// True if we can produce the versioned file, either as a full copy or from its chunks
+    pub fn has_version(&self, entry: &CommitEntry) -> bool {
+        util::fs::version_path(&self.repository, entry).exists() || self.has_manifest(&entry.hash)
+    }
+
+    /// Stores the full contents of a version we received in `path`, as chunks if it is large
+    pub fn put_version_file<P: AsRef<Path>>(
+        &self,
+        entry: &CommitEntry,
+        path: P,
+    ) -> Result<(), OxenError> {
+        let path = path.as_ref();
+        if self.should_chunk(entry.num_bytes) {
+            self.chunk_file(&entry.hash, path)?;
+            return Ok(());
+        }
+
+        let version_path = util::fs::version_path(&self.repository, entry);
+        let version_dir = version_path.parent().unwrap();
+        if !version_dir.exists() {
+            std::fs::create_dir_all(version_dir)?;
+        }
+        // Copy to a tmp file first so a reader never sees a partial version
+        let tmp_path = version_dir.join(format!("{}.tmp", uuid::Uuid::new_v4()));
+        std::fs::copy(path, &tmp_path)?;
+        std::fs::rename(&tmp_path, &version_path)?;
+        Ok(())
+    }
+
+    /// The kind of data in a version, like `util::fs::file_datatype`, without rebuilding a chunked file
+    pub fn version_datatype(&self, entry: &CommitEntry) -> Result<String, OxenError> {
+        let version_path = util::fs::version_path(&self.repository, entry);
+        let manifest = match self.get_manifest(&entry.hash)? {
+            Some(manifest) if !version_path.exists() => manifest,
+            _ => return Ok(util::fs::file_datatype(&version_path)),
+        };
+
+        if let Some(datatype) = util::fs::datatype_from_extension(&version_path) {
+            return Ok(datatype);
+        }
+        // Text is told apart by the first line, which is in the first chunk
+        let first_chunk = match manifest.chunks.first() {
+            Some(chunk) => self.read_chunk(&chunk.hash)?,
+            None => vec![],
+        };
+        let first_line = first_chunk.split(|b| *b == b'\n').next().unwrap_or(&[]);
+        if std::str::from_utf8(first_line).is_ok() {
+            Ok(String::from("text"))
+        } else {
+            Ok(String::from("unknown"))
+        }
+    }
+
+    /// Writes the versioned contents of an entry to `dst`
+    pub fn copy_version_to<P: AsRef<Path>>(
+        &self,
+        entry: &CommitEntry,
+        dst: P,
+    ) -> Result<(), OxenError> {
+        let version_path = util::fs::version_path(&self.repository, entry);
+        if version_path.exists() {
+            std::fs::copy(&version_path, dst)?;
+            return Ok(());
+        }
+
+        match self.get_manifest(&entry.hash)? {
+            Some(manifest) => self.restore_to(&manifest, dst),
+            None => Err(OxenError::file_does_not_exist(version_path)),
+        }
+    }
+
+    /// The full version file for readers that need a path to it. If we only have the manifest the file is
+    /// rebuilt in `.oxen/tmp`, the versions dir keeps just the chunks.
+    pub fn version_file(&self, entry: &CommitEntry) -> Result<VersionFile, OxenError> {
+        let version_path = util::fs::version_path(&self.repository, entry);
+        let manifest = match self.get_manifest(&entry.hash)? {
+            Some(manifest) if !version_path.exists() => manifest,
+            _ => return Ok(VersionFile::existing(version_path)),
+        };
+
+        // Keep the extension, readers go by it to tell the file type
+        let tmp_dir = util::fs::oxen_hidden_dir(&self.repository.path).join(TMP_DIR);
+        let tmp_path = tmp_dir
+            .join(uuid::Uuid::new_v4().to_string())
+            .with_extension(entry.extension());
+        log::debug!("ChunkStore rebuilding version file {:?}", tmp_path);
+        let version_file = VersionFile {
+            path: tmp_path,
+            is_tmp: true,
+        };
+        self.restore_to(&manifest, version_file.path())?;
+        Ok(version_file)
+    }
+
+    /// Removes a chunk and its index entry, returning the bytes freed
+    pub fn delete_chunk(&self, hash: &str) -> Result<u64, OxenError> {
+        let path = self.chunk_path(hash)?;
+        let mut num_bytes = 0;
+        if path.exists() {
+            num_bytes = std::fs::metadata(&path)?.len();
+            std::fs::remove_file(&path)?;
+        }
+        str_val_db::delete(self.index()?, hash)?;
+        Ok(num_bytes)
+    }
+
+    /// The chunk index, opened the first time it is needed and shared with the other stores on the repository
+    fn index(&self) -> Result<&ChunkIndex, OxenError> {
+        if let Some(db) = self.index.get() {
+            return Ok(db);
+        }
+
+        let mut indexes = CHUNK_INDEXES.lock().unwrap();
+        let db_path = ChunkStore::index_db_dir(&self.repository.path);
+        let db = match indexes.get(&db_path).and_then(Weak::upgrade) {
+            Some(db) => db,
+            None => {
+                if !db_path.exists() {
+                    std::fs::create_dir_all(&db_path)?;
+                }
+                let opts = db::opts::default();
+                let db = Arc::new(ChunkIndex::open(&opts, &db_path)?);
+                indexes.insert(db_path, Arc::downgrade(&db));
+                db
+            }
+        };
+        Ok(self.index.get_or_init(|| db))
+    }
+}
+
+impl Drop for ChunkStore {
+    fn drop(&mut self) {
+        // Close the index while holding the lock, so another store does not try to open it halfway through
+        if let Some(db) = self.index.take() {
+            let mut indexes = CHUNK_INDEXES.lock().unwrap_or_else(PoisonError::into_inner);
+            if Arc::strong_count(&db) == 1 {
+                indexes.remove(&ChunkStore::index_db_dir(&self.repository.path));
+            }
+            drop(db);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::OxenError;
+    use crate::index::ChunkStore;
+    use crate::model::CommitEntry;
+    use crate::test;
+    use crate::util;
+
+    use std::path::PathBuf;
+
+    // Small chunks keep the test files small
+    const TEST_AVG_CHUNK_SIZE: u64 = 4096;
+
+    fn random_bytes(num_bytes: usize) -> Vec<u8> {
+        (0..num_bytes).map(|_| rand::random::<u8>()).collect()
+    }
+
+    #[test]
+    fn test_chunk_store_chunk_and_restore_file() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let data = random_bytes(64 * 1024);
+            let path = repo.path.join("data.bin");
+            std::fs::write(&path, &data)?;
+            let hash = util::hasher::hash_file_contents(&path)?;
+
+            let store = ChunkStore::with_avg_chunk_size(&repo, TEST_AVG_CHUNK_SIZE);
+            let manifest = store.chunk_file(&hash, &path)?;
+            assert!(manifest.chunks.len() > 1);
+            assert_eq!(manifest.num_bytes, data.len() as u64);
+            assert_eq!(store.get_manifest(&hash)?, Some(manifest.clone()));
+            assert!(store.missing_chunks(&manifest.chunk_hashes())?.is_empty());
+
+            let restored = repo.path.join("restored.bin");
+            store.restore_to(&manifest, &restored)?;
+            assert_eq!(std::fs::read(restored)?, data);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_chunk_store_stores_share_the_index() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let data = random_bytes(1024);
+            let hash = util::hasher::hash_buffer(&data);
+
+            let first = ChunkStore::new(&repo);
+            let second = ChunkStore::new(&repo);
+            first.put_chunk(&hash, &data)?;
+            assert!(second.has_chunk(&hash));
+            drop(first);
+            assert_eq!(second.delete_chunk(&hash)?, data.len() as u64);
+            drop(second);
+
+            // Opens again once every store let go of it
+            let third = ChunkStore::new(&repo);
+            third.put_chunk(&hash, &data)?;
+            assert!(third.has_chunk(&hash));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_chunk_store_rejects_bad_hashes() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let store = ChunkStore::new(&repo);
+            for hash in ["", "a", "é", "éé", "../abc", "abcx"] {
+                assert!(!store.has_chunk(hash));
+                assert!(!store.has_manifest(hash));
+                assert!(store.read_chunk(hash).is_err());
+                assert!(store.get_manifest(hash).is_err());
+                assert!(store.missing_chunks(&[String::from(hash)]).is_err());
+            }
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_chunk_store_put_version_file_chunks_large_files() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let store = ChunkStore::with_avg_chunk_size(&repo, TEST_AVG_CHUNK_SIZE);
+            let versions = [("large.txt", 8 * 1024), ("small.txt", 100)];
+            for (filename, num_lines) in versions {
+                let data: String = (0..num_lines).map(|i| format!("line {i}\n")).collect();
+                let path = repo.path.join(filename);
+                std::fs::write(&path, &data)?;
+                let entry = CommitEntry {
+                    commit_id: String::from("commit"),
+                    path: PathBuf::from(filename),
+                    hash: util::hasher::hash_file_contents(&path)?,
+                    num_bytes: data.len() as u64,
+                    last_modified_seconds: 0,
+                    last_modified_nanoseconds: 0,
+                };
+
+                store.put_version_file(&entry, &path)?;
+                // Goes by the size the store was set up with
+                let is_large = store.should_chunk(entry.num_bytes);
+                assert_eq!(is_large, filename == "large.txt");
+                assert_eq!(store.has_manifest(&entry.hash), is_large);
+                assert_eq!(util::fs::version_path(&repo, &entry).exists(), !is_large);
+                assert_eq!(store.version_datatype(&entry)?, "text");
+                assert_eq!(std::fs::read_to_string(store.version_file(&entry)?)?, data);
+            }
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_chunk_store_version_file_does_not_undo_chunking() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let data = random_bytes(64 * 1024);
+            let path = repo.path.join("data.bin");
+            std::fs::write(&path, &data)?;
+            let entry = CommitEntry {
+                commit_id: String::from("commit"),
+                path: PathBuf::from("data.bin"),
+                hash: util::hasher::hash_file_contents(&path)?,
+                num_bytes: data.len() as u64,
+                last_modified_seconds: 0,
+                last_modified_nanoseconds: 0,
+            };
+
+            let store = ChunkStore::with_avg_chunk_size(&repo, TEST_AVG_CHUNK_SIZE);
+            store.chunk_file(&entry.hash, &path)?;
+
+            let tmp_path = {
+                let version_file = store.version_file(&entry)?;
+                assert_eq!(std::fs::read(version_file.path())?, data);
+                assert_eq!(version_file.path().extension().unwrap(), "bin");
+                version_file.path().to_path_buf()
+            };
+            // The rebuilt file is gone once the reader is done, and never ends up in the versions dir
+            assert!(!tmp_path.exists());
+            assert!(!util::fs::version_path(&repo, &entry).exists());
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_chunk_store_edit_only_adds_a_few_chunks() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let mut data = random_bytes(256 * 1024);
+            let path = repo.path.join("data.bin");
+            std::fs::write(&path, &data)?;
+            let hash = util::hasher::hash_file_contents(&path)?;
+
+            let store = ChunkStore::with_avg_chunk_size(&repo, TEST_AVG_CHUNK_SIZE);
+            let og_manifest = store.chunk_file(&hash, &path)?;
+
+            // Change a few bytes in the middle of the file
+            for byte in data[128 * 1024..128 * 1024 + 16].iter_mut() {
+                *byte = byte.wrapping_add(1);
+            }
+            std::fs::write(&path, &data)?;
+            let new_hash = util::hasher::hash_file_contents(&path)?;
+
+            let new_manifest = store.chunk_file(&new_hash, &path)?;
+            let shared = new_manifest
+                .chunks
+                .iter()
+                .filter(|c| og_manifest.chunks.iter().any(|og| og.hash == c.hash))
+                .count();
+            assert!(shared >= new_manifest.chunks.len() - 2);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_chunk_store_put_chunk_checks_hash() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let store = ChunkStore::new(&repo);
+            let data = random_bytes(1024);
+            let hash = util::hasher::hash_buffer(&data);
+            let hashes = vec![hash.clone()];
+            assert_eq!(store.missing_chunks(&hashes)?, hashes);
+
+            assert!(store.put_chunk("deadbeefdeadbeef", &data).is_err());
+            store.put_chunk(&hash, &data)?;
+            assert!(store.has_chunk(&hash));
+            assert_eq!(store.read_chunk(&hash)?, data);
+
+            Ok(())
+        })
+    }
+}
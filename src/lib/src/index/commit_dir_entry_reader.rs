@@ -2,6 +2,7 @@ use crate::constants::{FILES_DIR, HISTORY_DIR};
 use crate::db;
 use crate::db::path_db;
 use crate::error::OxenError;
+use crate::index::HistoryPack;
 use crate::model::{CommitEntry, LocalRepository};
 use crate::util;
 
@@ -41,6 +42,14 @@ impl CommitDirEntryReader {
             dir,
             db_path
         );
+        if let Some(db) = HistoryPack::open_db(repository, commit_id, &db_path)? {
+            return Ok(CommitDirEntryReader {
+                db,
+                dir: dir.to_owned(),
+                repository: repository.clone(),
+            });
+        }
+
         let opts = db::opts::default();
         // Must check the CURRENT file since the .oxen/history/COMMIT_ID/files/ path
         // may have already been created by a deeper object
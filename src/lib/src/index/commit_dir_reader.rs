@@ -1,7 +1,7 @@
 use crate::constants::{DIRS_DIR, HISTORY_DIR};
 use crate::db;
 use crate::error::OxenError;
-use crate::index::{CommitDirEntryReader, CommitReader};
+use crate::index::{ChunkStore, CommitDirEntryReader, CommitReader, HistoryPack};
 use crate::model::{Commit, CommitEntry, DirEntry};
 use crate::util;
 use crate::view::entry::ResourceVersion;
@@ -29,6 +29,14 @@ impl CommitDirReader {
             .join(HISTORY_DIR)
             .join(&commit.id)
             .join(DIRS_DIR);
+        if let Some(dir_db) = HistoryPack::open_db(repository, &commit.id, &db_path)? {
+            return Ok(CommitDirReader {
+                dir_db,
+                repository: repository.to_owned(),
+                commit_id: commit.id.to_owned(),
+            });
+        }
+
         let opts = db::opts::default();
 
         if !db_path.exists() {
@@ -241,13 +249,13 @@ impl CommitDirReader {
         let size = util::fs::version_file_size(&self.repository, entry)?;
         let latest_commit = commit_reader.get_commit_by_id(&entry.commit_id)?.unwrap();
 
-        let version_path = util::fs::version_path(&self.repository, entry);
+        let datatype = ChunkStore::new(&self.repository).version_datatype(entry)?;
         return Ok(DirEntry {
             filename: String::from(entry.path.file_name().unwrap().to_str().unwrap()),
             is_dir: false,
             size,
             latest_commit: Some(latest_commit),
-            datatype: util::fs::file_datatype(&version_path),
+            datatype,
             resource: ResourceVersion {
                 version: branch_or_commit_id.to_string(),
                 path: entry.path.to_str().unwrap().to_string(),
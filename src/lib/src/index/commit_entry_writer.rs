@@ -2,7 +2,10 @@ use crate::constants::{self, DEFAULT_BRANCH_NAME, HISTORY_DIR, VERSIONS_DIR};
 use crate::db;
 use crate::db::path_db;
 use crate::error::OxenError;
-use crate::index::{CommitDirEntryWriter, RefReader, RefWriter, SchemaWriter};
+use crate::index::{
+    schema_field_indexer, ChunkStore, CommitDirEntryWriter, HistoryPack, RefReader, RefWriter,
+    SchemaWriter, Stager,
+};
 use crate::model::schema::Schema;
 use crate::model::{
     Commit, CommitEntry, LocalRepository, StagedData, StagedEntry, StagedEntryStatus,
@@ -70,6 +73,9 @@ impl CommitEntryWriter {
                     parent_id
                 );
                 // We have a parent, we have to copy over last db, and continue
+                if HistoryPack::is_packed(repo, &parent_id) {
+                    HistoryPack::new(repo).unpack_commit(&parent_id)?;
+                }
                 let parent_commit_db_path = CommitEntryWriter::commit_dir(&repo.path, &parent_id);
                 let current_commit_db_path = CommitEntryWriter::commit_dir(&repo.path, commit_id);
                 log::debug!(
@@ -141,14 +147,28 @@ impl CommitEntryWriter {
     ) -> Result<(), OxenError> {
         // log::debug!("Commit [{}] add file {:?}", new_commit.id, path);
 
-        // then hash the actual file contents
+        // The contents kept when the file was staged, the working tree may have changed since
         let full_path = self.repository.path.join(path);
-
-        // Get last modified time
-        let metadata = fs::metadata(&full_path).unwrap();
-        let mtime = FileTime::from_last_modification_time(&metadata);
-
-        let metadata = fs::metadata(&full_path)?;
+        let staged_path = Stager::staged_contents_path(&self.repository, &staged_entry.hash);
+        let contents_path = if staged_path.exists() {
+            staged_path
+        } else {
+            full_path.to_owned()
+        };
+        let metadata = fs::metadata(&contents_path)?;
+
+        // The working tree timestamps let status skip hashing the file, unless it was edited after it was staged
+        let contents_mtime = FileTime::from_last_modification_time(&metadata);
+        let mtime = match fs::metadata(&full_path) {
+            Ok(working_metadata)
+                if working_metadata.len() == metadata.len()
+                    && FileTime::from_last_modification_time(&working_metadata)
+                        < contents_mtime =>
+            {
+                FileTime::from_last_modification_time(&working_metadata)
+            }
+            _ => contents_mtime,
+        };
 
         // Create entry object to as json
         let entry = CommitEntry {
@@ -161,7 +181,7 @@ impl CommitEntryWriter {
         };
 
         // Write to db & backup
-        self.add_commit_entry(writer, entry)?;
+        self.add_commit_entry(writer, entry, &contents_path)?;
         Ok(())
     }
 
@@ -169,8 +189,9 @@ impl CommitEntryWriter {
         &self,
         writer: &CommitDirEntryWriter,
         entry: CommitEntry,
+        contents_path: &Path,
     ) -> Result<(), OxenError> {
-        let entry = self.backup_file_to_versions_dir(entry)?;
+        let entry = self.backup_file_to_versions_dir(entry, contents_path)?;
         log::debug!(
             "add_commit_entry with hash {:?} -> {}",
             entry.path,
@@ -180,10 +201,20 @@ impl CommitEntryWriter {
         writer.add_commit_entry(&entry)
     }
 
-    fn backup_file_to_versions_dir(&self, entry: CommitEntry) -> Result<CommitEntry, OxenError> {
-        let full_path = self.repository.path.join(&entry.path);
+    fn backup_file_to_versions_dir(
+        &self,
+        entry: CommitEntry,
+        full_path: &Path,
+    ) -> Result<CommitEntry, OxenError> {
         log::debug!("backup_file_to_versions_dir {:?}", entry.path);
 
+        // Large files are split into chunks so that a small edit does not store a second full copy
+        let store = ChunkStore::new(&self.repository);
+        if store.should_chunk(entry.num_bytes) {
+            store.chunk_file(&entry.hash, full_path)?;
+            return Ok(entry);
+        }
+
         // if util::fs::is_tabular(&entry.path) {
         //     // We save off an .arrow file for tabular data for faster access and optimized DF commands
         //     entry = self.backup_arrow_file(commit, entry, &full_path)?;
@@ -217,7 +248,13 @@ impl CommitEntryWriter {
         staged_data: &StagedData,
     ) -> Result<(), OxenError> {
         self.commit_staged_entries_with_prog(commit, staged_data)?;
-        self.commit_schemas(commit, &staged_data.added_schemas)
+        self.commit_schemas(commit, &staged_data.added_schemas)?;
+        schema_field_indexer::index_commit(
+            &self.repository,
+            commit,
+            &staged_data.added_schemas,
+            &self.repository.path,
+        )
     }
 
     fn commit_schemas(
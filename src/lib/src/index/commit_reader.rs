@@ -52,6 +52,20 @@ impl CommitReader {
         Ok(commits)
     }
 
+    /// Whether `ancestor_id` is `commit_id` or somewhere in its history
+    pub fn is_ancestor(&self, ancestor_id: &str, commit_id: &str) -> Result<bool, OxenError> {
+        if ancestor_id == commit_id {
+            return Ok(true);
+        }
+        if !self.commit_id_exists(commit_id) {
+            return Err(OxenError::commit_id_does_not_exist(commit_id));
+        }
+
+        let mut commits: HashSet<Commit> = HashSet::new();
+        CommitDBReader::history_from_commit_id(&self.db, commit_id, &mut commits)?;
+        Ok(commits.iter().any(|commit| commit.id == ancestor_id))
+    }
+
     /// List the commit history from the HEAD commit
     pub fn history_from_head(&self) -> Result<Vec<Commit>, OxenError> {
         let head_commit = self.head_commit()?;
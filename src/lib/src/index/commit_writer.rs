@@ -171,6 +171,32 @@ impl CommitWriter {
         status: &StagedData,
         parent_ids: Vec<String>,
         message: &str,
+    ) -> Result<Commit, OxenError> {
+        let commit = self.gen_commit_with_parent_ids(status, parent_ids, message)?;
+        self.add_commit_from_status(&commit, status)?;
+        Ok(commit)
+    }
+
+    /// Writes the commit and its entries without moving HEAD, for work that is kept off of the branches.
+    /// The entries not in `status` are taken from HEAD, so the parent should be HEAD as well.
+    pub fn commit_off_branch(
+        &self,
+        status: &StagedData,
+        parent_ids: Vec<String>,
+        message: &str,
+    ) -> Result<Commit, OxenError> {
+        let commit = self.gen_commit_with_parent_ids(status, parent_ids, message)?;
+        let entry_writer = CommitEntryWriter::new(&self.repository, &commit)?;
+        entry_writer.commit_staged_entries(&commit, status)?;
+        self.add_commit_to_db(&commit)?;
+        Ok(commit)
+    }
+
+    fn gen_commit_with_parent_ids(
+        &self,
+        status: &StagedData,
+        parent_ids: Vec<String>,
+        message: &str,
     ) -> Result<Commit, OxenError> {
         let cfg = UserConfig::get()?;
         let timestamp = OffsetDateTime::now_utc();
@@ -184,9 +210,7 @@ impl CommitWriter {
         };
         let entries: Vec<StagedEntry> = status.added_files.values().cloned().collect();
         let id = util::hasher::compute_commit_hash(&commit, &entries);
-        let commit = Commit::from_new_and_id(&commit, id);
-        self.add_commit_from_status(&commit, status)?;
-        Ok(commit)
+        Ok(Commit::from_new_and_id(&commit, id))
     }
 
     pub fn add_commit_from_empty_status(&self, commit: &Commit) -> Result<(), OxenError> {
@@ -326,6 +350,11 @@ impl CommitWriter {
             let dst_path = self.repository.path.join(path);
             let version_path = util::fs::version_path(&self.repository, entry);
 
+            // Sparse clones never pulled the versions outside of their spec, so leave those absent
+            if !dst_path.exists() && !self.repository.is_in_sparse_tree(path) {
+                continue;
+            }
+
             // If we do not have the file, restore it from our versioned history
             if !dst_path.exists() {
                 log::debug!(
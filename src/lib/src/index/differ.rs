@@ -1,15 +1,13 @@
-use crate::df::{tabular, DFOpts};
+use crate::df::{self, tabular, DFDiffOpts, DFOpts};
 use crate::error::OxenError;
-use crate::index::{CommitDirEntryReader, CommitReader};
-use crate::model::{Commit, CommitEntry, DataFrameDiff, LocalRepository, Schema};
-use crate::{constants, util};
+use crate::index::{ChunkStore, CommitDirEntryReader, CommitDirReader, CommitReader};
+use crate::model::{Commit, CommitEntry, DataFrameDiff, DiffEntry, LocalRepository, TreeDiff};
+use crate::util;
 
 use colored::Colorize;
 use difference::{Changeset, Difference};
 use polars::export::ahash::HashMap;
-use polars::prelude::DataFrame;
-use polars::prelude::IntoLazy;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use super::SchemaReader;
 
@@ -17,9 +15,10 @@ pub fn diff(
     repo: &LocalRepository,
     commit_id: Option<&str>,
     path: &Path,
+    opts: &DFDiffOpts,
 ) -> Result<String, OxenError> {
     match _commit_or_head(repo, commit_id)? {
-        Some(commit) => _diff_commit(repo, &commit, path),
+        Some(commit) => _diff_commit(repo, &commit, path, opts),
         None => Err(OxenError::commit_id_does_not_exist(commit_id.unwrap())),
     }
 }
@@ -37,7 +36,12 @@ fn _commit_or_head(
 }
 
 // TODO: Change API to take two commits
-fn _diff_commit(repo: &LocalRepository, commit: &Commit, path: &Path) -> Result<String, OxenError> {
+fn _diff_commit(
+    repo: &LocalRepository,
+    commit: &Commit,
+    path: &Path,
+    opts: &DFDiffOpts,
+) -> Result<String, OxenError> {
     if let Some(parent) = path.parent() {
         let relative_parent = util::fs::path_relative_to_dir(parent, &repo.path)?;
         let commit_entry_reader = CommitDirEntryReader::new(repo, &commit.id, &relative_parent)?;
@@ -49,7 +53,7 @@ fn _diff_commit(repo: &LocalRepository, commit: &Commit, path: &Path) -> Result<
 
                 let current_commit = commits.first().unwrap();
 
-                return diff_tabular(repo, current_commit, &entry.path);
+                return diff_tabular(repo, current_commit, &entry.path, opts);
             } else if util::fs::is_utf8(path) {
                 // TODO: Change API to take two commits
                 return diff_utf8(repo, &entry);
@@ -65,221 +69,333 @@ fn _diff_commit(repo: &LocalRepository, commit: &Commit, path: &Path) -> Result<
     }
 }
 
+/// Compares all the entries in two commits, only looking at paths under `path_filter` if it is set.
+/// If the filter points at a single modified file, the line or row level diff of it is filled in as well.
+pub fn diff_commits(
+    repo: &LocalRepository,
+    base: &Commit,
+    head: &Commit,
+    path_filter: Option<&Path>,
+    opts: &DFDiffOpts,
+) -> Result<TreeDiff, OxenError> {
+    let is_in_filter = |entry: &CommitEntry| match path_filter {
+        Some(path) => {
+            path == Path::new("") || path == Path::new(".") || entry.path.starts_with(path)
+        }
+        None => true,
+    };
+
+    let base_entries: HashMap<PathBuf, CommitEntry> = CommitDirReader::new(repo, base)?
+        .list_entries()?
+        .into_iter()
+        .filter(is_in_filter)
+        .map(|entry| (entry.path.to_owned(), entry))
+        .collect();
+    let head_entries: HashMap<PathBuf, CommitEntry> = CommitDirReader::new(repo, head)?
+        .list_entries()?
+        .into_iter()
+        .filter(is_in_filter)
+        .map(|entry| (entry.path.to_owned(), entry))
+        .collect();
+
+    let mut added: Vec<DiffEntry> = vec![];
+    let mut modified: Vec<DiffEntry> = vec![];
+    for (path, head_entry) in head_entries.iter() {
+        match base_entries.get(path) {
+            Some(base_entry) => {
+                if base_entry.hash != head_entry.hash {
+                    modified.push(DiffEntry::modified(base_entry, head_entry));
+                }
+            }
+            None => added.push(DiffEntry::added(head_entry)),
+        }
+    }
+
+    let mut removed: Vec<DiffEntry> = base_entries
+        .iter()
+        .filter(|(path, _)| !head_entries.contains_key(*path))
+        .map(|(_, base_entry)| DiffEntry::removed(base_entry))
+        .collect();
+
+    // so is deterministic and returned in a readable order
+    added.sort_by(|a, b| a.path.cmp(&b.path));
+    modified.sort_by(|a, b| a.path.cmp(&b.path));
+    removed.sort_by(|a, b| a.path.cmp(&b.path));
+
+    if let Some(path) = path_filter {
+        if let Some(entry) = modified.iter_mut().find(|entry| entry.path == path) {
+            let base_entry = entry.base_entry.as_ref().unwrap();
+            let head_entry = entry.head_entry.as_ref().unwrap();
+            entry.contents = diff_entries(repo, base_entry, head_entry, opts)?;
+        }
+    }
+
+    Ok(TreeDiff {
+        base_commit: base.to_owned(),
+        head_commit: head.to_owned(),
+        added,
+        removed,
+        modified,
+    })
+}
+
+/// Diffs two versions of a file, returns None if we do not know how to diff the file type
+pub fn diff_entries(
+    repo: &LocalRepository,
+    base_entry: &CommitEntry,
+    head_entry: &CommitEntry,
+    opts: &DFDiffOpts,
+) -> Result<Option<String>, OxenError> {
+    if util::fs::is_tabular(&head_entry.path) {
+        let diff = diff_tabular_entries(repo, base_entry, head_entry, opts)?;
+        return Ok(Some(format_dataframe_diff(diff)));
+    }
+
+    let store = ChunkStore::new(repo);
+    let base_path = store.version_file(base_entry)?;
+    let head_path = store.version_file(head_entry)?;
+    if util::fs::is_utf8(head_path.path()) {
+        let original = util::fs::read_from_path(base_path.path())?;
+        let modified = util::fs::read_from_path(head_path.path())?;
+        Ok(Some(utf8_diff_lines(&original, &modified).join("")))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Row and column level diff of two versions of a tabular file
+pub fn diff_tabular_entries(
+    repo: &LocalRepository,
+    base_entry: &CommitEntry,
+    head_entry: &CommitEntry,
+    opts: &DFDiffOpts,
+) -> Result<DataFrameDiff, OxenError> {
+    let store = ChunkStore::new(repo);
+    let base_df = tabular::read_df(store.version_file(base_entry)?, DFOpts::empty())?;
+    let head_df = tabular::read_df(store.version_file(head_entry)?, DFOpts::empty())?;
+    df::diff::compare(base_df, head_df, opts)
+}
+
 pub fn diff_utf8(repo: &LocalRepository, entry: &CommitEntry) -> Result<String, OxenError> {
     let current_path = repo.path.join(&entry.path);
-    let version_path = util::fs::version_path(repo, entry);
+    let version_path = ChunkStore::new(repo).version_file(entry)?;
 
-    let original = util::fs::read_from_path(&version_path)?;
+    let original = util::fs::read_from_path(version_path.path())?;
     let modified = util::fs::read_from_path(&current_path)?;
-    let Changeset { diffs, .. } = Changeset::new(&original, &modified, "\n");
+
+    let outputs: Vec<String> = utf8_diff_lines(&original, &modified)
+        .into_iter()
+        .map(|line| {
+            if line.starts_with('+') {
+                line.green().to_string()
+            } else if line.starts_with('-') {
+                line.red().to_string()
+            } else {
+                line.normal().to_string()
+            }
+        })
+        .collect();
+
+    Ok(outputs.join(""))
+}
+
+// Lines prefixed with ' ', '+' or '-' depending on whether they were kept, added or removed
+fn utf8_diff_lines(original: &str, modified: &str) -> Vec<String> {
+    let Changeset { diffs, .. } = Changeset::new(original, modified, "\n");
 
     let mut outputs: Vec<String> = vec![];
     for diff in diffs {
         match diff {
             Difference::Same(ref x) => {
                 for split in x.split('\n') {
-                    outputs.push(format!(" {split}\n").normal().to_string());
+                    outputs.push(format!(" {split}\n"));
                 }
             }
             Difference::Add(ref x) => {
                 for split in x.split('\n') {
-                    outputs.push(format!("+{split}\n").green().to_string());
+                    outputs.push(format!("+{split}\n"));
                 }
             }
             Difference::Rem(ref x) => {
                 for split in x.split('\n') {
-                    outputs.push(format!("-{split}\n").red().to_string());
+                    outputs.push(format!("-{split}\n"));
                 }
             }
         }
     }
-
-    Ok(outputs.join(""))
+    outputs
 }
 
 pub fn diff_tabular(
     repo: &LocalRepository,
     commit: &Commit,
     path: &Path,
+    opts: &DFDiffOpts,
 ) -> Result<String, OxenError> {
     let schema_reader = SchemaReader::new(repo, &commit.id)?;
-    if let Some(schema) = schema_reader.get_schema_for_file(path)? {
-        let diff = compute_dataframe_diff(repo, commit, &schema, path)?;
+    if schema_reader.get_schema_for_file(path)?.is_some() {
+        let diff = compute_dataframe_diff(repo, commit, path, opts)?;
+        Ok(format_dataframe_diff(diff))
+    } else {
+        Err(OxenError::schema_does_not_exist_for_file(path))
+    }
+}
 
-        let mut results: Vec<String> = vec![];
-        if let Some(rows) = diff.added_rows {
-            results.push(format!("Added Rows\n\n{rows}\n\n"));
-        }
+fn format_dataframe_diff(diff: DataFrameDiff) -> String {
+    let mut results: Vec<String> = vec![];
+    if let Some(rows) = diff.added_rows {
+        results.push(format!("Added Rows\n\n{rows}\n\n"));
+    }
 
-        if let Some(rows) = diff.removed_rows {
-            results.push(format!("Removed Rows\n\n{rows}\n\n"));
-        }
+    if let Some(rows) = diff.removed_rows {
+        results.push(format!("Removed Rows\n\n{rows}\n\n"));
+    }
 
-        if let Some(cols) = diff.added_cols {
-            results.push(format!("Added Columns\n\n{cols}\n\n"));
+    if !diff.modified_rows.is_empty() {
+        let mut lines: Vec<String> = vec![String::from("Modified Rows\n")];
+        for row in diff.modified_rows.iter() {
+            lines.push(row.key.to_owned());
+            for cell in row.cells.iter() {
+                lines.push(format!("-  {}: {}", cell.column, cell.before));
+                lines.push(format!("+  {}: {}", cell.column, cell.after));
+            }
         }
+        results.push(format!("{}\n\n", lines.join("\n")));
+    }
 
-        if let Some(cols) = diff.removed_cols {
-            results.push(format!("Removed Columns\n\n{cols}\n\n"));
-        }
+    if let Some(cols) = diff.added_cols {
+        results.push(format!("Added Columns\n\n{cols}\n\n"));
+    }
 
-        Ok(results.join("\n"))
-    } else {
-        Err(OxenError::schema_does_not_exist_for_file(path))
+    if let Some(cols) = diff.removed_cols {
+        results.push(format!("Removed Columns\n\n{cols}\n\n"));
     }
+
+    results.join("\n")
 }
 
 fn compute_dataframe_diff(
     repo: &LocalRepository,
     commit: &Commit,
-    versioned_schema: &Schema,
     path: &Path,
+    opts: &DFDiffOpts,
 ) -> Result<DataFrameDiff, OxenError> {
     let commit_entry_reader = CommitDirEntryReader::new(repo, &commit.id, path.parent().unwrap())?;
     let filename = Path::new(path.file_name().unwrap().to_str().unwrap());
     if let Some(entry) = commit_entry_reader.get_entry(filename)? {
-        // Read current DF and get schema
+        // Compare the versioned df to the one in the working dir
         let current_path = repo.path.join(path);
-        let versioned_path = util::fs::version_path(repo, &entry);
+        let versioned_path = ChunkStore::new(repo).version_file(&entry)?;
         let current_df = tabular::read_df(&current_path, DFOpts::empty())?;
-        let current_schema = Schema::from_polars(&current_df.schema());
-
-        // If schemas don't match, figure out which columns are different
-        if versioned_schema.hash != current_schema.hash {
-            compute_new_columns(
-                &current_path,
-                &versioned_path,
-                &current_schema,
-                versioned_schema,
-            )
-        } else {
-            println!("Computing diff for {path:?}");
-            // Schemas match, find added and removed rows
-            // Read versioned df
-            let versioned_df = tabular::read_df(&versioned_path, DFOpts::empty())?;
-            compute_new_rows(current_df, versioned_df, versioned_schema)
-        }
+        let versioned_df = tabular::read_df(&versioned_path, DFOpts::empty())?;
+        df::diff::compare(versioned_df, current_df, opts)
     } else {
         Err(OxenError::file_does_not_exist(path))
     }
 }
 
-fn compute_new_rows(
-    current_df: DataFrame,
-    versioned_df: DataFrame,
-    versioned_schema: &Schema,
-) -> Result<DataFrameDiff, OxenError> {
-    // Hash the rows
-    let versioned_df = tabular::df_hash_rows(versioned_df)?;
-    let current_df = tabular::df_hash_rows(current_df)?;
-
-    // log::debug!("diff_current got current hashes {}", current_df);
-
-    let current_hash_indices: HashMap<String, u32> = current_df
-        .column(constants::ROW_HASH_COL_NAME)
-        .unwrap()
-        .utf8()
-        .unwrap()
-        .into_iter()
-        .enumerate()
-        .map(|(i, v)| (v.unwrap().to_string(), i as u32))
-        .collect();
-
-    let versioned_hash_indices: HashMap<String, u32> = versioned_df
-        .column(constants::ROW_HASH_COL_NAME)
-        .unwrap()
-        .utf8()
-        .unwrap()
-        .into_iter()
-        .enumerate()
-        .map(|(i, v)| (v.unwrap().to_string(), i as u32))
-        .collect();
-
-    // Added is all the row hashes that are in current that are not in other
-    let mut added_indices: Vec<u32> = current_hash_indices
-        .iter()
-        .filter(|(hash, _indices)| !versioned_hash_indices.contains_key(*hash))
-        .map(|(_hash, index_pair)| *index_pair)
-        .collect();
-    added_indices.sort(); // so is deterministic and returned in correct order
-
-    // Removed is all the row hashes that are in other that are not in current
-    let mut removed_indices: Vec<u32> = versioned_hash_indices
-        .iter()
-        .filter(|(hash, _indices)| !current_hash_indices.contains_key(*hash))
-        .map(|(_hash, index_pair)| *index_pair)
-        .collect();
-    removed_indices.sort(); // so is deterministic and returned in correct order
-
-    // log::debug!("diff_current added_indices {:?}", added_indices);
-
-    // log::debug!("diff_current removed_indices {:?}", removed_indices);
-
-    // Take added from the current df
-    let opts = DFOpts::from_schema_columns(versioned_schema);
-    let current_df = tabular::transform_df(current_df.lazy(), opts)?;
-    let added_rows = tabular::take(current_df.lazy(), added_indices)?;
-
-    // Take removed from versioned df
-    let opts = DFOpts::from_schema_columns(versioned_schema);
-    let versioned_df = tabular::transform_df(versioned_df.lazy(), opts)?;
-    let removed_rows = tabular::take(versioned_df.lazy(), removed_indices)?;
-
-    Ok(DataFrameDiff {
-        added_rows: if added_rows.height() > 0 {
-            Some(added_rows)
-        } else {
-            None
-        },
-        removed_rows: if removed_rows.height() > 0 {
-            Some(removed_rows)
-        } else {
-            None
-        },
-        added_cols: None,
-        removed_cols: None,
-    })
-}
-
-fn compute_new_columns(
-    current_path: &Path,
-    versioned_path: &Path,
-    current_schema: &Schema,
-    versioned_schema: &Schema,
-) -> Result<DataFrameDiff, OxenError> {
-    let added_fields = current_schema.added_fields(versioned_schema);
-    let removed_fields = current_schema.removed_fields(versioned_schema);
-
-    let added_cols = if !added_fields.is_empty() {
-        let opts = DFOpts::from_columns(added_fields);
-        let df_added = tabular::read_df(current_path, opts)?;
-        log::debug!("Got added col df: {}", df_added);
-        if df_added.width() > 0 {
-            Some(df_added)
-        } else {
-            None
-        }
-    } else {
-        None
-    };
-
-    let removed_cols = if !removed_fields.is_empty() {
-        let opts = DFOpts::from_columns(removed_fields);
-        let df_removed = tabular::read_df(versioned_path, opts)?;
-        log::debug!("Got removed col df: {}", df_removed);
-        if df_removed.width() > 0 {
-            Some(df_removed)
-        } else {
-            None
-        }
-    } else {
-        None
-    };
+#[cfg(test)]
+mod tests {
+    use crate::command;
+    use crate::df::DFDiffOpts;
+    use crate::error::OxenError;
+    use crate::index::differ;
+    use crate::model::DiffEntryStatus;
+    use crate::test;
+    use crate::util;
+
+    use std::path::Path;
+
+    #[test]
+    fn test_diff_commits_added_removed_modified() -> Result<(), OxenError> {
+        test::run_training_data_repo_test_fully_committed(|repo| {
+            let base = command::head_commit(&repo)?;
+
+            // Modify one file, remove one file, add one file
+            let labels_path = repo.path.join("labels.txt");
+            test::modify_txt_file(&labels_path, "cat\ndog\nfish")?;
+            let readme_path = repo.path.join("README.md");
+            std::fs::remove_file(&readme_path)?;
+            let new_path = repo.path.join("nlp").join("new.txt");
+            util::fs::write_to_path(&new_path, "new file")?;
+
+            command::add(&repo, &labels_path)?;
+            command::add(&repo, &readme_path)?;
+            command::add(&repo, &new_path)?;
+            let head = command::commit(&repo, "Changing some files")?.unwrap();
+
+            let diff = differ::diff_commits(&repo, &base, &head, None, &DFDiffOpts::empty())?;
+            assert_eq!(diff.num_changed(), 3);
+            assert_eq!(diff.added.len(), 1);
+            assert_eq!(diff.added[0].path, Path::new("nlp").join("new.txt"));
+            assert_eq!(diff.added[0].status, DiffEntryStatus::Added);
+            assert_eq!(diff.added[0].size_delta, 8);
+            assert_eq!(diff.removed.len(), 1);
+            assert_eq!(diff.removed[0].path, Path::new("README.md"));
+            assert!(diff.removed[0].size_delta < 0);
+            assert_eq!(diff.modified.len(), 1);
+            assert_eq!(diff.modified[0].path, Path::new("labels.txt"));
+            // Contents are only filled in when asking about a single file
+            assert!(diff.modified[0].contents.is_none());
+
+            // Filter down to a directory
+            let diff = differ::diff_commits(
+                &repo,
+                &base,
+                &head,
+                Some(Path::new("nlp")),
+                &DFDiffOpts::empty(),
+            )?;
+            assert_eq!(diff.num_changed(), 1);
+
+            // Ask about a single file to get the line diff
+            let diff = differ::diff_commits(
+                &repo,
+                &base,
+                &head,
+                Some(Path::new("labels.txt")),
+                &DFDiffOpts::empty(),
+            )?;
+            assert_eq!(diff.num_changed(), 1);
+            let contents = diff.modified[0].contents.as_ref().unwrap();
+            assert!(contents.contains("+fish"));
+
+            // Going the other way swaps added and removed
+            let diff = differ::diff_commits(&repo, &head, &base, None, &DFDiffOpts::empty())?;
+            assert_eq!(diff.added[0].path, Path::new("README.md"));
+            assert_eq!(diff.removed[0].path, Path::new("nlp").join("new.txt"));
+
+            Ok(())
+        })
+    }
 
-    Ok(DataFrameDiff {
-        added_rows: None,
-        removed_rows: None,
-        added_cols,
-        removed_cols,
-    })
+    #[test]
+    fn test_diff_commits_tabular_rows() -> Result<(), OxenError> {
+        test::run_training_data_repo_test_fully_committed(|repo| {
+            let base = command::head_commit(&repo)?;
+
+            let bbox_filename = Path::new("annotations")
+                .join("train")
+                .join("bounding_box.csv");
+            let bbox_file = repo.path.join(&bbox_filename);
+            let bbox_file =
+                test::append_line_txt_file(bbox_file, "train/cat_3.jpg,cat,41.0,31.5,410,427")?;
+            command::add(&repo, &bbox_file)?;
+            let head = command::commit(&repo, "Adding a bounding box")?.unwrap();
+
+            let diff = differ::diff_commits(
+                &repo,
+                &base,
+                &head,
+                Some(&bbox_filename),
+                &DFDiffOpts::empty(),
+            )?;
+            assert_eq!(diff.modified.len(), 1);
+            let contents = diff.modified[0].contents.as_ref().unwrap();
+            assert!(contents.contains("Added Rows"));
+            assert!(contents.contains("cat_3.jpg"));
+
+            Ok(())
+        })
+    }
 }
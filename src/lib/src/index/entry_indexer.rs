@@ -6,21 +6,21 @@ use flate2::Compression;
 use indicatif::ProgressBar;
 use jwalk::WalkDirGeneric;
 use rayon::prelude::*;
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fs;
-use std::io::{BufReader, Read};
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use crate::api;
-use crate::api::remote::commits::ChunkParams;
-use crate::constants::{AVG_CHUNK_SIZE, HISTORY_DIR};
+use crate::constants::{AVG_CHUNK_SIZE, DEFAULT_BRANCH_NAME, HISTORY_DIR};
 use crate::error::OxenError;
 use crate::index::{
-    CommitDirEntryReader, CommitDirEntryWriter, CommitDirReader, CommitReader, CommitWriter,
-    RefReader, RefWriter,
+    ChunkStore, CommitDirEntryReader, CommitDirEntryWriter, CommitDirReader, CommitReader,
+    CommitWriter, HistoryPack, Merger, RefReader, RefWriter,
+};
+use crate::model::{
+    Branch, Commit, CommitEntry, LocalRepository, RemoteBranch, RemoteRepository, Tag,
 };
-use crate::model::{Commit, CommitEntry, LocalRepository, RemoteBranch, RemoteRepository};
 use crate::util;
 
 pub struct UnsyncedCommitEntries {
@@ -40,9 +40,22 @@ impl EntryIndexer {
     }
 
     pub async fn push(&self, rb: &RemoteBranch) -> Result<RemoteRepository, OxenError> {
+        self.p_push(rb, false).await
+    }
+
+    /// Push even if the remote branch has commits we do not have, resetting it to our branch head
+    pub async fn force_push(&self, rb: &RemoteBranch) -> Result<RemoteRepository, OxenError> {
+        self.p_push(rb, true).await
+    }
+
+    async fn p_push(&self, rb: &RemoteBranch, force: bool) -> Result<RemoteRepository, OxenError> {
         let ref_reader = RefReader::new(&self.repository)?;
         let branch = ref_reader.get_branch_by_name(&rb.branch)?;
         if branch.is_none() {
+            // Pushing a tag sends just the tag, its commit has to be on the remote already
+            if let Some(tag) = ref_reader.get_tag_by_name(&rb.branch)? {
+                return self.push_tag(&rb.remote, &tag).await;
+            }
             return Err(OxenError::local_branch_not_found(&rb.branch));
         }
 
@@ -69,6 +82,19 @@ impl EntryIndexer {
         let commit_reader = CommitReader::new(&self.repository)?;
         let head_commit = commit_reader.get_commit_by_id(branch.commit_id)?.unwrap();
 
+        // Bail before uploading anything if we would drop commits from the remote branch,
+        // the server checks this again when we update the branch
+        if !force {
+            if let Some(remote_branch) =
+                api::remote::branches::get_by_name(&remote_repo, &rb.branch).await?
+            {
+                let merger = Merger::new(&self.repository)?;
+                if !merger.is_fast_forward(&remote_branch.commit_id, &head_commit.id)? {
+                    return Err(OxenError::remote_branch_not_fast_forward(&rb.branch));
+                }
+            }
+        }
+
         // This method will check with server to find out what commits need to be pushed
         // will fill in commits that are not synced
         let mut unsynced_commits: VecDeque<UnsyncedCommitEntries> = VecDeque::new();
@@ -100,15 +126,135 @@ impl EntryIndexer {
         }
 
         // Update the remote branch name last
-        api::remote::branches::update(&remote_repo, &rb.branch, &head_commit).await?;
+        if force {
+            api::remote::branches::force_update(&remote_repo, &rb.branch, &head_commit).await?;
+        } else {
+            api::remote::branches::update(&remote_repo, &rb.branch, &head_commit).await?;
+        }
+        self.set_remote_branch_for_commit(rb, &head_commit)?;
         println!(
             "Updated remote branch {} -> {}",
             &rb.branch, &head_commit.id
         );
 
+        // The branch is already pushed, so a server without tags should not fail the push
+        if let Err(err) = self.push_tags(&remote_repo).await {
+            eprintln!("Could not push tags: {err}");
+        }
+
+        Ok(remote_repo)
+    }
+
+    async fn push_tag(&self, remote: &str, tag: &Tag) -> Result<RemoteRepository, OxenError> {
+        let remote = self
+            .repository
+            .get_remote(remote)
+            .ok_or_else(OxenError::remote_not_set)?;
+        let remote_repo = match api::remote::repositories::get_by_remote(&remote).await {
+            Ok(Some(repo)) => repo,
+            Ok(None) => return Err(OxenError::remote_repo_not_found(&remote.url)),
+            Err(err) => return Err(err),
+        };
+
+        if !self.remote_has_commit(&remote_repo, &tag.commit_id).await? {
+            let err = format!(
+                "Commit {} of tag '{}' is not on the remote, push a branch that contains it first",
+                tag.commit_id, tag.name
+            );
+            return Err(OxenError::basic_str(err));
+        }
+
+        api::remote::tags::create(&remote_repo, tag).await?;
+        println!("Pushed tag {} -> {}", tag.name, tag.commit_id);
         Ok(remote_repo)
     }
 
+    async fn remote_has_commit(
+        &self,
+        remote_repo: &RemoteRepository,
+        commit_id: &str,
+    ) -> Result<bool, OxenError> {
+        match api::remote::commits::commit_is_synced(remote_repo, commit_id).await? {
+            Some(sync_status) => Ok(sync_status.is_valid),
+            None => Ok(false),
+        }
+    }
+
+    /// Pushes the tags the remote does not have yet whose commits are on the remote, like the ones on a
+    /// branch we just pushed. Tags that point at another commit on the remote are left alone.
+    pub async fn push_tags(&self, remote_repo: &RemoteRepository) -> Result<Vec<Tag>, OxenError> {
+        let local_tags = RefReader::new(&self.repository)?.list_tags()?;
+        if local_tags.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let remote_tags = api::remote::tags::list(remote_repo).await?;
+        let mut pushed: Vec<Tag> = vec![];
+        for tag in local_tags {
+            match remote_tags.iter().find(|t| t.name == tag.name) {
+                Some(remote_tag) if remote_tag.commit_id == tag.commit_id => {}
+                Some(remote_tag) => {
+                    eprintln!(
+                        "Tag '{}' is {} on the remote and {} here, not pushing it",
+                        tag.name, remote_tag.commit_id, tag.commit_id
+                    );
+                }
+                None => {
+                    if self.remote_has_commit(remote_repo, &tag.commit_id).await? {
+                        api::remote::tags::create(remote_repo, &tag).await?;
+                        println!("Pushed tag {} -> {}", tag.name, tag.commit_id);
+                        pushed.push(tag);
+                    }
+                }
+            }
+        }
+        Ok(pushed)
+    }
+
+    /// Saves the remote tags we do not have yet whose commits we have. With `prune` the local tags are made an
+    /// exact copy of the remote ones, for mirrors.
+    pub async fn pull_tags(
+        &self,
+        remote_repo: &RemoteRepository,
+        prune: bool,
+    ) -> Result<Vec<Tag>, OxenError> {
+        let remote_tags = api::remote::tags::list(remote_repo).await?;
+        let local_tags = RefReader::new(&self.repository)?.list_tags()?;
+        let commit_reader = CommitReader::new(&self.repository)?;
+
+        if prune {
+            for tag in local_tags.iter() {
+                let is_current = remote_tags
+                    .iter()
+                    .any(|t| t.name == tag.name && t.commit_id == tag.commit_id);
+                if !is_current {
+                    log::debug!("pull_tags deleting tag {} gone from remote", tag.name);
+                    api::local::tags::delete(&self.repository, &tag.name)?;
+                }
+            }
+        }
+
+        let mut pulled: Vec<Tag> = vec![];
+        for tag in remote_tags {
+            match local_tags.iter().find(|t| t.name == tag.name) {
+                Some(local_tag) if local_tag.commit_id == tag.commit_id => {}
+                Some(local_tag) if !prune => {
+                    eprintln!(
+                        "Tag '{}' is {} on the remote and {} here, not pulling it",
+                        tag.name, tag.commit_id, local_tag.commit_id
+                    );
+                }
+                _ => {
+                    if commit_reader.commit_id_exists(&tag.commit_id) {
+                        api::local::tags::save(&self.repository, &tag)?;
+                        pulled.push(tag);
+                    }
+                }
+            }
+        }
+        Ok(pulled)
+    }
+
     async fn poll_until_synced(
         &self,
         remote_repo: &RemoteRepository,
@@ -349,6 +495,10 @@ impl EntryIndexer {
             commit.message
         );
 
+        // A push that was interrupted may have already sent some of these, so skip the ones the remote has
+        let entries = self.entries_missing_on_remote(remote_repo, entries).await?;
+        let entries = &entries[..];
+
         println!("🐂 push computing size...");
         let total_size = self.compute_entries_size(entries)?;
 
@@ -364,26 +514,21 @@ impl EntryIndexer {
         // since bodies will be too big. Hence we chunk and send the big ones, and bundle and send the small ones
 
         // For files smaller than AVG_CHUNK_SIZE, we are going to group them, zip them up, and transfer them
+        let store = ChunkStore::new(&self.repository);
         let smaller_entries: Vec<CommitEntry> = entries
             .iter()
-            .filter(|e| e.num_bytes < AVG_CHUNK_SIZE)
+            .filter(|e| !store.should_chunk(e.num_bytes))
             .map(|e| e.to_owned())
             .collect();
 
-        // For files larger than AVG_CHUNK_SIZE, we are going break them into chunks and send the chunks in parallel
+        // For files larger than AVG_CHUNK_SIZE, we only send the content defined chunks the remote is missing
         let larger_entries: Vec<CommitEntry> = entries
             .iter()
-            .filter(|e| e.num_bytes > AVG_CHUNK_SIZE)
+            .filter(|e| store.should_chunk(e.num_bytes))
             .map(|e| e.to_owned())
             .collect();
 
-        let large_entries_sync = self.chunk_and_send_large_entries(
-            remote_repo,
-            larger_entries,
-            commit,
-            AVG_CHUNK_SIZE,
-            &bar,
-        );
+        let large_entries_sync = self.send_missing_chunks(remote_repo, larger_entries, &bar);
         let small_entries_sync = self.bundle_and_send_small_entries(
             remote_repo,
             smaller_entries,
@@ -394,6 +539,7 @@ impl EntryIndexer {
 
         match tokio::join!(large_entries_sync, small_entries_sync) {
             (Ok(_), Ok(_)) => {
+                api::remote::transfer::add_files(entries.len() as u64);
                 api::remote::commits::post_push_complete(remote_repo, &commit.id).await
             }
             (Err(err), Ok(_)) => {
@@ -408,12 +554,31 @@ impl EntryIndexer {
         }
     }
 
-    async fn chunk_and_send_large_entries(
+    async fn entries_missing_on_remote(
+        &self,
+        remote_repo: &RemoteRepository,
+        entries: &[CommitEntry],
+    ) -> Result<Vec<CommitEntry>, OxenError> {
+        // Keep the request bodies small for commits with many files
+        let batch_size = 1000;
+        let mut missing_entries: Vec<CommitEntry> = vec![];
+        for batch in entries.chunks(batch_size) {
+            let batch_missing =
+                api::remote::entries::list_missing_versions(remote_repo, batch).await?;
+            missing_entries.extend(batch_missing);
+        }
+
+        let num_skipped = entries.len() - missing_entries.len();
+        if num_skipped > 0 {
+            println!("Skipping {num_skipped} files the remote already has");
+        }
+        Ok(missing_entries)
+    }
+
+    async fn send_missing_chunks(
         &self,
         remote_repo: &RemoteRepository,
         entries: Vec<CommitEntry>,
-        commit: &Commit,
-        chunk_size: u64,
         bar: &Arc<ProgressBar>,
     ) -> Result<(), OxenError> {
         if entries.is_empty() {
@@ -424,21 +589,19 @@ impl EntryIndexer {
         type PieceOfWork = (
             CommitEntry,
             LocalRepository,
-            Commit,
             RemoteRepository,
             Arc<ProgressBar>,
         );
         type TaskQueue = deadqueue::limited::Queue<PieceOfWork>;
         type FinishedTaskQueue = deadqueue::limited::Queue<bool>;
 
-        log::debug!("Chunking and sending {} larger files", entries.len());
+        log::debug!("Sending chunks for {} larger files", entries.len());
         let entries: Vec<PieceOfWork> = entries
             .iter()
             .map(|e| {
                 (
                     e.to_owned(),
                     self.repository.to_owned(),
-                    commit.to_owned(),
                     remote_repo.to_owned(),
                     bar.to_owned(),
                 )
@@ -452,11 +615,8 @@ impl EntryIndexer {
             finished_queue.try_push(false).unwrap();
         }
 
-        let worker_count: usize = if num_cpus::get() > entries.len() {
-            entries.len()
-        } else {
-            num_cpus::get()
-        };
+        let worker_count: usize = api::remote::client::concurrency().min(entries.len());
+        let errors: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(vec![]));
 
         log::debug!(
             "worker_count {} entries len {}",
@@ -466,68 +626,20 @@ impl EntryIndexer {
         for worker in 0..worker_count {
             let queue = queue.clone();
             let finished_queue = finished_queue.clone();
+            let errors = errors.clone();
             tokio::spawn(async move {
                 loop {
-                    let (entry, repo, commit, remote_repo, bar) = queue.pop().await;
+                    let (entry, repo, remote_repo, bar) = queue.pop().await;
                     log::debug!("worker[{}] processing task...", worker);
 
-                    // Open versioned file
-                    let version_path = util::fs::version_path(&repo, &entry);
-                    let f = std::fs::File::open(&version_path).unwrap();
-                    let mut reader = BufReader::new(f);
-
-                    // Read chunks
-                    let total_size = entry.num_bytes;
-                    let num_chunks = ((total_size / chunk_size) + 1) as usize;
-                    let mut total_read = 0;
-                    let mut chunk_size = chunk_size;
-
-                    // TODO: We could probably upload chunks in parallel too
-                    for i in 0..num_chunks {
-                        // Make sure we read the last size correctly
-                        if (total_read + chunk_size) > total_size {
-                            chunk_size = total_size % chunk_size;
+                    match push_entry_chunks(&repo, &remote_repo, &entry, &bar).await {
+                        Ok(_) => {
+                            log::debug!("Successfully pushed chunks for {:?}", entry.path)
                         }
-
-                        // Only read as much as you need to send so we don't blow up memory on large files
-                        let mut buffer = vec![0u8; chunk_size as usize];
-                        reader.read_exact(&mut buffer).unwrap();
-                        total_read += chunk_size;
-
-                        let size = buffer.len() as u64;
-                        log::debug!("Got entry buffer of size {}", size);
-
-                        // Send data to server
-                        let is_compressed = false;
-                        let hidden_dir = util::fs::oxen_hidden_dir(&repo.path);
-                        let path =
-                            util::fs::path_relative_to_dir(&version_path, &hidden_dir).unwrap();
-                        let file_name = Some(String::from(path.to_str().unwrap()));
-
-                        let params = ChunkParams {
-                            chunk_num: i,
-                            total_chunks: num_chunks,
-                            total_size: total_size as usize,
-                        };
-
-                        match api::remote::commits::upload_data_chunk_to_server_with_retry(
-                            &remote_repo,
-                            &commit,
-                            &buffer,
-                            &entry.hash,
-                            &params,
-                            is_compressed,
-                            &file_name,
-                        )
-                        .await
-                        {
-                            Ok(_) => {
-                                bar.inc(buffer.len() as u64);
-                                log::debug!("Successfully uploaded chunk {}/{}", i, num_chunks)
-                            }
-                            Err(err) => {
-                                log::error!("Error uploading chunk: {:?}", err)
-                            }
+                        Err(err) => {
+                            log::error!("Error pushing chunks for {:?}: {}", entry.path, err);
+                            let err = format!("{}: {}", entry.path.to_string_lossy(), err);
+                            errors.lock().unwrap().push(err);
                         }
                     }
 
@@ -542,7 +654,7 @@ impl EntryIndexer {
         }
         log::debug!("All large file tasks done. :-)");
 
-        Ok(())
+        worker_errors(&errors)
     }
 
     /// Sends entries in tarballs of size ~chunk size
@@ -593,9 +705,10 @@ impl EntryIndexer {
             })
             .collect();
 
-        let worker_count: usize = num_cpus::get();
+        let worker_count: usize = api::remote::client::concurrency();
         let queue = Arc::new(TaskQueue::new(chunks.len()));
         let finished_queue = Arc::new(FinishedTaskQueue::new(entries.len()));
+        let errors: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(vec![]));
         for chunk in chunks {
             queue.try_push(chunk).unwrap();
             finished_queue.try_push(false).unwrap();
@@ -604,58 +717,40 @@ impl EntryIndexer {
         for worker in 0..worker_count {
             let queue = queue.clone();
             let finished_queue = finished_queue.clone();
+            let errors = errors.clone();
             tokio::spawn(async move {
                 loop {
                     let (chunk, repo, commit, remote_repo, bar) = queue.pop().await;
                     log::debug!("worker[{}] processing task...", worker);
 
-                    let enc = GzEncoder::new(Vec::new(), Compression::default());
-                    let mut tar = tar::Builder::new(enc);
                     log::debug!("Chunk size {}", chunk.len());
                     log::debug!("got repo {:?}", &repo.path);
-                    for entry in chunk.into_iter() {
-                        let hidden_dir = util::fs::oxen_hidden_dir(&repo.path);
-                        let version_path = util::fs::version_path(&repo, &entry);
-                        let name =
-                            util::fs::path_relative_to_dir(&version_path, &hidden_dir).unwrap();
-
-                        tar.append_path_with_name(version_path, name).unwrap();
-                    }
-
-                    let buffer = match tar.into_inner() {
-                        Ok(gz_encoder) => match gz_encoder.finish() {
-                            Ok(buffer) => {
-                                let size = buffer.len() as u64;
-                                log::debug!("Got tarball buffer of size {}", size);
-                                buffer
-                            }
-                            Err(err) => {
-                                panic!("Error creating tar.gz on entries: {}", err)
-                            }
-                        },
-                        Err(err) => {
-                            panic!("Error creating tar of entries: {}", err)
+                    let upload_id = api::remote::commits::upload_id(&commit, &chunk);
+                    let result = match tar_entries(&repo, &chunk) {
+                        // Send tar.gz to server
+                        Ok(buffer) => {
+                            let is_compressed = true;
+                            let file_name = None;
+                            api::remote::commits::post_data_to_server(
+                                &remote_repo,
+                                &commit,
+                                &upload_id,
+                                buffer,
+                                is_compressed,
+                                &file_name,
+                                bar,
+                            )
+                            .await
                         }
+                        Err(err) => Err(err),
                     };
-
-                    // Send tar.gz to server
-                    let is_compressed = true;
-                    let file_name = None;
-                    match api::remote::commits::post_data_to_server(
-                        &remote_repo,
-                        &commit,
-                        buffer,
-                        is_compressed,
-                        &file_name,
-                        bar,
-                    )
-                    .await
-                    {
+                    match result {
                         Ok(_) => {
                             log::debug!("Successfully uploaded data!")
                         }
                         Err(err) => {
-                            log::error!("Error uploading chunk: {:?}", err)
+                            log::error!("Error uploading chunk: {:?}", err);
+                            errors.lock().unwrap().push(err.to_string());
                         }
                     }
                     finished_queue.pop().await;
@@ -668,7 +763,7 @@ impl EntryIndexer {
         }
         log::debug!("All tasks done. :-)");
 
-        Ok(())
+        worker_errors(&errors)
     }
 
     pub async fn pull(&self, rb: &RemoteBranch) -> Result<(), OxenError> {
@@ -690,9 +785,202 @@ impl EntryIndexer {
             self.pull_entries_for_commit(&remote_repo, &commit, limit)
                 .await?;
         }
+
+        match self.pull_tags(&remote_repo, false).await {
+            Ok(tags) => {
+                for tag in tags {
+                    println!("Pulled tag {} -> {}", tag.name, tag.commit_id);
+                }
+            }
+            Err(err) => eprintln!("Could not pull tags: {err}"),
+        }
         Ok(())
     }
 
+    /// Downloads the commit objects and history dbs of a remote branch, or of every remote branch if `branch` is None,
+    /// and records them as remote-tracking refs like `origin/main`. The working directory and local branches are untouched.
+    pub async fn fetch(
+        &self,
+        remote: &str,
+        branch: Option<&str>,
+    ) -> Result<Vec<Branch>, OxenError> {
+        let remote = self
+            .repository
+            .get_remote(remote)
+            .ok_or_else(OxenError::remote_not_set)?;
+
+        let remote_repo = match api::remote::repositories::get_by_remote(&remote).await {
+            Ok(Some(repo)) => repo,
+            Ok(None) => return Err(OxenError::remote_repo_not_found(&remote.url)),
+            Err(err) => return Err(err),
+        };
+
+        let remote_branches = match branch {
+            Some(name) => {
+                let remote_branch_err = format!("Remote branch not found: {name}");
+                let remote_branch = api::remote::branches::get_by_name(&remote_repo, name)
+                    .await?
+                    .ok_or_else(|| OxenError::basic_str(&remote_branch_err))?;
+                vec![remote_branch]
+            }
+            None => api::remote::branches::list(&remote_repo).await?,
+        };
+
+        let mut fetched: Vec<Branch> = vec![];
+        for remote_branch in remote_branches {
+            match api::remote::commits::get_by_id(&remote_repo, &remote_branch.commit_id).await? {
+                Some(commit) => {
+                    println!("🐂 fetching commit objects {}", commit.id);
+                    self.rpull_missing_commit_objects(&remote_repo, &commit)
+                        .await?;
+
+                    let ref_writer = RefWriter::new(&self.repository)?;
+                    ref_writer.set_remote_branch_commit_id(
+                        &remote.name,
+                        &remote_branch.name,
+                        &commit.id,
+                    )?;
+                    fetched.push(Branch {
+                        name: format!("{}/{}", remote.name, remote_branch.name),
+                        commit_id: commit.id,
+                        is_head: false,
+                    });
+                }
+                None => {
+                    log::debug!(
+                        "fetch could not find commit {} for remote branch {}",
+                        remote_branch.commit_id,
+                        remote_branch.name
+                    );
+                }
+            }
+        }
+
+        if let Err(err) = self.pull_tags(&remote_repo, false).await {
+            eprintln!("Could not fetch tags: {err}");
+        }
+
+        Ok(fetched)
+    }
+
+    /// Makes this repository a copy of the remote, with every remote branch and tag at the same commit id and the
+    /// version files of their whole history. Branches and tags that were deleted on the remote are deleted here too.
+    pub async fn mirror(&self, remote: &str) -> Result<Vec<Branch>, OxenError> {
+        let remote = self
+            .repository
+            .get_remote(remote)
+            .ok_or_else(OxenError::remote_not_set)?;
+
+        let remote_repo = match api::remote::repositories::get_by_remote(&remote).await {
+            Ok(Some(repo)) => repo,
+            Ok(None) => return Err(OxenError::remote_repo_not_found(&remote.url)),
+            Err(err) => return Err(err),
+        };
+
+        let ref_writer = RefWriter::new(&self.repository)?;
+        let mut mirrored: Vec<Branch> = vec![];
+        for remote_branch in api::remote::branches::list(&remote_repo).await? {
+            let commit =
+                match api::remote::commits::get_by_id(&remote_repo, &remote_branch.commit_id)
+                    .await?
+                {
+                    Some(commit) => commit,
+                    None => {
+                        log::debug!(
+                            "mirror could not find commit {} for remote branch {}",
+                            remote_branch.commit_id,
+                            remote_branch.name
+                        );
+                        continue;
+                    }
+                };
+
+            self.rpull_missing_commit_objects(&remote_repo, &commit)
+                .await?;
+            self.pull_history_version_files(&remote_repo, &commit)
+                .await?;
+
+            ref_writer.set_branch_commit_id(&remote_branch.name, &commit.id)?;
+            ref_writer.set_remote_branch_commit_id(
+                &remote.name,
+                &remote_branch.name,
+                &commit.id,
+            )?;
+            mirrored.push(Branch {
+                name: remote_branch.name,
+                commit_id: commit.id,
+                is_head: false,
+            });
+        }
+
+        for branch in ref_writer.list_branches()? {
+            if !mirrored.iter().any(|b| b.name == branch.name) {
+                log::debug!("mirror deleting branch {} gone from remote", branch.name);
+                ref_writer.delete_branch(&branch.name)?;
+                ref_writer.delete_remote_branch(&remote.name, &branch.name)?;
+            }
+        }
+
+        // Keep HEAD on a branch that still exists
+        let head = ref_writer.read_head_ref()?;
+        if !mirrored.iter().any(|b| b.name == head) {
+            if mirrored.iter().any(|b| b.name == DEFAULT_BRANCH_NAME) {
+                ref_writer.set_head(DEFAULT_BRANCH_NAME);
+            } else if let Some(branch) = mirrored.first() {
+                ref_writer.set_head(&branch.name);
+            }
+        }
+        // Saving tags opens the refs again
+        drop(ref_writer);
+
+        // Tagged commits may not be on any branch anymore
+        for tag in api::remote::tags::list(&remote_repo).await? {
+            if let Some(commit) =
+                api::remote::commits::get_by_id(&remote_repo, &tag.commit_id).await?
+            {
+                self.rpull_missing_commit_objects(&remote_repo, &commit)
+                    .await?;
+                self.pull_history_version_files(&remote_repo, &commit)
+                    .await?;
+            }
+        }
+        // Tags can only be force deleted upstream, the mirror follows
+        self.pull_tags(&remote_repo, true).await?;
+
+        Ok(mirrored)
+    }
+
+    /// Downloads the version files of every commit in the history of `commit` that we do not have yet
+    async fn pull_history_version_files(
+        &self,
+        remote_repo: &RemoteRepository,
+        commit: &Commit,
+    ) -> Result<(), OxenError> {
+        let commit_reader = CommitReader::new(&self.repository)?;
+        let mut seen: HashSet<PathBuf> = HashSet::new();
+        let mut missing_entries: Vec<CommitEntry> = vec![];
+        for commit in commit_reader.history_from_commit_id(&commit.id)? {
+            let entries: Vec<CommitEntry> = CommitDirReader::new(&self.repository, &commit)?
+                .list_entries()?
+                .into_iter()
+                // The same version is usually in many commits, only download it once
+                .filter(|entry| seen.insert(util::fs::version_path(&self.repository, entry)))
+                .collect();
+            missing_entries.extend(self.get_missing_commit_entries(&entries));
+        }
+
+        if missing_entries.is_empty() {
+            return Ok(());
+        }
+
+        println!(
+            "🐂 mirroring {} files for commit {}",
+            missing_entries.len(),
+            commit.id
+        );
+        self.pull_version_files(remote_repo, &missing_entries).await
+    }
+
     pub async fn pull_all_commit_objects(
         &self,
         remote_repo: &RemoteRepository,
@@ -717,6 +1005,7 @@ impl EntryIndexer {
                 // Sync the commit objects
                 self.rpull_missing_commit_objects(remote_repo, &commit)
                     .await?;
+                self.set_remote_branch_for_commit(rb, &commit)?;
                 return Ok(Some(commit));
             }
             Ok(None) => {
@@ -730,6 +1019,15 @@ impl EntryIndexer {
         Ok(None)
     }
 
+    fn set_remote_branch_for_commit(
+        &self,
+        rb: &RemoteBranch,
+        commit: &Commit,
+    ) -> Result<(), OxenError> {
+        let ref_writer = RefWriter::new(&self.repository)?;
+        ref_writer.set_remote_branch_commit_id(&rb.remote, &rb.branch, &commit.id)
+    }
+
     fn set_branch_name_for_commit(&self, name: &str, commit: &Commit) -> Result<(), OxenError> {
         let ref_writer = RefWriter::new(&self.repository)?;
         // Make sure head is pointing to that branch
@@ -747,7 +1045,9 @@ impl EntryIndexer {
         let commit_db_dir = util::fs::oxen_hidden_dir(&self.repository.path)
             .join(HISTORY_DIR)
             .join(remote_head_commit.id.clone());
-        if !commit_db_dir.exists() {
+        if !commit_db_dir.exists()
+            && !HistoryPack::is_packed(&self.repository, &remote_head_commit.id)
+        {
             // We don't have db locally, so pull it
             log::debug!(
                 "commit db for {} not found, pull from remote",
@@ -833,8 +1133,12 @@ impl EntryIndexer {
         mut limit: usize,
     ) -> Result<Vec<CommitEntry>, OxenError> {
         let commit_reader = CommitDirReader::new(&self.repository, commit)?;
-        let entries = commit_reader.list_entries()?;
-        if limit == 0 {
+        let mut entries = commit_reader.list_entries()?;
+        // Sparse clones leave the entries outside of their spec absent
+        if let Some(filter) = self.repository.sparse_filter() {
+            entries.retain(|entry| filter.includes(&entry.path));
+        }
+        if limit == 0 || limit > entries.len() {
             limit = entries.len();
         }
         Ok(entries[0..limit].to_vec())
@@ -843,9 +1147,9 @@ impl EntryIndexer {
     fn get_missing_commit_entries(&self, entries: &[CommitEntry]) -> Vec<CommitEntry> {
         let mut missing_entries: Vec<CommitEntry> = vec![];
 
+        let store = ChunkStore::new(&self.repository);
         for entry in entries {
-            let version_path = util::fs::version_path(&self.repository, entry);
-            if !version_path.exists() {
+            if !store.has_version(entry) {
                 missing_entries.push(entry.to_owned())
             }
         }
@@ -902,53 +1206,66 @@ impl EntryIndexer {
             println!("🐂 pulling commit {} with {} entries", commit.id, total);
 
             let missing_entries = self.get_missing_commit_entries(&entries);
-            let total_size = self.compute_entries_size(&missing_entries)?;
-            println!("Total size {}", ByteSize::b(total_size));
+            self.pull_version_files(remote_repo, &missing_entries)
+                .await?;
+            log::debug!("Successfully synced entries!");
+            self.unpack_version_files(commit, entries)?;
+        }
 
-            // Some files may be much larger than others....so we can't just download them within a single body
-            // Hence we chunk and send the big ones, and bundle and download the small ones
+        // Cleanup files that shouldn't be there
+        self.cleanup_removed_entries(commit)?;
 
-            // For files smaller than AVG_CHUNK_SIZE, we are going to group them, zip them up, and transfer them
-            let smaller_entries: Vec<CommitEntry> = missing_entries
-                .iter()
-                .filter(|e| e.num_bytes < AVG_CHUNK_SIZE)
-                .map(|e| e.to_owned())
-                .collect();
+        Ok(())
+    }
 
-            // For files larger than AVG_CHUNK_SIZE, we are going break them into chunks and download the chunks in parallel
-            let larger_entries: Vec<CommitEntry> = missing_entries
-                .iter()
-                .filter(|e| e.num_bytes > AVG_CHUNK_SIZE)
-                .map(|e| e.to_owned())
-                .collect();
+    /// Downloads the version files of the entries, without touching the working directory
+    async fn pull_version_files(
+        &self,
+        remote_repo: &RemoteRepository,
+        entries: &[CommitEntry],
+    ) -> Result<(), OxenError> {
+        let total_size = self.compute_entries_size(entries)?;
+        println!("Total size {}", ByteSize::b(total_size));
 
-            // Progress bar to be shared between small and large entries
-            let bar = Arc::new(ProgressBar::new(total_size));
+        // Some files may be much larger than others....so we can't just download them within a single body
+        // Hence we chunk and send the big ones, and bundle and download the small ones
 
-            let large_entries_sync = self.pull_large_entries(remote_repo, larger_entries, &bar);
-            let small_entries_sync = self.pull_small_entries(remote_repo, smaller_entries, &bar);
+        // For files smaller than AVG_CHUNK_SIZE, we are going to group them, zip them up, and transfer them
+        let store = ChunkStore::new(&self.repository);
+        let smaller_entries: Vec<CommitEntry> = entries
+            .iter()
+            .filter(|e| !store.should_chunk(e.num_bytes))
+            .map(|e| e.to_owned())
+            .collect();
 
-            match tokio::join!(large_entries_sync, small_entries_sync) {
-                (Ok(_), Ok(_)) => {
-                    log::debug!("Successfully synced entries!");
-                    self.unpack_version_files(commit, entries)?;
-                }
-                (Err(err), Ok(_)) => {
-                    let err = format!("Error syncing large entries: {err}");
-                    return Err(OxenError::basic_str(err));
-                }
-                (Ok(_), Err(err)) => {
-                    let err = format!("Error syncing small entries: {err}");
-                    return Err(OxenError::basic_str(err));
-                }
-                _ => return Err(OxenError::basic_str("Unknown error syncing entries")),
-            }
-        }
+        // For files larger than AVG_CHUNK_SIZE, we only download the content defined chunks we are missing
+        let larger_entries: Vec<CommitEntry> = entries
+            .iter()
+            .filter(|e| store.should_chunk(e.num_bytes))
+            .map(|e| e.to_owned())
+            .collect();
 
-        // Cleanup files that shouldn't be there
-        self.cleanup_removed_entries(commit)?;
+        // Progress bar to be shared between small and large entries
+        let bar = Arc::new(ProgressBar::new(total_size));
 
-        Ok(())
+        let large_entries_sync = self.pull_large_entries(remote_repo, larger_entries, &bar);
+        let small_entries_sync = self.pull_small_entries(remote_repo, smaller_entries, &bar);
+
+        match tokio::join!(large_entries_sync, small_entries_sync) {
+            (Ok(_), Ok(_)) => {
+                api::remote::transfer::add_files(entries.len() as u64);
+                Ok(())
+            }
+            (Err(err), Ok(_)) => {
+                let err = format!("Error syncing large entries: {err}");
+                Err(OxenError::basic_str(err))
+            }
+            (Ok(_), Err(err)) => {
+                let err = format!("Error syncing small entries: {err}");
+                Err(OxenError::basic_str(err))
+            }
+            _ => Err(OxenError::basic_str("Unknown error syncing entries")),
+        }
     }
 
     async fn pull_large_entries(
@@ -992,11 +1309,7 @@ impl EntryIndexer {
             finished_queue.try_push(false).unwrap();
         }
 
-        let worker_count: usize = if num_cpus::get() > entries.len() {
-            entries.len()
-        } else {
-            num_cpus::get()
-        };
+        let worker_count: usize = api::remote::client::concurrency().min(entries.len());
 
         log::debug!(
             "worker_count {} entries len {}",
@@ -1011,20 +1324,12 @@ impl EntryIndexer {
                     let (entry, repo, remote_repo, bar) = queue.pop().await;
                     log::debug!("worker[{}] processing task...", worker);
 
-                    // Chunk and individual files
-                    match api::remote::entries::download_large_entry(
-                        &repo,
-                        &remote_repo,
-                        &entry,
-                        &bar,
-                    )
-                    .await
-                    {
+                    match pull_entry_chunks(&repo, &remote_repo, &entry, &bar).await {
                         Ok(_) => {
                             log::debug!("Downloaded large entry {:?}", entry.path);
                         }
                         Err(err) => {
-                            log::error!("Could not download chunk... {}", err)
+                            log::error!("Could not download chunks... {}", err)
                         }
                     }
 
@@ -1092,7 +1397,7 @@ impl EntryIndexer {
             })
             .collect();
 
-        let worker_count: usize = num_cpus::get();
+        let worker_count: usize = api::remote::client::concurrency();
         let queue = Arc::new(TaskQueue::new(chunks.len()));
         let finished_queue = Arc::new(FinishedTaskQueue::new(entries.len()));
         for chunk in chunks {
@@ -1144,6 +1449,7 @@ impl EntryIndexer {
         println!("Unpacking...");
         let bar = Arc::new(ProgressBar::new(entries.len() as u64));
         let dir_entries = self.group_entries_to_parent_dirs(&entries);
+        let store = ChunkStore::new(&self.repository);
 
         dir_entries.par_iter().for_each(|(dir, entries)| {
             let committer = CommitDirEntryWriter::new(&self.repository, &commit.id, dir).unwrap();
@@ -1158,10 +1464,8 @@ impl EntryIndexer {
                     }
 
                     log::debug!("pull_entries_for_commit unpack {:?}", entry.path);
-                    let version_path = util::fs::version_path(&self.repository, entry);
-                    // We will unpack tabular later into CADF
-                    if std::fs::copy(&version_path, &filepath).is_err() {
-                        log::error!("Could not unpack file {:?} -> {:?}", version_path, filepath);
+                    if let Err(err) = store.copy_version_to(entry, &filepath) {
+                        log::error!("Could not unpack file {:?}: {}", filepath, err);
                     }
 
                     log::debug!(
@@ -1253,6 +1557,108 @@ impl EntryIndexer {
     }
 }
 
+/// The version files of the entries in a tar.gz, laid out like the hidden dir
+fn tar_entries(repo: &LocalRepository, entries: &[CommitEntry]) -> Result<Vec<u8>, OxenError> {
+    let enc = GzEncoder::new(Vec::new(), Compression::default());
+    let mut tar = tar::Builder::new(enc);
+    let hidden_dir = util::fs::oxen_hidden_dir(&repo.path);
+    for entry in entries.iter() {
+        let version_path = util::fs::version_path(repo, entry);
+        let name = util::fs::path_relative_to_dir(&version_path, &hidden_dir)?;
+        tar.append_path_with_name(version_path, name)?;
+    }
+
+    let buffer = tar.into_inner()?.finish()?;
+    log::debug!("Got tarball buffer of size {}", buffer.len());
+    Ok(buffer)
+}
+
+// Workers log what went wrong as it happens, the push fails once they are all done
+fn worker_errors(errors: &Mutex<Vec<String>>) -> Result<(), OxenError> {
+    let errors = errors.lock().unwrap();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(OxenError::basic_str(errors.join("\n")))
+    }
+}
+
+/// Uploads the chunks of a large entry the remote does not have, then the manifest to rebuild it
+async fn push_entry_chunks(
+    repo: &LocalRepository,
+    remote_repo: &RemoteRepository,
+    entry: &CommitEntry,
+    bar: &Arc<ProgressBar>,
+) -> Result<(), OxenError> {
+    let store = ChunkStore::new(repo);
+    let manifest = match store.get_manifest(&entry.hash)? {
+        Some(manifest) => manifest,
+        // Versions committed before chunking still have a full copy we can chunk now
+        None => store.chunk_file(&entry.hash, util::fs::version_path(repo, entry))?,
+    };
+
+    let mut missing: HashSet<String> =
+        api::remote::chunks::list_missing(remote_repo, &manifest.chunk_hashes())
+            .await?
+            .into_iter()
+            .collect();
+    log::debug!(
+        "push_entry_chunks {:?} remote is missing {}/{} chunks",
+        entry.path,
+        missing.len(),
+        manifest.chunks.len()
+    );
+
+    for chunk in manifest.chunks.iter() {
+        // A chunk can repeat within a file, only send it once
+        if missing.remove(&chunk.hash) {
+            let data = store.read_chunk(&chunk.hash)?;
+            api::remote::chunks::upload_with_retry(remote_repo, &chunk.hash, &data).await?;
+        }
+        bar.inc(chunk.num_bytes);
+    }
+
+    api::remote::chunks::post_manifest(remote_repo, &manifest).await
+}
+
+/// Downloads the chunks of a large entry we do not have yet, and saves the manifest to rebuild it
+async fn pull_entry_chunks(
+    repo: &LocalRepository,
+    remote_repo: &RemoteRepository,
+    entry: &CommitEntry,
+    bar: &Arc<ProgressBar>,
+) -> Result<(), OxenError> {
+    let store = ChunkStore::new(repo);
+    let manifest = match api::remote::chunks::get_manifest(remote_repo, &entry.hash).await? {
+        Some(manifest) => manifest,
+        // The remote stored this version whole, before it chunked large files
+        None => {
+            return api::remote::entries::download_large_entry(repo, remote_repo, entry, bar).await
+        }
+    };
+
+    let mut missing: HashSet<String> = store
+        .missing_chunks(&manifest.chunk_hashes())?
+        .into_iter()
+        .collect();
+    log::debug!(
+        "pull_entry_chunks {:?} missing {}/{} chunks",
+        entry.path,
+        missing.len(),
+        manifest.chunks.len()
+    );
+
+    for chunk in manifest.chunks.iter() {
+        if missing.remove(&chunk.hash) {
+            let data = api::remote::chunks::download_with_retry(remote_repo, &chunk.hash).await?;
+            store.put_chunk(&chunk.hash, &data)?;
+        }
+        bar.inc(chunk.num_bytes);
+    }
+
+    store.put_manifest(&manifest)
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::VecDeque;
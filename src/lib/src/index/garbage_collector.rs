@@ -0,0 +1,453 @@
+//! # GarbageCollector
+//!
+//! Removes the data that no ref can reach anymore, such as what is left behind by `force_delete_branch`
+//! or a push that failed half way.
+//!
+//! Marks every commit in the history of the branches, HEAD and an in progress merge, along with the version files,
+//! chunks and packed history objects those commits use. Everything else in `.oxen/history`, `.oxen/versions`
+//! and `.oxen/packs` is swept.
+//!
+//! Pushed commits are not reachable from a branch until it is updated at the end of the push, so the commits
+//! of unfinished uploads are marked as well, and anything written within the grace period is left alone.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::constants::{
+    CHERRY_PICK_HEAD_FILE, DEFAULT_GC_GRACE_PERIOD_SECS, FILES_DIR, HISTORY_DIR, MERGE_HEAD_FILE,
+    ORIG_HEAD_FILE, VERSIONS_DIR,
+};
+use crate::db::kv_db;
+use crate::error::OxenError;
+use crate::index::{
+    ChunkStore, CommitDirReader, CommitReader, CommitWriter, HistoryPack, RefReader,
+    UploadSessionStore,
+};
+use crate::model::LocalRepository;
+use crate::util;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GCStats {
+    pub num_reachable_commits: usize,
+    pub num_commits: usize,
+    pub num_version_dirs: usize,
+    pub num_chunks: usize,
+    pub num_pack_objects: usize,
+    pub num_bytes: u64,
+}
+
+/// The commits that branches, remote-tracking refs, tags, stashes, HEAD and an in progress merge or cherry-pick point at
+pub fn ref_commit_ids(repo: &LocalRepository) -> Result<HashSet<String>, OxenError> {
+    let ref_reader = RefReader::new(repo)?;
+    let mut commit_ids: HashSet<String> = ref_reader
+        .list_branches()?
+        .into_iter()
+        .map(|branch| branch.commit_id)
+        .collect();
+    if let Some(head_commit_id) = ref_reader.head_commit_id()? {
+        commit_ids.insert(head_commit_id);
+    }
+    // Fetched history is only reachable from the remote-tracking refs until it is merged
+    for branch in ref_reader.list_remote_branches()? {
+        commit_ids.insert(branch.commit_id);
+    }
+    for tag in ref_reader.list_tags()? {
+        commit_ids.insert(tag.commit_id);
+    }
+    for stash in ref_reader.list_stashes()? {
+        commit_ids.insert(stash.commit_id);
+    }
+
+    let hidden_dir = util::fs::oxen_hidden_dir(&repo.path);
+    for file in [MERGE_HEAD_FILE, ORIG_HEAD_FILE, CHERRY_PICK_HEAD_FILE] {
+        let path = hidden_dir.join(file);
+        if path.exists() {
+            commit_ids.insert(util::fs::read_from_path(&path)?.trim().to_string());
+        }
+    }
+    Ok(commit_ids)
+}
+
+pub struct GarbageCollector {
+    repository: LocalRepository,
+    grace_period: Duration,
+}
+
+impl GarbageCollector {
+    pub fn new(repository: &LocalRepository) -> GarbageCollector {
+        GarbageCollector::with_grace_period(
+            repository,
+            Duration::from_secs(DEFAULT_GC_GRACE_PERIOD_SECS),
+        )
+    }
+
+    pub fn with_grace_period(
+        repository: &LocalRepository,
+        grace_period: Duration,
+    ) -> GarbageCollector {
+        GarbageCollector {
+            repository: repository.clone(),
+            grace_period,
+        }
+    }
+
+    /// Sweeps everything unreachable, or with `dry_run` only reports what would be swept
+    pub fn collect(&self, dry_run: bool) -> Result<GCStats, OxenError> {
+        let repo = &self.repository;
+        let reachable = self.reachable_commit_ids()?;
+        let mut stats = GCStats {
+            num_reachable_commits: reachable.len(),
+            ..GCStats::default()
+        };
+
+        // Mark the files and chunks the reachable commits use, the readers work for packed commits too
+        let commit_reader = CommitReader::new(repo)?;
+        let chunk_store = ChunkStore::new(repo);
+        let mut version_dirs: HashSet<PathBuf> = HashSet::new();
+        let mut chunks: HashSet<String> = HashSet::new();
+        for commit_id in reachable.iter() {
+            let commit = match commit_reader.get_commit_by_id(commit_id)? {
+                Some(commit) => commit,
+                None => continue,
+            };
+            // An upload that has not sent the history yet, the reader would create an empty db in its place
+            if !has_history(repo, commit_id) {
+                continue;
+            }
+            for entry in CommitDirReader::new(repo, &commit)?.list_entries()? {
+                if version_dirs.insert(util::fs::version_dir_from_hash(repo, entry.hash.clone())) {
+                    if let Some(manifest) = chunk_store.get_manifest(&entry.hash)? {
+                        chunks.extend(manifest.chunk_hashes());
+                    }
+                }
+            }
+        }
+        drop(commit_reader);
+
+        let removed = self.sweep_commits(&reachable, dry_run, &mut stats)?;
+        self.sweep_version_dirs(&version_dirs, dry_run, &mut stats)?;
+        self.sweep_chunks(&chunks, dry_run, &mut stats)?;
+
+        let history_pack = HistoryPack::new(repo);
+        for path in history_pack.unreferenced_objects(&removed)? {
+            if self.is_recent(&path)? {
+                continue;
+            }
+            stats.num_pack_objects += 1;
+            stats.num_bytes += fs::metadata(&path)?.len();
+            if !dry_run {
+                fs::remove_file(&path)?;
+            }
+        }
+
+        log::debug!("GarbageCollector::collect dry_run {} {:?}", dry_run, stats);
+        Ok(stats)
+    }
+
+    fn reachable_commit_ids(&self) -> Result<HashSet<String>, OxenError> {
+        let commit_reader = CommitReader::new(&self.repository)?;
+        let mut roots = ref_commit_ids(&self.repository)?;
+        for session in UploadSessionStore::new(&self.repository).list()? {
+            roots.extend(session.commit_id);
+        }
+
+        let mut reachable: HashSet<String> = HashSet::new();
+        for commit_id in roots {
+            if reachable.contains(&commit_id) || !commit_reader.commit_id_exists(&commit_id) {
+                continue;
+            }
+            for commit in commit_reader.history_from_commit_id(&commit_id)? {
+                reachable.insert(commit.id);
+            }
+        }
+        Ok(reachable)
+    }
+
+    // Commits in the db and history dirs of commits that never made it into the db
+    fn sweep_commits(
+        &self,
+        reachable: &HashSet<String>,
+        dry_run: bool,
+        stats: &mut GCStats,
+    ) -> Result<HashSet<String>, OxenError> {
+        let repo = &self.repository;
+        let commit_writer = CommitWriter::new(repo)?;
+        let history_pack = HistoryPack::new(repo);
+        let history_dir = util::fs::oxen_hidden_dir(&repo.path).join(HISTORY_DIR);
+
+        let mut commit_ids: HashSet<String> = kv_db::list_keys(&commit_writer.commits_db)?
+            .into_iter()
+            .collect();
+        commit_ids.extend(list_dir_names(&history_dir)?);
+        commit_ids.extend(history_pack.list_packed_commit_ids()?);
+
+        let mut removed: HashSet<String> = HashSet::new();
+        for commit_id in commit_ids {
+            let commit_dir = history_dir.join(&commit_id);
+            // Without any history the commit may have just been created by a push that is about to upload it
+            if reachable.contains(&commit_id)
+                || !has_history(repo, &commit_id)
+                || (commit_dir.exists() && self.is_recent(&commit_dir)?)
+            {
+                continue;
+            }
+
+            log::debug!("GarbageCollector removing commit {}", commit_id);
+            if commit_dir.exists() {
+                stats.num_bytes += dir_size(&commit_dir)?;
+            }
+            stats.num_commits += 1;
+            if !dry_run {
+                kv_db::delete(&commit_writer.commits_db, &commit_id)?;
+                if commit_dir.exists() {
+                    fs::remove_dir_all(&commit_dir)?;
+                }
+                history_pack.remove_commit(&commit_id)?;
+            }
+            removed.insert(commit_id);
+        }
+        Ok(removed)
+    }
+
+    // .oxen/versions/files/HASH[0..2]/HASH[2..]
+    fn sweep_version_dirs(
+        &self,
+        reachable: &HashSet<PathBuf>,
+        dry_run: bool,
+        stats: &mut GCStats,
+    ) -> Result<(), OxenError> {
+        let files_dir = util::fs::oxen_hidden_dir(&self.repository.path)
+            .join(VERSIONS_DIR)
+            .join(FILES_DIR);
+        for prefix in list_dir_names(&files_dir)? {
+            let prefix_dir = files_dir.join(prefix);
+            for suffix in list_dir_names(&prefix_dir)? {
+                let version_dir = prefix_dir.join(suffix);
+                if reachable.contains(&version_dir) || self.is_recent(&version_dir)? {
+                    continue;
+                }
+
+                stats.num_version_dirs += 1;
+                stats.num_bytes += dir_size(&version_dir)?;
+                if !dry_run {
+                    fs::remove_dir_all(&version_dir)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // .oxen/versions/chunks/HASH[0..2]/HASH[2..]
+    fn sweep_chunks(
+        &self,
+        reachable: &HashSet<String>,
+        dry_run: bool,
+        stats: &mut GCStats,
+    ) -> Result<(), OxenError> {
+        let chunk_store = ChunkStore::new(&self.repository);
+        let chunks_dir = ChunkStore::chunks_dir(&self.repository.path);
+        for prefix in list_dir_names(&chunks_dir)? {
+            for suffix in list_dir_names(&chunks_dir.join(&prefix))? {
+                let hash = format!("{prefix}{suffix}");
+                // Skips the tmp files of chunks that are still being written
+                if ChunkStore::validate_hash(&hash).is_err() {
+                    continue;
+                }
+                let chunk_path = chunk_store.chunk_path(&hash)?;
+                if reachable.contains(&hash) || self.is_recent(&chunk_path)? {
+                    continue;
+                }
+
+                stats.num_chunks += 1;
+                if dry_run {
+                    stats.num_bytes += fs::metadata(&chunk_path)?.len();
+                } else {
+                    stats.num_bytes += chunk_store.delete_chunk(&hash)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Whether the path, or anything below it, was written within the grace period
+    fn is_recent(&self, path: &Path) -> Result<bool, OxenError> {
+        if !util::fs::is_older_than(path, self.grace_period)? {
+            return Ok(true);
+        }
+        if path.is_dir() {
+            for entry in fs::read_dir(path)? {
+                if self.is_recent(&entry?.path())? {
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(false)
+    }
+}
+
+fn has_history(repo: &LocalRepository, commit_id: &str) -> bool {
+    util::fs::oxen_hidden_dir(&repo.path)
+        .join(HISTORY_DIR)
+        .join(commit_id)
+        .exists()
+        || HistoryPack::is_packed(repo, commit_id)
+}
+
+fn list_dir_names(dir: &Path) -> Result<Vec<String>, OxenError> {
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut names: Vec<String> = vec![];
+    for entry in fs::read_dir(dir)? {
+        if let Some(name) = entry?.file_name().to_str() {
+            names.push(name.to_string());
+        }
+    }
+    Ok(names)
+}
+
+fn dir_size(dir: &Path) -> Result<u64, OxenError> {
+    fs_extra::dir::get_size(dir).map_err(|err| OxenError::basic_str(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::command;
+    use crate::constants::{DEFAULT_BRANCH_NAME, HISTORY_DIR};
+    use crate::error::OxenError;
+    use crate::index::{CommitReader, GarbageCollector, HistoryPack, UploadSessionStore};
+    use crate::model::UploadSession;
+    use crate::test;
+    use crate::util;
+
+    use std::time::Duration;
+
+    #[test]
+    fn test_gc_removes_force_deleted_branch() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let labels = repo.path.join("labels.csv");
+            util::fs::write_to_path(&labels, "id,label\n1,cat\n")?;
+            command::add(&repo, &labels)?;
+            let main_commit = command::commit(&repo, "Adding labels")?.unwrap();
+
+            // Only the feature branch has this version of the file
+            command::create_checkout_branch(&repo, "feature")?;
+            util::fs::write_to_path(&labels, "id,label\n1,dog\n")?;
+            command::add(&repo, &labels)?;
+            let feature_commit = command::commit(&repo, "Relabel")?.unwrap();
+            command::checkout(&repo, DEFAULT_BRANCH_NAME)?;
+            command::force_delete_branch(&repo, "feature")?;
+
+            let feature_history = util::fs::oxen_hidden_dir(&repo.path)
+                .join(HISTORY_DIR)
+                .join(&feature_commit.id);
+            assert!(feature_history.exists());
+
+            // Everything is too new to collect, it may still be getting pushed
+            let stats = GarbageCollector::new(&repo).collect(false)?;
+            assert_eq!(stats.num_commits, 0);
+            assert_eq!(stats.num_version_dirs, 0);
+            assert!(feature_history.exists());
+
+            let collector = GarbageCollector::with_grace_period(&repo, Duration::ZERO);
+            let dry_run = collector.collect(true)?;
+            assert_eq!(dry_run.num_commits, 1);
+            assert_eq!(dry_run.num_version_dirs, 1);
+            assert!(dry_run.num_bytes > 0);
+            // Dry runs do not touch anything
+            assert!(feature_history.exists());
+
+            let stats = collector.collect(false)?;
+            assert_eq!(stats, dry_run);
+            assert!(!feature_history.exists());
+            let commit_reader = CommitReader::new(&repo)?;
+            assert!(!commit_reader.commit_id_exists(&feature_commit.id));
+            assert!(commit_reader.commit_id_exists(&main_commit.id));
+            drop(commit_reader);
+
+            // The reachable data is still intact
+            command::checkout(&repo, &main_commit.id)?;
+            assert_eq!(util::fs::read_from_path(&labels)?, "id,label\n1,cat\n");
+
+            // Nothing left to collect
+            let stats = collector.collect(false)?;
+            assert_eq!(stats.num_commits, 0);
+            assert_eq!(stats.num_version_dirs, 0);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_gc_removes_unreferenced_pack_objects() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let labels = repo.path.join("labels.csv");
+            util::fs::write_to_path(&labels, "id,label\n1,cat\n")?;
+            command::add(&repo, &labels)?;
+            command::commit(&repo, "Adding labels")?;
+
+            command::create_checkout_branch(&repo, "feature")?;
+            for i in 0..2 {
+                util::fs::write_to_path(&labels, &format!("id,label\n{i},dog\n"))?;
+                command::add(&repo, &labels)?;
+                command::commit(&repo, &format!("Commit {i}"))?;
+            }
+            command::checkout(&repo, DEFAULT_BRANCH_NAME)?;
+            let feature = command::list_branches(&repo)?
+                .into_iter()
+                .find(|branch| branch.name == "feature")
+                .unwrap();
+
+            // Packs the first feature commit, the branch tips are left on disk
+            HistoryPack::with_grace_period(&repo, Duration::ZERO).pack()?;
+            command::force_delete_branch(&repo, "feature")?;
+
+            let stats =
+                GarbageCollector::with_grace_period(&repo, Duration::ZERO).collect(false)?;
+            assert_eq!(stats.num_commits, 2);
+            assert!(stats.num_pack_objects > 0);
+            assert!(!HistoryPack::is_packed(&repo, &feature.commit_id));
+            assert!(HistoryPack::new(&repo).list_packed_commit_ids()?.len() <= 1);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_gc_keeps_commits_of_unfinished_uploads() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let labels = repo.path.join("labels.csv");
+            util::fs::write_to_path(&labels, "id,label\n1,cat\n")?;
+            command::add(&repo, &labels)?;
+            command::commit(&repo, "Adding labels")?;
+
+            // Stands in for a commit that was pushed but the branch has not been updated yet
+            command::create_checkout_branch(&repo, "feature")?;
+            util::fs::write_to_path(&labels, "id,label\n1,dog\n")?;
+            command::add(&repo, &labels)?;
+            let pushed = command::commit(&repo, "Relabel")?.unwrap();
+            command::checkout(&repo, DEFAULT_BRANCH_NAME)?;
+            command::force_delete_branch(&repo, "feature")?;
+
+            let store = UploadSessionStore::new(&repo);
+            let upload = UploadSession::new("abc", &pushed.id, None, 2, 4);
+            store.put_chunk(&upload, 0, b"ab")?;
+
+            let collector = GarbageCollector::with_grace_period(&repo, Duration::ZERO);
+            let stats = collector.collect(false)?;
+            assert_eq!(stats.num_commits, 0);
+            assert_eq!(stats.num_version_dirs, 0);
+
+            // Once the upload is gone so is the commit
+            store.remove("abc")?;
+            let stats = collector.collect(false)?;
+            assert_eq!(stats.num_commits, 1);
+            assert_eq!(stats.num_version_dirs, 1);
+
+            Ok(())
+        })
+    }
+}
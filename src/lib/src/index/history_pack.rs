@@ -0,0 +1,658 @@
+//! # HistoryPack
+//!
+//! Folds the per commit RocksDB directories in `.oxen/history/COMMIT_ID/` into a shared content addressed store.
+//!
+//! Each db of a packed commit is dumped to a gzipped object in `.oxen/packs/objects/` named by the hash of its
+//! contents, so the many identical dbs that get copied from commit to commit are stored once. A manifest in
+//! `.oxen/packs/commits/COMMIT_ID` maps each db path to its object. Readers load packed dbs into an in memory
+//! RocksDB, so they work the same with both layouts. Decoded objects are cached by hash, so reopening the same
+//! db does not read and unzip the object again.
+//!
+//! Branch heads, HEAD and any in progress merge are never packed, since new commits copy their parent's dbs.
+//! Neither are commits written within the grace period, which may still be in the middle of a push. Once a db
+//! is in the manifest readers use the pack, but another process may have just opened the files on disk, so
+//! they are only removed by a later pack after the manifest is older than the grace period.
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use lazy_static::lazy_static;
+use rocksdb::{DBWithThreadMode, Env, IteratorMode, MultiThreaded, WriteBatch};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::convert::TryInto;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::constants::{
+    CACHE_DIR, DEFAULT_GC_GRACE_PERIOD_SECS, HISTORY_DIR, PACKS_DIR, PACK_COMMITS_DIR,
+    PACK_OBJECTS_DIR,
+};
+use crate::db;
+use crate::error::OxenError;
+use crate::index::garbage_collector;
+use crate::model::LocalRepository;
+use crate::util;
+
+// Every RocksDB dir has a CURRENT file pointing at its manifest
+const ROCKSDB_CURRENT_FILE: &str = "CURRENT";
+// Most readers open the dirs db of a handful of commits over and over, so a small cache goes a long way
+const OBJECT_CACHE_SIZE: usize = 256;
+
+type KeyVal = (Vec<u8>, Vec<u8>);
+
+lazy_static! {
+    // Objects are content addressed, so a cached object never goes stale
+    static ref OBJECT_CACHE: Mutex<HashMap<String, Arc<Vec<KeyVal>>>> = Mutex::new(HashMap::new());
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PackStats {
+    pub num_commits: usize,
+    pub num_dbs: usize,
+    pub num_new_objects: usize,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+pub struct HistoryPack {
+    repository: LocalRepository,
+    grace_period: Duration,
+}
+
+impl HistoryPack {
+    pub fn new(repository: &LocalRepository) -> HistoryPack {
+        HistoryPack::with_grace_period(
+            repository,
+            Duration::from_secs(DEFAULT_GC_GRACE_PERIOD_SECS),
+        )
+    }
+
+    pub fn with_grace_period(repository: &LocalRepository, grace_period: Duration) -> HistoryPack {
+        HistoryPack {
+            repository: repository.clone(),
+            grace_period,
+        }
+    }
+
+    pub fn packs_dir(repo: &LocalRepository) -> PathBuf {
+        // .oxen/packs
+        util::fs::oxen_hidden_dir(&repo.path).join(PACKS_DIR)
+    }
+
+    fn commit_dir(repo: &LocalRepository, commit_id: &str) -> PathBuf {
+        util::fs::oxen_hidden_dir(&repo.path)
+            .join(HISTORY_DIR)
+            .join(commit_id)
+    }
+
+    fn manifest_path(repo: &LocalRepository, commit_id: &str) -> PathBuf {
+        // .oxen/packs/commits/COMMIT_ID
+        HistoryPack::packs_dir(repo)
+            .join(PACK_COMMITS_DIR)
+            .join(commit_id)
+    }
+
+    fn object_path(repo: &LocalRepository, hash: &str) -> PathBuf {
+        // .oxen/packs/objects/HASH[0..2]/HASH[2..]
+        let (prefix, suffix) = hash.split_at(2);
+        HistoryPack::packs_dir(repo)
+            .join(PACK_OBJECTS_DIR)
+            .join(prefix)
+            .join(suffix)
+    }
+
+    pub fn is_packed(repo: &LocalRepository, commit_id: &str) -> bool {
+        HistoryPack::manifest_path(repo, commit_id).exists()
+    }
+
+    fn read_manifest(
+        repo: &LocalRepository,
+        commit_id: &str,
+    ) -> Result<BTreeMap<String, String>, OxenError> {
+        let contents = util::fs::read_from_path(&HistoryPack::manifest_path(repo, commit_id))?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    // Path of the db relative to the commit dir, which is how the manifest refers to it
+    fn relative_db_path(
+        repo: &LocalRepository,
+        commit_id: &str,
+        db_path: &Path,
+    ) -> Result<String, OxenError> {
+        let commit_dir = HistoryPack::commit_dir(repo, commit_id);
+        let relative = util::fs::path_relative_to_dir(db_path, &commit_dir)?;
+        Ok(relative.to_string_lossy().trim_end_matches('/').to_string())
+    }
+
+    /// Whether the commit is packed and the manifest has a db at `db_path`
+    pub fn has_packed_db(
+        repo: &LocalRepository,
+        commit_id: &str,
+        db_path: &Path,
+    ) -> Result<bool, OxenError> {
+        if !HistoryPack::is_packed(repo, commit_id) {
+            return Ok(false);
+        }
+        let manifest = HistoryPack::read_manifest(repo, commit_id)?;
+        let relative = HistoryPack::relative_db_path(repo, commit_id, db_path)?;
+        Ok(manifest.contains_key(&relative))
+    }
+
+    /// Opens the packed db at `db_path` in memory. Returns None if the commit is not packed or the db was only
+    /// written to disk after packing, and an empty db if the commit is packed but never had a db at that path.
+    pub fn open_db(
+        repo: &LocalRepository,
+        commit_id: &str,
+        db_path: &Path,
+    ) -> Result<Option<DBWithThreadMode<MultiThreaded>>, OxenError> {
+        if !HistoryPack::is_packed(repo, commit_id) {
+            return Ok(None);
+        }
+
+        let manifest = HistoryPack::read_manifest(repo, commit_id)?;
+        let relative = HistoryPack::relative_db_path(repo, commit_id, db_path)?;
+        let hash = manifest.get(&relative);
+        if hash.is_none() && db_path.join(ROCKSDB_CURRENT_FILE).exists() {
+            return Ok(None);
+        }
+        log::debug!("HistoryPack::open_db {} {:?}", commit_id, relative);
+
+        let env = Env::mem_env()?;
+        let mut opts = db::opts::default();
+        opts.set_env(&env);
+        let db: DBWithThreadMode<MultiThreaded> = DBWithThreadMode::open(&opts, db_path)?;
+        if let Some(hash) = hash {
+            let mut batch = WriteBatch::default();
+            for (key, value) in HistoryPack::cached_object(repo, hash)?.iter() {
+                batch.put(key, value);
+            }
+            db.write(batch)?;
+        }
+        Ok(Some(db))
+    }
+
+    /// Packs the history of every commit that is not a branch head, HEAD, part of a merge or written within
+    /// the grace period
+    pub fn pack(&self) -> Result<PackStats, OxenError> {
+        let keep = garbage_collector::ref_commit_ids(&self.repository)?;
+        let history_dir = util::fs::oxen_hidden_dir(&self.repository.path).join(HISTORY_DIR);
+
+        let mut stats = PackStats::default();
+        if !history_dir.exists() {
+            return Ok(stats);
+        }
+
+        for entry in fs::read_dir(&history_dir)? {
+            let path = entry?.path();
+            let commit_id = match path.file_name().and_then(|name| name.to_str()) {
+                Some(commit_id) => commit_id.to_string(),
+                None => continue,
+            };
+            if !path.is_dir() || keep.contains(&commit_id) || self.is_recent(&path)? {
+                continue;
+            }
+
+            let commit_stats = self.pack_commit(&commit_id)?;
+            stats.num_commits += commit_stats.num_commits;
+            stats.num_dbs += commit_stats.num_dbs;
+            stats.num_new_objects += commit_stats.num_new_objects;
+            stats.bytes_before += commit_stats.bytes_before;
+            stats.bytes_after += commit_stats.bytes_after;
+        }
+
+        Ok(stats)
+    }
+
+    /// Moves the dbs of a commit into the pack, leaving the cache and any other files in place. The files of dbs
+    /// that were already packed are removed once the manifest is older than the grace period.
+    pub fn pack_commit(&self, commit_id: &str) -> Result<PackStats, OxenError> {
+        let repo = &self.repository;
+        let commit_dir = HistoryPack::commit_dir(repo, commit_id);
+        let manifest_path = HistoryPack::manifest_path(repo, commit_id);
+        let mut stats = PackStats::default();
+
+        let mut manifest = if HistoryPack::is_packed(repo, commit_id) {
+            HistoryPack::read_manifest(repo, commit_id)?
+        } else {
+            BTreeMap::new()
+        };
+
+        let mut db_paths: Vec<PathBuf> = vec![];
+        find_db_dirs(&commit_dir, &mut db_paths)?;
+        db_paths.retain(|path| !path.starts_with(commit_dir.join(CACHE_DIR)));
+
+        // Left on disk by an earlier pack, readers have been using the pack for a while now
+        let mut packed_paths: Vec<PathBuf> = vec![];
+        for db_path in db_paths.iter() {
+            if manifest.contains_key(&HistoryPack::relative_db_path(repo, commit_id, db_path)?) {
+                packed_paths.push(db_path.to_owned());
+            }
+        }
+        db_paths.retain(|path| !packed_paths.contains(path));
+        if !packed_paths.is_empty() && util::fs::is_older_than(&manifest_path, self.grace_period)? {
+            for db_path in packed_paths.iter() {
+                remove_db_files(db_path)?;
+            }
+            remove_empty_dirs(&commit_dir)?;
+        }
+        if db_paths.is_empty() {
+            return Ok(stats);
+        }
+
+        stats.num_commits = 1;
+        for db_path in db_paths.iter() {
+            stats.bytes_before += db_files_size(db_path)?;
+
+            let pairs = {
+                let opts = db::opts::default();
+                let db: DBWithThreadMode<MultiThreaded> =
+                    DBWithThreadMode::open_for_read_only(&opts, db_path, false)?;
+                db.iterator(IteratorMode::Start)
+                    .map(|(key, value)| (key.to_vec(), value.to_vec()))
+                    .collect::<Vec<KeyVal>>()
+            };
+
+            let (hash, is_new) = self.write_object(&pairs)?;
+            if is_new {
+                stats.num_new_objects += 1;
+                stats.bytes_after += fs::metadata(HistoryPack::object_path(repo, &hash))?.len();
+            }
+            manifest.insert(
+                HistoryPack::relative_db_path(repo, commit_id, db_path)?,
+                hash,
+            );
+            stats.num_dbs += 1;
+        }
+
+        // Write the manifest before removing anything, so the commit is never left without its dbs
+        fs::create_dir_all(manifest_path.parent().unwrap())?;
+        util::fs::write_to_path(&manifest_path, &serde_json::to_string(&manifest)?)?;
+
+        if self.grace_period.is_zero() {
+            for db_path in db_paths.iter() {
+                remove_db_files(db_path)?;
+            }
+            remove_empty_dirs(&commit_dir)?;
+        }
+
+        log::debug!("HistoryPack::pack_commit {} {:?}", commit_id, stats);
+        Ok(stats)
+    }
+
+    /// Writes the dbs of a packed commit back to disk, for when they need to be copied or synced
+    pub fn unpack_commit(&self, commit_id: &str) -> Result<(), OxenError> {
+        let repo = &self.repository;
+        if !HistoryPack::is_packed(repo, commit_id) {
+            return Ok(());
+        }
+
+        let commit_dir = HistoryPack::commit_dir(repo, commit_id);
+        for (relative, hash) in HistoryPack::read_manifest(repo, commit_id)? {
+            let db_path = commit_dir.join(relative);
+            fs::create_dir_all(&db_path)?;
+            let opts = db::opts::default();
+            let db: DBWithThreadMode<MultiThreaded> = DBWithThreadMode::open(&opts, &db_path)?;
+            for (key, value) in HistoryPack::read_object(repo, &hash)? {
+                db.put(key, value)?;
+            }
+        }
+
+        fs::remove_file(HistoryPack::manifest_path(repo, commit_id))?;
+        Ok(())
+    }
+
+    pub fn list_packed_commit_ids(&self) -> Result<Vec<String>, OxenError> {
+        let commits_dir = HistoryPack::packs_dir(&self.repository).join(PACK_COMMITS_DIR);
+        if !commits_dir.exists() {
+            return Ok(vec![]);
+        }
+
+        let mut commit_ids: Vec<String> = vec![];
+        for entry in fs::read_dir(&commits_dir)? {
+            if let Some(commit_id) = entry?.file_name().to_str() {
+                commit_ids.push(commit_id.to_string());
+            }
+        }
+        Ok(commit_ids)
+    }
+
+    /// Drops the manifest of a commit, its objects are left for `unreferenced_objects` to find
+    pub fn remove_commit(&self, commit_id: &str) -> Result<(), OxenError> {
+        let path = HistoryPack::manifest_path(&self.repository, commit_id);
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    /// Objects that no manifest refers to, ignoring the manifests of the `removed` commits
+    pub fn unreferenced_objects(
+        &self,
+        removed: &HashSet<String>,
+    ) -> Result<Vec<PathBuf>, OxenError> {
+        let objects_dir = HistoryPack::packs_dir(&self.repository).join(PACK_OBJECTS_DIR);
+        if !objects_dir.exists() {
+            return Ok(vec![]);
+        }
+
+        let mut referenced: HashSet<PathBuf> = HashSet::new();
+        for commit_id in self.list_packed_commit_ids()? {
+            if removed.contains(&commit_id) {
+                continue;
+            }
+            for hash in HistoryPack::read_manifest(&self.repository, &commit_id)?.values() {
+                referenced.insert(HistoryPack::object_path(&self.repository, hash));
+            }
+        }
+
+        let mut objects: Vec<PathBuf> = vec![];
+        for prefix in fs::read_dir(&objects_dir)? {
+            for object in fs::read_dir(prefix?.path())? {
+                let path = object?.path();
+                if !referenced.contains(&path) {
+                    objects.push(path);
+                }
+            }
+        }
+        Ok(objects)
+    }
+
+    // Objects are u32 length prefixed keys and values, in key order so equal dbs hash the same
+    fn write_object(&self, pairs: &[KeyVal]) -> Result<(String, bool), OxenError> {
+        let mut buffer: Vec<u8> = vec![];
+        for (key, value) in pairs {
+            buffer.extend_from_slice(&(key.len() as u32).to_le_bytes());
+            buffer.extend_from_slice(key);
+            buffer.extend_from_slice(&(value.len() as u32).to_le_bytes());
+            buffer.extend_from_slice(value);
+        }
+
+        let hash = util::hasher::hash_buffer(&buffer);
+        let path = HistoryPack::object_path(&self.repository, &hash);
+        if path.exists() {
+            return Ok((hash, false));
+        }
+
+        fs::create_dir_all(path.parent().unwrap())?;
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&buffer)?;
+        fs::write(&path, encoder.finish()?)?;
+        Ok((hash, true))
+    }
+
+    // Whether any db of the commit was written within the grace period
+    fn is_recent(&self, commit_dir: &Path) -> Result<bool, OxenError> {
+        let mut db_paths: Vec<PathBuf> = vec![];
+        find_db_dirs(commit_dir, &mut db_paths)?;
+        for db_path in db_paths {
+            if !util::fs::is_older_than(&db_path, self.grace_period)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn cached_object(repo: &LocalRepository, hash: &str) -> Result<Arc<Vec<KeyVal>>, OxenError> {
+        if let Some(pairs) = OBJECT_CACHE.lock().unwrap().get(hash) {
+            return Ok(pairs.clone());
+        }
+
+        let pairs = Arc::new(HistoryPack::read_object(repo, hash)?);
+        let mut cache = OBJECT_CACHE.lock().unwrap();
+        if cache.len() >= OBJECT_CACHE_SIZE {
+            cache.clear();
+        }
+        cache.insert(hash.to_string(), pairs.clone());
+        Ok(pairs)
+    }
+
+    fn read_object(repo: &LocalRepository, hash: &str) -> Result<Vec<KeyVal>, OxenError> {
+        let path = HistoryPack::object_path(repo, hash);
+        let mut decoder = GzDecoder::new(fs::File::open(&path)?);
+        let mut buffer: Vec<u8> = vec![];
+        decoder.read_to_end(&mut buffer)?;
+
+        let corrupt = || OxenError::basic_str(format!("Corrupt pack object {path:?}"));
+        let mut pairs: Vec<KeyVal> = vec![];
+        let mut i = 0;
+        while i < buffer.len() {
+            let key = read_prefixed(&buffer, &mut i).ok_or_else(corrupt)?;
+            let value = read_prefixed(&buffer, &mut i).ok_or_else(corrupt)?;
+            pairs.push((key, value));
+        }
+        Ok(pairs)
+    }
+}
+
+fn read_prefixed(buffer: &[u8], i: &mut usize) -> Option<Vec<u8>> {
+    let len_bytes: [u8; 4] = buffer.get(*i..*i + 4)?.try_into().ok()?;
+    let len = u32::from_le_bytes(len_bytes) as usize;
+    let start = *i + 4;
+    let value = buffer.get(start..start + len)?.to_vec();
+    *i = start + len;
+    Some(value)
+}
+
+// Dbs can be nested, files/ is the db for the root dir and files/sub/ the db for sub/
+fn find_db_dirs(dir: &Path, db_paths: &mut Vec<PathBuf>) -> Result<(), OxenError> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    if dir.join(ROCKSDB_CURRENT_FILE).exists() {
+        db_paths.push(dir.to_path_buf());
+    }
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            find_db_dirs(&path, db_paths)?;
+        }
+    }
+    Ok(())
+}
+
+fn db_files_size(db_path: &Path) -> Result<u64, OxenError> {
+    let mut size = 0;
+    for entry in fs::read_dir(db_path)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            size += entry.metadata()?.len();
+        }
+    }
+    Ok(size)
+}
+
+// Only the files that belong to this db, nested dbs live in subdirs
+fn remove_db_files(db_path: &Path) -> Result<(), OxenError> {
+    for entry in fs::read_dir(db_path)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            fs::remove_file(entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+fn remove_empty_dirs(dir: &Path) -> Result<bool, OxenError> {
+    let mut is_empty = true;
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if !path.is_dir() || !remove_empty_dirs(&path)? {
+            is_empty = false;
+        }
+    }
+    if is_empty {
+        fs::remove_dir(dir)?;
+    }
+    Ok(is_empty)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api;
+    use crate::command;
+    use crate::constants::{DIRS_DIR, HISTORY_DIR};
+    use crate::error::OxenError;
+    use crate::index::{CommitDirReader, HistoryPack, SchemaReader};
+    use crate::test;
+    use crate::util;
+
+    use std::path::Path;
+    use std::time::Duration;
+
+    #[test]
+    fn test_pack_old_commits_readers_work_on_both_layouts() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let labels = repo.path.join("labels.csv");
+            let nested = repo.path.join("images").join("train");
+            std::fs::create_dir_all(&nested)?;
+            util::fs::write_to_path(&labels, "id,label\n1,cat\n")?;
+            util::fs::write_to_path(&nested.join("a.txt"), "a")?;
+            command::add(&repo, &repo.path)?;
+            let first = command::commit(&repo, "Adding data")?.unwrap();
+
+            util::fs::write_to_path(&labels, "id,label\n1,cat\n2,dog\n")?;
+            command::add(&repo, &labels)?;
+            let second = command::commit(&repo, "More labels")?.unwrap();
+
+            let before = CommitDirReader::new(&repo, &first)?.list_entries()?;
+            let schemas_before = SchemaReader::new(&repo, &first.id)?.list_schemas()?;
+            assert_eq!(schemas_before.len(), 1);
+
+            let stats = HistoryPack::with_grace_period(&repo, Duration::ZERO).pack()?;
+            // HEAD is the second commit, so the initial commit and the first get packed
+            assert_eq!(stats.num_commits, 2);
+            assert!(stats.num_dbs > 0);
+            assert!(HistoryPack::is_packed(&repo, &first.id));
+            assert!(!HistoryPack::is_packed(&repo, &second.id));
+
+            let after = CommitDirReader::new(&repo, &first)?.list_entries()?;
+            assert_eq!(before.len(), 2);
+            assert_eq!(before, after);
+            let schemas_after = SchemaReader::new(&repo, &first.id)?.list_schemas()?;
+            assert_eq!(schemas_before[0].hash, schemas_after[0].hash);
+
+            let entry = api::local::entries::get_entry_for_commit(
+                &repo,
+                &first,
+                Path::new("images/train/a.txt"),
+            )?;
+            assert!(entry.is_some());
+
+            // Packing again is a no-op and unpacking restores the on disk layout
+            assert_eq!(
+                HistoryPack::with_grace_period(&repo, Duration::ZERO)
+                    .pack()?
+                    .num_commits,
+                0
+            );
+            HistoryPack::new(&repo).unpack_commit(&first.id)?;
+            assert!(!HistoryPack::is_packed(&repo, &first.id));
+            let unpacked = CommitDirReader::new(&repo, &first)?.list_entries()?;
+            assert_eq!(before, unpacked);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_pack_dedupes_identical_dbs() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let labels = repo.path.join("labels.csv");
+            let other = repo.path.join("other").join("a.txt");
+            std::fs::create_dir_all(other.parent().unwrap())?;
+            util::fs::write_to_path(&labels, "id,label\n1,cat\n")?;
+            util::fs::write_to_path(&other, "a")?;
+            command::add(&repo, &repo.path)?;
+            command::commit(&repo, "Adding data")?;
+
+            // Only the root dir changes, other/ is the same db in every commit
+            for i in 0..3 {
+                util::fs::write_to_path(&labels, &format!("id,label\n1,cat\n{i},dog\n"))?;
+                command::add(&repo, &labels)?;
+                command::commit(&repo, &format!("Commit {i}"))?;
+            }
+
+            let stats = HistoryPack::with_grace_period(&repo, Duration::ZERO).pack()?;
+            assert_eq!(stats.num_commits, 4);
+            assert!(stats.num_new_objects < stats.num_dbs);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_commit_on_top_of_packed_commit() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let labels = repo.path.join("labels.csv");
+            util::fs::write_to_path(&labels, "id,label\n1,cat\n")?;
+            command::add(&repo, &labels)?;
+            let first = command::commit(&repo, "Adding labels")?.unwrap();
+
+            util::fs::write_to_path(&labels, "id,label\n1,dog\n")?;
+            command::add(&repo, &labels)?;
+            command::commit(&repo, "Relabel")?;
+
+            HistoryPack::with_grace_period(&repo, Duration::ZERO).pack()?;
+            assert!(HistoryPack::is_packed(&repo, &first.id));
+
+            // Checking out restores files from the packed dbs
+            command::checkout(&repo, &first.id)?;
+            assert_eq!(util::fs::read_from_path(&labels)?, "id,label\n1,cat\n");
+
+            // and the next commit copies them from the pack
+            util::fs::write_to_path(&labels, "id,label\n1,fish\n")?;
+            command::add(&repo, &labels)?;
+            let commit = command::commit(&repo, "Fish")?.unwrap();
+            let entries = CommitDirReader::new(&repo, &commit)?.list_entries()?;
+            assert_eq!(entries.len(), 1);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_pack_waits_out_the_grace_period() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let labels = repo.path.join("labels.csv");
+            util::fs::write_to_path(&labels, "id,label\n1,cat\n")?;
+            command::add(&repo, &labels)?;
+            let first = command::commit(&repo, "Adding labels")?.unwrap();
+
+            util::fs::write_to_path(&labels, "id,label\n1,dog\n")?;
+            command::add(&repo, &labels)?;
+            command::commit(&repo, "Relabel")?;
+
+            // Too new, it may still be getting pushed
+            assert_eq!(HistoryPack::new(&repo).pack()?.num_commits, 0);
+            assert!(!HistoryPack::is_packed(&repo, &first.id));
+
+            let grace_period = Duration::from_millis(1500);
+            std::thread::sleep(grace_period);
+            let history_pack = HistoryPack::with_grace_period(&repo, grace_period);
+            assert!(history_pack.pack()?.num_commits > 0);
+            assert!(HistoryPack::is_packed(&repo, &first.id));
+
+            // The dbs stay on disk for readers that already opened them, but new readers use the pack
+            let dirs_db = util::fs::oxen_hidden_dir(&repo.path)
+                .join(HISTORY_DIR)
+                .join(&first.id)
+                .join(DIRS_DIR);
+            assert!(dirs_db.exists());
+            let entries = CommitDirReader::new(&repo, &first)?.list_entries()?;
+            assert_eq!(entries.len(), 1);
+
+            std::thread::sleep(grace_period);
+            assert_eq!(history_pack.pack()?.num_commits, 0);
+            assert!(!dirs_db.exists());
+            assert_eq!(
+                CommitDirReader::new(&repo, &first)?.list_entries()?,
+                entries
+            );
+
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,280 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::error::OxenError;
+use crate::index::{CommitDirEntryReader, CommitDirReader, CommitReader};
+use crate::model::{Commit, LocalRepository};
+use crate::opts::LogOpts;
+use crate::util;
+
+/// Keeps the commits that match all of the filters in `opts`, in the order they were given, up to `max_count`.
+/// With a path, a commit is only kept if the hash of that file, or of everything under that directory, differs
+/// from each of its parents. The root commit is kept if the path exists in it.
+pub fn filter_commits(
+    repo: &LocalRepository,
+    commits: Vec<Commit>,
+    opts: &LogOpts,
+) -> Result<Vec<Commit>, OxenError> {
+    let path = opts.path.as_ref().map(|p| normalize_path(p));
+    let commit_reader = CommitReader::new(repo)?;
+    // Each commit is usually looked up twice, once itself and once as a parent
+    let mut hashes: HashMap<String, Option<String>> = HashMap::new();
+
+    let mut filtered: Vec<Commit> = vec![];
+    for commit in commits {
+        if opts.max_count.is_some_and(|n| filtered.len() >= n) {
+            break;
+        }
+        if !matches_metadata(&commit, opts) {
+            continue;
+        }
+
+        if let Some(path) = &path {
+            let hash = cached_path_hash(repo, &mut hashes, &commit, path)?;
+            let mut changed = hash.is_some() || !commit.parent_ids.is_empty();
+            for parent_id in commit.parent_ids.iter() {
+                let parent = commit_reader
+                    .get_commit_by_id(parent_id)?
+                    .ok_or_else(|| OxenError::commit_db_corrupted(parent_id))?;
+                if cached_path_hash(repo, &mut hashes, &parent, path)? == hash {
+                    changed = false;
+                    break;
+                }
+            }
+            if !changed {
+                continue;
+            }
+        }
+
+        filtered.push(commit);
+    }
+    Ok(filtered)
+}
+
+/// The hash of a file in the commit, or a hash over the paths and hashes of all the files under a directory.
+/// None if there is nothing at the path.
+pub fn path_hash(
+    repo: &LocalRepository,
+    commit: &Commit,
+    path: &Path,
+) -> Result<Option<String>, OxenError> {
+    let dir_reader = CommitDirReader::new(repo, commit)?;
+
+    // A file, looked up in the entries of its parent dir
+    if let (Some(parent), Some(file_name)) = (path.parent(), path.file_name()) {
+        if dir_reader.has_dir(parent) {
+            let entry_reader = CommitDirEntryReader::new(repo, &commit.id, parent)?;
+            if let Some(entry) = entry_reader.get_entry(file_name)? {
+                return Ok(Some(entry.hash));
+            }
+        }
+    }
+
+    // Otherwise a directory, walk the entries of it and each dir below it
+    let mut dirs: Vec<PathBuf> = dir_reader
+        .list_committed_dirs()?
+        .into_iter()
+        .filter(|dir| dir.starts_with(path))
+        .collect();
+    dirs.sort();
+
+    let mut buffer = String::new();
+    for dir in dirs {
+        let entry_reader = CommitDirEntryReader::new(repo, &commit.id, &dir)?;
+        let mut entries = entry_reader.list_entries()?;
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        for entry in entries {
+            buffer.push_str(&format!(
+                "{}:{}\n",
+                entry.path.to_string_lossy(),
+                entry.hash
+            ));
+        }
+    }
+
+    if buffer.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(util::hasher::hash_str(buffer)))
+    }
+}
+
+fn cached_path_hash(
+    repo: &LocalRepository,
+    hashes: &mut HashMap<String, Option<String>>,
+    commit: &Commit,
+    path: &Path,
+) -> Result<Option<String>, OxenError> {
+    if let Some(hash) = hashes.get(&commit.id) {
+        return Ok(hash.to_owned());
+    }
+    let hash = path_hash(repo, commit, path)?;
+    hashes.insert(commit.id.to_owned(), hash.to_owned());
+    Ok(hash)
+}
+
+fn matches_metadata(commit: &Commit, opts: &LogOpts) -> bool {
+    if let Some(author) = &opts.author {
+        if !commit.author.contains(author.as_str()) && !commit.email.contains(author.as_str()) {
+            return false;
+        }
+    }
+    if let Some(grep) = &opts.grep {
+        if !commit.message.contains(grep.as_str()) {
+            return false;
+        }
+    }
+    if opts.since.is_some_and(|since| commit.timestamp < since) {
+        return false;
+    }
+    if opts.until.is_some_and(|until| commit.timestamp > until) {
+        return false;
+    }
+    true
+}
+
+// Paths are relative to the repo, so `./data/` and `data` are the same and `.` is everything
+fn normalize_path(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|c| !matches!(c, std::path::Component::CurDir))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::command;
+    use crate::error::OxenError;
+    use crate::opts::LogOpts;
+    use crate::test;
+    use crate::util;
+
+    #[test]
+    fn test_log_path_only_lists_commits_that_changed_it() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let train_dir = repo.path.join("train");
+            std::fs::create_dir_all(&train_dir)?;
+            let labels_file = train_dir.join("labels.csv");
+            util::fs::write_to_path(&labels_file, "file,label\na.jpg,cat\n")?;
+            command::add(&repo, &labels_file)?;
+            let first = command::commit(&repo, "Adding labels")?.unwrap();
+
+            let readme_file = repo.path.join("README.md");
+            util::fs::write_to_path(&readme_file, "Readme")?;
+            command::add(&repo, &readme_file)?;
+            command::commit(&repo, "Adding readme")?;
+
+            util::fs::write_to_path(&labels_file, "file,label\na.jpg,cat\nb.jpg,dog\n")?;
+            command::add(&repo, &labels_file)?;
+            let third = command::commit(&repo, "Adding dog")?.unwrap();
+
+            let opts = LogOpts {
+                path: Some(PathBuf::from("train/labels.csv")),
+                ..LogOpts::default()
+            };
+            let ids: Vec<String> = command::log_with_opts(&repo, &opts)?
+                .into_iter()
+                .map(|c| c.id)
+                .collect();
+            assert_eq!(ids, vec![third.id.to_owned(), first.id.to_owned()]);
+
+            // Directories match anything changed below them
+            let opts = LogOpts {
+                path: Some(PathBuf::from("./train")),
+                ..LogOpts::default()
+            };
+            assert_eq!(command::log_with_opts(&repo, &opts)?.len(), 2);
+
+            let opts = LogOpts {
+                path: Some(PathBuf::from("train/labels.csv")),
+                max_count: Some(1),
+                ..LogOpts::default()
+            };
+            let commits = command::log_with_opts(&repo, &opts)?;
+            assert_eq!(commits.len(), 1);
+            assert_eq!(commits[0].id, third.id);
+
+            let opts = LogOpts {
+                path: Some(PathBuf::from("missing.txt")),
+                ..LogOpts::default()
+            };
+            assert!(command::log_with_opts(&repo, &opts)?.is_empty());
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_log_path_sees_removed_file() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let hello_file = repo.path.join("hello.txt");
+            util::fs::write_to_path(&hello_file, "Hello")?;
+            command::add(&repo, &hello_file)?;
+            command::commit(&repo, "Adding hello")?;
+
+            std::fs::remove_file(&hello_file)?;
+            command::add(&repo, &hello_file)?;
+            let removed = command::commit(&repo, "Removing hello")?.unwrap();
+
+            let opts = LogOpts {
+                path: Some(PathBuf::from("hello.txt")),
+                ..LogOpts::default()
+            };
+            let commits = command::log_with_opts(&repo, &opts)?;
+            assert_eq!(commits.len(), 2);
+            assert_eq!(commits[0].id, removed.id);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_log_filter_message_author_and_dates() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let hello_file = repo.path.join("hello.txt");
+            util::fs::write_to_path(&hello_file, "Hello")?;
+            command::add(&repo, &hello_file)?;
+            command::commit(&repo, "Adding hello")?;
+
+            util::fs::write_to_path(&hello_file, "Hello World")?;
+            command::add(&repo, &hello_file)?;
+            let commit = command::commit(&repo, "Fixing hello")?.unwrap();
+
+            let opts = LogOpts {
+                grep: Some(String::from("Fixing")),
+                ..LogOpts::default()
+            };
+            let commits = command::log_with_opts(&repo, &opts)?;
+            assert_eq!(commits.len(), 1);
+            assert_eq!(commits[0].id, commit.id);
+
+            let opts = LogOpts {
+                author: Some(commit.author.to_owned()),
+                ..LogOpts::default()
+            };
+            // Plus the initial commit
+            assert_eq!(command::log_with_opts(&repo, &opts)?.len(), 3);
+            let opts = LogOpts {
+                author: Some(String::from("nobody-wrote-this")),
+                ..LogOpts::default()
+            };
+            assert!(command::log_with_opts(&repo, &opts)?.is_empty());
+
+            let opts = LogOpts {
+                since: Some(LogOpts::parse_since("2000-01-01")?),
+                until: Some(LogOpts::parse_until("2000-12-31")?),
+                ..LogOpts::default()
+            };
+            assert!(command::log_with_opts(&repo, &opts)?.is_empty());
+            let opts = LogOpts {
+                since: Some(LogOpts::parse_since("2000-01-01T00:00:00Z")?),
+                ..LogOpts::default()
+            };
+            assert_eq!(command::log_with_opts(&repo, &opts)?.len(), 3);
+            assert!(LogOpts::parse_since("last tuesday").is_err());
+
+            Ok(())
+        })
+    }
+}
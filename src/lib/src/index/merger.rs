@@ -1,17 +1,19 @@
 use crate::command;
-use crate::constants::{MERGE_DIR, MERGE_HEAD_FILE, ORIG_HEAD_FILE};
+use crate::constants::{CHERRY_PICK_HEAD_FILE, MERGE_DIR, MERGE_HEAD_FILE, ORIG_HEAD_FILE};
 use crate::db;
+use crate::df::{merge, tabular, DFOpts};
 use crate::error::OxenError;
 use crate::index::{
-    CommitDirReader, CommitReader, CommitWriter, MergeConflictDBReader, RefReader, RefWriter,
-    Stager,
+    ChunkStore, CommitDirReader, CommitReader, CommitWriter, MergeConflictDBReader, RefReader,
+    RefWriter, Stager,
 };
 use crate::model::{Commit, CommitEntry, LocalRepository, MergeConflict};
 
 use crate::util;
 
 use rocksdb::DB;
-use std::path::Path;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use std::str;
 
 use super::restore;
@@ -32,6 +34,8 @@ impl MergeCommits {
 pub struct Merger {
     repository: LocalRepository,
     merge_db: DB,
+    // Column used to line up rows when merging tabular files, falls back to the row hash
+    primary_key: Option<String>,
 }
 
 impl Merger {
@@ -42,9 +46,21 @@ impl Merger {
         Ok(Merger {
             repository: repo.to_owned(),
             merge_db: DB::open(&opts, &db_path)?,
+            primary_key: None,
         })
     }
 
+    /// Merger that matches up rows of tabular files by the value in the `primary_key` column,
+    /// so that rows edited on only one side can be merged
+    pub fn new_with_primary_key<S: AsRef<str>>(
+        repo: &LocalRepository,
+        primary_key: S,
+    ) -> Result<Merger, OxenError> {
+        let mut merger = Merger::new(repo)?;
+        merger.primary_key = Some(primary_key.as_ref().to_string());
+        Ok(merger)
+    }
+
     /// Merge a branch name into the current checked out branch, returns the HEAD commit if successful,
     /// and None if there were conflicts. Conflicts get written to disk so we can return to them to fix.
     pub fn merge<S: AsRef<str>>(&self, branch_name: S) -> Result<Option<Commit>, OxenError> {
@@ -88,6 +104,155 @@ impl Merger {
         }
     }
 
+    /// Apply the changes between a commit and its first parent onto HEAD, returns the new commit if successful,
+    /// and None if there were conflicts. The parent acts as the LCA of a three way merge, and conflicts get
+    /// written to disk like a merge, along with the CHERRY_PICK_HEAD file so we can continue once they are fixed.
+    pub fn cherry_pick(&self, commit: &Commit) -> Result<Option<Commit>, OxenError> {
+        let commit_reader = CommitReader::new(&self.repository)?;
+        let parent_id = match commit.parent_ids.as_slice() {
+            [parent_id] => parent_id,
+            [] => {
+                return Err(OxenError::basic_str(format!(
+                    "Cannot cherry-pick {}, it is the root commit",
+                    commit.id
+                )))
+            }
+            _ => {
+                return Err(OxenError::basic_str(format!(
+                    "Cannot cherry-pick merge commit {}, pick the commits it merged instead",
+                    commit.id
+                )))
+            }
+        };
+        let merge_commits = MergeCommits {
+            lca: commit_reader
+                .get_commit_by_id(parent_id)?
+                .ok_or_else(|| OxenError::commit_db_corrupted(parent_id))?,
+            head: commit_reader.head_commit()?,
+            merge: commit.to_owned(),
+        };
+
+        // User output
+        println!(
+            "Cherry-pick {} onto {}",
+            merge_commits.merge.id, merge_commits.head.id
+        );
+
+        let conflicts = self.three_way_merge(&merge_commits)?;
+
+        // Stage what merged cleanly, conflicts get staged by the user once they are fixed
+        let conflict_paths: HashSet<&Path> = conflicts
+            .iter()
+            .map(|c| c.head_entry.path.as_path())
+            .collect();
+        let repo = &self.repository;
+        let stager = Stager::new(repo)?;
+        let head_reader = CommitDirReader::new(repo, &merge_commits.head)?;
+        for path in self.changed_paths(&merge_commits.lca, &merge_commits.merge)? {
+            let full_path = repo.path.join(&path);
+            if conflict_paths.contains(path.as_path())
+                || !(full_path.exists() || head_reader.has_file(&path))
+            {
+                continue;
+            }
+            stager.add(&full_path, &head_reader)?;
+        }
+
+        if conflicts.is_empty() {
+            let commit = self.create_cherry_pick_commit(&stager, &head_reader, commit)?;
+            Ok(Some(commit))
+        } else {
+            let hidden_dir = util::fs::oxen_hidden_dir(&repo.path);
+            util::fs::write_to_path(&hidden_dir.join(CHERRY_PICK_HEAD_FILE), &commit.id)?;
+            self.write_conflicts_to_db(&conflicts)?;
+            Ok(None)
+        }
+    }
+
+    /// Commit a cherry-pick that stopped on conflicts, once they have all been fixed and staged
+    pub fn continue_cherry_pick(&self) -> Result<Commit, OxenError> {
+        let repo = &self.repository;
+        let cherry_pick_head_path =
+            util::fs::oxen_hidden_dir(&repo.path).join(CHERRY_PICK_HEAD_FILE);
+        if !cherry_pick_head_path.exists() {
+            return Err(OxenError::basic_str("No cherry-pick in progress"));
+        }
+        if MergeConflictDBReader::has_conflicts(&self.merge_db)? {
+            return Err(OxenError::basic_str(
+                "Fix the conflicts and `oxen add` them before continuing the cherry-pick",
+            ));
+        }
+
+        let commit_id = util::fs::read_from_path(&cherry_pick_head_path)?;
+        let commit = CommitReader::new(repo)?
+            .get_commit_by_id(commit_id.trim())?
+            .ok_or_else(|| OxenError::commit_id_does_not_exist(commit_id.trim()))?;
+
+        let stager = Stager::new(repo)?;
+        let head_reader = CommitDirReader::new_from_head(repo)?;
+        let cherry_pick_commit = self.create_cherry_pick_commit(&stager, &head_reader, &commit)?;
+        std::fs::remove_file(cherry_pick_head_path)?;
+        Ok(cherry_pick_commit)
+    }
+
+    /// Apply the changes between `base` and `commit` to the working directory on top of HEAD without committing
+    /// or staging them. Conflicts are recorded like a merge so they can be fixed and added, and are returned.
+    pub fn apply_changes(
+        &self,
+        base: &Commit,
+        commit: &Commit,
+    ) -> Result<Vec<MergeConflict>, OxenError> {
+        let merge_commits = MergeCommits {
+            lca: base.to_owned(),
+            head: CommitReader::new(&self.repository)?.head_commit()?,
+            merge: commit.to_owned(),
+        };
+        let conflicts = self.three_way_merge(&merge_commits)?;
+        self.write_conflicts_to_db(&conflicts)?;
+        Ok(conflicts)
+    }
+
+    fn create_cherry_pick_commit(
+        &self,
+        stager: &Stager,
+        head_reader: &CommitDirReader,
+        commit: &Commit,
+    ) -> Result<Commit, OxenError> {
+        let status = stager.status(head_reader)?;
+        if !status.has_added_entries() {
+            return Err(OxenError::basic_str(format!(
+                "Nothing to cherry-pick, the changes in commit {} are already on HEAD",
+                commit.id
+            )));
+        }
+
+        log::debug!("create_cherry_pick_commit {}", commit.id);
+        let commit_writer = CommitWriter::new(&self.repository)?;
+        let cherry_pick_commit = commit_writer.commit(&status, &commit.message)?;
+        stager.unstage()?;
+        Ok(cherry_pick_commit)
+    }
+
+    // Files that were added, removed or modified between the two commits
+    fn changed_paths(&self, from: &Commit, to: &Commit) -> Result<Vec<PathBuf>, OxenError> {
+        let from_entries = CommitDirReader::new(&self.repository, from)?.list_entries_set()?;
+        let to_entries = CommitDirReader::new(&self.repository, to)?.list_entries_set()?;
+
+        let mut paths: Vec<PathBuf> = vec![];
+        for entry in from_entries.iter() {
+            match to_entries.get(entry) {
+                Some(to_entry) if to_entry.hash == entry.hash => {}
+                _ => paths.push(entry.path.to_owned()),
+            }
+        }
+        for entry in to_entries.iter() {
+            if !from_entries.contains(entry) {
+                paths.push(entry.path.to_owned());
+            }
+        }
+        Ok(paths)
+    }
+
     pub fn has_file(&self, path: &Path) -> Result<bool, OxenError> {
         MergeConflictDBReader::has_file(&self.merge_db, path)
     }
@@ -99,6 +264,22 @@ impl Merger {
         Ok(())
     }
 
+    /// Forget a merge or cherry-pick in progress, removing the recorded conflicts and the files tracking its commits
+    pub fn clear_merge_state(&self) -> Result<(), OxenError> {
+        let hidden_dir = util::fs::oxen_hidden_dir(&self.repository.path);
+        for file in [MERGE_HEAD_FILE, ORIG_HEAD_FILE, CHERRY_PICK_HEAD_FILE] {
+            let path = hidden_dir.join(file);
+            if path.exists() {
+                std::fs::remove_file(path)?;
+            }
+        }
+
+        for conflict in MergeConflictDBReader::list_conflicts(&self.merge_db)? {
+            self.remove_conflict_path(&conflict.head_entry.path)?;
+        }
+        Ok(())
+    }
+
     fn write_conflicts_to_disk(
         &self,
         merge_commits: &MergeCommits,
@@ -111,6 +292,10 @@ impl Merger {
         util::fs::write_to_path(&merge_head_path, &merge_commits.merge.id)?;
         util::fs::write_to_path(&orig_head_path, &merge_commits.head.id)?;
 
+        self.write_conflicts_to_db(conflicts)
+    }
+
+    fn write_conflicts_to_db(&self, conflicts: &[MergeConflict]) -> Result<(), OxenError> {
         for conflict in conflicts.iter() {
             let key = conflict.head_entry.path.to_str().unwrap();
             let key_bytes = key.as_bytes();
@@ -134,6 +319,10 @@ impl Merger {
         let commit = command::head_commit(repo)?;
         let reader = CommitDirReader::new(repo, &commit)?;
         stager.add(&repo.path, &reader)?;
+        // Adding the repo dir does not pick up the files the merge removed
+        for path in stager.status(&reader)?.removed_files {
+            stager.add(&repo.path.join(path), &reader)?;
+        }
 
         let commit_msg = format!("Merge branch '{}'", branch_name.as_ref());
 
@@ -242,6 +431,31 @@ impl Merger {
         self.p_lowest_common_ancestor(&commit_reader, &head_commit, &merge_commit)
     }
 
+    /// Check if moving a branch from `base_commit_id` to `new_commit_id` is a fast forward,
+    /// meaning the base commit is the lowest common ancestor of the two, so no history is dropped.
+    /// A base commit we do not have locally cannot be in the history, so it is never a fast forward.
+    pub fn is_fast_forward(
+        &self,
+        base_commit_id: &str,
+        new_commit_id: &str,
+    ) -> Result<bool, OxenError> {
+        if base_commit_id == new_commit_id {
+            return Ok(true);
+        }
+
+        let commit_reader = CommitReader::new(&self.repository)?;
+        let base_commit = match commit_reader.get_commit_by_id(base_commit_id)? {
+            Some(commit) => commit,
+            None => return Ok(false),
+        };
+        let new_commit = commit_reader
+            .get_commit_by_id(new_commit_id)?
+            .ok_or_else(|| OxenError::commit_id_does_not_exist(new_commit_id))?;
+
+        let lca = self.p_lowest_common_ancestor(&commit_reader, &base_commit, &new_commit)?;
+        Ok(lca.id == base_commit.id)
+    }
+
     fn p_lowest_common_ancestor(
         &self,
         commit_reader: &CommitReader,
@@ -335,29 +549,104 @@ impl Merger {
                     // If Merge and LCA are the same, but HEAD is different, take HEAD
                     // Since we are already on HEAD, this means do nothing
 
-                    // If all three are different, mark as conflict
+                    // If all three are different, mark as conflict, unless it is tabular and the rows merge
                     if head_entry.hash != lca_entry.hash
                         && lca_entry.hash != merge_entry.hash
                         && head_entry.hash != merge_entry.hash
                     {
-                        conflicts.push(MergeConflict {
-                            lca_entry: lca_entry.to_owned(),
-                            head_entry: head_entry.to_owned(),
-                            merge_entry: merge_entry.to_owned(),
-                        });
+                        if util::fs::is_tabular(&merge_entry.path) {
+                            if let Some(conflict) =
+                                self.three_way_merge_rows(lca_entry, head_entry, merge_entry)?
+                            {
+                                conflicts.push(conflict);
+                            }
+                        } else {
+                            conflicts.push(MergeConflict {
+                                lca_entry: lca_entry.to_owned(),
+                                head_entry: head_entry.to_owned(),
+                                merge_entry: merge_entry.to_owned(),
+                                row_conflicts: vec![],
+                            });
+                        }
                     }
                 } // merge entry doesn't exist in LCA, which is fine, we will catch it in HEAD
+            } else if let Some(lca_entry) = lca_entries.get(merge_entry) {
+                // HEAD removed it, which wins unless merge changed it since the LCA
+                if lca_entry.hash != merge_entry.hash {
+                    self.update_entry(merge_entry)?;
+                }
             } else {
                 // merge entry does not exist in HEAD, so create it
                 self.update_entry(merge_entry)?;
             }
         }
+
+        // Entries removed in merge get removed from HEAD too, unless HEAD changed them since the LCA
+        for lca_entry in lca_entries.iter() {
+            if merge_entries.contains(lca_entry) {
+                continue;
+            }
+
+            if let Some(head_entry) = head_entries.get(lca_entry) {
+                let path = self.repository.path.join(&head_entry.path);
+                if head_entry.hash == lca_entry.hash && path.exists() {
+                    std::fs::remove_file(path)?;
+                }
+            }
+        }
         log::debug!("three_way_merge conflicts.len() {}", conflicts.len());
 
         Ok(conflicts)
     }
 
+    /// Merges a tabular file row by row into the working directory, returns a conflict if any rows were
+    /// edited differently on both sides, or if the schemas changed so the rows cannot be lined up. Without a
+    /// primary key an edited row cannot be told apart from a removed one, so any row both sides dropped conflicts
+    fn three_way_merge_rows(
+        &self,
+        lca_entry: &CommitEntry,
+        head_entry: &CommitEntry,
+        merge_entry: &CommitEntry,
+    ) -> Result<Option<MergeConflict>, OxenError> {
+        let store = ChunkStore::new(&self.repository);
+        let lca_df = tabular::read_df(store.version_file(lca_entry)?, DFOpts::empty())?;
+        let head_df = tabular::read_df(store.version_file(head_entry)?, DFOpts::empty())?;
+        let merge_df = tabular::read_df(store.version_file(merge_entry)?, DFOpts::empty())?;
+
+        let mut conflict = MergeConflict {
+            lca_entry: lca_entry.to_owned(),
+            head_entry: head_entry.to_owned(),
+            merge_entry: merge_entry.to_owned(),
+            row_conflicts: vec![],
+        };
+
+        let primary_key = self.primary_key.as_deref();
+        match merge::three_way_merge(lca_df, head_df, merge_df, primary_key)? {
+            Some(mut result) => {
+                log::debug!(
+                    "three_way_merge_rows {:?} got {} row conflicts",
+                    merge_entry.path,
+                    result.conflicts.len()
+                );
+                let output_path = self.repository.path.join(&head_entry.path);
+                tabular::write_df(&mut result.df, &output_path)?;
+
+                if result.has_conflicts() {
+                    conflict.row_conflicts = result.conflicts;
+                    Ok(Some(conflict))
+                } else {
+                    Ok(None)
+                }
+            }
+            None => Ok(Some(conflict)),
+        }
+    }
+
     fn update_entry(&self, merge_entry: &CommitEntry) -> Result<(), OxenError> {
+        let full_path = self.repository.path.join(&merge_entry.path);
+        if let Some(parent) = full_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
         restore::restore_file(
             &self.repository,
             &merge_entry.path,
@@ -371,11 +660,13 @@ impl Merger {
 #[cfg(test)]
 mod tests {
     use crate::command;
+    use crate::df::{tabular, DFOpts};
     use crate::error::OxenError;
     use crate::index::{CommitReader, MergeConflictReader, Merger};
     use crate::model::{Commit, LocalRepository};
     use crate::test;
     use crate::util;
+    use std::path::PathBuf;
 
     fn populate_threeway_merge_repo(
         repo: &LocalRepository,
@@ -591,6 +882,29 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_merge_is_fast_forward() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let merge_branch_name = "B"; // see populate function
+            let lca = populate_threeway_merge_repo(&repo, merge_branch_name)?;
+            let head = command::head_commit(&repo)?;
+            let merge_head = command::log_commit_or_branch_history(&repo, merge_branch_name)?
+                .first()
+                .unwrap()
+                .to_owned();
+
+            let merger = Merger::new(&repo)?;
+            assert!(merger.is_fast_forward(&lca.id, &head.id)?);
+            assert!(merger.is_fast_forward(&lca.id, &merge_head.id)?);
+            assert!(merger.is_fast_forward(&head.id, &head.id)?);
+            assert!(!merger.is_fast_forward(&head.id, &lca.id)?);
+            assert!(!merger.is_fast_forward(&head.id, &merge_head.id)?);
+            assert!(!merger.is_fast_forward("not-a-commit", &head.id)?);
+
+            Ok(())
+        })
+    }
+
     #[test]
     fn test_merge_no_conflict_three_way_merge() -> Result<(), OxenError> {
         test::run_empty_local_repo_test(|repo| {
@@ -769,4 +1083,231 @@ mod tests {
             Ok(())
         })
     }
+
+    fn populate_tabular_merge_repo(
+        repo: &LocalRepository,
+        merge_branch_name: &str,
+        head_contents: &str,
+        merge_contents: &str,
+    ) -> Result<PathBuf, OxenError> {
+        let og_branch = command::current_branch(repo)?.unwrap();
+        let labels_path = repo.path.join("labels.csv");
+        util::fs::write_to_path(&labels_path, "id,label\n1,cat\n2,dog\n3,fish\n")?;
+        command::add(repo, &labels_path)?;
+        command::commit(repo, "Add labels.csv")?;
+
+        command::create_checkout_branch(repo, merge_branch_name)?;
+        let labels_path = test::modify_txt_file(labels_path, merge_contents)?;
+        command::add(repo, &labels_path)?;
+        command::commit(repo, "Modify labels.csv on branch")?;
+
+        command::checkout(repo, &og_branch.name)?;
+        let labels_path = test::modify_txt_file(labels_path, head_contents)?;
+        command::add(repo, &labels_path)?;
+        command::commit(repo, "Modify labels.csv on main")?;
+
+        Ok(labels_path)
+    }
+
+    #[test]
+    fn test_merge_tabular_rows_edited_on_different_sides() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let merge_branch_name = "edit-dog";
+            let labels_path = populate_tabular_merge_repo(
+                &repo,
+                merge_branch_name,
+                "id,label\n1,lion\n2,dog\n3,fish\n4,human\n",
+                "id,label\n1,cat\n2,wolf\n",
+            )?;
+
+            {
+                let merger = Merger::new_with_primary_key(&repo, "id")?;
+                let commit = merger.merge(merge_branch_name)?;
+                assert!(commit.is_some());
+            }
+
+            let df = tabular::read_df(&labels_path, DFOpts::empty())?;
+            let labels: Vec<&str> = df
+                .column("label")
+                .unwrap()
+                .utf8()
+                .unwrap()
+                .into_no_null_iter()
+                .collect();
+            assert_eq!(labels, vec!["lion", "wolf", "human"]);
+
+            let conflict_reader = MergeConflictReader::new(&repo)?;
+            assert!(!conflict_reader.has_conflicts()?);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_merge_tabular_rows_edited_on_both_sides_conflict() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let merge_branch_name = "edit-cat";
+            populate_tabular_merge_repo(
+                &repo,
+                merge_branch_name,
+                "id,label\n1,lion\n2,dog\n3,fish\n",
+                "id,label\n1,tiger\n2,wolf\n3,fish\n",
+            )?;
+
+            {
+                let merger = Merger::new_with_primary_key(&repo, "id")?;
+                let commit = merger.merge(merge_branch_name)?;
+                assert!(commit.is_none());
+            }
+
+            let conflict_reader = MergeConflictReader::new(&repo)?;
+            let conflicts = conflict_reader.list_conflicts()?;
+            assert_eq!(conflicts.len(), 1);
+            assert_eq!(conflicts[0].row_conflicts.len(), 1);
+            assert_eq!(conflicts[0].row_conflicts[0].key, "1");
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_merge_three_way_removes_file_removed_on_branch() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let merge_branch_name = "B"; // see populate function
+            populate_threeway_merge_repo(&repo, merge_branch_name)?;
+
+            // Remove a.txt on the branch, main never touched it since the LCA
+            command::checkout(&repo, merge_branch_name)?;
+            let a_path = repo.path.join("a.txt");
+            command::rm(&repo, &a_path)?;
+            command::commit(&repo, "Removing a.txt file")?;
+            let main_branch = command::list_branches(&repo)?
+                .into_iter()
+                .find(|b| b.name != merge_branch_name)
+                .unwrap();
+            command::checkout(&repo, &main_branch.name)?;
+            assert!(a_path.exists());
+
+            {
+                let merger = Merger::new(&repo)?;
+                merger.merge(merge_branch_name)?.unwrap();
+            }
+
+            assert!(!a_path.exists());
+            assert!(repo.path.join("e.txt").exists());
+            assert!(command::status(&repo)?.is_clean());
+
+            Ok(())
+        })
+    }
+
+    // main: a.txt, then a second commit that leaves a.txt alone
+    // fixes: b.txt, then a commit that fixes a.txt and removes b.txt
+    fn populate_cherry_pick_repo(repo: &LocalRepository) -> Result<Commit, OxenError> {
+        let main_branch = command::current_branch(repo)?.unwrap();
+        let a_path = repo.path.join("a.txt");
+        util::fs::write_to_path(&a_path, "a")?;
+        command::add(repo, &a_path)?;
+        command::commit(repo, "Committing a.txt file")?;
+
+        command::create_checkout_branch(repo, "fixes")?;
+        let b_path = repo.path.join("b.txt");
+        util::fs::write_to_path(&b_path, "b")?;
+        command::add(repo, &b_path)?;
+        command::commit(repo, "Committing b.txt file")?;
+
+        util::fs::write_to_path(&a_path, "a fixed")?;
+        command::add(repo, &a_path)?;
+        let c_path = repo.path.join("c.txt");
+        util::fs::write_to_path(&c_path, "c")?;
+        command::add(repo, &c_path)?;
+        let fix = command::commit(repo, "Fixing a.txt, adding c.txt")?.unwrap();
+
+        command::checkout(repo, &main_branch.name)?;
+        let d_path = repo.path.join("d.txt");
+        util::fs::write_to_path(&d_path, "d")?;
+        command::add(repo, &d_path)?;
+        command::commit(repo, "Committing d.txt file")?;
+
+        Ok(fix)
+    }
+
+    #[test]
+    fn test_cherry_pick_applies_only_the_commit() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let fix = populate_cherry_pick_repo(&repo)?;
+            let head = command::head_commit(&repo)?;
+
+            let commit = command::cherry_pick(&repo, &fix.id)?.unwrap();
+            assert_eq!(commit.message, fix.message);
+            assert_eq!(commit.parent_ids, vec![head.id]);
+
+            assert_eq!(
+                util::fs::read_from_path(&repo.path.join("a.txt"))?,
+                "a fixed"
+            );
+            assert!(repo.path.join("c.txt").exists());
+            assert!(repo.path.join("d.txt").exists());
+            // b.txt came from an earlier commit on the branch
+            assert!(!repo.path.join("b.txt").exists());
+            assert!(command::status(&repo)?.is_clean());
+
+            // The changes are all there now
+            assert!(command::cherry_pick(&repo, &fix.id).is_err());
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_cherry_pick_conflict_continue() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let fix = populate_cherry_pick_repo(&repo)?;
+            let a_path = repo.path.join("a.txt");
+            util::fs::write_to_path(&a_path, "a fixed differently")?;
+            command::add(&repo, &a_path)?;
+            command::commit(&repo, "Fixing a.txt on main")?;
+
+            assert!(command::cherry_pick(&repo, &fix.id)?.is_none());
+            let status = command::status(&repo)?;
+            assert_eq!(status.merge_conflicts.len(), 1);
+            // The clean part of the pick is already staged
+            assert!(status
+                .added_files
+                .contains_key(std::path::Path::new("c.txt")));
+            assert!(command::cherry_pick_continue(&repo).is_err());
+
+            command::checkout_theirs(&repo, "a.txt")?;
+            command::add(&repo, &a_path)?;
+            let commit = command::cherry_pick_continue(&repo)?;
+            assert_eq!(commit.message, fix.message);
+            assert_eq!(commit.parent_ids.len(), 1);
+            assert_eq!(util::fs::read_from_path(&a_path)?, "a fixed");
+            assert!(command::status(&repo)?.is_clean());
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_cherry_pick_abort() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let fix = populate_cherry_pick_repo(&repo)?;
+            let a_path = repo.path.join("a.txt");
+            util::fs::write_to_path(&a_path, "a fixed differently")?;
+            command::add(&repo, &a_path)?;
+            let head = command::commit(&repo, "Fixing a.txt on main")?.unwrap();
+
+            assert!(command::cherry_pick(&repo, &fix.id)?.is_none());
+            command::cherry_pick_abort(&repo)?;
+
+            assert_eq!(command::head_commit(&repo)?.id, head.id);
+            assert_eq!(util::fs::read_from_path(&a_path)?, "a fixed differently");
+            assert!(!repo.path.join("c.txt").exists());
+            assert!(command::status(&repo)?.is_clean());
+            assert!(command::cherry_pick_abort(&repo).is_err());
+
+            Ok(())
+        })
+    }
 }
@@ -1,8 +1,8 @@
-use crate::constants::{HEAD_FILE, REFS_DIR};
+use crate::constants::{HEAD_FILE, REFS_DIR, REMOTE_REFS_DIR, STASH_DIR, TAGS_DIR};
 use crate::db;
 use crate::error::OxenError;
 use crate::index::CommitReader;
-use crate::model::{Branch, LocalRepository};
+use crate::model::{Branch, LocalRepository, Stash, Tag};
 use crate::util;
 
 use rocksdb::{IteratorMode, DB};
@@ -153,6 +153,128 @@ impl RefReader {
         Ok(branch_names)
     }
 
+    /// Resolves a local branch name, a remote-tracking ref like `origin/main`, or a tag
+    pub fn get_commit_id_for_ref(&self, name: &str) -> Result<Option<String>, OxenError> {
+        if let Some(commit_id) = self.get_commit_id_for_branch(name)? {
+            return Ok(Some(commit_id));
+        }
+        if let Some(commit_id) = self.get_commit_id_for_remote_branch(name)? {
+            return Ok(Some(commit_id));
+        }
+        Ok(self.get_tag_by_name(name)?.map(|tag| tag.commit_id))
+    }
+
+    pub fn get_tag_by_name(&self, name: &str) -> Result<Option<Tag>, OxenError> {
+        match self.open_tags_db()? {
+            Some(db) => match db.get(name.as_bytes())? {
+                Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+                None => Ok(None),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// All the tags, sorted by name
+    pub fn list_tags(&self) -> Result<Vec<Tag>, OxenError> {
+        let mut tags: Vec<Tag> = vec![];
+        if let Some(db) = self.open_tags_db()? {
+            for (_key, value) in db.iterator(IteratorMode::Start) {
+                match serde_json::from_slice(&value) {
+                    Ok(tag) => tags.push(tag),
+                    Err(err) => {
+                        log::error!("Could not read tag: {}", err)
+                    }
+                }
+            }
+        }
+        Ok(tags)
+    }
+
+    fn open_tags_db(&self) -> Result<Option<DB>, OxenError> {
+        // Only exists once refs have been written
+        let tags_dir = util::fs::oxen_hidden_dir(&self.repository.path).join(TAGS_DIR);
+        if !tags_dir.exists() {
+            return Ok(None);
+        }
+
+        let error_if_log_file_exist = false;
+        let opts = db::opts::default();
+        Ok(Some(DB::open_for_read_only(
+            &opts,
+            &tags_dir,
+            error_if_log_file_exist,
+        )?))
+    }
+
+    /// All the stashes, newest first, so the index of a stash is its position in `stash@{n}`
+    pub fn list_stashes(&self) -> Result<Vec<Stash>, OxenError> {
+        let mut stashes: Vec<Stash> = vec![];
+        let stash_dir = util::fs::oxen_hidden_dir(&self.repository.path).join(STASH_DIR);
+        if !stash_dir.exists() {
+            return Ok(stashes);
+        }
+
+        let error_if_log_file_exist = false;
+        let opts = db::opts::default();
+        let db = DB::open_for_read_only(&opts, &stash_dir, error_if_log_file_exist)?;
+        for (_key, value) in db.iterator(IteratorMode::End) {
+            match serde_json::from_slice(&value) {
+                Ok(stash) => stashes.push(stash),
+                Err(err) => {
+                    log::error!("Could not read stash: {}", err)
+                }
+            }
+        }
+        Ok(stashes)
+    }
+
+    pub fn get_commit_id_for_remote_branch(&self, name: &str) -> Result<Option<String>, OxenError> {
+        match self.open_remote_refs_db()? {
+            Some(db) => match db.get(name.as_bytes())? {
+                Some(value) => Ok(Some(String::from(str::from_utf8(&value)?))),
+                None => Ok(None),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// The remote-tracking refs from the last fetch, named like `origin/main`
+    pub fn list_remote_branches(&self) -> Result<Vec<Branch>, OxenError> {
+        let mut branches: Vec<Branch> = vec![];
+        if let Some(db) = self.open_remote_refs_db()? {
+            for (key, value) in db.iterator(IteratorMode::Start) {
+                match (str::from_utf8(&key), str::from_utf8(&value)) {
+                    (Ok(key), Ok(value)) => branches.push(Branch {
+                        name: String::from(key),
+                        commit_id: String::from(value),
+                        is_head: false,
+                    }),
+                    _ => {
+                        eprintln!("Could not read utf8 val...")
+                    }
+                }
+            }
+        }
+        Ok(branches)
+    }
+
+    fn open_remote_refs_db(&self) -> Result<Option<DB>, OxenError> {
+        // Only exists once something has been fetched
+        let remote_refs_dir =
+            util::fs::oxen_hidden_dir(&self.repository.path).join(REMOTE_REFS_DIR);
+        if !remote_refs_dir.exists() {
+            return Ok(None);
+        }
+
+        let error_if_log_file_exist = false;
+        let opts = db::opts::default();
+        Ok(Some(DB::open_for_read_only(
+            &opts,
+            &remote_refs_dir,
+            error_if_log_file_exist,
+        )?))
+    }
+
     pub fn get_branch_by_name(&self, name: &str) -> Result<Option<Branch>, OxenError> {
         log::debug!("get_branch_by_name {name}");
         let maybe_head_id = self.head_commit_id()?;
@@ -178,7 +300,7 @@ impl RefReader {
 mod tests {
     use crate::command;
     use crate::error::OxenError;
-    use crate::index::RefReader;
+    use crate::index::{RefReader, RefWriter};
     use crate::test;
 
     #[test]
@@ -200,4 +322,34 @@ mod tests {
             Ok(())
         })
     }
+
+    #[test]
+    fn test_ref_reader_remote_branches() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let head = command::head_commit(&repo)?;
+            let ref_reader = RefReader::new(&repo)?;
+            assert!(ref_reader.list_remote_branches()?.is_empty());
+            assert_eq!(ref_reader.get_commit_id_for_ref("origin/main")?, None);
+
+            {
+                let ref_writer = RefWriter::new(&repo)?;
+                ref_writer.set_remote_branch_commit_id("origin", "main", &head.id)?;
+            }
+
+            // Remote-tracking refs resolve, but are not local branches
+            let ref_reader = RefReader::new(&repo)?;
+            assert_eq!(
+                ref_reader.get_commit_id_for_ref("origin/main")?,
+                Some(head.id.to_owned())
+            );
+            assert!(!ref_reader.has_branch("origin/main"));
+            assert_eq!(ref_reader.list_branches()?.len(), 1);
+
+            let remote_branches = ref_reader.list_remote_branches()?;
+            assert_eq!(remote_branches.len(), 1);
+            assert_eq!(remote_branches[0].name, "origin/main");
+
+            Ok(())
+        })
+    }
 }
@@ -1,8 +1,8 @@
-use crate::constants::{HEAD_FILE, REFS_DIR};
+use crate::constants::{HEAD_FILE, REFS_DIR, REMOTE_REFS_DIR, STASH_DIR, TAGS_DIR};
 use crate::db;
 use crate::error::OxenError;
 use crate::index::RefDBReader;
-use crate::model::{Branch, LocalRepository};
+use crate::model::{Branch, LocalRepository, Stash, Tag};
 use crate::util;
 
 use rocksdb::{IteratorMode, DB};
@@ -11,17 +11,27 @@ use std::str;
 
 pub struct RefWriter {
     refs_db: DB,
+    remote_refs_db: DB,
+    tags_db: DB,
+    stash_db: DB,
     head_file: PathBuf,
 }
 
 impl RefWriter {
     pub fn new(repository: &LocalRepository) -> Result<RefWriter, OxenError> {
         let refs_dir = util::fs::oxen_hidden_dir(&repository.path).join(Path::new(REFS_DIR));
+        let remote_refs_dir =
+            util::fs::oxen_hidden_dir(&repository.path).join(Path::new(REMOTE_REFS_DIR));
+        let tags_dir = util::fs::oxen_hidden_dir(&repository.path).join(Path::new(TAGS_DIR));
+        let stash_dir = util::fs::oxen_hidden_dir(&repository.path).join(Path::new(STASH_DIR));
         let head_filename = util::fs::oxen_hidden_dir(&repository.path).join(Path::new(HEAD_FILE));
 
         let opts = db::opts::default();
         Ok(RefWriter {
             refs_db: DB::open(&opts, refs_dir)?,
+            remote_refs_db: DB::open(&opts, remote_refs_dir)?,
+            tags_db: DB::open(&opts, tags_dir)?,
+            stash_db: DB::open(&opts, stash_dir)?,
             head_file: head_filename,
         })
     }
@@ -61,6 +71,67 @@ impl RefWriter {
         Ok(())
     }
 
+    /// Records where `branch` on `remote` pointed when we last fetched, as the ref `remote/branch`
+    pub fn set_remote_branch_commit_id(
+        &self,
+        remote: &str,
+        branch: &str,
+        commit_id: &str,
+    ) -> Result<(), OxenError> {
+        let name = format!("{remote}/{branch}");
+        self.remote_refs_db.put(name, commit_id)?;
+        Ok(())
+    }
+
+    pub fn delete_remote_branch(&self, remote: &str, branch: &str) -> Result<(), OxenError> {
+        let name = format!("{remote}/{branch}");
+        self.remote_refs_db.delete(name)?;
+        Ok(())
+    }
+
+    pub fn has_tag(&self, name: &str) -> bool {
+        RefDBReader::has_branch(&self.tags_db, name)
+    }
+
+    /// Tags are immutable, so this fails if the tag already exists
+    pub fn create_tag(&self, tag: &Tag) -> Result<(), OxenError> {
+        log::debug!("create_tag {} -> {}", tag.name, tag.commit_id);
+        if self.has_tag(&tag.name) {
+            let err = format!("Tag already exists: {}", tag.name);
+            return Err(OxenError::basic_str(err));
+        }
+
+        self.tags_db.put(&tag.name, serde_json::to_vec(tag)?)?;
+        Ok(())
+    }
+
+    pub fn push_stash(&self, stash: &Stash) -> Result<(), OxenError> {
+        log::debug!("push_stash {} -> {}", stash.key(), stash.commit_id);
+        self.stash_db.put(stash.key(), serde_json::to_vec(stash)?)?;
+        Ok(())
+    }
+
+    pub fn delete_stash(&self, stash: &Stash) -> Result<(), OxenError> {
+        let key = stash.key();
+        if self.stash_db.get(&key)?.is_none() {
+            let err = format!("Stash does not exist: {}", stash.commit_id);
+            Err(OxenError::basic_str(err))
+        } else {
+            self.stash_db.delete(key)?;
+            Ok(())
+        }
+    }
+
+    pub fn delete_tag(&self, name: &str) -> Result<(), OxenError> {
+        if !self.has_tag(name) {
+            let err = format!("Tag does not exist: {name}");
+            Err(OxenError::basic_str(err))
+        } else {
+            self.tags_db.delete(name)?;
+            Ok(())
+        }
+    }
+
     pub fn set_head_commit_id(&self, commit_id: &str) -> Result<(), OxenError> {
         // if we have head ref in HEAD file then write it to that db
         let head_val = self.read_head_ref()?; // could be branch name or commit ID
@@ -161,8 +232,12 @@ impl RefWriter {
 #[cfg(test)]
 mod tests {
     use crate::error::OxenError;
+    use crate::index::{RefReader, RefWriter};
+    use crate::model::Tag;
     use crate::test;
 
+    use time::OffsetDateTime;
+
     #[test]
     fn test_default_head() -> Result<(), OxenError> {
         test::run_referencer_test(|referencer| {
@@ -227,6 +302,46 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_ref_writer_tags_are_immutable() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let tag = Tag {
+                name: String::from("v1.3-train"),
+                commit_id: String::from("1"),
+                tagger: String::from("Ox"),
+                email: String::from("ox@oxen.ai"),
+                message: String::from("Train split"),
+                timestamp: OffsetDateTime::now_utc(),
+            };
+
+            {
+                let ref_writer = RefWriter::new(&repo)?;
+                ref_writer.create_tag(&tag)?;
+                let moved = Tag {
+                    commit_id: String::from("2"),
+                    ..tag.clone()
+                };
+                assert!(ref_writer.create_tag(&moved).is_err());
+            }
+
+            let ref_reader = RefReader::new(&repo)?;
+            assert_eq!(ref_reader.get_tag_by_name(&tag.name)?, Some(tag.clone()));
+            assert_eq!(ref_reader.list_tags()?, vec![tag.clone()]);
+            assert_eq!(
+                ref_reader.get_commit_id_for_ref(&tag.name)?,
+                Some(String::from("1"))
+            );
+            drop(ref_reader);
+
+            let ref_writer = RefWriter::new(&repo)?;
+            ref_writer.delete_tag(&tag.name)?;
+            assert!(ref_writer.delete_tag(&tag.name).is_err());
+            assert!(RefReader::new(&repo)?.list_tags()?.is_empty());
+
+            Ok(())
+        })
+    }
+
     #[test]
     fn test_ref_writer_create_branch_same_name() -> Result<(), OxenError> {
         test::run_referencer_test(|referencer| {
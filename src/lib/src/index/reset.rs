@@ -0,0 +1,288 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::error::OxenError;
+use crate::index::{restore, CommitDirReader, CommitReader, Merger, RefReader, RefWriter, Stager};
+use crate::model::{Commit, LocalRepository};
+use crate::opts::ResetMode;
+use crate::util;
+
+/// Moves the current branch to `commit`, or HEAD itself if it is detached, then updates the stager
+/// and working tree depending on the `mode`. Any merge in progress is abandoned.
+///
+/// A soft reset leaves the working tree and what was staged alone, and stages the contents the old HEAD
+/// had for the files it changed since `commit`, so committing right after gives back the old HEAD.
+pub fn reset(repo: &LocalRepository, commit: &Commit, mode: ResetMode) -> Result<(), OxenError> {
+    let head_commit = CommitReader::new(repo)?.head_commit()?;
+    let head_reader = CommitDirReader::new(repo, &head_commit)?;
+    let commit_reader = CommitDirReader::new(repo, commit)?;
+
+    let stager = Stager::new(repo)?;
+    let status = stager.status(&head_reader)?;
+    log::debug!(
+        "reset {:?} {} -> {} with {} staged files",
+        mode,
+        head_commit.id,
+        commit.id,
+        status.added_files.len()
+    );
+
+    move_head(repo, &commit.id)?;
+    Merger::new(repo)?.clear_merge_state()?;
+
+    // Paths that changed between the two commits
+    let mut changed_paths: HashSet<PathBuf> = diff_paths(&head_reader, &commit_reader)?;
+    match mode {
+        ResetMode::Soft => {
+            for path in changed_paths.iter() {
+                if status.added_files.contains_key(path) {
+                    // Already staged, only its status against the new HEAD changes
+                    stager.restage(path, &commit_reader)?;
+                    continue;
+                }
+                match head_reader.get_entry(path)? {
+                    Some(entry) => stager.add_version(&entry, &commit_reader)?,
+                    None => stager.add_removed(path, &commit_reader)?,
+                }
+            }
+        }
+        ResetMode::Mixed => stager.unstage()?,
+        ResetMode::Hard => {
+            stager.unstage()?;
+            changed_paths.extend(status.modified_files.iter().cloned());
+            changed_paths.extend(status.removed_files.iter().cloned());
+            changed_paths.extend(status.added_files.keys().cloned());
+            reset_working_tree(repo, commit, &commit_reader, &changed_paths)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn move_head(repo: &LocalRepository, commit_id: &str) -> Result<(), OxenError> {
+    let current_branch = RefReader::new(repo)?.get_current_branch()?;
+    let ref_writer = RefWriter::new(repo)?;
+    match current_branch {
+        Some(branch) => ref_writer.set_branch_commit_id(&branch.name, commit_id)?,
+        None => ref_writer.set_head(commit_id),
+    }
+    Ok(())
+}
+
+/// Files that were added, removed or modified between the two commits
+pub fn diff_paths(
+    from_reader: &CommitDirReader,
+    to_reader: &CommitDirReader,
+) -> Result<HashSet<PathBuf>, OxenError> {
+    let from_entries = from_reader.list_entries_set()?;
+    let to_entries = to_reader.list_entries_set()?;
+
+    let mut paths: HashSet<PathBuf> = HashSet::new();
+    for entry in from_entries.iter() {
+        match to_entries.get(entry) {
+            Some(to_entry) if to_entry.hash == entry.hash => {}
+            _ => {
+                paths.insert(entry.path.to_owned());
+            }
+        }
+    }
+    for entry in to_entries.iter() {
+        if !from_entries.contains(entry) {
+            paths.insert(entry.path.to_owned());
+        }
+    }
+    Ok(paths)
+}
+
+/// Puts the paths in the working tree back to how they are in `commit`, removing the ones it does not have
+pub fn reset_working_tree(
+    repo: &LocalRepository,
+    commit: &Commit,
+    commit_reader: &CommitDirReader,
+    paths: &HashSet<PathBuf>,
+) -> Result<(), OxenError> {
+    for path in paths.iter() {
+        let full_path = repo.path.join(path);
+        match commit_reader.get_entry(path)? {
+            Some(entry) => {
+                // Sparse clones never pulled the versions outside of their spec, so leave those absent
+                if !full_path.exists() && !repo.is_in_sparse_tree(path) {
+                    continue;
+                }
+
+                if full_path.exists() && util::hasher::hash_file_contents(&full_path)? == entry.hash
+                {
+                    continue;
+                }
+
+                log::debug!("reset_working_tree restoring {:?}", path);
+                if let Some(parent) = full_path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                restore::restore_file(repo, path, &commit.id, &entry)?;
+            }
+            None => {
+                if full_path.is_file() {
+                    log::debug!("reset_working_tree removing {:?}", path);
+                    std::fs::remove_file(&full_path)?;
+                    remove_empty_parents(repo, path)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Cleans up the directories that only held files we removed
+fn remove_empty_parents(repo: &LocalRepository, path: &Path) -> Result<(), OxenError> {
+    let mut parent = path.parent();
+    while let Some(dir) = parent {
+        if dir == Path::new("") {
+            break;
+        }
+
+        let full_dir = repo.path.join(dir);
+        if !full_dir.is_dir() || std::fs::read_dir(&full_dir)?.next().is_some() {
+            break;
+        }
+        std::fs::remove_dir(&full_dir)?;
+        parent = dir.parent();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use crate::command;
+    use crate::error::OxenError;
+    use crate::index::{ChunkStore, CommitDirReader};
+    use crate::opts::ResetMode;
+    use crate::test;
+    use crate::util;
+
+    #[test]
+    fn test_reset_hard_moves_branch_and_working_tree() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let hello_file = repo.path.join("hello.txt");
+            util::fs::write_to_path(&hello_file, "Hello")?;
+            command::add(&repo, &hello_file)?;
+            let first_commit = command::commit(&repo, "Adding hello")?.unwrap();
+
+            util::fs::write_to_path(&hello_file, "Hello World")?;
+            let world_file = repo.path.join("nested").join("world.txt");
+            std::fs::create_dir_all(world_file.parent().unwrap())?;
+            util::fs::write_to_path(&world_file, "World")?;
+            command::add(&repo, &hello_file)?;
+            command::add(&repo, &world_file)?;
+            command::commit(&repo, "Editing hello, adding world")?;
+
+            // Some uncommitted work that a hard reset throws away
+            util::fs::write_to_path(&hello_file, "Goodbye")?;
+
+            command::reset(&repo, &first_commit.id, ResetMode::Hard)?;
+
+            let branch = command::current_branch(&repo)?.unwrap();
+            assert_eq!(branch.commit_id, first_commit.id);
+            assert_eq!(util::fs::read_from_path(&hello_file)?, "Hello");
+            assert!(!world_file.exists());
+            assert!(!world_file.parent().unwrap().exists());
+            assert!(command::status(&repo)?.is_clean());
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_reset_soft_and_mixed_keep_working_tree() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let hello_file = repo.path.join("hello.txt");
+            util::fs::write_to_path(&hello_file, "Hello")?;
+            command::add(&repo, &hello_file)?;
+            let first_commit = command::commit(&repo, "Adding hello")?.unwrap();
+
+            let world_file = repo.path.join("world.txt");
+            util::fs::write_to_path(&world_file, "World")?;
+            command::add(&repo, &world_file)?;
+            let second_commit = command::commit(&repo, "Adding world")?.unwrap();
+
+            // Soft leaves the undone commit staged, so committing again gets it back
+            command::reset(&repo, &first_commit.id, ResetMode::Soft)?;
+            assert!(world_file.exists());
+            let status = command::status(&repo)?;
+            assert_eq!(status.added_files.len(), 1);
+            assert!(status
+                .added_files
+                .contains_key(std::path::Path::new("world.txt")));
+            command::commit(&repo, "Adding world again")?;
+
+            // Mixed leaves it on disk but untracked
+            command::reset(&repo, &first_commit.id, ResetMode::Mixed)?;
+            assert!(world_file.exists());
+            let status = command::status(&repo)?;
+            assert!(status.added_files.is_empty());
+            assert_eq!(status.untracked_files.len(), 1);
+
+            // Can move forward again by commit id
+            command::reset(&repo, &second_commit.id, ResetMode::Mixed)?;
+            let head = command::head_commit(&repo)?;
+            assert_eq!(head.id, second_commit.id);
+            assert!(command::status(&repo)?.is_clean());
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_reset_soft_leaves_unstaged_changes_alone() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let hello_file = repo.path.join("hello.txt");
+            let labels_file = repo.path.join("labels.txt");
+            util::fs::write_to_path(&hello_file, "Hello")?;
+            util::fs::write_to_path(&labels_file, "cat")?;
+            command::add(&repo, &hello_file)?;
+            command::add(&repo, &labels_file)?;
+            let first_commit = command::commit(&repo, "Adding hello and labels")?.unwrap();
+
+            util::fs::write_to_path(&hello_file, "Hello World")?;
+            command::add(&repo, &hello_file)?;
+            let second_commit = command::commit(&repo, "Editing hello")?.unwrap();
+            let committed = CommitDirReader::new(&repo, &second_commit)?
+                .get_entry(Path::new("hello.txt"))?
+                .unwrap();
+
+            // Modified but not staged, one of them changed by the commit we undo
+            util::fs::write_to_path(&hello_file, "Hello Local")?;
+            util::fs::write_to_path(&labels_file, "dog")?;
+
+            command::reset(&repo, &first_commit.id, ResetMode::Soft)?;
+            assert_eq!(util::fs::read_from_path(&hello_file)?, "Hello Local");
+            assert_eq!(util::fs::read_from_path(&labels_file)?, "dog");
+
+            // Only what the undone commit changed is staged, with the contents it committed
+            let status = command::status(&repo)?;
+            assert_eq!(status.added_files.len(), 1);
+            let staged = &status.added_files[Path::new("hello.txt")];
+            assert_eq!(staged.hash, committed.hash);
+            assert_eq!(status.modified_files, vec![PathBuf::from("labels.txt")]);
+
+            let commit = command::commit(&repo, "Editing hello again")?.unwrap();
+            let entry = CommitDirReader::new(&repo, &commit)?
+                .get_entry(Path::new("hello.txt"))?
+                .unwrap();
+            assert_eq!(entry.hash, committed.hash);
+            let restored = repo.path.join("restored.txt");
+            ChunkStore::new(&repo).copy_version_to(&entry, &restored)?;
+            assert_eq!(util::fs::read_from_path(&restored)?, "Hello World");
+            std::fs::remove_file(&restored)?;
+
+            // The local edit is still there to be added
+            let status = command::status(&repo)?;
+            assert!(status.added_files.is_empty());
+            assert_eq!(status.modified_files.len(), 2);
+
+            Ok(())
+        })
+    }
+}
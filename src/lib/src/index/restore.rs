@@ -3,10 +3,10 @@ use std::path::Path;
 
 use crate::error::OxenError;
 use crate::index::Stager;
-use crate::index::{CommitDirEntryWriter, CommitDirReader};
+use crate::index::{ChunkStore, CommitDirEntryWriter, CommitDirReader};
 use crate::model::{Commit, CommitEntry, LocalRepository};
 use crate::opts::RestoreOpts;
-use crate::util::{self, resource};
+use crate::util::resource;
 
 use super::CommitDirEntryReader;
 
@@ -94,8 +94,6 @@ fn restore_regular(
     path: &Path,
     entry: &CommitEntry,
 ) -> Result<(), OxenError> {
-    let version_path = util::fs::version_path(repo, entry);
     let working_path = repo.path.join(path);
-    std::fs::copy(version_path, working_path)?;
-    Ok(())
+    ChunkStore::new(repo).copy_version_to(entry, working_path)
 }
@@ -0,0 +1,330 @@
+use polars::prelude::DataFrame;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use crate::command;
+use crate::df::{merge, tabular, DFOpts};
+use crate::error::OxenError;
+use crate::index::{restore, ChunkStore, CommitDirReader, CommitReader, CommitWriter, Stager};
+use crate::model::{Commit, CommitEntry, LocalRepository};
+use crate::util;
+
+// What we have to do to a single file to undo a commit
+enum RevertChange {
+    Restore(CommitEntry),
+    Remove(PathBuf),
+    Write(PathBuf, DataFrame),
+}
+
+pub struct Reverter {
+    repository: LocalRepository,
+}
+
+impl Reverter {
+    pub fn new(repo: &LocalRepository) -> Result<Reverter, OxenError> {
+        Ok(Reverter {
+            repository: repo.to_owned(),
+        })
+    }
+
+    /// Creates a new commit on HEAD that undoes the changes `commit` made to its parent.
+    ///
+    /// This is a three way merge where the commit is the common ancestor, HEAD is one side and the parent of
+    /// the commit is the other. Files that were changed again since the commit are merged row by row if they
+    /// are tabular, otherwise nothing is touched and the conflicting paths are returned in the error.
+    pub fn revert(&self, commit: &Commit) -> Result<Commit, OxenError> {
+        let repo = &self.repository;
+        if commit.parent_ids.len() > 1 {
+            return Err(OxenError::basic_str(format!(
+                "Cannot revert merge commit {}, revert the commits it merged instead",
+                commit.id
+            )));
+        }
+
+        let status = command::status(repo)?;
+        if status.has_added_entries()
+            || status.has_modified_entries()
+            || status.has_removed_entries()
+            || status.has_merge_conflicts()
+        {
+            return Err(OxenError::basic_str(
+                "Cannot revert with local changes, commit or restore them first",
+            ));
+        }
+
+        let commit_reader = CommitReader::new(repo)?;
+        let parent = match commit.parent_ids.first() {
+            Some(parent_id) => Some(
+                commit_reader
+                    .get_commit_by_id(parent_id)?
+                    .ok_or_else(|| OxenError::commit_db_corrupted(parent_id))?,
+            ),
+            None => None,
+        };
+        let head = commit_reader.head_commit()?;
+
+        let changes = self.find_changes(commit, parent.as_ref(), &head)?;
+        if changes.is_empty() {
+            return Err(OxenError::basic_str(format!(
+                "Nothing to revert, the changes in commit {} are already undone",
+                commit.id
+            )));
+        }
+        log::debug!("revert {} -> {} changes", commit.id, changes.len());
+
+        // Apply the changes, then stage and commit exactly those paths
+        let mut paths: Vec<PathBuf> = vec![];
+        for change in changes {
+            paths.push(self.apply_change(change)?);
+        }
+
+        let stager = Stager::new(repo)?;
+        let head_reader = CommitDirReader::new(repo, &head)?;
+        for path in paths.iter() {
+            stager.add(&repo.path.join(path), &head_reader)?;
+        }
+        let status = stager.status(&head_reader)?;
+        let message = format!("Revert \"{}\"", commit.message);
+        let commit_writer = CommitWriter::new(repo)?;
+        let revert_commit = commit_writer.commit(&status, &message)?;
+        stager.unstage()?;
+
+        Ok(revert_commit)
+    }
+
+    fn find_changes(
+        &self,
+        commit: &Commit,
+        parent: Option<&Commit>,
+        head: &Commit,
+    ) -> Result<Vec<RevertChange>, OxenError> {
+        let commit_entries = self.entries_by_path(Some(commit))?;
+        let parent_entries = self.entries_by_path(parent)?;
+        let head_entries = self.entries_by_path(Some(head))?;
+
+        let paths: HashSet<&PathBuf> = commit_entries.keys().chain(parent_entries.keys()).collect();
+        let mut paths: Vec<&PathBuf> = paths.into_iter().collect();
+        paths.sort();
+
+        let mut changes: Vec<RevertChange> = vec![];
+        let mut conflicts: Vec<PathBuf> = vec![];
+        for path in paths {
+            let commit_entry = commit_entries.get(path);
+            let parent_entry = parent_entries.get(path);
+            let head_entry = head_entries.get(path);
+
+            let commit_hash = commit_entry.map(|e| &e.hash);
+            let parent_hash = parent_entry.map(|e| &e.hash);
+            let head_hash = head_entry.map(|e| &e.hash);
+
+            if commit_hash == parent_hash || head_hash == parent_hash {
+                // Untouched by the commit, or already undone
+                continue;
+            }
+
+            if head_hash == commit_hash {
+                // Nobody touched it since, so go straight back to the parent version
+                match parent_entry {
+                    Some(entry) => changes.push(RevertChange::Restore(entry.to_owned())),
+                    None => changes.push(RevertChange::Remove(path.to_owned())),
+                }
+                continue;
+            }
+
+            // Changed on both sides, only tabular data that exists in all three can be merged
+            if let (Some(commit_entry), Some(parent_entry), Some(head_entry)) =
+                (commit_entry, parent_entry, head_entry)
+            {
+                if util::fs::is_tabular(path) {
+                    if let Some(df) = self.revert_rows(commit_entry, parent_entry, head_entry)? {
+                        changes.push(RevertChange::Write(path.to_owned(), df));
+                        continue;
+                    }
+                }
+            }
+            conflicts.push(path.to_owned());
+        }
+
+        if !conflicts.is_empty() {
+            let paths: Vec<String> = conflicts
+                .iter()
+                .map(|p| format!("  {}", p.to_string_lossy()))
+                .collect();
+            return Err(OxenError::basic_str(format!(
+                "Cannot revert commit {}, these files changed again since:\n{}",
+                commit.id,
+                paths.join("\n")
+            )));
+        }
+
+        Ok(changes)
+    }
+
+    /// Undoes the rows the commit changed while keeping the rows changed since, None if they overlap
+    fn revert_rows(
+        &self,
+        commit_entry: &CommitEntry,
+        parent_entry: &CommitEntry,
+        head_entry: &CommitEntry,
+    ) -> Result<Option<DataFrame>, OxenError> {
+        let store = ChunkStore::new(&self.repository);
+        let commit_df = tabular::read_df(store.version_file(commit_entry)?, DFOpts::empty())?;
+        let parent_df = tabular::read_df(store.version_file(parent_entry)?, DFOpts::empty())?;
+        let head_df = tabular::read_df(store.version_file(head_entry)?, DFOpts::empty())?;
+
+        match merge::three_way_merge(commit_df, head_df, parent_df, None)? {
+            Some(result) if !result.has_conflicts() => Ok(Some(result.df)),
+            _ => Ok(None),
+        }
+    }
+
+    fn apply_change(&self, change: RevertChange) -> Result<PathBuf, OxenError> {
+        let repo = &self.repository;
+        match change {
+            RevertChange::Restore(entry) => {
+                let full_path = repo.path.join(&entry.path);
+                if let Some(parent) = full_path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                restore::restore_file(repo, &entry.path, &entry.commit_id, &entry)?;
+                Ok(entry.path)
+            }
+            RevertChange::Remove(path) => {
+                let full_path = repo.path.join(&path);
+                if full_path.exists() {
+                    std::fs::remove_file(full_path)?;
+                }
+                Ok(path)
+            }
+            RevertChange::Write(path, mut df) => {
+                tabular::write_df(&mut df, repo.path.join(&path))?;
+                Ok(path)
+            }
+        }
+    }
+
+    fn entries_by_path(
+        &self,
+        commit: Option<&Commit>,
+    ) -> Result<HashMap<PathBuf, CommitEntry>, OxenError> {
+        match commit {
+            Some(commit) => {
+                let reader = CommitDirReader::new(&self.repository, commit)?;
+                Ok(reader
+                    .list_entries()?
+                    .into_iter()
+                    .map(|entry| (entry.path.to_owned(), entry))
+                    .collect())
+            }
+            None => Ok(HashMap::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::command;
+    use crate::df::{tabular, DFOpts};
+    use crate::error::OxenError;
+    use crate::test;
+    use crate::util;
+
+    #[test]
+    fn test_revert_restores_modified_and_removes_added() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let hello_file = repo.path.join("hello.txt");
+            util::fs::write_to_path(&hello_file, "Hello")?;
+            command::add(&repo, &hello_file)?;
+            command::commit(&repo, "Adding hello")?;
+
+            util::fs::write_to_path(&hello_file, "Hello World")?;
+            let world_file = repo.path.join("world.txt");
+            util::fs::write_to_path(&world_file, "World")?;
+            command::add(&repo, &hello_file)?;
+            command::add(&repo, &world_file)?;
+            let commit = command::commit(&repo, "Editing hello, adding world")?.unwrap();
+
+            // Unrelated change on top that the revert should keep
+            let other_file = repo.path.join("other.txt");
+            util::fs::write_to_path(&other_file, "Other")?;
+            command::add(&repo, &other_file)?;
+            command::commit(&repo, "Adding other")?;
+
+            let revert = command::revert(&repo, &commit.id)?;
+            assert_eq!(revert.message, "Revert \"Editing hello, adding world\"");
+            assert_eq!(util::fs::read_from_path(&hello_file)?, "Hello");
+            assert!(!world_file.exists());
+            assert!(other_file.exists());
+            assert!(command::status(&repo)?.is_clean());
+            assert_eq!(command::log(&repo)?.len(), 5);
+
+            // Reverting it twice has nothing left to undo
+            assert!(command::revert(&repo, &commit.id).is_err());
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_revert_tabular_rows_changed_since() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let csv_file = repo.path.join("labels.csv");
+            util::fs::write_to_path(&csv_file, "file,label\na.jpg,cat\nb.jpg,dog\n")?;
+            command::add(&repo, &csv_file)?;
+            command::commit(&repo, "Adding labels")?;
+
+            util::fs::write_to_path(&csv_file, "file,label\na.jpg,cat\nb.jpg,dog\nc.jpg,fish\n")?;
+            command::add(&repo, &csv_file)?;
+            let commit = command::commit(&repo, "Adding fish")?.unwrap();
+
+            util::fs::write_to_path(
+                &csv_file,
+                "file,label\na.jpg,cat\nb.jpg,dog\nc.jpg,fish\nd.jpg,bird\n",
+            )?;
+            command::add(&repo, &csv_file)?;
+            command::commit(&repo, "Adding bird")?;
+
+            command::revert(&repo, &commit.id)?;
+
+            let df = tabular::read_df(&csv_file, DFOpts::empty())?;
+            assert_eq!(df.height(), 3);
+            let files: Vec<&str> = df
+                .column("file")
+                .unwrap()
+                .utf8()
+                .unwrap()
+                .into_iter()
+                .flatten()
+                .collect();
+            assert_eq!(files, vec!["a.jpg", "b.jpg", "d.jpg"]);
+            assert!(command::status(&repo)?.is_clean());
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_revert_conflict_leaves_working_tree() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let hello_file = repo.path.join("hello.txt");
+            util::fs::write_to_path(&hello_file, "Hello")?;
+            command::add(&repo, &hello_file)?;
+            command::commit(&repo, "Adding hello")?;
+
+            util::fs::write_to_path(&hello_file, "Hello World")?;
+            command::add(&repo, &hello_file)?;
+            let commit = command::commit(&repo, "Editing hello")?.unwrap();
+
+            util::fs::write_to_path(&hello_file, "Hello Again")?;
+            command::add(&repo, &hello_file)?;
+            let head = command::commit(&repo, "Editing hello again")?.unwrap();
+
+            let result = command::revert(&repo, &commit.id);
+            assert!(result.is_err());
+            assert_eq!(util::fs::read_from_path(&hello_file)?, "Hello Again");
+            assert_eq!(command::head_commit(&repo)?.id, head.id);
+
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,226 @@
+//! Builds the value -> row indices for indexed schema fields
+//!
+//! Fields are indexed with `oxen schemas create_index`, which indexes every file with the schema in the HEAD commit.
+//! Each commit copies the indices of its parent, and re-indexes the committed files whose schema has indexed fields.
+//! Equality filters on an indexed field can then `take` the rows from the index instead of scanning the whole file.
+
+use polars::prelude::*;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::api;
+use crate::df::{tabular, DFOpts};
+use crate::error::OxenError;
+use crate::index::{
+    ChunkStore, SchemaFieldValIndex, SchemaIndexReader, SchemaIndexWriter, SchemaReader,
+};
+use crate::model::schema::{DataType, Field};
+use crate::model::{Commit, LocalRepository, Schema};
+
+/// Only exact values can be looked up, so floats, dates and lists are not indexed
+pub fn is_indexable(field: &Field) -> bool {
+    is_integer(field) || matches!(DataType::from_string(&field.dtype), DataType::String)
+}
+
+fn is_integer(field: &Field) -> bool {
+    matches!(
+        DataType::from_string(&field.dtype),
+        DataType::Int8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::UInt8
+            | DataType::UInt16
+            | DataType::UInt32
+            | DataType::UInt64
+    )
+}
+
+/// Marks the field as indexed and indexes every file with the schema in the commit
+pub fn create_index<S: AsRef<str>>(
+    repo: &LocalRepository,
+    commit: &Commit,
+    schema: &Schema,
+    field_name: S,
+) -> Result<Field, OxenError> {
+    let field_name = field_name.as_ref();
+    let field = schema.get_field(field_name).cloned().ok_or_else(|| {
+        OxenError::basic_str(format!(
+            "Field {field_name:?} does not exist in schema {}",
+            schema.hash
+        ))
+    })?;
+
+    if !is_indexable(&field) {
+        let err = format!(
+            "Cannot index field {:?} of type {}, only str and integer fields can be indexed",
+            field.name, field.dtype
+        );
+        return Err(OxenError::basic_str(err));
+    }
+
+    {
+        let writer = SchemaIndexWriter::new(repo, commit, schema)?;
+        writer.create_field_index(&field)?;
+    }
+
+    let paths = SchemaReader::new(repo, &commit.id)?.list_files_for_schema(&schema.hash)?;
+    let store = ChunkStore::new(repo);
+    for path in paths {
+        let entry = api::local::entries::get_entry_for_commit(repo, commit, &path)?
+            .ok_or_else(|| OxenError::file_does_not_exist_in_commit(&path, &commit.id))?;
+        let version_path = store.version_file(&entry)?;
+        index_file(repo, commit, schema, &field, &path, version_path.path())?;
+    }
+
+    Ok(field)
+}
+
+/// Re-indexes the files added in a commit, `data_dir` is where their committed contents live
+pub fn index_commit(
+    repo: &LocalRepository,
+    commit: &Commit,
+    schemas: &HashMap<std::path::PathBuf, Schema>,
+    data_dir: &Path,
+) -> Result<(), OxenError> {
+    for (path, schema) in schemas.iter() {
+        if !data_dir.join(path).exists() {
+            continue;
+        }
+        let fields = SchemaIndexReader::new(repo, commit, schema)?.list_field_indices()?;
+        for field in fields {
+            log::debug!("index_commit re-indexing {:?} field {}", path, field.name);
+            index_file(repo, commit, schema, &field, path, &data_dir.join(path))?;
+        }
+    }
+    Ok(())
+}
+
+fn index_file(
+    repo: &LocalRepository,
+    commit: &Commit,
+    schema: &Schema,
+    field: &Field,
+    path: &Path,
+    data_path: &Path,
+) -> Result<(), OxenError> {
+    let df = tabular::read_df(data_path, DFOpts::empty())?;
+    let column = df
+        .column(&field.name)
+        .map_err(|err| OxenError::basic_str(err.to_string()))?;
+
+    let mut rows: HashMap<String, Vec<u32>> = HashMap::new();
+    for (i, val) in column.iter().enumerate() {
+        let key = match val {
+            AnyValue::Null => continue,
+            AnyValue::Utf8(val) => val.to_string(),
+            val => val.to_string(),
+        };
+        rows.entry(key).or_default().push(i as u32);
+    }
+
+    let index = SchemaFieldValIndex::new(repo, commit, schema, field)?;
+    index.delete_file_indices(path)?;
+    for (value, indices) in rows {
+        index.insert_file_indices(path, value, indices)?;
+    }
+    Ok(())
+}
+
+/// The rows of the file at `path` where `field_name == value`, None if the field is not indexed
+pub fn lookup<S: AsRef<str>>(
+    repo: &LocalRepository,
+    commit: &Commit,
+    path: &Path,
+    field_name: S,
+    value: S,
+) -> Result<Option<Vec<u32>>, OxenError> {
+    let schema = match SchemaReader::new(repo, &commit.id)?.get_schema_for_file(path)? {
+        Some(schema) => schema,
+        None => return Ok(None),
+    };
+    let field =
+        match SchemaIndexReader::new(repo, commit, &schema)?.get_field_index_by_name(field_name)? {
+            Some(field) => field,
+            None => return Ok(None),
+        };
+
+    // Keys are written the way polars prints the value, so "01" has to become "1" for an integer field
+    let value = value.as_ref();
+    let key = if is_integer(&field) {
+        match value.parse::<i64>() {
+            Ok(val) => val.to_string(),
+            Err(_) => return Ok(None),
+        }
+    } else {
+        String::from(value)
+    };
+
+    match SchemaFieldValIndex::open_read_only(repo, commit, &schema, &field)? {
+        Some(index) => Ok(Some(index.get_file_indices(path, key)?.unwrap_or_default())),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::command;
+    use crate::error::OxenError;
+    use crate::index::schema_field_indexer;
+    use crate::test;
+    use crate::util;
+
+    use std::path::Path;
+
+    #[test]
+    fn test_create_index_and_lookup() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let labels = repo.path.join("labels.csv");
+            util::fs::write_to_path(&labels, "id,label\n1,cat\n2,dog\n3,cat\n4,fish\n")?;
+            command::add(&repo, &labels)?;
+            let commit = command::commit(&repo, "Adding labels")?.unwrap();
+
+            let schema = command::schema_list(&repo, Some(&commit.id))?
+                .pop()
+                .unwrap();
+            schema_field_indexer::create_index(&repo, &commit, &schema, "label")?;
+
+            let path = Path::new("labels.csv");
+            let rows = schema_field_indexer::lookup(&repo, &commit, path, "label", "cat")?;
+            assert_eq!(rows, Some(vec![0, 2]));
+            let rows = schema_field_indexer::lookup(&repo, &commit, path, "label", "lion")?;
+            assert_eq!(rows, Some(vec![]));
+            // Not indexed
+            let rows = schema_field_indexer::lookup(&repo, &commit, path, "id", "1")?;
+            assert_eq!(rows, None);
+
+            // The next commit re-indexes the file
+            util::fs::write_to_path(&labels, "id,label\n1,cat\n2,cat\n3,dog\n4,fish\n")?;
+            command::add(&repo, &labels)?;
+            let commit = command::commit(&repo, "Relabel")?.unwrap();
+            let rows = schema_field_indexer::lookup(&repo, &commit, path, "label", "cat")?;
+            assert_eq!(rows, Some(vec![0, 1]));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_create_index_unknown_field() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let labels = repo.path.join("labels.csv");
+            util::fs::write_to_path(&labels, "id,score\n1,0.5\n")?;
+            command::add(&repo, &labels)?;
+            let commit = command::commit(&repo, "Adding labels")?.unwrap();
+
+            let schema = command::schema_list(&repo, Some(&commit.id))?
+                .pop()
+                .unwrap();
+            assert!(schema_field_indexer::create_index(&repo, &commit, &schema, "label").is_err());
+            // Floats are not indexable
+            assert!(schema_field_indexer::create_index(&repo, &commit, &schema, "score").is_err());
+
+            Ok(())
+        })
+    }
+}
@@ -1,11 +1,12 @@
 use crate::constants::{FIELDS_DIR, HISTORY_DIR, INDICES_DIR};
 use crate::db;
 use crate::error::OxenError;
+use crate::index::HistoryPack;
 use crate::model::{schema::Field, Commit, Schema};
 use crate::util;
 
-use rocksdb::{DBWithThreadMode, MultiThreaded};
-use std::path::PathBuf;
+use rocksdb::{DBWithThreadMode, Direction, IteratorMode, MultiThreaded};
+use std::path::{Path, PathBuf};
 use std::str;
 
 use crate::model::LocalRepository;
@@ -50,6 +51,41 @@ impl SchemaFieldValIndex {
         })
     }
 
+    /// Opens an index that has already been built, None if the field was never indexed in this commit
+    pub fn open_read_only(
+        repository: &LocalRepository,
+        commit: &Commit,
+        schema: &Schema,
+        field: &Field,
+    ) -> Result<Option<SchemaFieldValIndex>, OxenError> {
+        let db_path = SchemaFieldValIndex::db_dir(repository, commit, schema, field);
+        if !db_path.exists() {
+            if !HistoryPack::has_packed_db(repository, &commit.id, &db_path)? {
+                return Ok(None);
+            }
+            return Ok(HistoryPack::open_db(repository, &commit.id, &db_path)?
+                .map(|field_indices_db| SchemaFieldValIndex { field_indices_db }));
+        }
+
+        let opts = db::opts::default();
+        Ok(Some(SchemaFieldValIndex {
+            field_indices_db: DBWithThreadMode::open_for_read_only(&opts, &db_path, false)?,
+        }))
+    }
+
+    // Many files can share a schema, so values are keyed by FILE_PATH_HASH:VALUE
+    fn file_key_prefix(path: &Path) -> String {
+        format!("{}:", util::hasher::hash_str(path.to_str().unwrap()))
+    }
+
+    fn file_key<S: AsRef<str>>(path: &Path, value: S) -> String {
+        format!(
+            "{}{}",
+            SchemaFieldValIndex::file_key_prefix(path),
+            value.as_ref()
+        )
+    }
+
     pub fn insert_index<S: AsRef<str>>(&self, key: S, indices: Vec<u32>) -> Result<(), OxenError> {
         db::index_db::insert_indices(&self.field_indices_db, key, indices)
     }
@@ -57,6 +93,39 @@ impl SchemaFieldValIndex {
     pub fn get_indices<S: AsRef<str>>(&self, key: S) -> Result<Option<Vec<u32>>, OxenError> {
         db::index_db::get_indices(&self.field_indices_db, key)
     }
+
+    /// Row indices within the file at `path` where the field equals `value`
+    pub fn insert_file_indices<S: AsRef<str>>(
+        &self,
+        path: &Path,
+        value: S,
+        indices: Vec<u32>,
+    ) -> Result<(), OxenError> {
+        self.insert_index(SchemaFieldValIndex::file_key(path, value), indices)
+    }
+
+    pub fn get_file_indices<S: AsRef<str>>(
+        &self,
+        path: &Path,
+        value: S,
+    ) -> Result<Option<Vec<u32>>, OxenError> {
+        self.get_indices(SchemaFieldValIndex::file_key(path, value))
+    }
+
+    /// Remove every value indexed for the file at `path`, before it is re-indexed
+    pub fn delete_file_indices(&self, path: &Path) -> Result<(), OxenError> {
+        let prefix = SchemaFieldValIndex::file_key_prefix(path);
+        let iter = self
+            .field_indices_db
+            .iterator(IteratorMode::From(prefix.as_bytes(), Direction::Forward));
+        for (key, _) in iter {
+            if !key.starts_with(prefix.as_bytes()) {
+                break;
+            }
+            self.field_indices_db.delete(key)?;
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -2,6 +2,7 @@ use crate::constants::{HISTORY_DIR, INDICES_DIR};
 use crate::db;
 use crate::db::str_json_db;
 use crate::error::OxenError;
+use crate::index::HistoryPack;
 use crate::model::{schema, Commit, Schema};
 use crate::util;
 
@@ -38,6 +39,12 @@ impl SchemaIndexReader {
         let field_indices_db_path =
             SchemaIndexReader::field_indices_db_dir(repository, commit, schema);
         log::debug!("SchemaIndexReader db {:?}", field_indices_db_path);
+        if let Some(field_indices_db) =
+            HistoryPack::open_db(repository, &commit.id, &field_indices_db_path)?
+        {
+            return Ok(SchemaIndexReader { field_indices_db });
+        }
+
         let opts = db::opts::default();
         if !field_indices_db_path.exists() {
             std::fs::create_dir_all(&field_indices_db_path)?;
@@ -2,6 +2,7 @@ use crate::constants::{FILES_DIR, HISTORY_DIR, SCHEMAS_DIR};
 use crate::db;
 use crate::db::{str_json_db, str_val_db};
 use crate::error::OxenError;
+use crate::index::HistoryPack;
 use crate::model::Schema;
 use crate::util;
 
@@ -40,6 +41,16 @@ impl SchemaReader {
         log::debug!("SchemaReader db {:?}", schema_db_path);
         let schema_files_db_path = SchemaReader::schema_files_db_dir(repository, commit_id);
         log::debug!("SchemaReader files db {:?}", schema_files_db_path);
+        if let (Some(schema_db), Some(schema_files_db)) = (
+            HistoryPack::open_db(repository, commit_id, &schema_db_path)?,
+            HistoryPack::open_db(repository, commit_id, &schema_files_db_path)?,
+        ) {
+            return Ok(SchemaReader {
+                schema_db,
+                schema_files_db,
+            });
+        }
+
         let opts = db::opts::default();
         if !schema_db_path.exists() {
             std::fs::create_dir_all(&schema_db_path)?;
@@ -95,6 +106,16 @@ impl SchemaReader {
     pub fn list_schemas(&self) -> Result<Vec<Schema>, OxenError> {
         str_json_db::list_vals(&self.schema_db)
     }
+
+    /// List the paths of all the files that have the schema
+    pub fn list_files_for_schema<S: AsRef<str>>(&self, hash: S) -> Result<Vec<PathBuf>, OxenError> {
+        let hash = hash.as_ref();
+        Ok(str_val_db::list::<String>(&self.schema_files_db)?
+            .into_iter()
+            .filter(|(_, file_hash)| file_hash == hash)
+            .map(|(path, _)| PathBuf::from(path))
+            .collect())
+    }
 }
 
 #[cfg(test)]
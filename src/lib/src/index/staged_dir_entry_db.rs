@@ -19,17 +19,29 @@ pub struct StagedDirEntryDB {
 impl StagedDirEntryDB {
     pub fn staging_dir(repo: &LocalRepository, dir: &Path) -> PathBuf {
         log::debug!("StagedDirEntryDB got repo path {:?}", repo.path);
-        util::fs::oxen_hidden_dir(&repo.path)
-            .join(Path::new(STAGED_DIR))
-            .join("files")
-            .join(dir)
+        let staged_dir = util::fs::oxen_hidden_dir(&repo.path).join(Path::new(STAGED_DIR));
+        StagedDirEntryDB::staging_dir_in(&staged_dir, dir)
+    }
+
+    fn staging_dir_in(staged_dir: &Path, dir: &Path) -> PathBuf {
+        staged_dir.join("files").join(dir)
     }
 
     /// # Create new staged dir
     /// Contains all the staged files within that dir, for faster filtering during `oxen status`
     pub fn new(repository: &LocalRepository, dir: &Path) -> Result<StagedDirEntryDB, OxenError> {
+        let staged_dir = util::fs::oxen_hidden_dir(&repository.path).join(Path::new(STAGED_DIR));
+        StagedDirEntryDB::new_in(&staged_dir, repository, dir)
+    }
+
+    /// Same as `new`, for a stager that keeps its staged files in `staged_dir` instead of the repository's
+    pub fn new_in(
+        staged_dir: &Path,
+        repository: &LocalRepository,
+        dir: &Path,
+    ) -> Result<StagedDirEntryDB, OxenError> {
         log::debug!("StagedDirEntryDB got dir {:?}", dir);
-        let db_path = StagedDirEntryDB::staging_dir(repository, dir);
+        let db_path = StagedDirEntryDB::staging_dir_in(staged_dir, dir);
 
         log::debug!("StagedDirEntryDB db_path {:?}", db_path);
         if !db_path.exists() {
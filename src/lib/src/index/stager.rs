@@ -5,7 +5,7 @@ use crate::df::tabular;
 use crate::df::DFOpts;
 use crate::error::OxenError;
 use crate::index::{
-    CommitDirEntryReader, CommitDirReader, CommitReader, MergeConflictReader, Merger,
+    ChunkStore, CommitDirEntryReader, CommitDirReader, CommitReader, MergeConflictReader, Merger,
     StagedDirEntryDB,
 };
 
@@ -15,6 +15,7 @@ use crate::model::{
     StagedEntryStatus,
 };
 use crate::util;
+use crate::util::{OxenIgnore, SparseFilter};
 
 use filetime::FileTime;
 use indicatif::ProgressBar;
@@ -26,8 +27,10 @@ use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::str;
+use std::sync::Arc;
 
 pub const STAGED_DIR: &str = "staged";
+const STAGED_CONTENTS_DIR: &str = "contents";
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum FileStatus {
@@ -40,15 +43,21 @@ pub enum FileStatus {
 pub struct Stager {
     dir_db: DBWithThreadMode<MultiThreaded>,
     schemas_db: DBWithThreadMode<MultiThreaded>,
+    // Where the staged files are kept, the repository's `.oxen/staged` unless made with `new_in_dir`
+    staged_dir: PathBuf,
     pub repository: LocalRepository,
     merger: Option<Merger>,
+    ignore: Arc<OxenIgnore>,
+    sparse: Option<SparseFilter>,
 }
 
 impl Stager {
-    pub fn dirs_db_path(path: &Path) -> Result<PathBuf, OxenError> {
-        let path = util::fs::oxen_hidden_dir(path)
-            .join(Path::new(STAGED_DIR))
-            .join(constants::DIRS_DIR);
+    pub fn staged_dir(path: &Path) -> PathBuf {
+        util::fs::oxen_hidden_dir(path).join(Path::new(STAGED_DIR))
+    }
+
+    pub fn dirs_db_path(staged_dir: &Path) -> Result<PathBuf, OxenError> {
+        let path = staged_dir.join(constants::DIRS_DIR);
 
         log::debug!("Stager new dir dir_db_path {:?}", path);
         if !path.exists() {
@@ -58,10 +67,8 @@ impl Stager {
         Ok(path)
     }
 
-    pub fn schemas_db_path(path: &Path) -> Result<PathBuf, OxenError> {
-        let path = util::fs::oxen_hidden_dir(path)
-            .join(Path::new(STAGED_DIR))
-            .join(constants::SCHEMAS_DIR);
+    pub fn schemas_db_path(staged_dir: &Path) -> Result<PathBuf, OxenError> {
+        let path = staged_dir.join(constants::SCHEMAS_DIR);
         log::debug!("Stager new dir schemas_db_path {:?}", path);
         if !path.exists() {
             std::fs::create_dir_all(&path)?;
@@ -69,33 +76,94 @@ impl Stager {
         Ok(path)
     }
 
+    /// Where `add` keeps a copy of each staged file, so that what was staged survives later edits to the
+    /// working tree until it is committed
+    pub fn staged_contents_dir(path: &Path) -> PathBuf {
+        Stager::staged_dir(path).join(STAGED_CONTENTS_DIR)
+    }
+
+    pub fn staged_contents_path(repo: &LocalRepository, hash: &str) -> PathBuf {
+        Stager::contents_path_in(&Stager::staged_dir(&repo.path), hash)
+    }
+
+    fn contents_path_in(staged_dir: &Path, hash: &str) -> PathBuf {
+        staged_dir
+            .join(STAGED_CONTENTS_DIR)
+            .join(&hash[..2])
+            .join(&hash[2..])
+    }
+
     pub fn new(repository: &LocalRepository) -> Result<Stager, OxenError> {
-        let dir_db_path = Stager::dirs_db_path(&repository.path)?;
-        let schemas_db_path = Stager::schemas_db_path(&repository.path)?;
+        Stager::new_in_dir(repository, &Stager::staged_dir(&repository.path))
+    }
+
+    /// Stages into `staged_dir` instead of the repository's `.oxen/staged`, to work out a status without
+    /// touching what the user has staged. Commits still take the contents from the repository's staged
+    /// files or the working tree.
+    pub fn new_in_dir(
+        repository: &LocalRepository,
+        staged_dir: &Path,
+    ) -> Result<Stager, OxenError> {
+        let dir_db_path = Stager::dirs_db_path(staged_dir)?;
+        let schemas_db_path = Stager::schemas_db_path(staged_dir)?;
 
         let opts = db::opts::default();
         Ok(Stager {
             dir_db: DBWithThreadMode::open(&opts, dir_db_path)?,
             schemas_db: DBWithThreadMode::open(&opts, schemas_db_path)?,
+            staged_dir: staged_dir.to_path_buf(),
             repository: repository.clone(),
             merger: None,
+            ignore: Arc::new(OxenIgnore::new(&repository.path)),
+            sparse: repository.sparse_filter(),
         })
     }
 
     pub fn new_with_merge(repository: &LocalRepository) -> Result<Stager, OxenError> {
-        let dir_db_path = Stager::dirs_db_path(&repository.path)?;
-        let schemas_db_path = Stager::schemas_db_path(&repository.path)?;
+        let staged_dir = Stager::staged_dir(&repository.path);
+        let dir_db_path = Stager::dirs_db_path(&staged_dir)?;
+        let schemas_db_path = Stager::schemas_db_path(&staged_dir)?;
 
         let opts = db::opts::default();
         Ok(Stager {
             dir_db: DBWithThreadMode::open(&opts, dir_db_path)?,
             schemas_db: DBWithThreadMode::open(&opts, schemas_db_path)?,
+            staged_dir,
             repository: repository.clone(),
             merger: Some(Merger::new(&repository.clone())?),
+            ignore: Arc::new(OxenIgnore::new(&repository.path)),
+            sparse: repository.sparse_filter(),
         })
     }
 
     pub fn add(&self, path: &Path, commit_reader: &CommitDirReader) -> Result<(), OxenError> {
+        self.p_add(path, commit_reader, false)
+    }
+
+    /// Same as `add` but stages the path even if it matches an .oxenignore pattern
+    pub fn add_force(&self, path: &Path, commit_reader: &CommitDirReader) -> Result<(), OxenError> {
+        self.p_add(path, commit_reader, true)
+    }
+
+    /// Checks the .oxenignore files to see if a path should be skipped
+    pub fn is_ignored<P: AsRef<Path>>(&self, path: P, is_dir: bool) -> bool {
+        self.ignore.is_ignored(path, is_dir)
+    }
+
+    /// Entries outside the spec of a sparse clone are absent on purpose, so they are never removed
+    fn is_in_sparse_tree<P: AsRef<Path>>(&self, path: P) -> bool {
+        match &self.sparse {
+            Some(filter) => filter.includes(path),
+            None => true,
+        }
+    }
+
+    fn p_add(
+        &self,
+        path: &Path,
+        commit_reader: &CommitDirReader,
+        force: bool,
+    ) -> Result<(), OxenError> {
         if path
             .to_str()
             .unwrap()
@@ -112,12 +180,20 @@ impl Stager {
             for entry in (std::fs::read_dir(path)?).flatten() {
                 let path = entry.path();
                 let entry_path = self.repository.path.join(path);
-                self.add(&entry_path, commit_reader)?;
+                if !force && self.is_ignored(&entry_path, entry_path.is_dir()) {
+                    log::debug!("stager.add skipping ignored path {:?}", entry_path);
+                    continue;
+                }
+                self.p_add(&entry_path, commit_reader, force)?;
             }
             log::debug!("ADD CURRENT DIR: {:?}", path);
             return Ok(());
         }
 
+        if !force && path.exists() && self.is_ignored(path, path.is_dir()) {
+            return Err(OxenError::path_is_ignored(path));
+        }
+
         // If it doesn't exist on disk, it might have been removed, and we can't tell if it is a file or dir
         // so we have to check if it is committed, and what the backup version is
         if !path.exists() {
@@ -128,7 +204,9 @@ impl Stager {
             );
             // Since entries that are committed are only files.. we will have to have different logic for dirs
             if let Ok(Some(value)) = commit_reader.get_entry(&relative_path) {
-                self.add_removed_file(&relative_path, &value)?;
+                if self.is_in_sparse_tree(&relative_path) {
+                    self.add_removed_file(&relative_path, &value)?;
+                }
                 return Ok(());
             }
 
@@ -140,7 +218,9 @@ impl Stager {
             );
             if !files_in_dir.is_empty() {
                 for entry in files_in_dir.iter() {
-                    self.add_removed_file(&entry.path, entry)?;
+                    if self.is_in_sparse_tree(&entry.path) {
+                        self.add_removed_file(&entry.path, entry)?;
+                    }
                 }
 
                 log::debug!(
@@ -153,7 +233,7 @@ impl Stager {
 
         log::debug!("Stager.add() is_dir? {} path: {:?}", path.is_dir(), path);
         if path.is_dir() {
-            match self.add_dir(path, commit_reader) {
+            match self.p_add_dir(path, commit_reader, force) {
                 Ok(_) => Ok(()),
                 Err(err) => Err(err),
             }
@@ -249,7 +329,8 @@ impl Stager {
         let commit = committer.head_commit()?;
         let root_commit_dir_reader = CommitDirReader::new(&self.repository, &commit)?;
         let relative_dir = util::fs::path_relative_to_dir(full_dir, &self.repository.path)?;
-        let staged_dir_db = StagedDirEntryDB::new(&self.repository, &relative_dir)?;
+        let staged_dir_db =
+            StagedDirEntryDB::new_in(&self.staged_dir, &self.repository, &relative_dir)?;
         let root_commit_entry_reader =
             CommitDirEntryReader::new(&self.repository, &commit.id, &relative_dir)?;
 
@@ -262,7 +343,21 @@ impl Stager {
             // Files in working directory as candidates
             for path in read_dir? {
                 let path = path?.path();
+                let is_dir = path.is_dir();
                 let path = util::fs::path_relative_to_dir(&path, &self.repository.path)?;
+                // Ignored paths only show up if they were force added
+                if self.is_ignored(&path, is_dir) {
+                    let is_staged = if is_dir {
+                        self.has_staged_dir(&path)
+                    } else {
+                        path.file_name()
+                            .map(|name| staged_dir_db.has_entry(name))
+                            .unwrap_or(false)
+                    };
+                    if !is_staged {
+                        continue;
+                    }
+                }
                 // log::debug!("adding candidate from dir {:?}", path);
                 candidate_files.insert(path);
             }
@@ -330,7 +425,9 @@ impl Stager {
                             staged_data.modified_files.push(relative.to_path_buf());
                         }
                         FileStatus::Removed => {
-                            staged_data.removed_files.push(relative.to_path_buf());
+                            if self.is_in_sparse_tree(relative) {
+                                staged_data.removed_files.push(relative.to_path_buf());
+                            }
                         }
                     }
                 }
@@ -430,13 +527,101 @@ impl Stager {
                 filename,
                 parent
             );
-            let staged_dir = StagedDirEntryDB::new(&self.repository, parent)?;
+            let staged_dir = StagedDirEntryDB::new_in(&self.staged_dir, &self.repository, parent)?;
             staged_dir.remove_path(filename)
         } else {
             Err(OxenError::file_has_no_parent(path))
         }
     }
 
+    /// Stages the contents `entry` was committed with instead of the working tree file, which is left alone.
+    /// Nothing is staged if `entry_reader` already has those contents at the path.
+    pub fn add_version(
+        &self,
+        entry: &CommitEntry,
+        entry_reader: &CommitDirReader,
+    ) -> Result<(), OxenError> {
+        let contents_path = Stager::contents_path_in(&self.staged_dir, &entry.hash);
+        if !contents_path.exists() {
+            let tmp_path = self.staged_contents_tmp_path(&contents_path)?;
+            ChunkStore::new(&self.repository).copy_version_to(entry, &tmp_path)?;
+            fs::rename(&tmp_path, &contents_path)?;
+        }
+        // The working tree file may not match, so a commit must not take its timestamps
+        filetime::set_file_mtime(&contents_path, FileTime::zero())?;
+
+        self.stage_hash(&entry.path, &entry.hash, entry_reader)
+    }
+
+    /// Stages the removal of the path, whether or not it is still in the working tree
+    pub fn add_removed(
+        &self,
+        path: &Path,
+        entry_reader: &CommitDirReader,
+    ) -> Result<(), OxenError> {
+        match entry_reader.get_entry(path)? {
+            Some(entry) => {
+                self.add_removed_file(path, &entry)?;
+                Ok(())
+            }
+            None => self.remove_staged_file(path),
+        }
+    }
+
+    /// Recomputes the status of a staged path against `entry_reader`, for when HEAD moved but the
+    /// staged contents stay the same
+    pub fn restage(&self, path: &Path, entry_reader: &CommitDirReader) -> Result<(), OxenError> {
+        match self.get_entry(self.repository.path.join(path))? {
+            Some(entry) if entry.status == StagedEntryStatus::Removed => {
+                self.add_removed(path, entry_reader)
+            }
+            Some(entry) => self.stage_hash(path, &entry.hash, entry_reader),
+            None => Ok(()),
+        }
+    }
+
+    fn stage_hash(
+        &self,
+        path: &Path,
+        hash: &str,
+        entry_reader: &CommitDirReader,
+    ) -> Result<(), OxenError> {
+        let status = match entry_reader.get_entry(path)? {
+            Some(entry) if entry.hash == hash => return self.remove_staged_file(path),
+            Some(_) => StagedEntryStatus::Modified,
+            None => StagedEntryStatus::Added,
+        };
+        let staged_entry = StagedEntry {
+            hash: hash.to_string(),
+            status,
+        };
+        let parent = path
+            .parent()
+            .ok_or_else(|| OxenError::file_has_no_parent(path))?;
+        let staged_db = StagedDirEntryDB::new_in(&self.staged_dir, &self.repository, parent)?;
+        self.add_staged_entry_to_db(path, &staged_entry, &staged_db)
+    }
+
+    // Copies the file being staged, it is only ever written once per hash
+    fn keep_staged_contents(&self, path: &Path, hash: &str) -> Result<(), OxenError> {
+        let contents_path = Stager::contents_path_in(&self.staged_dir, hash);
+        if contents_path.exists() {
+            return Ok(());
+        }
+        let tmp_path = self.staged_contents_tmp_path(&contents_path)?;
+        fs::copy(path, &tmp_path)?;
+        fs::rename(&tmp_path, &contents_path)?;
+        Ok(())
+    }
+
+    // Written next to the final path first, so an add of the same contents in parallel never sees a partial file
+    fn staged_contents_tmp_path(&self, contents_path: &Path) -> Result<PathBuf, OxenError> {
+        if let Some(parent) = contents_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        Ok(contents_path.with_extension(format!("tmp.{}", uuid::Uuid::new_v4())))
+    }
+
     fn add_removed_file(&self, path: &Path, entry: &CommitEntry) -> Result<StagedEntry, OxenError> {
         log::debug!("add_removed_file {:?}", path);
         if let (Some(parent), Some(filename)) = (path.parent(), path.file_name()) {
@@ -445,7 +630,7 @@ impl Stager {
                 filename,
                 parent
             );
-            let staged_dir = StagedDirEntryDB::new(&self.repository, parent)?;
+            let staged_dir = StagedDirEntryDB::new_in(&self.staged_dir, &self.repository, parent)?;
             staged_dir.add_removed_file(filename, entry)
         } else {
             Err(OxenError::file_has_no_parent(path))
@@ -457,21 +642,35 @@ impl Stager {
     fn list_unadded_files_in_dir<P: AsRef<Path>>(
         &self,
         dir: P,
+        force: bool,
     ) -> (HashMap<PathBuf, Vec<PathBuf>>, usize) {
         let mut files: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
         let mut total: usize = 0;
         let repository = self.repository.to_owned();
+        let staged_dir = self.staged_dir.to_owned();
+        let ignore = Arc::clone(&self.ignore);
         for dir_entry_result in WalkDirGeneric::<((), Option<bool>)>::new(&dir)
             .skip_hidden(true)
             .parallelism(jwalk::Parallelism::RayonDefaultPool)
             .process_read_dir(move |_, parent, _, dir_entry_results| {
+                // Dropping ignored entries here also keeps jwalk from descending into ignored dirs
+                if !force {
+                    dir_entry_results.retain(|dir_entry_result| match dir_entry_result {
+                        Ok(dir_entry) => {
+                            !ignore.is_ignored(dir_entry.path(), dir_entry.file_type.is_dir())
+                        }
+                        Err(_) => true,
+                    });
+                }
+
                 let parent = util::fs::path_relative_to_dir(parent, &repository.path).unwrap();
                 log::debug!(
                     "list_unadded_files_in_dir process_dir {:?} with {} files",
                     parent,
                     dir_entry_results.len()
                 );
-                let staged_dir_db = StagedDirEntryDB::new(&repository, &parent).unwrap();
+                let staged_dir_db =
+                    StagedDirEntryDB::new_in(&staged_dir, &repository, &parent).unwrap();
 
                 dir_entry_results
                     .par_iter_mut()
@@ -530,6 +729,15 @@ impl Stager {
     }
 
     pub fn add_dir(&self, dir: &Path, entry_reader: &CommitDirReader) -> Result<(), OxenError> {
+        self.p_add_dir(dir, entry_reader, false)
+    }
+
+    fn p_add_dir(
+        &self,
+        dir: &Path,
+        entry_reader: &CommitDirReader,
+        force: bool,
+    ) -> Result<(), OxenError> {
         if !dir.exists() || !dir.is_dir() {
             let err = format!("Cannot stage non-existant dir: {dir:?}");
             return Err(OxenError::basic_str(err));
@@ -541,14 +749,15 @@ impl Stager {
         path_db::put(&self.dir_db, &short_path, &0)?;
 
         // Add all untracked files and modified files
-        let (dir_paths, total) = self.list_unadded_files_in_dir(dir);
+        let (dir_paths, total) = self.list_unadded_files_in_dir(dir, force);
         log::debug!("Stager.add_dir {:?} -> {}", dir, total);
 
         println!("Adding files in directory: {short_path:?}");
         let size: u64 = unsafe { std::mem::transmute(total) };
         let bar = ProgressBar::new(size);
         dir_paths.par_iter().for_each(|(parent, paths)| {
-            let staged_db = StagedDirEntryDB::new(&self.repository, parent).unwrap();
+            let staged_db =
+                StagedDirEntryDB::new_in(&self.staged_dir, &self.repository, parent).unwrap();
             let entry_reader =
                 CommitDirEntryReader::new(&self.repository, &entry_reader.commit_id, parent)
                     .unwrap();
@@ -580,7 +789,9 @@ impl Stager {
         let path = path.as_ref();
         if let Ok(relative) = util::fs::path_relative_to_dir(path, &self.repository.path) {
             if let Some(parent) = relative.parent() {
-                if let Ok(staged_dir) = StagedDirEntryDB::new(&self.repository, parent) {
+                if let Ok(staged_dir) =
+                    StagedDirEntryDB::new_in(&self.staged_dir, &self.repository, parent)
+                {
                     let filename = relative.file_name().unwrap().to_str().unwrap();
                     return staged_dir.has_entry(filename);
                 } else {
@@ -603,7 +814,8 @@ impl Stager {
                 log::debug!("get_entry got parent for path {:?} -> {:?}", path, parent);
                 log::debug!("get_entry relative {:?}", file_name);
 
-                let staged_db = StagedDirEntryDB::new(&self.repository, parent)?;
+                let staged_db =
+                    StagedDirEntryDB::new_in(&self.staged_dir, &self.repository, parent)?;
                 return staged_db.get_entry(file_name);
             } else {
                 log::warn!("get_entry could not get file_name: {:?}", path);
@@ -675,7 +887,8 @@ impl Stager {
         log::debug!("add_staged_entry {:?}", path);
         if let Some(parent) = path.parent() {
             let relative_parent = util::fs::path_relative_to_dir(parent, &self.repository.path)?;
-            let staged_db = StagedDirEntryDB::new(&self.repository, &relative_parent)?;
+            let staged_db =
+                StagedDirEntryDB::new_in(&self.staged_dir, &self.repository, &relative_parent)?;
             let entry_reader = CommitDirEntryReader::new(
                 &self.repository,
                 &entry_reader.commit_id,
@@ -708,6 +921,7 @@ impl Stager {
         // if repository: /Users/username/Datasets/MyRepo
         //   /Users/username/Datasets/MyRepo/train -> train
         //   /Users/username/Datasets/MyRepo/annotations/train.txt -> annotations/train.txt
+        let full_path = path;
         let path = util::fs::path_relative_to_dir(path, &self.repository.path)?;
 
         let mut staged_entry = StagedEntry {
@@ -719,6 +933,7 @@ impl Stager {
         if let Some(merger) = &self.merger {
             if merger.has_file(&path)? {
                 log::debug!("add_staged_entry_in_dir_db merger has file! {:?}", path);
+                self.keep_staged_contents(full_path, &hash)?;
                 self.add_staged_entry_to_db(&path, &staged_entry, staged_db)?;
                 merger.remove_conflict_path(&path)?;
                 return Ok(path);
@@ -752,6 +967,7 @@ impl Stager {
         }
 
         log::debug!("add_staged_entry_in_dir_db {:?} {:?}", path, staged_entry);
+        self.keep_staged_contents(full_path, &hash)?;
         self.add_staged_entry_to_db(&path, &staged_entry, staged_db)?;
 
         Ok(path)
@@ -810,7 +1026,7 @@ impl Stager {
 
     fn list_added_files_in_dir(&self, dir: &Path) -> Result<Vec<PathBuf>, OxenError> {
         let relative = util::fs::path_relative_to_dir(dir, &self.repository.path)?;
-        let staged_dir = StagedDirEntryDB::new(&self.repository, &relative)?;
+        let staged_dir = StagedDirEntryDB::new_in(&self.staged_dir, &self.repository, &relative)?;
         staged_dir.list_added_paths()
     }
 
@@ -858,7 +1074,8 @@ impl Stager {
         let mut paths: Vec<PathBuf> = vec![];
         for short_path in entry_reader.list_files()? {
             let path = self.repository.path.join(&short_path);
-            if !path.exists() && !self.has_entry(&short_path) {
+            if !path.exists() && !self.has_entry(&short_path) && self.is_in_sparse_tree(&short_path)
+            {
                 paths.push(short_path);
             }
         }
@@ -894,6 +1111,10 @@ impl Stager {
                     continue;
                 }
 
+                if self.is_ignored(&relative_path, false) {
+                    continue;
+                }
+
                 // File is staged
                 if !self.has_entry(&relative_path) {
                     paths.push(relative_path);
@@ -916,7 +1137,8 @@ impl Stager {
                 log::debug!("Removing files from added_dir: {:?}", added_dir);
 
                 // Remove all files within that dir
-                let staged_dir = StagedDirEntryDB::new(&self.repository, added_dir)?;
+                let staged_dir =
+                    StagedDirEntryDB::new_in(&self.staged_dir, &self.repository, added_dir)?;
                 staged_dir.unstage()?;
 
                 // Remove from dir db
@@ -932,19 +1154,26 @@ impl Stager {
         log::debug!("Unstage dirs: {}", added_dirs.len());
         for dir in added_dirs {
             log::debug!("Unstaging dir: {:?}", dir);
-            let staged_dir = StagedDirEntryDB::new(&self.repository, &dir)?;
+            let staged_dir = StagedDirEntryDB::new_in(&self.staged_dir, &self.repository, &dir)?;
             staged_dir.unstage()?;
         }
-        let staged_dir_db = StagedDirEntryDB::new(&self.repository, Path::new(""))?;
+        let staged_dir_db =
+            StagedDirEntryDB::new_in(&self.staged_dir, &self.repository, Path::new(""))?;
         staged_dir_db.unstage()?;
         path_db::clear(&self.dir_db)?;
         path_db::clear(&self.schemas_db)?;
+
+        let contents_dir = self.staged_dir.join(STAGED_CONTENTS_DIR);
+        if contents_dir.exists() {
+            fs::remove_dir_all(contents_dir)?;
+        }
         Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::constants;
     use crate::error::OxenError;
     use crate::index::{CommitDirReader, CommitReader, CommitWriter, Stager};
     use crate::model::StagedEntryStatus;
@@ -1289,6 +1518,25 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_stager_list_untracked_files_skips_ignored() -> Result<(), OxenError> {
+        test::run_empty_stager_test(|stager, _repo| {
+            let entry_reader = CommitDirReader::new_from_head(&stager.repository)?;
+
+            let repo_path = &stager.repository.path;
+            let ignore_file = repo_path.join(constants::OXEN_IGNORE_FILE);
+            util::fs::write_to_path(&ignore_file, "*.ckpt\n")?;
+            let _ = test::add_file_to_dir(repo_path, "weights", "ckpt")?;
+
+            // Only the .oxenignore file shows up
+            let files = stager.list_untracked_files(&entry_reader)?;
+            assert_eq!(files.len(), 1);
+            assert_eq!(files[0], PathBuf::from(constants::OXEN_IGNORE_FILE));
+
+            Ok(())
+        })
+    }
+
     #[test]
     fn test_stager_list_modified_files() -> Result<(), OxenError> {
         test::run_empty_stager_test(|stager, repo| {
@@ -1580,4 +1828,38 @@ mod tests {
             Ok(())
         })
     }
+
+    #[test]
+    fn test_stager_in_dir_leaves_index_alone() -> Result<(), OxenError> {
+        test::run_empty_stager_test(|stager, repo| {
+            let entry_reader = CommitDirReader::new_from_head(&repo)?;
+            let hello_file = test::add_txt_file_to_dir(&repo.path, "Hello World")?;
+            let sub_dir = repo.path.join("training_data");
+            std::fs::create_dir_all(&sub_dir)?;
+            test::add_txt_file_to_dir(&sub_dir, "Hello 1")?;
+
+            let scratch_dir = util::fs::oxen_hidden_dir(&repo.path)
+                .join(constants::TMP_DIR)
+                .join("scratch");
+            {
+                let scratch = Stager::new_in_dir(&repo, &scratch_dir)?;
+                scratch.add(&repo.path, &entry_reader)?;
+                let status = scratch.status(&entry_reader)?;
+                assert_eq!(status.added_files.len(), 2);
+            }
+
+            // Nothing got staged in the repository
+            let status = stager.status(&entry_reader)?;
+            assert!(status.added_files.is_empty());
+            assert!(status.added_dirs.paths.is_empty());
+            assert_eq!(
+                status.untracked_files,
+                vec![PathBuf::from(hello_file.file_name().unwrap())]
+            );
+            assert_eq!(status.untracked_dirs.len(), 1);
+
+            std::fs::remove_dir_all(scratch_dir)?;
+            Ok(())
+        })
+    }
 }
@@ -0,0 +1,433 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::constants;
+use crate::error::OxenError;
+use crate::index::{
+    self, CommitDirReader, CommitReader, CommitWriter, MergeConflictReader, Merger, RefReader,
+    RefWriter, Stager,
+};
+use crate::model::{Commit, LocalRepository, MergeConflict, StagedData, Stash};
+use crate::opts::ResetMode;
+use crate::util;
+
+pub struct Stasher {
+    repository: LocalRepository,
+}
+
+impl Stasher {
+    pub fn new(repo: &LocalRepository) -> Result<Stasher, OxenError> {
+        Ok(Stasher {
+            repository: repo.to_owned(),
+        })
+    }
+
+    /// Saves what is staged in an index commit on HEAD, and the staged, modified, removed and untracked files in
+    /// a commit on top of HEAD and the index commit. Neither is on any branch. Records the stash in the stash refs,
+    /// then resets the working directory back to HEAD.
+    pub fn push(&self, message: Option<&str>) -> Result<Stash, OxenError> {
+        let repo = &self.repository;
+        if MergeConflictReader::new(repo)?.has_conflicts()? {
+            return Err(OxenError::basic_str(
+                "Cannot stash with merge conflicts, fix and add them first",
+            ));
+        }
+
+        let head = CommitReader::new(repo)?.head_commit()?;
+        let head_reader = CommitDirReader::new(repo, &head)?;
+        // What is staged now, its contents are kept by the stager even if the files were edited since.
+        // The stager has to be closed before the reset at the end opens it again.
+        let index_status = Stager::new(repo)?.status(&head_reader)?;
+
+        // The working tree gets staged in a scratch dir so that it lands in the stash commit, including the
+        // files that were edited after they were staged, while what the user staged is left alone
+        let scratch_dir = util::fs::oxen_hidden_dir(&repo.path)
+            .join(constants::TMP_DIR)
+            .join(format!("stash-{}", uuid::Uuid::new_v4()));
+        let status = self.working_tree_status(&scratch_dir, &head_reader, &index_status);
+        if scratch_dir.exists() {
+            std::fs::remove_dir_all(&scratch_dir)?;
+        }
+        let status = status?;
+        if !status.has_added_entries() {
+            return Err(OxenError::basic_str("No local changes to stash"));
+        }
+
+        let branch = RefReader::new(repo)?.get_current_branch()?.map(|b| b.name);
+        let message = match message {
+            Some(message) => String::from(message),
+            None => format!(
+                "WIP on {}: {} {}",
+                branch.as_deref().unwrap_or("(no branch)"),
+                head.id,
+                head.message
+            ),
+        };
+
+        let commit_writer = CommitWriter::new(repo)?;
+        let index_message = format!(
+            "index on {}: {} {}",
+            branch.as_deref().unwrap_or("(no branch)"),
+            head.id,
+            head.message
+        );
+        let index_commit = commit_writer.commit_off_branch(
+            &index_status,
+            vec![head.id.to_owned()],
+            &index_message,
+        )?;
+        let commit = commit_writer.commit_off_branch(
+            &status,
+            vec![head.id.to_owned(), index_commit.id.to_owned()],
+            &message,
+        )?;
+        let stash = Stash {
+            commit_id: commit.id.to_owned(),
+            branch,
+            message,
+            timestamp: commit.timestamp,
+        };
+        {
+            let ref_writer = RefWriter::new(repo)?;
+            ref_writer.push_stash(&stash)?;
+        }
+        log::debug!("stash push {} -> {}", head.id, stash.commit_id);
+
+        // A hard reset leaves untracked files alone, the ones that went into the stash are cleared too
+        index::reset(repo, &head, ResetMode::Hard)?;
+        let paths: HashSet<PathBuf> = status.added_files.keys().cloned().collect();
+        index::reset::reset_working_tree(repo, &head, &head_reader, &paths)?;
+        Ok(stash)
+    }
+
+    // Everything in the working tree and the index, as if it was all staged
+    fn working_tree_status(
+        &self,
+        scratch_dir: &Path,
+        head_reader: &CommitDirReader,
+        index_status: &StagedData,
+    ) -> Result<StagedData, OxenError> {
+        let repo = &self.repository;
+        let stager = Stager::new_in_dir(repo, scratch_dir)?;
+        stager.add(&repo.path, head_reader)?;
+        let paths = index_status
+            .added_files
+            .keys()
+            .chain(index_status.removed_files.iter());
+        for path in paths {
+            let full_path = repo.path.join(path);
+            if full_path.exists() || head_reader.has_file(path) {
+                stager.add(&full_path, head_reader)?;
+            }
+        }
+        stager.status(head_reader)
+    }
+
+    /// Newest first
+    pub fn list(&self) -> Result<Vec<Stash>, OxenError> {
+        RefReader::new(&self.repository)?.list_stashes()
+    }
+
+    pub fn get(&self, index: usize) -> Result<Stash, OxenError> {
+        self.list()?
+            .into_iter()
+            .nth(index)
+            .ok_or_else(|| OxenError::basic_str(format!("No stash entry stash@{{{index}}}")))
+    }
+
+    /// Applies the stash onto HEAD and stages what was staged when it was made, with the contents from the
+    /// index commit. Returns the conflicts if HEAD changed the same files since, which are recorded to be
+    /// fixed like a merge.
+    pub fn apply(&self, index: usize) -> Result<Vec<MergeConflict>, OxenError> {
+        let repo = &self.repository;
+        let stash = self.get(index)?;
+
+        let status = Stager::new(repo)?.status(&CommitDirReader::new_from_head(repo)?)?;
+        if status.has_added_entries()
+            || status.has_modified_entries()
+            || status.has_removed_entries()
+            || status.has_merge_conflicts()
+        {
+            return Err(OxenError::basic_str(
+                "Cannot apply a stash with local changes, commit or stash them first",
+            ));
+        }
+
+        let commit_reader = CommitReader::new(repo)?;
+        let stash_commit = commit_reader
+            .get_commit_by_id(&stash.commit_id)?
+            .ok_or_else(|| OxenError::commit_id_does_not_exist(&stash.commit_id))?;
+        let base = self.stash_base(&commit_reader, &stash_commit)?;
+        self.check_untracked_files(&stash_commit)?;
+
+        let conflicts = {
+            let merger = Merger::new(repo)?;
+            merger.apply_changes(&base, &stash_commit)?
+        };
+        log::debug!(
+            "stash apply {} conflicts.len() {}",
+            stash.commit_id,
+            conflicts.len()
+        );
+
+        let conflict_paths: HashSet<&Path> = conflicts
+            .iter()
+            .map(|c| c.head_entry.path.as_path())
+            .collect();
+        let index_id = stash_commit
+            .parent_ids
+            .get(1)
+            .ok_or_else(|| OxenError::commit_db_corrupted(&stash_commit.id))?;
+        let index_commit = commit_reader
+            .get_commit_by_id(index_id)?
+            .ok_or_else(|| OxenError::commit_db_corrupted(index_id))?;
+        let index_reader = CommitDirReader::new(repo, &index_commit)?;
+        let base_reader = CommitDirReader::new(repo, &base)?;
+
+        let stager = Stager::new(repo)?;
+        let head_reader = CommitDirReader::new_from_head(repo)?;
+        for path in index::reset::diff_paths(&base_reader, &index_reader)? {
+            if conflict_paths.contains(path.as_path()) {
+                continue;
+            }
+            match index_reader.get_entry(&path)? {
+                Some(entry) => stager.add_version(&entry, &head_reader)?,
+                None => stager.add_removed(&path, &head_reader)?,
+            }
+        }
+
+        Ok(conflicts)
+    }
+
+    /// Applies the stash and drops it, unless there were conflicts in which case it is kept around
+    pub fn pop(&self, index: usize) -> Result<Vec<MergeConflict>, OxenError> {
+        let conflicts = self.apply(index)?;
+        if conflicts.is_empty() {
+            self.remove(index)?;
+        }
+        Ok(conflicts)
+    }
+
+    pub fn remove(&self, index: usize) -> Result<Stash, OxenError> {
+        let stash = self.get(index)?;
+        let ref_writer = RefWriter::new(&self.repository)?;
+        ref_writer.delete_stash(&stash)?;
+        Ok(stash)
+    }
+
+    fn stash_base(
+        &self,
+        commit_reader: &CommitReader,
+        stash_commit: &Commit,
+    ) -> Result<Commit, OxenError> {
+        let parent_id = stash_commit
+            .parent_ids
+            .first()
+            .ok_or_else(|| OxenError::commit_db_corrupted(&stash_commit.id))?;
+        commit_reader
+            .get_commit_by_id(parent_id)?
+            .ok_or_else(|| OxenError::commit_db_corrupted(parent_id))
+    }
+
+    // Files the stash would create that are already sitting untracked in the working directory
+    fn check_untracked_files(&self, stash_commit: &Commit) -> Result<(), OxenError> {
+        let repo = &self.repository;
+        let head_reader = CommitDirReader::new_from_head(repo)?;
+        let stash_reader = CommitDirReader::new(repo, stash_commit)?;
+
+        let mut paths: Vec<String> = vec![];
+        for entry in stash_reader.list_entries()? {
+            let full_path = repo.path.join(&entry.path);
+            if !head_reader.has_file(&entry.path)
+                && full_path.is_file()
+                && util::hasher::hash_file_contents(&full_path)? != entry.hash
+            {
+                paths.push(format!("  {}", entry.path.to_string_lossy()));
+            }
+        }
+
+        if paths.is_empty() {
+            Ok(())
+        } else {
+            Err(OxenError::basic_str(format!(
+                "Cannot apply stash, these untracked files would be overwritten:\n{}",
+                paths.join("\n")
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::command;
+    use crate::error::OxenError;
+    use crate::index::{ChunkStore, CommitDirReader, CommitReader, Stasher};
+    use crate::test;
+    use crate::util;
+
+    #[test]
+    fn test_stash_push_and_pop_restores_working_tree() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let hello_file = repo.path.join("hello.txt");
+            let world_file = repo.path.join("world.txt");
+            util::fs::write_to_path(&hello_file, "Hello")?;
+            util::fs::write_to_path(&world_file, "World")?;
+            command::add(&repo, &hello_file)?;
+            command::add(&repo, &world_file)?;
+            let head = command::commit(&repo, "Adding hello and world")?.unwrap();
+
+            // Modified, staged, untracked and removed files
+            util::fs::write_to_path(&hello_file, "Hello Stash")?;
+            let staged_file = repo.path.join("staged.txt");
+            util::fs::write_to_path(&staged_file, "Staged")?;
+            command::add(&repo, &staged_file)?;
+            let untracked_file = repo.path.join("untracked").join("new.txt");
+            std::fs::create_dir_all(untracked_file.parent().unwrap())?;
+            util::fs::write_to_path(&untracked_file, "Untracked")?;
+            std::fs::remove_file(&world_file)?;
+
+            let stasher = Stasher::new(&repo)?;
+            let stash = stasher.push(None)?;
+            assert_eq!(stash.branch.as_deref(), Some("main"));
+            let stash_commit = CommitReader::new(&repo)?
+                .get_commit_by_id(&stash.commit_id)?
+                .unwrap();
+            assert_eq!(stash_commit.parent_ids.len(), 2);
+            assert_eq!(stash_commit.parent_ids[0], head.id);
+
+            // Back on a clean HEAD, which did not move
+            assert!(command::status(&repo)?.is_clean());
+            assert_eq!(command::head_commit(&repo)?.id, head.id);
+            assert_eq!(util::fs::read_from_path(&hello_file)?, "Hello");
+            assert!(world_file.exists());
+            assert!(!staged_file.exists());
+            assert!(!untracked_file.exists());
+            assert_eq!(stasher.list()?.len(), 1);
+
+            let conflicts = stasher.pop(0)?;
+            assert!(conflicts.is_empty());
+            assert!(stasher.list()?.is_empty());
+            assert_eq!(util::fs::read_from_path(&hello_file)?, "Hello Stash");
+            assert!(!world_file.exists());
+            assert!(untracked_file.exists());
+
+            // Only what was staged before is staged again
+            let status = command::status(&repo)?;
+            assert_eq!(status.added_files.len(), 1);
+            assert!(status.added_files.contains_key(Path::new("staged.txt")));
+            assert_eq!(status.modified_files.len(), 1);
+            assert_eq!(status.removed_files.len(), 1);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_stash_apply_on_other_branch_and_drop() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let hello_file = repo.path.join("hello.txt");
+            util::fs::write_to_path(&hello_file, "Hello")?;
+            command::add(&repo, &hello_file)?;
+            command::commit(&repo, "Adding hello")?;
+
+            command::create_checkout_branch(&repo, "feature")?;
+            let feature_file = repo.path.join("feature.txt");
+            util::fs::write_to_path(&feature_file, "Feature")?;
+            command::add(&repo, &feature_file)?;
+            command::commit(&repo, "Adding feature")?;
+            command::checkout(&repo, "main")?;
+
+            util::fs::write_to_path(&hello_file, "Hello Stash")?;
+            let stasher = Stasher::new(&repo)?;
+            stasher.push(Some("Editing hello"))?;
+            assert!(stasher.push(None).is_err());
+
+            command::checkout(&repo, "feature")?;
+            let conflicts = stasher.apply(0)?;
+            assert!(conflicts.is_empty());
+            assert_eq!(util::fs::read_from_path(&hello_file)?, "Hello Stash");
+            assert!(feature_file.exists());
+
+            // Apply keeps the entry around until it is dropped
+            let stashes = stasher.list()?;
+            assert_eq!(stashes.len(), 1);
+            assert_eq!(stashes[0].message, "Editing hello");
+            stasher.remove(0)?;
+            assert!(stasher.list()?.is_empty());
+            assert!(stasher.remove(0).is_err());
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_stash_pop_conflict_keeps_stash() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let hello_file = repo.path.join("hello.txt");
+            util::fs::write_to_path(&hello_file, "Hello")?;
+            command::add(&repo, &hello_file)?;
+            command::commit(&repo, "Adding hello")?;
+
+            util::fs::write_to_path(&hello_file, "Hello Stash")?;
+            let stasher = Stasher::new(&repo)?;
+            stasher.push(None)?;
+
+            util::fs::write_to_path(&hello_file, "Hello Commit")?;
+            command::add(&repo, &hello_file)?;
+            command::commit(&repo, "Editing hello")?;
+
+            let conflicts = stasher.pop(0)?;
+            assert_eq!(conflicts.len(), 1);
+            assert_eq!(stasher.list()?.len(), 1);
+            assert!(command::status(&repo)?.has_merge_conflicts());
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_stash_keeps_staged_contents_of_edited_file() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let hello_file = repo.path.join("hello.txt");
+            util::fs::write_to_path(&hello_file, "Hello")?;
+            command::add(&repo, &hello_file)?;
+            command::commit(&repo, "Adding hello")?;
+
+            // Staged, then edited again without staging
+            util::fs::write_to_path(&hello_file, "Hello Staged")?;
+            command::add(&repo, &hello_file)?;
+            let staged_hash = util::hasher::hash_file_contents(&hello_file)?;
+            util::fs::write_to_path(&hello_file, "Hello Edited")?;
+
+            let stasher = Stasher::new(&repo)?;
+            stasher.push(None)?;
+            assert_eq!(util::fs::read_from_path(&hello_file)?, "Hello");
+
+            let conflicts = stasher.pop(0)?;
+            assert!(conflicts.is_empty());
+            assert_eq!(util::fs::read_from_path(&hello_file)?, "Hello Edited");
+            let status = command::status(&repo)?;
+            assert_eq!(status.added_files.len(), 1);
+            assert_eq!(status.added_files[Path::new("hello.txt")].hash, staged_hash);
+
+            // Committing takes what was staged, and leaves the edit in the working tree
+            let commit = command::commit(&repo, "Staged hello")?.unwrap();
+            let entry = CommitDirReader::new(&repo, &commit)?
+                .get_entry(Path::new("hello.txt"))?
+                .unwrap();
+            assert_eq!(entry.hash, staged_hash);
+            let restored = repo.path.join("restored.txt");
+            ChunkStore::new(&repo).copy_version_to(&entry, &restored)?;
+            assert_eq!(util::fs::read_from_path(&restored)?, "Hello Staged");
+            std::fs::remove_file(&restored)?;
+            assert_eq!(
+                command::status(&repo)?.modified_files,
+                vec![Path::new("hello.txt").to_path_buf()]
+            );
+
+            Ok(())
+        })
+    }
+}
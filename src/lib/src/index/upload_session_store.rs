@@ -0,0 +1,316 @@
+//! # UploadSessionStore
+//!
+//! Server side bookkeeping for uploads that are too large for a single request.
+//!
+//! The pieces of an upload land in `.oxen/tmp/chunked/HASH/` next to a `SESSION` file that records which
+//! pieces have arrived. A client that lost its connection asks for the session and only sends what is missing.
+//! Sessions that have not seen a piece within the TTL are expired.
+
+use lazy_static::lazy_static;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use time::OffsetDateTime;
+
+use crate::constants::{CHUNKED_UPLOADS_DIR, TMP_DIR, UPLOAD_SESSION_FILE};
+use crate::error::OxenError;
+use crate::model::{LocalRepository, UploadSession};
+use crate::util;
+
+lazy_static! {
+    // Pieces of the same upload can arrive in parallel, so updates to a SESSION file take turns
+    static ref UPLOAD_SESSION_LOCK: Mutex<()> = Mutex::new(());
+}
+
+pub struct UploadSessionStore {
+    repository: LocalRepository,
+}
+
+impl UploadSessionStore {
+    pub fn new(repository: &LocalRepository) -> UploadSessionStore {
+        UploadSessionStore {
+            repository: repository.clone(),
+        }
+    }
+
+    pub fn uploads_dir(&self) -> PathBuf {
+        // .oxen/tmp/chunked
+        util::fs::oxen_hidden_dir(&self.repository.path)
+            .join(TMP_DIR)
+            .join(CHUNKED_UPLOADS_DIR)
+    }
+
+    /// The upload hash comes from the client and becomes a dir we remove, so it has to be a plain hex hash
+    pub fn validate_hash(hash: &str) -> Result<(), OxenError> {
+        if hash.is_empty() || hash.len() > 32 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(OxenError::basic_str(format!(
+                "Invalid upload hash '{hash}'"
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn session_dir(&self, hash: &str) -> Result<PathBuf, OxenError> {
+        UploadSessionStore::validate_hash(hash)?;
+        // .oxen/tmp/chunked/HASH
+        Ok(self.uploads_dir().join(hash))
+    }
+
+    fn session_path(&self, hash: &str) -> Result<PathBuf, OxenError> {
+        Ok(self.session_dir(hash)?.join(UPLOAD_SESSION_FILE))
+    }
+
+    fn chunk_path(&self, hash: &str, chunk_num: usize) -> Result<PathBuf, OxenError> {
+        Ok(self
+            .session_dir(hash)?
+            .join(format!("chunk_{chunk_num:016}")))
+    }
+
+    pub fn get(&self, hash: &str) -> Result<Option<UploadSession>, OxenError> {
+        let path = self.session_path(hash)?;
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let contents = util::fs::read_from_path(&path)?;
+        Ok(Some(serde_json::from_str(&contents)?))
+    }
+
+    /// Every upload that has not finished or expired yet
+    pub fn list(&self) -> Result<Vec<UploadSession>, OxenError> {
+        let uploads_dir = self.uploads_dir();
+        if !uploads_dir.exists() {
+            return Ok(vec![]);
+        }
+
+        let mut sessions: Vec<UploadSession> = vec![];
+        for entry in fs::read_dir(&uploads_dir)? {
+            if let Some(hash) = entry?.file_name().to_str() {
+                if UploadSessionStore::validate_hash(hash).is_err() {
+                    continue;
+                }
+                if let Some(session) = self.get(hash)? {
+                    sessions.push(session);
+                }
+            }
+        }
+        Ok(sessions)
+    }
+
+    /// Saves a piece of `upload`, starting the session if this is the first piece we have seen. If the client
+    /// rebuilt the data since it sent the pieces we have, they are thrown away and the session starts over.
+    pub fn put_chunk(
+        &self,
+        upload: &UploadSession,
+        chunk_num: usize,
+        data: &[u8],
+    ) -> Result<UploadSession, OxenError> {
+        let hash = &upload.hash;
+        let session_dir = self.session_dir(hash)?;
+        if chunk_num >= upload.total_chunks {
+            let total_chunks = upload.total_chunks;
+            let err = format!("Chunk {chunk_num} is out of range for {total_chunks} chunks");
+            return Err(OxenError::basic_str(err));
+        }
+
+        let _lock = UPLOAD_SESSION_LOCK.lock().unwrap();
+        let mut session = match self.get(hash)? {
+            Some(session) if session.has_same_contents(upload) => session,
+            Some(_) => {
+                log::debug!("UploadSessionStore restarting upload {}", hash);
+                self.remove(hash)?;
+                upload.to_owned()
+            }
+            None => upload.to_owned(),
+        };
+
+        if !session_dir.exists() {
+            fs::create_dir_all(&session_dir)?;
+        }
+
+        // Write to a tmp file first so a dropped connection never leaves a partial piece marked as received
+        let chunk_path = self.chunk_path(hash, chunk_num)?;
+        let tmp_path = chunk_path.with_extension(uuid::Uuid::new_v4().to_string());
+        fs::write(&tmp_path, data)?;
+        fs::rename(&tmp_path, &chunk_path)?;
+
+        if let Err(i) = session.received_chunks.binary_search(&chunk_num) {
+            session.received_chunks.insert(i, chunk_num);
+        }
+        session.updated_at = OffsetDateTime::now_utc();
+        util::fs::write_to_path(&self.session_path(hash)?, &serde_json::to_string(&session)?)?;
+
+        Ok(session)
+    }
+
+    /// The pieces of a complete upload put back together
+    pub fn read_all(&self, session: &UploadSession) -> Result<Vec<u8>, OxenError> {
+        let mut buffer: Vec<u8> = Vec::with_capacity(session.total_size);
+        for chunk_num in 0..session.total_chunks {
+            buffer.extend(fs::read(self.chunk_path(&session.hash, chunk_num)?)?);
+        }
+
+        if buffer.len() != session.total_size {
+            let err = format!(
+                "Upload {} has {} bytes, expected {}",
+                session.hash,
+                buffer.len(),
+                session.total_size
+            );
+            return Err(OxenError::basic_str(err));
+        }
+        if let Some(content_hash) = &session.content_hash {
+            if util::hasher::hash_buffer(&buffer) != *content_hash {
+                let err = format!("Upload {} does not match its content hash", session.hash);
+                return Err(OxenError::basic_str(err));
+            }
+        }
+        Ok(buffer)
+    }
+
+    pub fn remove(&self, hash: &str) -> Result<(), OxenError> {
+        let session_dir = self.session_dir(hash)?;
+        if session_dir.exists() {
+            fs::remove_dir_all(session_dir)?;
+        }
+        Ok(())
+    }
+
+    /// Removes the uploads that have not received a piece within `ttl`, returns how many were removed
+    pub fn expire_stale(&self, ttl: Duration) -> Result<usize, OxenError> {
+        let uploads_dir = self.uploads_dir();
+        if !uploads_dir.exists() {
+            return Ok(0);
+        }
+
+        let mut num_expired = 0;
+        for entry in fs::read_dir(&uploads_dir)? {
+            let path = entry?.path();
+            // Anything else in there was not put there by us
+            let hash = match path.file_name().and_then(|name| name.to_str()) {
+                Some(hash) if UploadSessionStore::validate_hash(hash).is_ok() => hash.to_string(),
+                _ => continue,
+            };
+
+            // Uploads from before sessions were tracked only have the modified time of their dir
+            let age = match self.get(&hash) {
+                Ok(Some(session)) => {
+                    let age = OffsetDateTime::now_utc() - session.updated_at;
+                    Duration::from_secs(age.whole_seconds().max(0) as u64)
+                }
+                _ => SystemTime::now()
+                    .duration_since(fs::metadata(&path)?.modified()?)
+                    .unwrap_or_default(),
+            };
+
+            if age >= ttl {
+                log::debug!("UploadSessionStore expiring upload {} age {:?}", hash, age);
+                self.remove(&hash)?;
+                num_expired += 1;
+            }
+        }
+        Ok(num_expired)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::OxenError;
+    use crate::index::UploadSessionStore;
+    use crate::model::UploadSession;
+    use crate::test;
+    use crate::util;
+
+    use std::time::Duration;
+
+    #[test]
+    fn test_upload_session_tracks_missing_chunks() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let store = UploadSessionStore::new(&repo);
+            assert!(store.get("abc")?.is_none());
+            let content_hash = util::hasher::hash_buffer(b"abcdef");
+            let upload = UploadSession::new("abc", "commit", Some(&content_hash), 3, 6);
+
+            // Pieces can arrive out of order and more than once
+            let session = store.put_chunk(&upload, 2, b"ef")?;
+            assert_eq!(session.missing_chunks(), vec![0, 1]);
+            store.put_chunk(&upload, 0, b"ab")?;
+            let session = store.put_chunk(&upload, 0, b"ab")?;
+            assert_eq!(session.received_chunks, vec![0, 2]);
+            assert!(!session.is_complete());
+
+            // The session survives between requests
+            let session = store.get("abc")?.unwrap();
+            assert_eq!(session.missing_chunks(), vec![1]);
+
+            let session = store.put_chunk(&upload, 1, b"cd")?;
+            assert!(session.is_complete());
+            assert_eq!(store.read_all(&session)?, b"abcdef".to_vec());
+
+            assert!(store.put_chunk(&upload, 3, b"gh").is_err());
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_upload_session_restarts_when_contents_change() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let store = UploadSessionStore::new(&repo);
+            let old_hash = util::hasher::hash_buffer(b"abcdef");
+            let old_upload = UploadSession::new("abc", "commit", Some(&old_hash), 3, 6);
+            store.put_chunk(&old_upload, 0, b"ab")?;
+            store.put_chunk(&old_upload, 1, b"cd")?;
+
+            // Same commit and entries, but the client rebuilt the data so the old pieces are no good
+            let new_hash = util::hasher::hash_buffer(b"abcxyz");
+            let new_upload = UploadSession::new("abc", "commit", Some(&new_hash), 3, 6);
+            let session = store.put_chunk(&new_upload, 2, b"yz")?;
+            assert_eq!(session.missing_chunks(), vec![0, 1]);
+            store.put_chunk(&new_upload, 0, b"ab")?;
+            let session = store.put_chunk(&new_upload, 1, b"cx")?;
+            assert_eq!(store.read_all(&session)?, b"abcxyz".to_vec());
+
+            // A piece that got mangled on the way is caught when putting them together
+            let session = store.put_chunk(&new_upload, 1, b"cd")?;
+            assert!(store.read_all(&session).is_err());
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_upload_session_expire_stale() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let store = UploadSessionStore::new(&repo);
+            let upload = UploadSession::new("abc", "commit", None, 2, 4);
+            store.put_chunk(&upload, 0, b"ab")?;
+
+            assert_eq!(store.expire_stale(Duration::from_secs(60))?, 0);
+            assert!(store.get("abc")?.is_some());
+
+            assert_eq!(store.expire_stale(Duration::from_secs(0))?, 1);
+            assert!(store.get("abc")?.is_none());
+            assert!(!store.session_dir("abc")?.exists());
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_upload_session_rejects_paths_as_hashes() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let store = UploadSessionStore::new(&repo);
+            for hash in ["", "..", "../../..", "abc/def", "/tmp", "abcx"] {
+                assert!(store.get(hash).is_err());
+                assert!(store.remove(hash).is_err());
+                let upload = UploadSession::new(hash, "commit", None, 1, 2);
+                assert!(store.put_chunk(&upload, 0, b"ab").is_err());
+            }
+            assert!(repo.path.join(".oxen").exists());
+
+            Ok(())
+        })
+    }
+}
@@ -1,4 +1,6 @@
 pub mod branch;
+pub mod branch_policy;
+pub mod chunk;
 pub mod commit;
 pub mod data_frame_diff;
 pub mod entry;
@@ -10,23 +12,38 @@ pub mod repository;
 pub mod schema;
 pub mod staged_data;
 pub mod staged_dir_stats;
+pub mod stash;
 pub mod summarized_staged_dir_stats;
+pub mod tag;
+pub mod transfer_report;
+pub mod tree_diff;
+pub mod upload_session;
 pub mod user;
 
 // Repository
 pub use crate::model::repository::local_repository::{LocalRepository, RepositoryNew};
 pub use crate::model::repository::remote_repository::RemoteRepository;
 pub use crate::model::repository::repo_stats::{DataTypeStat, RepoStats};
+pub use crate::model::repository::sparse_spec::SparseSpec;
 
 // Commit
 pub use crate::model::commit::{Commit, CommitStats, NewCommit};
 
 // Merge
-pub use crate::model::merge_conflict::MergeConflict;
+pub use crate::model::merge_conflict::{MergeConflict, RowConflict};
 
 // Branch
 pub use crate::model::branch::Branch;
+pub use crate::model::branch_policy::{BranchPolicy, ProtectedBranch};
 pub use crate::model::remote_branch::RemoteBranch;
+pub use crate::model::tag::Tag;
+
+// Chunk
+pub use crate::model::chunk::{Chunk, ChunkManifest};
+pub use crate::model::upload_session::UploadSession;
+
+// Transfer
+pub use crate::model::transfer_report::TransferReport;
 
 // Entry
 pub use crate::model::entry::commit_entry::CommitEntry;
@@ -39,11 +56,13 @@ pub use crate::model::user::User;
 
 pub use crate::model::staged_data::StagedData;
 pub use crate::model::staged_dir_stats::StagedDirStats;
+pub use crate::model::stash::Stash;
 pub use crate::model::summarized_staged_dir_stats::SummarizedStagedDirStats;
 
 pub use crate::model::remote::Remote;
 
-pub use crate::model::data_frame_diff::DataFrameDiff;
+pub use crate::model::data_frame_diff::{DataFrameDiff, ModifiedCell, ModifiedRow};
+pub use crate::model::tree_diff::{DiffEntry, DiffEntryStatus, TreeDiff};
 
 pub use crate::model::schema::Schema;
 
@@ -0,0 +1,128 @@
+use ignore::gitignore::GitignoreBuilder;
+use serde::{Deserialize, Serialize};
+
+use crate::error::OxenError;
+
+fn default_true() -> bool {
+    true
+}
+
+/// Rules for the branches whose names match `pattern`, a branch name or a glob like `release/*`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ProtectedBranch {
+    pub pattern: String,
+    #[serde(default = "default_true")]
+    pub prevent_deletion: bool,
+    #[serde(default = "default_true")]
+    pub prevent_force_push: bool,
+    /// Only accept pushes of commits the server finished validating successfully
+    #[serde(default)]
+    pub require_validation: bool,
+}
+
+impl ProtectedBranch {
+    /// Protect the branches matching `pattern` from deletion and force pushes
+    pub fn new<S: AsRef<str>>(pattern: S) -> ProtectedBranch {
+        ProtectedBranch {
+            pattern: String::from(pattern.as_ref()),
+            prevent_deletion: true,
+            prevent_force_push: true,
+            require_validation: false,
+        }
+    }
+
+    /// Patterns are anchored to the whole branch name, and `*` does not cross a `/`
+    pub fn matches<S: AsRef<str>>(&self, branch_name: S) -> Result<bool, OxenError> {
+        let pattern = self.pattern.trim_start_matches('/');
+        let mut builder = GitignoreBuilder::new("");
+        if let Err(err) = builder.add_line(None, &format!("/{pattern}")) {
+            let err = format!("Invalid branch pattern {:?}: {err}", self.pattern);
+            return Err(OxenError::basic_str(err));
+        }
+
+        match builder.build() {
+            Ok(matcher) => Ok(matcher.matched(branch_name.as_ref(), false).is_ignore()),
+            Err(err) => Err(OxenError::basic_str(format!(
+                "Invalid branch pattern {:?}: {err}",
+                self.pattern
+            ))),
+        }
+    }
+}
+
+/// The branch protections of a repository on the server
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct BranchPolicy {
+    #[serde(default)]
+    pub protected_branches: Vec<ProtectedBranch>,
+}
+
+impl BranchPolicy {
+    /// All the protections that apply to a branch, a branch can match more than one pattern
+    pub fn protections_for<S: AsRef<str>>(
+        &self,
+        branch_name: S,
+    ) -> Result<Vec<&ProtectedBranch>, OxenError> {
+        let mut protections: Vec<&ProtectedBranch> = vec![];
+        for protection in self.protected_branches.iter() {
+            if protection.matches(branch_name.as_ref())? {
+                protections.push(protection);
+            }
+        }
+        Ok(protections)
+    }
+
+    /// Adds the protection, replacing any existing one with the same pattern
+    pub fn protect(&mut self, protection: ProtectedBranch) {
+        match self
+            .protected_branches
+            .iter_mut()
+            .find(|p| p.pattern == protection.pattern)
+        {
+            Some(existing) => *existing = protection,
+            None => self.protected_branches.push(protection),
+        }
+    }
+
+    /// Removes the protection with this pattern, returning it if there was one
+    pub fn unprotect<S: AsRef<str>>(&mut self, pattern: S) -> Option<ProtectedBranch> {
+        let pattern = pattern.as_ref();
+        let idx = self
+            .protected_branches
+            .iter()
+            .position(|p| p.pattern == pattern)?;
+        Some(self.protected_branches.remove(idx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::OxenError;
+    use crate::model::{BranchPolicy, ProtectedBranch};
+
+    #[test]
+    fn test_branch_policy_matches_names_and_globs() -> Result<(), OxenError> {
+        let mut policy = BranchPolicy::default();
+        policy.protect(ProtectedBranch::new("main"));
+        policy.protect(ProtectedBranch::new("release/*"));
+
+        assert_eq!(policy.protections_for("main")?.len(), 1);
+        assert_eq!(policy.protections_for("release/1.0")?.len(), 1);
+        assert!(policy.protections_for("feature/main")?.is_empty());
+        assert!(policy.protections_for("release/1.0/hotfix")?.is_empty());
+        assert!(policy.protections_for("mainline")?.is_empty());
+
+        // Protecting the same pattern again replaces it
+        let mut protection = ProtectedBranch::new("main");
+        protection.require_validation = true;
+        policy.protect(protection);
+        assert_eq!(policy.protected_branches.len(), 2);
+        assert!(policy.protections_for("main")?[0].require_validation);
+
+        assert!(policy.unprotect("main").is_some());
+        assert!(policy.unprotect("main").is_none());
+        assert!(policy.protections_for("main")?.is_empty());
+
+        Ok(())
+    }
+}
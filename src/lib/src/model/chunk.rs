@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+/// A content defined slice of a versioned file
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Chunk {
+    pub hash: String,
+    pub offset: u64,
+    pub num_bytes: u64,
+}
+
+/// The ordered list of chunks a versioned file is rebuilt from
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ChunkManifest {
+    pub hash: String,
+    pub num_bytes: u64,
+    pub chunks: Vec<Chunk>,
+}
+
+impl ChunkManifest {
+    pub fn chunk_hashes(&self) -> Vec<String> {
+        self.chunks.iter().map(|c| c.hash.to_owned()).collect()
+    }
+}
@@ -1,8 +1,34 @@
 use polars::prelude::DataFrame;
+use serde::{Deserialize, Serialize};
 
 pub struct DataFrameDiff {
     pub added_rows: Option<DataFrame>,
     pub removed_rows: Option<DataFrame>,
+    pub modified_rows: Vec<ModifiedRow>,
     pub added_cols: Option<DataFrame>,
     pub removed_cols: Option<DataFrame>,
 }
+
+impl DataFrameDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_rows.is_none()
+            && self.removed_rows.is_none()
+            && self.modified_rows.is_empty()
+            && self.added_cols.is_none()
+            && self.removed_cols.is_none()
+    }
+}
+
+/// A row that exists on both sides of a diff under the same primary key, but with different values
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ModifiedRow {
+    pub key: String,
+    pub cells: Vec<ModifiedCell>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ModifiedCell {
+    pub column: String,
+    pub before: String,
+    pub after: String,
+}
@@ -1,3 +1,5 @@
+use crate::error::OxenError;
+use crate::index::{ChunkStore, VersionFile};
 use crate::model::{ContentHashable, LocalRepository, RemoteEntry};
 use crate::util;
 
@@ -49,11 +51,12 @@ impl CommitEntry {
         }
     }
 
-    pub fn version_file(&self) -> PathBuf {
-        let current_dir = env::current_dir().unwrap();
-        let repo_dir = util::fs::get_repo_root(&current_dir).expect("Oxen repo not found.");
-        let repo = LocalRepository::from_dir(&repo_dir).unwrap();
-        util::fs::version_path(&repo, self)
+    pub fn version_file(&self) -> Result<VersionFile, OxenError> {
+        let current_dir = env::current_dir()?;
+        let repo_dir =
+            util::fs::get_repo_root(&current_dir).ok_or_else(OxenError::local_repo_not_found)?;
+        let repo = LocalRepository::from_dir(&repo_dir)?;
+        ChunkStore::new(&repo).version_file(self)
     }
 
     pub fn filename(&self) -> PathBuf {
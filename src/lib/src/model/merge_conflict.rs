@@ -6,4 +6,16 @@ pub struct MergeConflict {
     pub lca_entry: CommitEntry,   // Least Common Ancestor Entry
     pub head_entry: CommitEntry,  // Entry that existed in the HEAD commit
     pub merge_entry: CommitEntry, // Entry we are trying to merge in
+    // Rows of a tabular file that were edited differently on both sides, empty for whole file conflicts
+    #[serde(default)]
+    pub row_conflicts: Vec<RowConflict>,
+}
+
+/// A row that both branches changed in different ways. Values are None if the row did not exist on that side.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct RowConflict {
+    pub key: String,
+    pub lca_row: Option<Vec<String>>,
+    pub head_row: Option<Vec<String>>,
+    pub merge_row: Option<Vec<String>>,
 }
@@ -1,3 +1,4 @@
 pub mod local_repository;
 pub mod remote_repository;
 pub mod repo_stats;
+pub mod sparse_spec;
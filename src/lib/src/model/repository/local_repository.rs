@@ -2,8 +2,9 @@ use crate::api;
 use crate::constants;
 use crate::error::OxenError;
 use crate::index::EntryIndexer;
-use crate::model::{Commit, Remote, RemoteBranch, RemoteRepository};
+use crate::model::{Commit, Remote, RemoteBranch, RemoteRepository, SparseSpec};
 use crate::util;
+use crate::util::SparseFilter;
 use crate::view::RepositoryView;
 
 use http::Uri;
@@ -43,6 +44,9 @@ pub struct LocalRepository {
     pub path: PathBuf,
     remote_name: Option<String>, // this is the current remote name
     pub remotes: Vec<Remote>,
+    // only set for sparse clones, the paths we keep in the working tree
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sparse: Option<SparseSpec>,
 }
 
 impl LocalRepository {
@@ -52,6 +56,7 @@ impl LocalRepository {
             path: path.to_path_buf(),
             remotes: vec![],
             remote_name: None,
+            sparse: None,
         })
     }
 
@@ -60,6 +65,7 @@ impl LocalRepository {
             path: std::env::current_dir()?.join(view.name),
             remotes: vec![],
             remote_name: None,
+            sparse: None,
         })
     }
 
@@ -68,6 +74,7 @@ impl LocalRepository {
             path: path.to_owned(),
             remotes: vec![repo.remote],
             remote_name: Some(String::from(constants::DEFAULT_REMOTE_NAME)),
+            sparse: None,
         })
     }
 
@@ -102,10 +109,42 @@ impl LocalRepository {
         Ok(())
     }
 
+    /// Checks if a path relative to the repo root is in the working tree, always true unless this is a sparse clone
+    pub fn is_in_sparse_tree<P: AsRef<Path>>(&self, path: P) -> bool {
+        match self.sparse_filter() {
+            Some(filter) => filter.includes(path),
+            None => true,
+        }
+    }
+
+    /// The matcher for the sparse spec, None if this is not a sparse clone
+    pub fn sparse_filter(&self) -> Option<SparseFilter> {
+        match &self.sparse {
+            Some(spec) if !spec.is_empty() => match SparseFilter::new(spec) {
+                Ok(filter) => Some(filter),
+                Err(err) => {
+                    log::error!("Ignoring invalid sparse spec {:?}: {}", spec, err);
+                    None
+                }
+            },
+            _ => None,
+        }
+    }
+
     pub async fn clone_remote(
         url: &str,
         dst: &Path,
         shallow: bool,
+    ) -> Result<Option<LocalRepository>, OxenError> {
+        LocalRepository::clone_remote_sparse(url, dst, shallow, None).await
+    }
+
+    /// Clone that only pulls the entries matching `sparse`, and remembers it for later pulls
+    pub async fn clone_remote_sparse(
+        url: &str,
+        dst: &Path,
+        shallow: bool,
+        sparse: Option<SparseSpec>,
     ) -> Result<Option<LocalRepository>, OxenError> {
         log::debug!("clone_remote {} -> {:?} -> shallow? {shallow}", url, dst);
         let remote = Remote {
@@ -114,7 +153,7 @@ impl LocalRepository {
         };
         match api::remote::repositories::get_by_remote(&remote).await {
             Ok(Some(remote_repo)) => Ok(Some(
-                LocalRepository::clone_repo(remote_repo, dst, shallow).await?,
+                LocalRepository::clone_repo(remote_repo, dst, shallow, sparse).await?,
             )),
             Ok(None) => Ok(None),
             Err(_) => {
@@ -179,10 +218,35 @@ impl LocalRepository {
         }
     }
 
+    /// Creates an empty repository at `repo_path` whose `origin` is the remote, nothing is pulled yet
+    pub fn create_from_remote(
+        repo: &RemoteRepository,
+        repo_path: &Path,
+        sparse: Option<SparseSpec>,
+    ) -> Result<LocalRepository, OxenError> {
+        std::fs::create_dir_all(repo_path)?;
+
+        // if create successful, create .oxen directory
+        let oxen_hidden_path = util::fs::oxen_hidden_dir(repo_path);
+        std::fs::create_dir(&oxen_hidden_path)?;
+
+        // save Repository in .oxen directory
+        let repo_config_file = oxen_hidden_path.join(Path::new("config.toml"));
+        let mut local_repo = LocalRepository::from_remote(repo.clone(), repo_path)?;
+        local_repo.path = repo_path.to_path_buf();
+        local_repo.add_remote("origin", &repo.remote.url);
+        local_repo.sparse = sparse;
+
+        let toml = toml::to_string(&local_repo)?;
+        util::fs::write_to_path(&repo_config_file, &toml)?;
+        Ok(local_repo)
+    }
+
     async fn clone_repo(
         repo: RemoteRepository,
         dst: &Path,
         shallow: bool,
+        sparse: Option<SparseSpec>,
     ) -> Result<LocalRepository, OxenError> {
         // let url = String::from(&repo.url);
         // let repo_new = RepositoryNew::from_url(&repo.url)?;
@@ -193,21 +257,7 @@ impl LocalRepository {
             return Err(OxenError::basic_str(err));
         }
 
-        // if directory does not exist, create it
-        std::fs::create_dir_all(&repo_path)?;
-
-        // if create successful, create .oxen directory
-        let oxen_hidden_path = util::fs::oxen_hidden_dir(&repo_path);
-        std::fs::create_dir(&oxen_hidden_path)?;
-
-        // save Repository in .oxen directory
-        let repo_config_file = oxen_hidden_path.join(Path::new("config.toml"));
-        let mut local_repo = LocalRepository::from_remote(repo.clone(), &repo_path)?;
-        local_repo.path = repo_path;
-        local_repo.add_remote("origin", &repo.remote.url);
-
-        let toml = toml::to_string(&local_repo)?;
-        util::fs::write_to_path(&repo_config_file, &toml)?;
+        let local_repo = LocalRepository::create_from_remote(&repo, &repo_path, sparse)?;
 
         // Pull all commit objects, but not entries
         let indexer = EntryIndexer::new(&local_repo)?;
@@ -1,3 +1,4 @@
+use crate::api::remote::client::ClientPool;
 use crate::model::Remote;
 use crate::view::RepositoryView;
 use serde::{Deserialize, Serialize};
@@ -7,6 +8,9 @@ pub struct RemoteRepository {
     pub namespace: String,
     pub name: String,
     pub remote: Remote,
+    /// Shared by the requests made for this repository
+    #[serde(skip)]
+    pub client_pool: ClientPool,
 }
 
 impl RemoteRepository {
@@ -15,6 +19,7 @@ impl RemoteRepository {
             namespace: repository.namespace.clone(),
             name: repository.name.clone(),
             remote: remote.clone(),
+            client_pool: ClientPool::new(),
         }
     }
 }
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+/// The paths a sparse clone keeps in its working tree, as gitignore style globs.
+/// Committed entries that do not match are known but left absent on disk.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct SparseSpec {
+    #[serde(default)]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+impl SparseSpec {
+    pub fn new(include: Vec<String>, exclude: Vec<String>) -> SparseSpec {
+        SparseSpec { include, exclude }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Adds include patterns we do not already have
+    pub fn add_includes(&mut self, patterns: &[String]) {
+        for pattern in patterns {
+            if !self.include.contains(pattern) {
+                self.include.push(pattern.to_owned());
+            }
+        }
+    }
+}
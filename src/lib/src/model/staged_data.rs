@@ -160,10 +160,17 @@ impl StagedData {
                 //     conflict.merge_entry.version_file()
                 // );
 
-                vec![
+                let mut output = vec![
                     "  both modified: ".to_string().yellow(),
-                    format!("{}\n", path.to_str().unwrap()).yellow().bold(),
-                ]
+                    path.to_str().unwrap().to_string().yellow().bold(),
+                ];
+                if !conflict.row_conflicts.is_empty() {
+                    output.push(
+                        format!(" ({} conflicting rows)", conflict.row_conflicts.len()).yellow(),
+                    );
+                }
+                output.push("\n".to_string().normal());
+                output
             },
             outputs,
             skip,
@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+/// Uncommitted work put aside with `oxen stash`. Like git, what was staged is saved in an index commit on top of
+/// the commit it was stashed from, and the working tree in a commit with both of them as parents. Neither is on
+/// any branch.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Stash {
+    pub commit_id: String,
+    /// The branch we were on, None if HEAD was detached
+    pub branch: Option<String>,
+    pub message: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+}
+
+impl Stash {
+    /// Key in the stash db, sorts in the order the stashes were made
+    pub fn key(&self) -> String {
+        format!("{:040}", self.timestamp.unix_timestamp_nanos())
+    }
+}
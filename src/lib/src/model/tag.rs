@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::error::OxenError;
+
+/// A named release of a commit, unlike a branch it never moves
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Tag {
+    pub name: String,
+    pub commit_id: String,
+    pub tagger: String,
+    pub email: String,
+    pub message: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+}
+
+impl Tag {
+    /// Tag names end up in urls and file paths, like `/file/v1.3-train/annotations.csv`
+    pub fn validate_name(name: &str) -> Result<(), OxenError> {
+        let is_valid = !name.is_empty()
+            && !name.starts_with(['-', '/', '.'])
+            && !name.ends_with(['/', '.'])
+            && !name.contains("..")
+            && !name.contains("//")
+            && !name
+                .chars()
+                .any(|c| c.is_whitespace() || c.is_control() || "~^:?*[\\".contains(c));
+        if is_valid {
+            Ok(())
+        } else {
+            Err(OxenError::basic_str(format!("Invalid tag name '{name}'")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::Tag;
+
+    #[test]
+    fn test_tag_validate_name() {
+        assert!(Tag::validate_name("v1.3-train").is_ok());
+        assert!(Tag::validate_name("release/2023-01").is_ok());
+
+        assert!(Tag::validate_name("").is_err());
+        assert!(Tag::validate_name("has space").is_err());
+        assert!(Tag::validate_name("-v1").is_err());
+        assert!(Tag::validate_name("v1..v2").is_err());
+        assert!(Tag::validate_name("v1/").is_err());
+        assert!(Tag::validate_name("v1:train").is_err());
+    }
+}
@@ -0,0 +1,44 @@
+use bytesize::ByteSize;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+use crate::error::OxenError;
+use crate::util;
+
+/// What a push, pull or clone moved over the network
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct TransferReport {
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    pub files: u64,
+    pub retries: u64,
+    pub elapsed_secs: f64,
+    /// Bytes sent and received per second over the whole transfer
+    pub throughput_bytes_per_sec: u64,
+    pub limit_rate_bytes_per_sec: Option<u64>,
+}
+
+impl TransferReport {
+    /// Writes the report as JSON, for logs that are read by other programs
+    pub fn save(&self, path: &Path) -> Result<(), OxenError> {
+        let json = serde_json::to_string_pretty(self)?;
+        util::fs::write_to_path(path, &json)?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for TransferReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Sent {}, received {}, {} files in {:.1}s ({}/s), {} retries",
+            ByteSize::b(self.bytes_sent),
+            ByteSize::b(self.bytes_received),
+            self.files,
+            self.elapsed_secs,
+            ByteSize::b(self.throughput_bytes_per_sec),
+            self.retries
+        )
+    }
+}
@@ -0,0 +1,146 @@
+use crate::model::{Commit, CommitEntry};
+
+use bytesize::ByteSize;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone)]
+pub enum DiffEntryStatus {
+    Added,
+    Modified,
+    Removed,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct DiffEntry {
+    pub path: PathBuf,
+    pub status: DiffEntryStatus,
+    pub base_entry: Option<CommitEntry>,
+    pub head_entry: Option<CommitEntry>,
+    // How many bytes the file grew or shrank by going from base to head
+    pub size_delta: i64,
+    // Line or row level diff of the contents, only computed when asked for a single file
+    pub contents: Option<String>,
+}
+
+impl DiffEntry {
+    pub fn added(head_entry: &CommitEntry) -> DiffEntry {
+        DiffEntry::new(DiffEntryStatus::Added, None, Some(head_entry))
+    }
+
+    pub fn removed(base_entry: &CommitEntry) -> DiffEntry {
+        DiffEntry::new(DiffEntryStatus::Removed, Some(base_entry), None)
+    }
+
+    pub fn modified(base_entry: &CommitEntry, head_entry: &CommitEntry) -> DiffEntry {
+        DiffEntry::new(
+            DiffEntryStatus::Modified,
+            Some(base_entry),
+            Some(head_entry),
+        )
+    }
+
+    fn new(
+        status: DiffEntryStatus,
+        base_entry: Option<&CommitEntry>,
+        head_entry: Option<&CommitEntry>,
+    ) -> DiffEntry {
+        let path = head_entry.or(base_entry).unwrap().path.to_owned();
+        let base_bytes = base_entry.map(|e| e.num_bytes as i64).unwrap_or(0);
+        let head_bytes = head_entry.map(|e| e.num_bytes as i64).unwrap_or(0);
+
+        DiffEntry {
+            path,
+            status,
+            base_entry: base_entry.cloned(),
+            head_entry: head_entry.cloned(),
+            size_delta: head_bytes - base_bytes,
+            contents: None,
+        }
+    }
+}
+
+/// All the files that changed between two commits
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct TreeDiff {
+    pub base_commit: Commit,
+    pub head_commit: Commit,
+    pub added: Vec<DiffEntry>,
+    pub removed: Vec<DiffEntry>,
+    pub modified: Vec<DiffEntry>,
+}
+
+impl TreeDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+
+    pub fn num_changed(&self) -> usize {
+        self.added.len() + self.removed.len() + self.modified.len()
+    }
+
+    pub fn size_delta(&self) -> i64 {
+        self.added
+            .iter()
+            .chain(self.removed.iter())
+            .chain(self.modified.iter())
+            .map(|e| e.size_delta)
+            .sum()
+    }
+
+    pub fn print_stdout(&self) {
+        println!(
+            "Comparing {} -> {}\n",
+            self.base_commit.id, self.head_commit.id
+        );
+
+        for entry in self.added.iter() {
+            println!("  {}", format_entry("added:   ", entry).green());
+        }
+        for entry in self.modified.iter() {
+            println!("  {}", format_entry("modified:", entry).yellow());
+        }
+        for entry in self.removed.iter() {
+            println!("  {}", format_entry("removed: ", entry).red());
+        }
+
+        println!(
+            "\n{} files changed, {}",
+            self.num_changed(),
+            format_size_delta(self.size_delta())
+        );
+
+        for entry in self.modified.iter() {
+            if let Some(contents) = &entry.contents {
+                println!("\n{}\n", entry.path.to_str().unwrap().bold());
+                for line in contents.lines() {
+                    if line.starts_with('+') {
+                        println!("{}", line.green());
+                    } else if line.starts_with('-') {
+                        println!("{}", line.red());
+                    } else {
+                        println!("{line}");
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn format_entry(label: &str, entry: &DiffEntry) -> String {
+    format!(
+        "{} {} ({})",
+        label,
+        entry.path.to_str().unwrap(),
+        format_size_delta(entry.size_delta)
+    )
+}
+
+fn format_size_delta(delta: i64) -> String {
+    if delta < 0 {
+        format!("-{}", ByteSize::b(delta.unsigned_abs()))
+    } else {
+        format!("+{}", ByteSize::b(delta as u64))
+    }
+}
@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+/// The pieces of a chunked upload the server has received so far, so an interrupted push can resume.
+/// `hash` identifies the upload by the commit and the entries in it, so it stays the same when the client
+/// rebuilds the data, and `content_hash` tells whether the pieces it already sent can be reused.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct UploadSession {
+    pub hash: String,
+    /// The commit being pushed, which gc has to keep around until the push is done
+    #[serde(default)]
+    pub commit_id: Option<String>,
+    #[serde(default)]
+    pub content_hash: Option<String>,
+    pub total_chunks: usize,
+    pub total_size: usize,
+    pub received_chunks: Vec<usize>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+impl UploadSession {
+    pub fn new(
+        hash: &str,
+        commit_id: &str,
+        content_hash: Option<&str>,
+        total_chunks: usize,
+        total_size: usize,
+    ) -> UploadSession {
+        UploadSession {
+            hash: hash.to_string(),
+            commit_id: Some(commit_id.to_string()),
+            content_hash: content_hash.map(String::from),
+            total_chunks,
+            total_size,
+            received_chunks: vec![],
+            updated_at: OffsetDateTime::now_utc(),
+        }
+    }
+
+    /// Whether the pieces of this upload can be reused for `other`
+    pub fn has_same_contents(&self, other: &UploadSession) -> bool {
+        self.content_hash == other.content_hash
+            && self.total_chunks == other.total_chunks
+            && self.total_size == other.total_size
+    }
+
+    pub fn missing_chunks(&self) -> Vec<usize> {
+        (0..self.total_chunks)
+            .filter(|num| self.received_chunks.binary_search(num).is_err())
+            .collect()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.missing_chunks().is_empty()
+    }
+}
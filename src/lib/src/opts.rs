@@ -1,3 +1,9 @@
+pub mod log_opts;
+pub mod reset_mode;
 pub mod restore_opts;
+pub mod transfer_opts;
 
+pub use crate::opts::log_opts::LogOpts;
+pub use crate::opts::reset_mode::ResetMode;
 pub use crate::opts::restore_opts::RestoreOpts;
+pub use crate::opts::transfer_opts::TransferOpts;
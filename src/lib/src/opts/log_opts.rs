@@ -0,0 +1,62 @@
+use std::path::PathBuf;
+
+use time::format_description::well_known::Rfc3339;
+use time::macros::format_description;
+use time::{Date, Duration, OffsetDateTime};
+
+use crate::error::OxenError;
+
+#[derive(Clone, Debug, Default)]
+pub struct LogOpts {
+    /// The branch, remote-tracking ref or commit id to start from, HEAD if None
+    pub revision: Option<String>,
+    /// Only the commits that changed this file or anything under this directory
+    pub path: Option<PathBuf>,
+    /// Substring of the author name or email
+    pub author: Option<String>,
+    pub since: Option<OffsetDateTime>,
+    pub until: Option<OffsetDateTime>,
+    /// Substring of the commit message
+    pub grep: Option<String>,
+    /// Stop after this many commits
+    pub max_count: Option<usize>,
+}
+
+impl LogOpts {
+    pub fn from_revision<S: AsRef<str>>(revision: S) -> LogOpts {
+        LogOpts {
+            revision: Some(revision.as_ref().to_string()),
+            ..LogOpts::default()
+        }
+    }
+
+    /// Parses either a full RFC 3339 timestamp or a YYYY-MM-DD date, which is the start of that day in UTC
+    pub fn parse_since(value: &str) -> Result<OffsetDateTime, OxenError> {
+        LogOpts::parse_date(value, false)
+    }
+
+    /// Like `parse_since`, but a YYYY-MM-DD date includes the whole day
+    pub fn parse_until(value: &str) -> Result<OffsetDateTime, OxenError> {
+        LogOpts::parse_date(value, true)
+    }
+
+    fn parse_date(value: &str, end_of_day: bool) -> Result<OffsetDateTime, OxenError> {
+        if let Ok(timestamp) = OffsetDateTime::parse(value, &Rfc3339) {
+            return Ok(timestamp);
+        }
+
+        match Date::parse(value, format_description!("[year]-[month]-[day]")) {
+            Ok(date) => {
+                let start = date.midnight().assume_utc();
+                if end_of_day {
+                    Ok(start + Duration::days(1) - Duration::nanoseconds(1))
+                } else {
+                    Ok(start)
+                }
+            }
+            Err(_) => Err(OxenError::basic_str(format!(
+                "Invalid date: {value}, use YYYY-MM-DD or an RFC 3339 timestamp"
+            ))),
+        }
+    }
+}
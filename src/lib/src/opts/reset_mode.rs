@@ -0,0 +1,11 @@
+/// How much of the repository `reset` moves along with the branch pointer
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ResetMode {
+    /// Keep the working tree, and stage the changes between the new HEAD and the old one
+    Soft,
+    /// Keep the working tree, but unstage everything
+    #[default]
+    Mixed,
+    /// Unstage everything and overwrite the working tree with the new HEAD
+    Hard,
+}
@@ -0,0 +1,9 @@
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Default)]
+pub struct TransferOpts {
+    /// Cap on the bytes per second sent and received across all the workers of a transfer
+    pub limit_rate: Option<u64>,
+    /// Where to write the JSON report of the transfer
+    pub report_path: Option<PathBuf>,
+}
@@ -1,10 +1,14 @@
 pub mod fs;
 pub mod hasher;
+pub mod oxenignore;
 pub mod read_progress;
 pub mod resource;
+pub mod sparse_filter;
 pub mod str;
 
+pub use crate::util::oxenignore::OxenIgnore;
 pub use crate::util::read_progress::ReadProgress;
+pub use crate::util::sparse_filter::SparseFilter;
 
 pub mod oxen_date_format {
     use chrono::{DateTime, Local};
@@ -7,6 +7,8 @@ use std::io::prelude::*;
 use std::io::BufReader;
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 use std::{fs, io};
 
 use crate::api;
@@ -15,8 +17,10 @@ use crate::constants::CONTENT_IS_VALID;
 use crate::constants::DATA_ARROW_FILE;
 use crate::constants::HISTORY_DIR;
 use crate::error::OxenError;
+use crate::index::{ChunkStore, VersionFile};
 use crate::model::Commit;
 use crate::model::{CommitEntry, LocalRepository};
+use crate::util::OxenIgnore;
 
 pub fn oxen_hidden_dir(repo_path: &Path) -> PathBuf {
     PathBuf::from(&repo_path).join(Path::new(constants::OXEN_HIDDEN_DIR))
@@ -37,20 +41,20 @@ pub fn commit_content_is_valid_path(repo: &LocalRepository, commit: &Commit) ->
         .join(CONTENT_IS_VALID)
 }
 
+/// The versioned file at `filepath` in the commit, a file rebuilt from chunks is removed once it is dropped
 pub fn version_path_for_commit_id(
     repo: &LocalRepository,
     commit_id: &str,
     filepath: &Path,
-) -> Result<PathBuf, OxenError> {
+) -> Result<VersionFile, OxenError> {
     match api::local::commits::get_by_id(repo, commit_id)? {
         Some(commit) => match api::local::entries::get_entry_for_commit(repo, &commit, filepath)? {
             Some(entry) => {
-                let path = version_path(repo, &entry);
-                let arrow_path = path.parent().unwrap().join(DATA_ARROW_FILE);
+                let arrow_path = df_version_path(repo, &entry);
                 if arrow_path.exists() {
-                    Ok(arrow_path)
+                    Ok(VersionFile::existing(arrow_path))
                 } else {
-                    Ok(path)
+                    ChunkStore::new(repo).version_file(&entry)
                 }
             }
             None => Err(OxenError::file_does_not_exist(filepath)),
@@ -74,7 +78,10 @@ pub fn version_file_size(repo: &LocalRepository, entry: &CommitEntry) -> Result<
     //     Ok(meta.len())
     // } else {
     if !version_path.exists() {
-        return Err(OxenError::file_does_not_exist(version_path));
+        return match ChunkStore::new(repo).get_manifest(&entry.hash)? {
+            Some(manifest) => Ok(manifest.num_bytes),
+            None => Err(OxenError::file_does_not_exist(version_path)),
+        };
     }
     let meta = std::fs::metadata(&version_path)?;
     Ok(meta.len())
@@ -109,6 +116,15 @@ pub fn version_dir_from_hash(repo: &LocalRepository, hash: String) -> PathBuf {
         .join(subdir)
 }
 
+/// Whether the file or dir was last modified at least `age` ago, false if the clock went backwards since
+pub fn is_older_than(path: &Path, age: Duration) -> Result<bool, OxenError> {
+    let modified = fs::metadata(path)?.modified()?;
+    Ok(modified
+        .elapsed()
+        .map(|elapsed| elapsed >= age)
+        .unwrap_or(false))
+}
+
 pub fn read_from_path(path: &Path) -> Result<String, OxenError> {
     match fs::read_to_string(path) {
         Ok(contents) => Ok(contents),
@@ -350,20 +366,27 @@ pub fn is_utf8(path: &Path) -> bool {
 }
 
 pub fn file_datatype(path: &Path) -> String {
+    match datatype_from_extension(path) {
+        Some(datatype) => datatype,
+        None if is_utf8(path) => String::from("text"),
+        None => String::from("unknown"),
+    }
+}
+
+/// The datatype we can tell from the extension alone, without reading the file
+pub fn datatype_from_extension(path: &Path) -> Option<String> {
     if is_markdown(path) {
-        String::from("markdown")
+        Some(String::from("markdown"))
     } else if is_image(path) {
-        String::from("image")
+        Some(String::from("image"))
     } else if is_video(path) {
-        String::from("video")
+        Some(String::from("video"))
     } else if is_audio(path) {
-        String::from("audio")
+        Some(String::from("audio"))
     } else if is_tabular(path) {
-        String::from("tabular")
-    } else if is_utf8(path) {
-        String::from("text")
+        Some(String::from("tabular"))
     } else {
-        String::from("unknown")
+        None
     }
 }
 
@@ -435,8 +458,19 @@ pub fn recursive_eligible_files(dir: &Path) -> Vec<PathBuf> {
         return files;
     }
 
+    // Respect the .oxenignore files of the repo we are in, if any
+    let ignore = get_repo_root(dir).map(|root| Arc::new(OxenIgnore::new(&root)));
+    let walker = WalkDir::new(dir).process_read_dir(move |_, _, _, children| {
+        if let Some(ignore) = &ignore {
+            children.retain(|child| match child {
+                Ok(entry) => !ignore.is_ignored(entry.path(), entry.file_type.is_dir()),
+                Err(_) => true,
+            });
+        }
+    });
+
     let mut mod_idx = 10;
-    for entry in WalkDir::new(dir) {
+    for entry in walker {
         match entry {
             Ok(val) => {
                 let path = val.path();
@@ -0,0 +1,162 @@
+//! # OxenIgnore
+//!
+//! gitignore style `.oxenignore` files that keep paths out of `add` and `status`.
+//!
+//! Every directory in the repository may contain an `.oxenignore` file. Patterns are
+//! relative to the directory the file lives in, and deeper files take precedence over
+//! the ones above them, so a `!negation` in a sub directory can re-include a path.
+//!
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use crate::constants;
+use crate::util;
+
+pub struct OxenIgnore {
+    repo_path: PathBuf,
+    // Lazily loaded matchers keyed by the directory relative to the repo root
+    matchers: RwLock<HashMap<PathBuf, Option<Gitignore>>>,
+}
+
+impl OxenIgnore {
+    pub fn new(repo_path: &Path) -> OxenIgnore {
+        OxenIgnore {
+            repo_path: repo_path.to_path_buf(),
+            matchers: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Checks if a file or directory (absolute or relative to the repo root) is ignored
+    pub fn is_ignored<P: AsRef<Path>>(&self, path: P, is_dir: bool) -> bool {
+        let relative = match util::fs::path_relative_to_dir(path.as_ref(), &self.repo_path) {
+            Ok(relative) => relative,
+            Err(_) => return false,
+        };
+
+        if relative == Path::new("") || util::fs::is_in_oxen_hidden_dir(&relative) {
+            return false;
+        }
+
+        let full_path = self.repo_path.join(&relative);
+        // Walk from the deepest .oxenignore up to the root, the first match wins
+        let mut dir = relative.parent();
+        while let Some(current) = dir {
+            if let Some(matcher) = self.matcher_for_dir(current) {
+                match matcher.matched_path_or_any_parents(&full_path, is_dir) {
+                    Match::Ignore(glob) => {
+                        log::debug!("OxenIgnore {:?} ignored by {:?}", relative, glob);
+                        return true;
+                    }
+                    Match::Whitelist(_) => return false,
+                    Match::None => {}
+                }
+            }
+            dir = current.parent();
+        }
+        false
+    }
+
+    fn matcher_for_dir(&self, dir: &Path) -> Option<Gitignore> {
+        if let Some(matcher) = self.matchers.read().unwrap().get(dir) {
+            return matcher.clone();
+        }
+
+        let matcher = self.load_matcher(dir);
+        self.matchers
+            .write()
+            .unwrap()
+            .insert(dir.to_path_buf(), matcher.clone());
+        matcher
+    }
+
+    fn load_matcher(&self, dir: &Path) -> Option<Gitignore> {
+        let full_dir = self.repo_path.join(dir);
+        let ignore_file = full_dir.join(constants::OXEN_IGNORE_FILE);
+        if !ignore_file.is_file() {
+            return None;
+        }
+
+        let mut builder = GitignoreBuilder::new(&full_dir);
+        if let Some(err) = builder.add(&ignore_file) {
+            log::warn!("Invalid pattern in {:?}: {}", ignore_file, err);
+        }
+
+        match builder.build() {
+            Ok(matcher) => Some(matcher),
+            Err(err) => {
+                log::warn!("Could not read {:?}: {}", ignore_file, err);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::constants;
+    use crate::error::OxenError;
+    use crate::test;
+    use crate::util;
+    use crate::util::oxenignore::OxenIgnore;
+
+    #[test]
+    fn test_oxenignore_globs_and_dirs() -> Result<(), OxenError> {
+        test::run_empty_dir_test(|dir| {
+            let ignore_file = dir.join(constants::OXEN_IGNORE_FILE);
+            util::fs::write_to_path(&ignore_file, "*.ckpt\n__pycache__/\n")?;
+
+            let ignore = OxenIgnore::new(dir);
+            assert!(ignore.is_ignored(dir.join("model.ckpt"), false));
+            assert!(ignore.is_ignored(dir.join("runs").join("model.ckpt"), false));
+            assert!(ignore.is_ignored(dir.join("src").join("__pycache__"), true));
+            assert!(ignore.is_ignored(dir.join("src").join("__pycache__").join("mod.pyc"), false));
+            assert!(!ignore.is_ignored(dir.join("train.csv"), false));
+            assert!(!ignore.is_ignored(dir.join(constants::OXEN_IGNORE_FILE), false));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_oxenignore_negation_and_anchored() -> Result<(), OxenError> {
+        test::run_empty_dir_test(|dir| {
+            let ignore_file = dir.join(constants::OXEN_IGNORE_FILE);
+            util::fs::write_to_path(&ignore_file, "*.log\n!keep.log\n/scratch\n")?;
+
+            let ignore = OxenIgnore::new(dir);
+            assert!(ignore.is_ignored(dir.join("train.log"), false));
+            assert!(!ignore.is_ignored(dir.join("keep.log"), false));
+            assert!(ignore.is_ignored(dir.join("scratch"), true));
+            // Anchored to the root, so nested scratch dirs are kept
+            assert!(!ignore.is_ignored(dir.join("data").join("scratch"), true));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_oxenignore_per_directory_file() -> Result<(), OxenError> {
+        test::run_empty_dir_test(|dir| {
+            util::fs::write_to_path(&dir.join(constants::OXEN_IGNORE_FILE), "*.tmp\n")?;
+            let sub_dir = dir.join("annotations");
+            std::fs::create_dir_all(&sub_dir)?;
+            util::fs::write_to_path(
+                &sub_dir.join(constants::OXEN_IGNORE_FILE),
+                "!labels.tmp\n/drafts\n",
+            )?;
+
+            let ignore = OxenIgnore::new(dir);
+            assert!(ignore.is_ignored(dir.join("cache.tmp"), false));
+            assert!(ignore.is_ignored(sub_dir.join("other.tmp"), false));
+            assert!(!ignore.is_ignored(sub_dir.join("labels.tmp"), false));
+            assert!(ignore.is_ignored(sub_dir.join("drafts"), true));
+            assert!(!ignore.is_ignored(dir.join("drafts"), true));
+
+            Ok(())
+        })
+    }
+}
@@ -57,13 +57,13 @@ pub fn parse_resource(
         // if we have no components, looking at base dir within that branch
         if components.is_empty() {
             let branch_name = file_path.to_str().unwrap();
-            if let Some(branch) = ref_reader.get_branch_by_name(branch_name)? {
+            if let Some((commit_id, name)) = get_branch_or_tag(&ref_reader, branch_name)? {
                 log::debug!(
-                    "parse_resource got branch [{}] with no file path",
+                    "parse_resource got branch or tag [{}] with no file path",
                     branch_name
                 );
 
-                return Ok(Some((branch.commit_id, branch.name, PathBuf::from("./"))));
+                return Ok(Some((commit_id, name, PathBuf::from("./"))));
             } else {
                 return Ok(None);
             }
@@ -77,27 +77,41 @@ pub fn parse_resource(
 
         let branch_name = branch_path.to_str().unwrap();
         log::debug!("parse_resource looking for branch [{}]", branch_name);
-        if let Some(branch) = ref_reader.get_branch_by_name(branch_name)? {
+        if let Some((commit_id, name)) = get_branch_or_tag(&ref_reader, branch_name)? {
             log::debug!(
-                "parse_resource got branch [{}] and filepath [{:?}]",
+                "parse_resource got branch or tag [{}] and filepath [{:?}]",
                 branch_name,
                 file_path
             );
 
-            return Ok(Some((branch.commit_id, branch.name, file_path)));
+            return Ok(Some((commit_id, name, file_path)));
         }
     }
 
     Ok(None)
 }
 
+/// Branches win over tags with the same name
+fn get_branch_or_tag(
+    ref_reader: &RefReader,
+    name: &str,
+) -> Result<Option<(String, String)>, OxenError> {
+    if let Some(branch) = ref_reader.get_branch_by_name(name)? {
+        return Ok(Some((branch.commit_id, branch.name)));
+    }
+    Ok(ref_reader
+        .get_tag_by_name(name)?
+        .map(|tag| (tag.commit_id, tag.name)))
+}
+
 /// Pass in a branch name and maybe get a commit id back
 pub fn maybe_get_commit_id_from_branch_name<S: AsRef<str>>(
     repo: &LocalRepository,
     commit_id_or_branch_name: S,
 ) -> Result<Option<String>, OxenError> {
     let ref_reader = RefReader::new(repo)?;
-    ref_reader.get_commit_id_for_branch(commit_id_or_branch_name.as_ref())
+    // Remote-tracking refs like origin/main resolve too, so fetched history can be compared
+    ref_reader.get_commit_id_for_ref(commit_id_or_branch_name.as_ref())
 }
 
 /// Pass in a commit id or a branch name and resolve it to a
@@ -119,6 +133,19 @@ pub fn maybe_get_commit<S: AsRef<str>>(
     }
 }
 
+/// Splits a range like `main..my-branch` into the base and head commit ids or branch names
+pub fn parse_commit_range<S: AsRef<str>>(range: S) -> Result<(String, String), OxenError> {
+    let range = range.as_ref();
+    match range.split_once("..") {
+        Some((base, head)) if !base.is_empty() && !head.is_empty() => {
+            Ok((base.to_string(), head.to_string()))
+        }
+        _ => Err(OxenError::basic_str(format!(
+            "Invalid range `{range}`, expected `<base>..<head>`"
+        ))),
+    }
+}
+
 pub fn get_head_commit(repo: &LocalRepository) -> Result<Commit, OxenError> {
     let committer = CommitReader::new(repo)?;
     committer.head_commit()
@@ -239,4 +266,38 @@ mod tests {
             Ok(())
         })
     }
+
+    #[test]
+    fn test_parse_resource_for_tag() -> Result<(), OxenError> {
+        crate::test::run_training_data_repo_test_fully_committed(|repo| {
+            let tag = command::create_tag(&repo, "v1.3", None, "First release")?;
+
+            let path = Path::new("v1.3/annotations/train/one_shot.csv");
+            match resource::parse_resource(&repo, path) {
+                Ok(Some((commit_id, ref_name, path))) => {
+                    assert_eq!(tag.commit_id, commit_id);
+                    assert_eq!(ref_name, "v1.3");
+                    assert_eq!(path, Path::new("annotations/train/one_shot.csv"));
+                }
+                _ => {
+                    panic!("Should return a tag");
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_parse_commit_range() -> Result<(), OxenError> {
+        let (base, head) = resource::parse_commit_range("main..add-labels")?;
+        assert_eq!(base, "main");
+        assert_eq!(head, "add-labels");
+
+        assert!(resource::parse_commit_range("main").is_err());
+        assert!(resource::parse_commit_range("main..").is_err());
+        assert!(resource::parse_commit_range("..main").is_err());
+
+        Ok(())
+    }
 }
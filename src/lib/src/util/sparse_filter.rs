@@ -0,0 +1,104 @@
+//! # SparseFilter
+//!
+//! Matches repository paths against the `--include` and `--exclude` globs of a sparse clone.
+//!
+//! Patterns follow `.oxenignore` rules and are relative to the repository root, so `images/val/`
+//! keeps everything in that directory and `*.csv` keeps csv files anywhere. With no include patterns
+//! every path is included, and exclude patterns win over include patterns.
+//!
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::path::Path;
+
+use crate::error::OxenError;
+use crate::model::repository::sparse_spec::SparseSpec;
+
+pub struct SparseFilter {
+    include: Option<Gitignore>,
+    exclude: Option<Gitignore>,
+}
+
+impl SparseFilter {
+    pub fn new(spec: &SparseSpec) -> Result<SparseFilter, OxenError> {
+        Ok(SparseFilter {
+            include: SparseFilter::build(&spec.include)?,
+            exclude: SparseFilter::build(&spec.exclude)?,
+        })
+    }
+
+    fn build(patterns: &[String]) -> Result<Option<Gitignore>, OxenError> {
+        if patterns.is_empty() {
+            return Ok(None);
+        }
+
+        let mut builder = GitignoreBuilder::new("");
+        for pattern in patterns {
+            if let Err(err) = builder.add_line(None, pattern) {
+                let err = format!("Invalid sparse pattern {pattern:?}: {err}");
+                return Err(OxenError::basic_str(err));
+            }
+        }
+
+        match builder.build() {
+            Ok(matcher) => Ok(Some(matcher)),
+            Err(err) => Err(OxenError::basic_str(format!(
+                "Invalid sparse patterns: {err}"
+            ))),
+        }
+    }
+
+    /// Checks if a file path relative to the repository root is part of the sparse working tree
+    pub fn includes<P: AsRef<Path>>(&self, path: P) -> bool {
+        let path = path.as_ref();
+        if let Some(exclude) = &self.exclude {
+            if exclude.matched_path_or_any_parents(path, false).is_ignore() {
+                return false;
+            }
+        }
+
+        match &self.include {
+            Some(include) => include.matched_path_or_any_parents(path, false).is_ignore(),
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::OxenError;
+    use crate::model::SparseSpec;
+    use crate::util::SparseFilter;
+
+    #[test]
+    fn test_sparse_filter_include_and_exclude() -> Result<(), OxenError> {
+        let spec = SparseSpec::new(
+            vec![
+                String::from("images/val/"),
+                String::from("annotations/val.csv"),
+            ],
+            vec![String::from("*.tmp")],
+        );
+        let filter = SparseFilter::new(&spec)?;
+
+        assert!(filter.includes("images/val/dog_1.jpg"));
+        assert!(filter.includes("images/val/nested/cat_2.jpg"));
+        assert!(filter.includes("annotations/val.csv"));
+        assert!(!filter.includes("images/train/dog_1.jpg"));
+        assert!(!filter.includes("annotations/train.csv"));
+        assert!(!filter.includes("images/val/scratch.tmp"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sparse_filter_only_exclude() -> Result<(), OxenError> {
+        let spec = SparseSpec::new(vec![], vec![String::from("images/train")]);
+        let filter = SparseFilter::new(&spec)?;
+
+        assert!(filter.includes("README.md"));
+        assert!(filter.includes("images/val/dog_1.jpg"));
+        assert!(!filter.includes("images/train/dog_1.jpg"));
+
+        Ok(())
+    }
+}
@@ -1,19 +1,24 @@
 pub mod branch;
+pub mod chunk;
 pub mod commit;
+pub mod compare;
 pub mod entry;
 pub mod entry_meta_data;
 pub mod http;
 pub mod json_data_frame;
+pub mod json_data_frame_diff;
 pub mod namespace;
 pub mod oxen_response;
 pub mod repository;
 pub mod schema;
 pub mod status_message;
+pub mod tag;
 pub mod version;
 
 pub use crate::view::status_message::{IsValidStatusMessage, StatusMessage};
 
 pub use crate::view::json_data_frame::{JsonDataFrame, JsonDataFrameSliceResponse};
+pub use crate::view::json_data_frame_diff::JsonDataFrameDiff;
 pub use crate::view::namespace::{ListNamespacesResponse, NamespaceResponse, NamespaceView};
 pub use crate::view::schema::{ListSchemaResponse, SchemaResponse};
 
@@ -22,15 +27,25 @@ pub use crate::view::repository::{
 };
 
 pub use crate::view::entry::{
-    EntryResponse, PaginatedDirEntries, PaginatedEntries, RemoteEntryResponse,
+    CommitEntries, CommitEntriesResponse, EntryResponse, PaginatedDirEntries, PaginatedEntries,
+    RemoteEntryResponse,
 };
 
 pub use crate::view::commit::{
     CommitParentsResponse, CommitResponse, CommitStatsResponse, ListCommitResponse,
-    PaginatedCommits,
+    PaginatedCommits, PaginatedCommitsResponse, UploadSessionResponse,
 };
 
-pub use crate::view::branch::{BranchNew, BranchResponse, BranchUpdate, ListBranchesResponse};
+pub use crate::view::compare::CompareResponse;
+
+pub use crate::view::chunk::{ChunkHashes, ChunkHashesResponse, ChunkManifestResponse};
+
+pub use crate::view::branch::{
+    BranchNew, BranchResponse, BranchUpdate, ListBranchesResponse, ListProtectedBranchesResponse,
+    ProtectedBranchResponse,
+};
+
+pub use crate::view::tag::{ListTagsResponse, TagResponse};
 
 pub use crate::view::entry_meta_data::EntryMetaDataResponse;
 
@@ -1,4 +1,4 @@
-use crate::model::Branch;
+use crate::model::{Branch, ProtectedBranch};
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -16,6 +16,9 @@ pub struct BranchNew {
 #[derive(Deserialize, Serialize, Debug)]
 pub struct BranchUpdate {
     pub commit_id: String,
+    /// Move the branch even if the commit does not descend from the current branch head
+    #[serde(default)]
+    pub force: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -24,3 +27,17 @@ pub struct ListBranchesResponse {
     pub status_message: String,
     pub branches: Vec<Branch>,
 }
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ProtectedBranchResponse {
+    pub status: String,
+    pub status_message: String,
+    pub protected_branch: ProtectedBranch,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ListProtectedBranchesResponse {
+    pub status: String,
+    pub status_message: String,
+    pub protected_branches: Vec<ProtectedBranch>,
+}
@@ -0,0 +1,21 @@
+use crate::model::ChunkManifest;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ChunkHashes {
+    pub hashes: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ChunkHashesResponse {
+    pub status: String,
+    pub status_message: String,
+    pub hashes: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ChunkManifestResponse {
+    pub status: String,
+    pub status_message: String,
+    pub manifest: ChunkManifest,
+}
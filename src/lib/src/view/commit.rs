@@ -1,4 +1,4 @@
-use crate::model::{Commit, CommitStats};
+use crate::model::{Commit, CommitStats, UploadSession};
 use crate::view::http::{MSG_RESOURCE_FOUND, STATUS_SUCCESS};
 use serde::{Deserialize, Serialize};
 
@@ -16,6 +16,14 @@ pub struct CommitParentsResponse {
     pub parents: Vec<Commit>,
 }
 
+#[derive(Deserialize, Serialize, Debug)]
+pub struct UploadSessionResponse {
+    pub status: String,
+    pub status_message: String,
+    pub session: UploadSession,
+    pub missing_chunks: Vec<usize>,
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 pub struct CommitStatsResponse {
     pub status: String,
@@ -48,3 +56,15 @@ pub struct PaginatedCommits {
     pub total_pages: usize,
     pub total_entries: usize,
 }
+
+/// A page of the history, clients that read it as a `ListCommitResponse` still get the commits
+#[derive(Deserialize, Serialize, Debug)]
+pub struct PaginatedCommitsResponse {
+    pub status: String,
+    pub status_message: String,
+    pub commits: Vec<Commit>,
+    pub page_size: usize,
+    pub page_number: usize,
+    pub total_pages: usize,
+    pub total_entries: usize,
+}
@@ -0,0 +1,13 @@
+use crate::model::TreeDiff;
+use crate::view::JsonDataFrameDiff;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct CompareResponse {
+    pub status: String,
+    pub status_message: String,
+    pub compare: TreeDiff,
+    // Only set when comparing a single modified tabular file
+    #[serde(default)]
+    pub df_diff: Option<JsonDataFrameDiff>,
+}
@@ -8,6 +8,18 @@ pub struct EntryResponse {
     pub entry: CommitEntry,
 }
 
+#[derive(Deserialize, Serialize, Debug)]
+pub struct CommitEntries {
+    pub entries: Vec<CommitEntry>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct CommitEntriesResponse {
+    pub status: String,
+    pub status_message: String,
+    pub entries: Vec<CommitEntry>,
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 pub struct RemoteEntryResponse {
     pub status: String,
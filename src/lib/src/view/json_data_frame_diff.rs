@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+use crate::model::{DataFrameDiff, ModifiedRow};
+use crate::view::JsonDataFrame;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct JsonDataFrameDiff {
+    pub added_rows: Option<JsonDataFrame>,
+    pub removed_rows: Option<JsonDataFrame>,
+    pub modified_rows: Vec<ModifiedRow>,
+    pub added_cols: Option<JsonDataFrame>,
+    pub removed_cols: Option<JsonDataFrame>,
+}
+
+impl JsonDataFrameDiff {
+    pub fn from_diff(diff: DataFrameDiff) -> JsonDataFrameDiff {
+        JsonDataFrameDiff {
+            added_rows: diff
+                .added_rows
+                .map(|mut df| JsonDataFrame::from_df(&mut df)),
+            removed_rows: diff
+                .removed_rows
+                .map(|mut df| JsonDataFrame::from_df(&mut df)),
+            modified_rows: diff.modified_rows,
+            added_cols: diff
+                .added_cols
+                .map(|mut df| JsonDataFrame::from_df(&mut df)),
+            removed_cols: diff
+                .removed_cols
+                .map(|mut df| JsonDataFrame::from_df(&mut df)),
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use crate::model::Tag;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct TagResponse {
+    pub status: String,
+    pub status_message: String,
+    pub tag: Tag,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ListTagsResponse {
+    pub status: String,
+    pub status_message: String,
+    pub tags: Vec<Tag>,
+}
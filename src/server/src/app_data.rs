@@ -1,14 +1,28 @@
+use liboxen::constants::DEFAULT_UPLOAD_TTL_SECS;
 use std::path::PathBuf;
+use std::time::Duration;
 
 #[derive(Debug, Clone)]
 pub struct OxenAppData {
     pub path: PathBuf,
+    /// How long an unfinished chunked upload is kept around for the client to resume
+    pub upload_ttl: Duration,
 }
 
 impl OxenAppData {
-    pub fn from(path: &str) -> OxenAppData {
+    pub fn new(path: PathBuf) -> OxenAppData {
         OxenAppData {
-            path: PathBuf::from(path),
+            path,
+            upload_ttl: Duration::from_secs(DEFAULT_UPLOAD_TTL_SECS),
         }
     }
+
+    pub fn from(path: &str) -> OxenAppData {
+        OxenAppData::new(PathBuf::from(path))
+    }
+
+    pub fn with_upload_ttl(mut self, upload_ttl: Duration) -> OxenAppData {
+        self.upload_ttl = upload_ttl;
+        self
+    }
 }
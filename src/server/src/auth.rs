@@ -1,2 +1,3 @@
+pub mod access_control;
 pub mod access_keys;
 pub mod validator;
@@ -0,0 +1,530 @@
+use liboxen::error::OxenError;
+use liboxen::util;
+
+use actix_web::http::Method;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+pub const ACCESS_DIR: &str = "access";
+
+/// What a collaborator can do in a repository, each role includes the ones before it
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Read,
+    Write,
+    Admin,
+}
+
+impl FromStr for Role {
+    type Err = OxenError;
+
+    fn from_str(role: &str) -> Result<Role, OxenError> {
+        match role {
+            "read" => Ok(Role::Read),
+            "write" => Ok(Role::Write),
+            "admin" => Ok(Role::Admin),
+            other => Err(OxenError::basic_str(format!(
+                "Unknown role '{other}', must be one of read, write or admin"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Role::Read => write!(f, "read"),
+            Role::Write => write!(f, "write"),
+            Role::Admin => write!(f, "admin"),
+        }
+    }
+}
+
+/// Owners of a namespace and the collaborators on each of its repositories, keyed by email
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NamespaceAccess {
+    #[serde(default)]
+    pub owners: Vec<String>,
+    #[serde(default)]
+    pub repos: BTreeMap<String, BTreeMap<String, Role>>,
+}
+
+impl NamespaceAccess {
+    pub fn is_owner(&self, email: &str) -> bool {
+        self.owners.iter().any(|owner| owner == email)
+    }
+
+    /// Namespace owners are admins of all of its repositories
+    pub fn role_for(&self, repo_name: &str, email: &str) -> Option<Role> {
+        if self.is_owner(email) {
+            return Some(Role::Admin);
+        }
+
+        self.repos
+            .get(repo_name)
+            .and_then(|collaborators| collaborators.get(email))
+            .copied()
+    }
+}
+
+/// The access a request needs, a request without a repository needs to own the namespace
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequiredAccess {
+    pub namespace: String,
+    pub repo_name: Option<String>,
+    pub role: Role,
+}
+
+impl RequiredAccess {
+    /// Maps a request under `/api/repos` to the access it needs, reads need read access,
+    /// pushes and branch changes need write access, and deleting the repository or a tag, or changing
+    /// its branch policy needs admin. Creating a repository is checked by the controller, since
+    /// the namespace is in the body.
+    pub fn from_request(method: &Method, path: &str) -> Option<RequiredAccess> {
+        let path = path.strip_prefix("/api/repos")?;
+        let mut segments = path.trim_matches('/').splitn(3, '/');
+        let namespace = segments.next().filter(|s| !s.is_empty())?;
+        let repo_name = match segments.next() {
+            Some(repo_name) => repo_name,
+            None => {
+                return Some(RequiredAccess {
+                    namespace: String::from(namespace),
+                    repo_name: None,
+                    role: Role::Admin,
+                })
+            }
+        };
+        let rest = segments.next().unwrap_or("");
+        // The route the request is for, like `tags` in `tags/v1.0`
+        let section = rest.split('/').next().unwrap_or("");
+
+        let role = if (section.is_empty() || section == "tags") && method == Method::DELETE {
+            Role::Admin
+        } else if section == "protected_branches" {
+            if method == Method::GET {
+                Role::Read
+            } else {
+                Role::Admin
+            }
+        } else if method == Method::GET
+            || (method == Method::POST && matches!(rest, "versions" | "sql"))
+        {
+            // POST to versions is the legacy way to download version files, and sql only reads
+            Role::Read
+        } else {
+            Role::Write
+        };
+
+        Some(RequiredAccess {
+            namespace: String::from(namespace),
+            repo_name: Some(String::from(repo_name)),
+            role,
+        })
+    }
+
+    pub fn describe(&self) -> String {
+        match &self.repo_name {
+            Some(repo_name) => format!("{} access to {}/{}", self.role, self.namespace, repo_name),
+            None => format!("owner access to namespace {}", self.namespace),
+        }
+    }
+}
+
+/// Reads and writes who can access which namespaces and repositories on the server.
+///
+/// Namespaces that already had repositories before access control have no access file. Nobody has access to
+/// them until someone is made an owner with `oxen-server grant`.
+pub struct AccessControl {
+    sync_dir: PathBuf,
+    access_dir: PathBuf,
+}
+
+impl AccessControl {
+    pub fn new(sync_dir: &Path) -> AccessControl {
+        AccessControl {
+            sync_dir: sync_dir.to_path_buf(),
+            access_dir: util::fs::oxen_hidden_dir(sync_dir).join(ACCESS_DIR),
+        }
+    }
+
+    /// Namespaces and repository names come from the request path and end up in file names, so they are limited
+    /// to letters, digits, `.`, `_` and `-`. No `%` either, otherwise a name could decode to another one.
+    pub fn validate_name(name: &str) -> Result<(), OxenError> {
+        let is_plain = name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+        if name.is_empty() || name == "." || name == ".." || !is_plain {
+            return Err(OxenError::basic_str(format!(
+                "Invalid name '{name}', use letters, digits, '.', '_' or '-'"
+            )));
+        }
+        Ok(())
+    }
+
+    fn namespace_path(&self, namespace: &str) -> Result<PathBuf, OxenError> {
+        AccessControl::validate_name(namespace)?;
+        Ok(self.access_dir.join(format!("{namespace}.toml")))
+    }
+
+    /// A namespace with repositories but no access file, from before access control
+    pub fn is_legacy_namespace(&self, namespace: &str) -> Result<bool, OxenError> {
+        Ok(!self.namespace_path(namespace)?.exists() && self.sync_dir.join(namespace).is_dir())
+    }
+
+    pub fn get(&self, namespace: &str) -> Result<NamespaceAccess, OxenError> {
+        let path = self.namespace_path(namespace)?;
+        if !path.exists() {
+            return Ok(NamespaceAccess::default());
+        }
+
+        let contents = util::fs::read_from_path(&path)?;
+        let access: NamespaceAccess = toml::from_str(&contents)?;
+        Ok(access)
+    }
+
+    pub fn save(&self, namespace: &str, access: &NamespaceAccess) -> Result<(), OxenError> {
+        if !self.access_dir.exists() {
+            std::fs::create_dir_all(&self.access_dir)?;
+        }
+
+        let toml = toml::to_string(access)?;
+        util::fs::write_to_path(&self.namespace_path(namespace)?, &toml)?;
+        Ok(())
+    }
+
+    pub fn add_owner(&self, namespace: &str, email: &str) -> Result<(), OxenError> {
+        let mut access = self.get(namespace)?;
+        if !access.is_owner(email) {
+            access.owners.push(String::from(email));
+            self.save(namespace, &access)?;
+        }
+        Ok(())
+    }
+
+    /// Returns false if they were not an owner
+    pub fn remove_owner(&self, namespace: &str, email: &str) -> Result<bool, OxenError> {
+        let mut access = self.get(namespace)?;
+        let num_owners = access.owners.len();
+        access.owners.retain(|owner| owner != email);
+        if access.owners.len() == num_owners {
+            return Ok(false);
+        }
+        self.save(namespace, &access)?;
+        Ok(true)
+    }
+
+    /// Adds a collaborator to the repository, or changes their role if they already are one
+    pub fn grant(
+        &self,
+        namespace: &str,
+        repo_name: &str,
+        email: &str,
+        role: Role,
+    ) -> Result<(), OxenError> {
+        let mut access = self.get(namespace)?;
+        access
+            .repos
+            .entry(String::from(repo_name))
+            .or_default()
+            .insert(String::from(email), role);
+        self.save(namespace, &access)
+    }
+
+    /// Returns false if they were not a collaborator
+    pub fn revoke(&self, namespace: &str, repo_name: &str, email: &str) -> Result<bool, OxenError> {
+        let mut access = self.get(namespace)?;
+        let removed = match access.repos.get_mut(repo_name) {
+            Some(collaborators) => collaborators.remove(email).is_some(),
+            None => false,
+        };
+        if access.repos.get(repo_name).is_some_and(|c| c.is_empty()) {
+            access.repos.remove(repo_name);
+        }
+        if removed {
+            self.save(namespace, &access)?;
+        }
+        Ok(removed)
+    }
+
+    /// Forget the collaborators of a deleted repository, so a new one with the same name starts fresh
+    pub fn remove_repo(&self, namespace: &str, repo_name: &str) -> Result<(), OxenError> {
+        let mut access = self.get(namespace)?;
+        if access.repos.remove(repo_name).is_some() {
+            self.save(namespace, &access)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the permission the user is missing to create a repository in the namespace, or None
+    /// if they can. The first user to create a repository in a new namespace becomes its owner.
+    pub fn missing_create_permission(
+        &self,
+        email: &str,
+        namespace: &str,
+    ) -> Result<Option<String>, OxenError> {
+        // Nobody gets to take over the repositories that are already there by creating one next to them
+        if self.is_legacy_namespace(namespace)? {
+            return Ok(Some(format!(
+                "namespace {namespace} has no owner yet, an admin has to grant one with `oxen-server grant`"
+            )));
+        }
+
+        let access = self.get(namespace)?;
+        if access.owners.is_empty() {
+            self.add_owner(namespace, email)?;
+            return Ok(None);
+        }
+
+        let required = RequiredAccess {
+            namespace: String::from(namespace),
+            repo_name: None,
+            role: Role::Admin,
+        };
+        self.missing_permission(email, &required)
+    }
+
+    /// Returns the permission the user is missing, or None if they have the access
+    pub fn missing_permission(
+        &self,
+        email: &str,
+        required: &RequiredAccess,
+    ) -> Result<Option<String>, OxenError> {
+        let access = self.get(&required.namespace)?;
+        let has_access = match &required.repo_name {
+            Some(repo_name) => access
+                .role_for(repo_name, email)
+                .map(|role| role >= required.role)
+                .unwrap_or(false),
+            None => access.is_owner(email),
+        };
+
+        if has_access {
+            Ok(None)
+        } else {
+            Ok(Some(format!(
+                "{} does not have {}",
+                email,
+                required.describe()
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::Method;
+
+    use crate::auth::access_control::{AccessControl, RequiredAccess, Role};
+    use crate::test;
+    use liboxen::error::OxenError;
+
+    #[test]
+    fn test_access_control_required_access_from_request() {
+        let required = |method: Method, path: &str| RequiredAccess::from_request(&method, path);
+
+        let read = required(Method::GET, "/api/repos/ox/data/commits/main/history").unwrap();
+        assert_eq!(read.namespace, "ox");
+        assert_eq!(read.repo_name, Some(String::from("data")));
+        assert_eq!(read.role, Role::Read);
+
+        assert_eq!(
+            required(Method::POST, "/api/repos/ox/data/versions")
+                .unwrap()
+                .role,
+            Role::Read
+        );
+        assert_eq!(
+            required(Method::POST, "/api/repos/ox/data/sql")
+                .unwrap()
+                .role,
+            Role::Read
+        );
+        assert_eq!(
+            required(Method::POST, "/api/repos/ox/data/commits")
+                .unwrap()
+                .role,
+            Role::Write
+        );
+        assert_eq!(
+            required(Method::DELETE, "/api/repos/ox/data/branches/feature")
+                .unwrap()
+                .role,
+            Role::Write
+        );
+        assert_eq!(
+            required(Method::DELETE, "/api/repos/ox/data").unwrap().role,
+            Role::Admin
+        );
+        assert_eq!(
+            required(Method::POST, "/api/repos/ox/data/protected_branches")
+                .unwrap()
+                .role,
+            Role::Admin
+        );
+
+        assert_eq!(
+            required(Method::POST, "/api/repos/ox/data/tags")
+                .unwrap()
+                .role,
+            Role::Write
+        );
+        assert_eq!(
+            required(Method::DELETE, "/api/repos/ox/data/tags/v1.3")
+                .unwrap()
+                .role,
+            Role::Admin
+        );
+        // Only whole route segments count
+        assert_eq!(
+            required(Method::DELETE, "/api/repos/ox/data/tagsets/v1.3")
+                .unwrap()
+                .role,
+            Role::Write
+        );
+        assert_eq!(
+            required(Method::GET, "/api/repos/ox/data/protected_branches_backup")
+                .unwrap()
+                .role,
+            Role::Read
+        );
+
+        let namespace = required(Method::GET, "/api/repos/ox").unwrap();
+        assert_eq!(namespace.repo_name, None);
+
+        // Checked in the controller
+        assert!(required(Method::POST, "/api/repos").is_none());
+        assert!(required(Method::GET, "/api/namespaces").is_none());
+    }
+
+    #[test]
+    fn test_access_control_grant_and_revoke() -> Result<(), OxenError> {
+        test::run_empty_sync_dir_test(|sync_dir| {
+            let access = AccessControl::new(sync_dir);
+            let write = RequiredAccess {
+                namespace: String::from("ox"),
+                repo_name: Some(String::from("data")),
+                role: Role::Write,
+            };
+            let owner = RequiredAccess {
+                namespace: String::from("ox"),
+                repo_name: None,
+                role: Role::Admin,
+            };
+
+            // Nobody has access until it is granted
+            let missing = access.missing_permission("bessie@oxen.ai", &write)?;
+            assert_eq!(
+                missing,
+                Some(String::from(
+                    "bessie@oxen.ai does not have write access to ox/data"
+                ))
+            );
+
+            access.grant("ox", "data", "bessie@oxen.ai", Role::Read)?;
+            assert!(access
+                .missing_permission("bessie@oxen.ai", &write)?
+                .is_some());
+            access.grant("ox", "data", "bessie@oxen.ai", Role::Write)?;
+            assert!(access
+                .missing_permission("bessie@oxen.ai", &write)?
+                .is_none());
+            assert!(access
+                .missing_permission("bessie@oxen.ai", &owner)?
+                .is_some());
+
+            // Owners can do anything in their namespace
+            access.add_owner("ox", "ox@oxen.ai")?;
+            assert!(access.missing_permission("ox@oxen.ai", &write)?.is_none());
+            assert!(access.missing_permission("ox@oxen.ai", &owner)?.is_none());
+
+            assert!(access.revoke("ox", "data", "bessie@oxen.ai")?);
+            assert!(!access.revoke("ox", "data", "bessie@oxen.ai")?);
+            assert!(access
+                .missing_permission("bessie@oxen.ai", &write)?
+                .is_some());
+            assert!(access.remove_owner("ox", "ox@oxen.ai")?);
+            assert!(access.missing_permission("ox@oxen.ai", &write)?.is_some());
+
+            // The first to create a repo in a namespace owns it
+            assert!(access
+                .missing_create_permission("ox@oxen.ai", "ox")?
+                .is_none());
+            assert!(access.missing_permission("ox@oxen.ai", &owner)?.is_none());
+            assert_eq!(
+                access.missing_create_permission("bessie@oxen.ai", "ox")?,
+                Some(String::from(
+                    "bessie@oxen.ai does not have owner access to namespace ox"
+                ))
+            );
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_access_control_rejects_bad_namespaces() -> Result<(), OxenError> {
+        test::run_empty_sync_dir_test(|sync_dir| {
+            let access = AccessControl::new(sync_dir);
+            for namespace in [
+                "", ".", "..", "../ox", "ox/data", "ox\\data", "%6Fx", "ox data",
+            ] {
+                assert!(access.get(namespace).is_err());
+                assert!(access.add_owner(namespace, "ox@oxen.ai").is_err());
+            }
+            assert!(!sync_dir.join(".oxen").join("ox.toml").exists());
+            assert!(access.get("ox")?.owners.is_empty());
+            assert!(AccessControl::validate_name("Testing-Name_1.0").is_ok());
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_access_control_legacy_namespace_is_closed() -> Result<(), OxenError> {
+        test::run_empty_sync_dir_test(|sync_dir| {
+            // A namespace with repositories from before access control, without an access file
+            std::fs::create_dir_all(sync_dir.join("legacy").join("data"))?;
+            let access = AccessControl::new(sync_dir);
+            let write = RequiredAccess {
+                namespace: String::from("legacy"),
+                repo_name: Some(String::from("data")),
+                role: Role::Write,
+            };
+
+            assert!(access
+                .missing_permission("bessie@oxen.ai", &write)?
+                .is_some());
+            // Creating a repo in it does not take the namespace over
+            assert!(access
+                .missing_create_permission("bessie@oxen.ai", "legacy")?
+                .is_some());
+            assert!(access.get("legacy")?.owners.is_empty());
+
+            // Once it has an owner the access file applies
+            access.add_owner("legacy", "ox@oxen.ai")?;
+            assert!(access.missing_permission("ox@oxen.ai", &write)?.is_none());
+            assert!(access
+                .missing_create_permission("ox@oxen.ai", "legacy")?
+                .is_none());
+            assert!(access
+                .missing_permission("bessie@oxen.ai", &write)?
+                .is_some());
+
+            // New namespaces are closed from the start
+            let other = RequiredAccess {
+                namespace: String::from("other"),
+                repo_name: Some(String::from("data")),
+                role: Role::Read,
+            };
+            assert!(access
+                .missing_permission("bessie@oxen.ai", &other)?
+                .is_some());
+
+            Ok(())
+        })
+    }
+}
@@ -1,22 +1,175 @@
+use crate::auth::access_control::{RequiredAccess, Role};
+
 use liboxen::error::OxenError;
 use liboxen::model::User;
 use liboxen::util;
 
 use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
 use rand_core::OsRng;
-use rocksdb::{DBWithThreadMode, LogLevel, MultiThreaded, Options};
+use rocksdb::{DBWithThreadMode, IteratorMode, LogLevel, MultiThreaded, Options};
 use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fmt;
 use std::path::{Path, PathBuf};
 use std::str;
+use std::str::FromStr;
+use std::time::Duration;
+use time::OffsetDateTime;
 use x25519_dalek::{EphemeralSecret, PublicKey};
 
 pub const SECRET_KEY_FILENAME: &str = "SECRET_KEY_BASE";
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+/// Limits what a token can do on top of the access its user has been granted
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub enum TokenScope {
+    /// `read`, the token cannot change anything
+    ReadOnly,
+    /// `namespace:<namespace>`, the token only works in this namespace
+    Namespace(String),
+    /// `push:<namespace>/<repo_name>`, the token can only change this repository
+    Push(String, String),
+}
+
+impl FromStr for TokenScope {
+    type Err = OxenError;
+
+    fn from_str(scope: &str) -> Result<TokenScope, OxenError> {
+        match scope.split_once(':') {
+            None if scope == "read" => Ok(TokenScope::ReadOnly),
+            Some(("namespace", namespace)) if !namespace.is_empty() => {
+                Ok(TokenScope::Namespace(String::from(namespace)))
+            }
+            Some(("push", repo)) => match repo.split_once('/') {
+                Some((namespace, name)) if !namespace.is_empty() && !name.is_empty() => Ok(
+                    TokenScope::Push(String::from(namespace), String::from(name)),
+                ),
+                _ => Err(OxenError::basic_str(format!(
+                    "Invalid token scope '{scope}', push scopes look like push:<namespace>/<repo_name>"
+                ))),
+            },
+            _ => Err(OxenError::basic_str(format!(
+                "Invalid token scope '{scope}', must be read, namespace:<namespace> or push:<namespace>/<repo_name>"
+            ))),
+        }
+    }
+}
+
+impl TryFrom<String> for TokenScope {
+    type Error = OxenError;
+
+    fn try_from(scope: String) -> Result<TokenScope, OxenError> {
+        TokenScope::from_str(&scope)
+    }
+}
+
+impl From<TokenScope> for String {
+    fn from(scope: TokenScope) -> String {
+        scope.to_string()
+    }
+}
+
+impl fmt::Display for TokenScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenScope::ReadOnly => write!(f, "read"),
+            TokenScope::Namespace(namespace) => write!(f, "namespace:{namespace}"),
+            TokenScope::Push(namespace, name) => write!(f, "push:{namespace}/{name}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct JWTClaim {
     id: String,
     name: String,
     email: String,
+    /// Seconds since the epoch after which the token is rejected, tokens without it never expire
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    exp: Option<u64>,
+    /// A token without scopes can do anything its user has access to
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    scopes: Vec<TokenScope>,
+}
+
+impl JWTClaim {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn email(&self) -> &str {
+        &self.email
+    }
+
+    pub fn expires_at(&self) -> Option<OffsetDateTime> {
+        self.exp
+            .and_then(|exp| OffsetDateTime::from_unix_timestamp(exp as i64).ok())
+    }
+
+    pub fn is_expired(&self) -> bool {
+        match self.exp {
+            Some(exp) => exp < OffsetDateTime::now_utc().unix_timestamp() as u64,
+            None => false,
+        }
+    }
+
+    pub fn scopes(&self) -> &[TokenScope] {
+        &self.scopes
+    }
+
+    /// Returns why the token's scopes do not allow the access, or None if they do
+    pub fn missing_scope(&self, required: &RequiredAccess) -> Option<String> {
+        if required.role > Role::Read && self.scopes.contains(&TokenScope::ReadOnly) {
+            return Some(format!(
+                "Token is read only, the request needs {}",
+                required.describe()
+            ));
+        }
+
+        let namespaces: Vec<&String> = self
+            .scopes
+            .iter()
+            .filter_map(|scope| match scope {
+                TokenScope::Namespace(namespace) => Some(namespace),
+                _ => None,
+            })
+            .collect();
+        if !namespaces.is_empty() && !namespaces.contains(&&required.namespace) {
+            return Some(format!(
+                "Token is not scoped to namespace {}",
+                required.namespace
+            ));
+        }
+
+        let pushes: Vec<(&String, &String)> = self
+            .scopes
+            .iter()
+            .filter_map(|scope| match scope {
+                TokenScope::Push(namespace, name) => Some((namespace, name)),
+                _ => None,
+            })
+            .collect();
+        if required.role > Role::Read && !pushes.is_empty() {
+            let can_push = match &required.repo_name {
+                Some(repo_name) => pushes.iter().any(|(namespace, name)| {
+                    **namespace == required.namespace && *name == repo_name
+                }),
+                None => false,
+            };
+            if !can_push {
+                return Some(format!(
+                    "Token can only push to its push scopes, the request needs {}",
+                    required.describe()
+                ));
+            }
+        }
+
+        None
+    }
 }
 
 pub struct AccessKeyManager {
@@ -74,10 +227,26 @@ impl AccessKeyManager {
     }
 
     pub fn create(&self, user: &User) -> Result<(User, String), OxenError> {
+        self.create_scoped(user, &[], None)
+    }
+
+    /// Creates a token limited to the scopes, that stops working after `expires_in` if it is set
+    pub fn create_scoped(
+        &self,
+        user: &User,
+        scopes: &[TokenScope],
+        expires_in: Option<Duration>,
+    ) -> Result<(User, String), OxenError> {
+        let exp = expires_in.map(|expires_in| {
+            let now = OffsetDateTime::now_utc().unix_timestamp() as u64;
+            now + expires_in.as_secs()
+        });
         let user_claims = JWTClaim {
             id: format!("{}", uuid::Uuid::new_v4()),
             name: user.name.to_owned(),
             email: user.email.to_owned(),
+            exp,
+            scopes: scopes.to_vec(),
         };
 
         let secret_key = self.read_secret_key()?;
@@ -125,6 +294,42 @@ impl AccessKeyManager {
         }
     }
 
+    /// The claims of every token that has not been revoked, including expired ones
+    pub fn list(&self) -> Result<Vec<JWTClaim>, OxenError> {
+        let mut claims: Vec<JWTClaim> = vec![];
+        for (_token, value) in self.db.iterator(IteratorMode::Start) {
+            let value = str::from_utf8(&value)?;
+            claims.push(serde_json::from_str(value)?);
+        }
+        Ok(claims)
+    }
+
+    /// Revokes the token with this claim id, returns false if there was none
+    pub fn revoke(&self, id: &str) -> Result<bool, OxenError> {
+        let num_revoked = self.p_revoke(|claim| claim.id == id)?;
+        Ok(num_revoked > 0)
+    }
+
+    /// Revokes all the tokens of a user, returns how many there were
+    pub fn revoke_user(&self, email: &str) -> Result<usize, OxenError> {
+        self.p_revoke(|claim| claim.email == email)
+    }
+
+    fn p_revoke<F: Fn(&JWTClaim) -> bool>(&self, should_revoke: F) -> Result<usize, OxenError> {
+        let mut tokens: Vec<Box<[u8]>> = vec![];
+        for (token, value) in self.db.iterator(IteratorMode::Start) {
+            let claim: JWTClaim = serde_json::from_str(str::from_utf8(&value)?)?;
+            if should_revoke(&claim) {
+                tokens.push(token);
+            }
+        }
+
+        for token in tokens.iter() {
+            self.db.delete(token)?;
+        }
+        Ok(tokens.len())
+    }
+
     pub fn token_is_valid(&self, token: &str) -> bool {
         match self.get_claim(token) {
             Ok(Some(claim)) => {
@@ -135,6 +340,8 @@ impl AccessKeyManager {
 
                 let mut validator = Validation::new(Algorithm::HS256);
                 validator.set_required_spec_claims(&["email"]);
+                // We are the only ones minting tokens so there is no clock skew to allow for
+                validator.leeway = 0;
                 match decode::<JWTClaim>(
                     token,
                     &DecodingKey::from_secret(secret.unwrap().as_ref()),
@@ -164,10 +371,13 @@ impl AccessKeyManager {
 #[cfg(test)]
 mod tests {
 
-    use crate::auth::access_keys::AccessKeyManager;
+    use crate::auth::access_control::{RequiredAccess, Role};
+    use crate::auth::access_keys::{AccessKeyManager, TokenScope};
     use crate::test;
     use liboxen::error::OxenError;
     use liboxen::model::User;
+    use std::str::FromStr;
+    use std::time::Duration;
 
     #[test]
     fn test_constructor() -> Result<(), OxenError> {
@@ -238,4 +448,113 @@ mod tests {
             Ok(())
         })
     }
+
+    #[test]
+    fn test_revoke_token() -> Result<(), OxenError> {
+        test::run_empty_sync_dir_test(|sync_dir| {
+            let keygen = AccessKeyManager::new(sync_dir)?;
+            let ox = User {
+                name: String::from("Ox"),
+                email: String::from("ox@oxen.ai"),
+            };
+            let bessie = User {
+                name: String::from("Bessie"),
+                email: String::from("bessie@oxen.ai"),
+            };
+            let (_user, token) = keygen.create(&ox)?;
+            let (_user, other_token) = keygen.create(&ox)?;
+            let (_user, bessie_token) = keygen.create(&bessie)?;
+            assert_eq!(keygen.list()?.len(), 3);
+
+            let claim = keygen.get_claim(&token)?.unwrap();
+            assert!(keygen.revoke(claim.id())?);
+            assert!(!keygen.revoke(claim.id())?);
+            assert!(!keygen.token_is_valid(&token));
+            assert!(keygen.token_is_valid(&other_token));
+
+            assert_eq!(keygen.revoke_user("ox@oxen.ai")?, 1);
+            assert!(!keygen.token_is_valid(&other_token));
+            assert!(keygen.token_is_valid(&bessie_token));
+            assert_eq!(keygen.list()?.len(), 1);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_expired_token_is_invalid() -> Result<(), OxenError> {
+        test::run_empty_sync_dir_test(|sync_dir| {
+            let keygen = AccessKeyManager::new(sync_dir)?;
+            let new_user = User {
+                name: String::from("Ox"),
+                email: String::from("ox@oxen.ai"),
+            };
+            let (_user, token) =
+                keygen.create_scoped(&new_user, &[], Some(Duration::from_secs(0)))?;
+            let (_user, long_lived) =
+                keygen.create_scoped(&new_user, &[], Some(Duration::from_secs(60 * 60)))?;
+
+            std::thread::sleep(Duration::from_secs(2));
+            assert!(keygen.get_claim(&token)?.unwrap().is_expired());
+            assert!(!keygen.token_is_valid(&token));
+            assert!(keygen.token_is_valid(&long_lived));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_token_scopes() -> Result<(), OxenError> {
+        test::run_empty_sync_dir_test(|sync_dir| {
+            let keygen = AccessKeyManager::new(sync_dir)?;
+            let new_user = User {
+                name: String::from("Ox"),
+                email: String::from("ox@oxen.ai"),
+            };
+            let scopes = vec![
+                TokenScope::from_str("namespace:ox")?,
+                TokenScope::from_str("push:ox/data")?,
+            ];
+            let (_user, token) = keygen.create_scoped(&new_user, &scopes, None)?;
+            assert!(keygen.token_is_valid(&token));
+            let claim = keygen.get_claim(&token)?.unwrap();
+            assert_eq!(claim.scopes(), scopes);
+
+            let required = |namespace: &str, repo_name: &str, role: Role| RequiredAccess {
+                namespace: String::from(namespace),
+                repo_name: Some(String::from(repo_name)),
+                role,
+            };
+            assert!(claim
+                .missing_scope(&required("ox", "data", Role::Write))
+                .is_none());
+            assert!(claim
+                .missing_scope(&required("ox", "other", Role::Read))
+                .is_none());
+            assert!(claim
+                .missing_scope(&required("ox", "other", Role::Write))
+                .is_some());
+            assert_eq!(
+                claim.missing_scope(&required("cow", "data", Role::Read)),
+                Some(String::from("Token is not scoped to namespace cow"))
+            );
+
+            let (_user, token) = keygen.create_scoped(&new_user, &[TokenScope::ReadOnly], None)?;
+            let claim = keygen.get_claim(&token)?.unwrap();
+            assert!(claim
+                .missing_scope(&required("cow", "data", Role::Read))
+                .is_none());
+            assert_eq!(
+                claim.missing_scope(&required("cow", "data", Role::Write)),
+                Some(String::from(
+                    "Token is read only, the request needs write access to cow/data"
+                ))
+            );
+
+            assert!(TokenScope::from_str("push:ox").is_err());
+            assert!(TokenScope::from_str("admin").is_err());
+
+            Ok(())
+        })
+    }
 }
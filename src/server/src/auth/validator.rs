@@ -1,8 +1,12 @@
 use crate::app_data::OxenAppData;
 use crate::auth;
+use crate::auth::access_control::{AccessControl, RequiredAccess};
 
 use actix_web::dev::ServiceRequest;
+use actix_web::error::InternalError;
+use actix_web::{HttpMessage, HttpResponse};
 use actix_web_httpauth::extractors::bearer::BearerAuth;
+use liboxen::view::StatusMessage;
 
 pub async fn validate(
     req: ServiceRequest,
@@ -12,14 +16,85 @@ pub async fn validate(
     match auth::access_keys::AccessKeyManager::new_read_only(&app_data.path) {
         Ok(keygen) => {
             let token = credentials.token();
-            if keygen.token_is_valid(token) {
-                Ok(req)
-            } else {
-                Err(actix_web::error::ErrorUnauthorized("unauthorized"))
+            if !keygen.token_is_valid(token) {
+                return Err(actix_web::error::ErrorUnauthorized("unauthorized"));
             }
+
+            let claim = match keygen.get_claim(token) {
+                Ok(Some(claim)) => claim,
+                _ => return Err(actix_web::error::ErrorUnauthorized("unauthorized")),
+            };
+
+            if let Some(required) = required_access(&req) {
+                let names = std::iter::once(&required.namespace).chain(&required.repo_name);
+                if let Some(Err(err)) = names
+                    .map(|n| AccessControl::validate_name(n))
+                    .find(|r| r.is_err())
+                {
+                    return Err(actix_web::error::ErrorBadRequest(err.to_string()));
+                }
+                if let Some(missing) = claim.missing_scope(&required) {
+                    return Err(forbidden(&req, missing));
+                }
+
+                let access = AccessControl::new(&app_data.path);
+                match access.missing_permission(claim.email(), &required) {
+                    Ok(None) => {}
+                    Ok(Some(missing)) => return Err(forbidden(&req, missing)),
+                    Err(err) => {
+                        return Err(actix_web::error::ErrorInternalServerError(format!(
+                            "Err could not check access: {err}"
+                        )))
+                    }
+                }
+            }
+
+            // Controllers that need to know who is calling, like repository creation, read the claim
+            req.extensions_mut().insert(claim);
+            Ok(req)
         }
         Err(err) => Err(actix_web::error::ErrorInternalServerError(format!(
             "Err could not get keygen: {err}"
         ))),
     }
 }
+
+// Classified from the path the router matches on, which has the percent encoding decoded, so `%74ags` is `tags`
+fn required_access(req: &ServiceRequest) -> Option<RequiredAccess> {
+    RequiredAccess::from_request(req.method(), req.match_info().as_str())
+}
+
+fn forbidden(req: &ServiceRequest, missing: String) -> actix_web::Error {
+    log::debug!("403 {} {}: {}", req.method(), req.path(), missing);
+    let response = HttpResponse::Forbidden().json(StatusMessage::error(&missing));
+    InternalError::from_response(missing, response).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+
+    use crate::auth::access_control::Role;
+
+    #[actix_web::test]
+    async fn test_validator_required_access_decodes_the_path() {
+        let req = TestRequest::delete()
+            .uri("/api/repos/ox/data/%74ags/v1.0")
+            .to_srv_request();
+        let required = super::required_access(&req).unwrap();
+        assert_eq!(required.role, Role::Admin);
+
+        let req = TestRequest::post()
+            .uri("/api/repos/ox/data/%70rotected_branches")
+            .to_srv_request();
+        let required = super::required_access(&req).unwrap();
+        assert_eq!(required.role, Role::Admin);
+
+        // An encoded name is not decoded into another one
+        let req = TestRequest::get()
+            .uri("/api/repos/%2E%2E%2Fox/data/commits")
+            .to_srv_request();
+        let required = super::required_access(&req).unwrap();
+        assert_ne!(required.namespace, "ox");
+    }
+}
@@ -1,10 +1,14 @@
 pub mod branches;
+pub mod chunks;
 pub mod commits;
+pub mod compare;
 pub mod df;
 pub mod dir;
 pub mod entries;
 pub mod file;
 pub mod namespaces;
+pub mod protected_branches;
 pub mod repositories;
 pub mod schemas;
+pub mod tags;
 pub mod version;
@@ -3,6 +3,7 @@ use crate::app_data::OxenAppData;
 use actix_web::{HttpRequest, HttpResponse};
 
 use liboxen::api;
+use liboxen::error::OxenError;
 use liboxen::view::http::{
     MSG_RESOURCE_CREATED, MSG_RESOURCE_DELETED, MSG_RESOURCE_FOUND, MSG_RESOURCE_UPDATED,
     STATUS_SUCCESS,
@@ -170,6 +171,23 @@ pub async fn delete(req: HttpRequest) -> HttpResponse {
             Ok(Some(repository)) => {
                 match api::local::branches::get_by_name(&repository, branch_name) {
                     Ok(Some(branch)) => {
+                        match api::local::branch_policies::delete_violation(
+                            &repository,
+                            branch_name,
+                        ) {
+                            Ok(None) => {}
+                            Ok(Some(violation)) => {
+                                log::debug!("Refusing to delete branch: {}", violation);
+                                return HttpResponse::Forbidden()
+                                    .json(StatusMessage::error(&violation));
+                            }
+                            Err(err) => {
+                                log::error!("Could not check branch policy: {}", err);
+                                return HttpResponse::InternalServerError()
+                                    .json(StatusMessage::internal_server_error());
+                            }
+                        }
+
                         match api::local::branches::force_delete(&repository, branch_name) {
                             Ok(_) => HttpResponse::Ok().json(BranchResponse {
                                 status: String::from(STATUS_SUCCESS),
@@ -227,12 +245,78 @@ pub async fn update(req: HttpRequest, body: String) -> HttpResponse {
                 name,
             ) {
                 Ok(Some(repo)) => {
-                    match api::local::branches::update(&repo, branch_name, &data.commit_id) {
-                        Ok(branch) => HttpResponse::Ok().json(BranchResponse {
+                    // Everything below is checked against this commit, and the branch only moves if it is
+                    // still there at the end
+                    let current_commit_id =
+                        match api::local::branches::get_by_name(&repo, branch_name) {
+                            Ok(branch) => branch.map(|branch| branch.commit_id),
+                            Err(err) => {
+                                log::error!("Could not get branch {}: {}", branch_name, err);
+                                return HttpResponse::InternalServerError()
+                                    .json(StatusMessage::internal_server_error());
+                            }
+                        };
+
+                    match api::local::branch_policies::update_violation(
+                        &repo,
+                        branch_name,
+                        &data.commit_id,
+                        data.force,
+                    ) {
+                        Ok(None) => {}
+                        Ok(Some(violation)) => {
+                            log::debug!("Refusing to update branch: {}", violation);
+                            return HttpResponse::Forbidden()
+                                .json(StatusMessage::error(&violation));
+                        }
+                        Err(err) => {
+                            log::error!("Could not check branch policy: {}", err);
+                            return HttpResponse::InternalServerError()
+                                .json(StatusMessage::internal_server_error());
+                        }
+                    }
+
+                    if !data.force {
+                        match api::local::branches::is_fast_forward(
+                            &repo,
+                            current_commit_id.as_deref(),
+                            &data.commit_id,
+                        ) {
+                            Ok(true) => {}
+                            Ok(false) => {
+                                log::debug!(
+                                    "Rejecting non fast forward update of branch {} to {}",
+                                    branch_name,
+                                    data.commit_id
+                                );
+                                let err = OxenError::remote_branch_not_fast_forward(branch_name);
+                                return HttpResponse::BadRequest()
+                                    .json(StatusMessage::error(&err.to_string()));
+                            }
+                            Err(err) => {
+                                log::debug!("Error checking branch {}: {}", branch_name, err);
+                                return HttpResponse::BadRequest()
+                                    .json(StatusMessage::error(&err.to_string()));
+                            }
+                        }
+                    }
+
+                    match api::local::branches::compare_and_update(
+                        &repo,
+                        branch_name,
+                        current_commit_id.as_deref(),
+                        &data.commit_id,
+                    ) {
+                        Ok(Some(branch)) => HttpResponse::Ok().json(BranchResponse {
                             status: String::from(STATUS_SUCCESS),
                             status_message: String::from(MSG_RESOURCE_UPDATED),
                             branch,
                         }),
+                        Ok(None) => {
+                            log::debug!("Branch {} moved during the update", branch_name);
+                            let err = OxenError::remote_branch_not_fast_forward(branch_name);
+                            HttpResponse::Conflict().json(StatusMessage::error(&err.to_string()))
+                        }
                         Err(err) => {
                             log::debug!("Error updating branch {}: {}", branch_name, err);
                             HttpResponse::InternalServerError()
@@ -271,10 +355,13 @@ mod tests {
     use actix_web::body::to_bytes;
 
     use liboxen::api;
+    use liboxen::command;
     use liboxen::constants::DEFAULT_BRANCH_NAME;
     use liboxen::error::OxenError;
-    use liboxen::view::http::STATUS_SUCCESS;
-    use liboxen::view::{BranchResponse, ListBranchesResponse};
+    use liboxen::model::ProtectedBranch;
+    use liboxen::util;
+    use liboxen::view::http::{STATUS_ERROR, STATUS_SUCCESS};
+    use liboxen::view::{BranchResponse, ListBranchesResponse, StatusMessage};
 
     use crate::controllers;
     use crate::test;
@@ -394,4 +481,83 @@ mod tests {
 
         Ok(())
     }
+
+    #[actix_web::test]
+    async fn test_controllers_branch_update_rejects_non_fast_forward() -> Result<(), OxenError> {
+        let sync_dir = test::get_sync_dir()?;
+
+        let namespace = "Testing-Namespace";
+        let repo_name = "Testing-Branches-Update";
+        let repo = test::create_local_repo(&sync_dir, namespace, repo_name)?;
+
+        // main: first -> second, other: first -> diverged
+        let path = repo.path.join("hello.txt");
+        util::fs::write_to_path(&path, "hello")?;
+        command::add(&repo, &path)?;
+        command::commit(&repo, "first")?;
+        command::create_checkout_branch(&repo, "other")?;
+        util::fs::write_to_path(&path, "diverged")?;
+        command::add(&repo, &path)?;
+        let diverged = command::commit(&repo, "diverged")?.unwrap();
+        command::checkout(&repo, DEFAULT_BRANCH_NAME)?;
+        util::fs::write_to_path(&path, "second")?;
+        command::add(&repo, &path)?;
+        let second = command::commit(&repo, "second")?.unwrap();
+
+        let uri = format!("/oxen/{namespace}/{repo_name}/branches/{DEFAULT_BRANCH_NAME}");
+        let data = format!(r#"{{"commit_id": "{}"}}"#, diverged.id);
+        let req = test::repo_request_with_param(
+            &sync_dir,
+            &uri,
+            namespace,
+            repo_name,
+            "branch_name",
+            DEFAULT_BRANCH_NAME,
+        );
+        let resp = controllers::branches::update(req, data).await;
+        assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+        let body = to_bytes(resp.into_body()).await.unwrap();
+        let text = std::str::from_utf8(&body).unwrap();
+        let status: StatusMessage = serde_json::from_str(text)?;
+        assert_eq!(status.status, STATUS_ERROR);
+        assert!(status.status_message.contains("Fetch and merge first"));
+        let branch = api::local::branches::get_by_name(&repo, DEFAULT_BRANCH_NAME)?.unwrap();
+        assert_eq!(branch.commit_id, second.id);
+
+        // Forcing it moves the branch
+        let data = format!(r#"{{"commit_id": "{}", "force": true}}"#, diverged.id);
+        let req = test::repo_request_with_param(
+            &sync_dir,
+            &uri,
+            namespace,
+            repo_name,
+            "branch_name",
+            DEFAULT_BRANCH_NAME,
+        );
+        let resp = controllers::branches::update(req, data).await;
+        assert_eq!(resp.status(), http::StatusCode::OK);
+        let branch = api::local::branches::get_by_name(&repo, DEFAULT_BRANCH_NAME)?.unwrap();
+        assert_eq!(branch.commit_id, diverged.id);
+
+        // Unless the branch is protected
+        api::local::branch_policies::protect(&repo, ProtectedBranch::new(DEFAULT_BRANCH_NAME))?;
+        let data = format!(r#"{{"commit_id": "{}", "force": true}}"#, second.id);
+        let req = test::repo_request_with_param(
+            &sync_dir,
+            &uri,
+            namespace,
+            repo_name,
+            "branch_name",
+            DEFAULT_BRANCH_NAME,
+        );
+        let resp = controllers::branches::update(req, data).await;
+        assert_eq!(resp.status(), http::StatusCode::FORBIDDEN);
+        let branch = api::local::branches::get_by_name(&repo, DEFAULT_BRANCH_NAME)?.unwrap();
+        assert_eq!(branch.commit_id, diverged.id);
+
+        // cleanup
+        std::fs::remove_dir_all(sync_dir)?;
+
+        Ok(())
+    }
 }
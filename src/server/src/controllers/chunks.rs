@@ -0,0 +1,277 @@
+use crate::app_data::OxenAppData;
+
+use liboxen::api;
+use liboxen::index::ChunkStore;
+use liboxen::model::ChunkManifest;
+use liboxen::view::http::{MSG_RESOURCE_CREATED, MSG_RESOURCE_FOUND, STATUS_SUCCESS};
+use liboxen::view::{ChunkHashes, ChunkHashesResponse, ChunkManifestResponse, StatusMessage};
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use futures_util::stream::StreamExt as _;
+
+/// Given a list of chunk hashes, respond with the ones this repo does not have
+pub async fn list_missing(req: HttpRequest, body: String) -> HttpResponse {
+    let app_data = req.app_data::<OxenAppData>().unwrap();
+    let namespace: &str = req.match_info().get("namespace").unwrap();
+    let name: &str = req.match_info().get("repo_name").unwrap();
+
+    let data: ChunkHashes = match serde_json::from_str(&body) {
+        Ok(data) => data,
+        Err(err) => {
+            log::error!("chunks::list_missing could not parse body: {}", err);
+            return HttpResponse::BadRequest().json(StatusMessage::error("Invalid body."));
+        }
+    };
+    for hash in data.hashes.iter() {
+        if let Err(err) = ChunkStore::validate_hash(hash) {
+            return HttpResponse::BadRequest().json(StatusMessage::error(&err.to_string()));
+        }
+    }
+
+    match api::local::repositories::get_by_namespace_and_name(&app_data.path, namespace, name) {
+        Ok(Some(repo)) => match ChunkStore::new(&repo).missing_chunks(&data.hashes) {
+            Ok(hashes) => HttpResponse::Ok().json(ChunkHashesResponse {
+                status: String::from(STATUS_SUCCESS),
+                status_message: String::from(MSG_RESOURCE_FOUND),
+                hashes,
+            }),
+            Err(err) => {
+                log::error!("Unable to list missing chunks. Err: {}", err);
+                HttpResponse::InternalServerError().json(StatusMessage::internal_server_error())
+            }
+        },
+        Ok(None) => {
+            log::debug!("404 could not get repo {}", name);
+            HttpResponse::NotFound().json(StatusMessage::resource_not_found())
+        }
+        Err(err) => {
+            log::error!("Unable to get repository {}. Err: {}", name, err);
+            HttpResponse::InternalServerError().json(StatusMessage::internal_server_error())
+        }
+    }
+}
+
+pub async fn upload(req: HttpRequest, mut body: web::Payload) -> HttpResponse {
+    let app_data = req.app_data::<OxenAppData>().unwrap();
+    let namespace: &str = req.match_info().get("namespace").unwrap();
+    let name: &str = req.match_info().get("repo_name").unwrap();
+    let hash: &str = req.match_info().get("hash").unwrap();
+
+    match api::local::repositories::get_by_namespace_and_name(&app_data.path, namespace, name) {
+        Ok(Some(repo)) => {
+            let mut bytes = web::BytesMut::new();
+            while let Some(item) = body.next().await {
+                match item {
+                    Ok(item) => bytes.extend_from_slice(&item),
+                    Err(err) => {
+                        log::error!("chunks::upload could not read body: {}", err);
+                        return HttpResponse::BadRequest()
+                            .json(StatusMessage::error("Could not read chunk."));
+                    }
+                }
+            }
+
+            match ChunkStore::new(&repo).put_chunk(hash, &bytes) {
+                Ok(_) => HttpResponse::Ok().json(StatusMessage::success(MSG_RESOURCE_CREATED)),
+                Err(err) => {
+                    log::error!("Could not save chunk {}. Err: {}", hash, err);
+                    HttpResponse::BadRequest().json(StatusMessage::error(&err.to_string()))
+                }
+            }
+        }
+        Ok(None) => {
+            log::debug!("404 could not get repo {}", name);
+            HttpResponse::NotFound().json(StatusMessage::resource_not_found())
+        }
+        Err(err) => {
+            log::error!("Unable to get repository {}. Err: {}", name, err);
+            HttpResponse::InternalServerError().json(StatusMessage::internal_server_error())
+        }
+    }
+}
+
+pub async fn download(req: HttpRequest) -> HttpResponse {
+    let app_data = req.app_data::<OxenAppData>().unwrap();
+    let namespace: &str = req.match_info().get("namespace").unwrap();
+    let name: &str = req.match_info().get("repo_name").unwrap();
+    let hash: &str = req.match_info().get("hash").unwrap();
+    if let Err(err) = ChunkStore::validate_hash(hash) {
+        return HttpResponse::BadRequest().json(StatusMessage::error(&err.to_string()));
+    }
+
+    match api::local::repositories::get_by_namespace_and_name(&app_data.path, namespace, name) {
+        Ok(Some(repo)) => {
+            let store = ChunkStore::new(&repo);
+            if !store.has_chunk(hash) {
+                log::debug!("404 could not find chunk {}", hash);
+                return HttpResponse::NotFound().json(StatusMessage::resource_not_found());
+            }
+
+            match store.read_chunk(hash) {
+                Ok(data) => HttpResponse::Ok().body(data),
+                Err(err) => {
+                    log::error!("Could not read chunk {}. Err: {}", hash, err);
+                    HttpResponse::InternalServerError().json(StatusMessage::internal_server_error())
+                }
+            }
+        }
+        Ok(None) => {
+            log::debug!("404 could not get repo {}", name);
+            HttpResponse::NotFound().json(StatusMessage::resource_not_found())
+        }
+        Err(err) => {
+            log::error!("Unable to get repository {}. Err: {}", name, err);
+            HttpResponse::InternalServerError().json(StatusMessage::internal_server_error())
+        }
+    }
+}
+
+pub async fn show_manifest(req: HttpRequest) -> HttpResponse {
+    let app_data = req.app_data::<OxenAppData>().unwrap();
+    let namespace: &str = req.match_info().get("namespace").unwrap();
+    let name: &str = req.match_info().get("repo_name").unwrap();
+    let hash: &str = req.match_info().get("hash").unwrap();
+    if let Err(err) = ChunkStore::validate_hash(hash) {
+        return HttpResponse::BadRequest().json(StatusMessage::error(&err.to_string()));
+    }
+
+    match api::local::repositories::get_by_namespace_and_name(&app_data.path, namespace, name) {
+        Ok(Some(repo)) => match ChunkStore::new(&repo).get_manifest(hash) {
+            Ok(Some(manifest)) => HttpResponse::Ok().json(ChunkManifestResponse {
+                status: String::from(STATUS_SUCCESS),
+                status_message: String::from(MSG_RESOURCE_FOUND),
+                manifest,
+            }),
+            Ok(None) => {
+                // Versions stored whole are downloaded whole, a GET does not chunk them
+                log::debug!("404 could not find manifest {}", hash);
+                HttpResponse::NotFound().json(StatusMessage::resource_not_found())
+            }
+            Err(err) => {
+                log::error!("Could not get manifest {}. Err: {}", hash, err);
+                HttpResponse::InternalServerError().json(StatusMessage::internal_server_error())
+            }
+        },
+        Ok(None) => {
+            log::debug!("404 could not get repo {}", name);
+            HttpResponse::NotFound().json(StatusMessage::resource_not_found())
+        }
+        Err(err) => {
+            log::error!("Unable to get repository {}. Err: {}", name, err);
+            HttpResponse::InternalServerError().json(StatusMessage::internal_server_error())
+        }
+    }
+}
+
+/// Saves a manifest once all of its chunks have been uploaded
+pub async fn create_manifest(req: HttpRequest, body: String) -> HttpResponse {
+    let app_data = req.app_data::<OxenAppData>().unwrap();
+    let namespace: &str = req.match_info().get("namespace").unwrap();
+    let name: &str = req.match_info().get("repo_name").unwrap();
+
+    let manifest: ChunkManifest = match serde_json::from_str(&body) {
+        Ok(manifest) => manifest,
+        Err(err) => {
+            log::error!("chunks::create_manifest could not parse body: {}", err);
+            return HttpResponse::BadRequest().json(StatusMessage::error("Invalid body."));
+        }
+    };
+
+    match api::local::repositories::get_by_namespace_and_name(&app_data.path, namespace, name) {
+        Ok(Some(repo)) => match ChunkStore::new(&repo).put_manifest(&manifest) {
+            Ok(_) => HttpResponse::Ok().json(ChunkManifestResponse {
+                status: String::from(STATUS_SUCCESS),
+                status_message: String::from(MSG_RESOURCE_CREATED),
+                manifest,
+            }),
+            Err(err) => {
+                log::error!("Could not save manifest {}. Err: {}", manifest.hash, err);
+                HttpResponse::BadRequest().json(StatusMessage::error(&err.to_string()))
+            }
+        },
+        Ok(None) => {
+            log::debug!("404 could not get repo {}", name);
+            HttpResponse::NotFound().json(StatusMessage::resource_not_found())
+        }
+        Err(err) => {
+            log::error!("Unable to get repository {}. Err: {}", name, err);
+            HttpResponse::InternalServerError().json(StatusMessage::internal_server_error())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http;
+    use std::path::Path;
+
+    use liboxen::api;
+    use liboxen::command;
+    use liboxen::error::OxenError;
+    use liboxen::index::ChunkStore;
+    use liboxen::util;
+
+    use crate::controllers;
+    use crate::test;
+
+    #[actix_web::test]
+    async fn test_controllers_chunks_show_manifest_does_not_chunk() -> Result<(), OxenError> {
+        let sync_dir = test::get_sync_dir()?;
+        let namespace = "Testing-Namespace";
+        let name = "Testing-Chunks";
+        let repo = test::create_local_repo(&sync_dir, namespace, name)?;
+        let path = repo.path.join("labels.txt");
+        util::fs::write_to_path(&path, "cat\ndog")?;
+        command::add(&repo, &path)?;
+        command::commit(&repo, "Adding labels")?;
+        let entry = api::local::entries::get_entry_for_commit(
+            &repo,
+            &api::local::commits::get_head_commit(&repo)?,
+            Path::new("labels.txt"),
+        )?
+        .unwrap();
+
+        let uri = format!("/oxen/{namespace}/{name}/manifests/{}", entry.hash);
+        let req = test::repo_request_with_param(
+            &sync_dir,
+            &uri,
+            namespace,
+            name,
+            "hash",
+            entry.hash.clone(),
+        );
+        let resp = controllers::chunks::show_manifest(req).await;
+        assert_eq!(resp.status(), http::StatusCode::NOT_FOUND);
+        assert!(!ChunkStore::new(&repo).has_manifest(&entry.hash));
+
+        // cleanup
+        std::fs::remove_dir_all(sync_dir)?;
+
+        Ok(())
+    }
+
+    #[actix_web::test]
+    async fn test_controllers_chunks_download_rejects_bad_hash() -> Result<(), OxenError> {
+        let sync_dir = test::get_sync_dir()?;
+        let namespace = "Testing-Namespace";
+        let name = "Testing-Chunks";
+        test::create_local_repo(&sync_dir, namespace, name)?;
+
+        for hash in ["a", "é", "abcx"] {
+            let uri = format!("/oxen/{namespace}/{name}/chunks/hash");
+            let req = test::repo_request_with_param(&sync_dir, &uri, namespace, name, "hash", hash);
+            let resp = controllers::chunks::download(req).await;
+            assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+
+            let uri = format!("/oxen/{namespace}/{name}/chunks/hash/manifest");
+            let req = test::repo_request_with_param(&sync_dir, &uri, namespace, name, "hash", hash);
+            let resp = controllers::chunks::show_manifest(req).await;
+            assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+        }
+
+        // cleanup
+        std::fs::remove_dir_all(sync_dir)?;
+
+        Ok(())
+    }
+}
@@ -3,11 +3,13 @@ use liboxen::command;
 use liboxen::compute::cachers::content_validator;
 use liboxen::compute::commit_cacher;
 use liboxen::compute::commit_cacher::CacherStatusType;
+use liboxen::constants;
 use liboxen::constants::HASH_FILE;
 use liboxen::constants::HISTORY_DIR;
 use liboxen::error::OxenError;
-use liboxen::index::CommitWriter;
-use liboxen::model::{Commit, LocalRepository};
+use liboxen::index::{CommitWriter, HistoryPack, UploadSessionStore};
+use liboxen::model::{Commit, LocalRepository, UploadSession};
+use liboxen::opts::LogOpts;
 use liboxen::util;
 use liboxen::view::http::MSG_FAILED_PROCESS;
 use liboxen::view::http::MSG_INTERNAL_SERVER_ERROR;
@@ -15,7 +17,8 @@ use liboxen::view::http::MSG_RESOURCE_IS_PROCESSING;
 use liboxen::view::http::STATUS_ERROR;
 use liboxen::view::http::{MSG_RESOURCE_CREATED, MSG_RESOURCE_FOUND, STATUS_SUCCESS};
 use liboxen::view::{
-    CommitParentsResponse, CommitResponse, IsValidStatusMessage, ListCommitResponse, StatusMessage,
+    CommitParentsResponse, CommitResponse, IsValidStatusMessage, ListCommitResponse,
+    PaginatedCommitsResponse, StatusMessage, UploadSessionResponse,
 };
 
 use crate::app_data::OxenAppData;
@@ -28,20 +31,19 @@ use flate2::Compression;
 use futures_util::stream::StreamExt as _;
 use serde::Deserialize;
 use std::convert::TryFrom;
-use std::io::Read;
 use std::io::Write;
-use std::path::Path;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tar::Archive;
 
 #[derive(Deserialize, Debug)]
 pub struct ChunkedDataUploadQuery {
-    hash: String,             // UUID to tie all the chunks together (hash of the contents)
-    chunk_num: usize,         // which chunk it is, so that we can combine it all in the end
-    total_chunks: usize,      // how many chunks to expect
-    total_size: usize,        // total size so we can know when we are finished
-    is_compressed: bool,      // whether or not we need to decompress the archive
-    filename: Option<String>, // maybe a file name if !compressed
+    hash: String, // UUID to tie all the chunks together (hash of the commit and entries)
+    content_hash: Option<String>, // hash of the contents, to know whether earlier chunks can be reused
+    chunk_num: usize,             // which chunk it is, so that we can combine it all in the end
+    total_chunks: usize,          // how many chunks to expect
+    total_size: usize,            // total size so we can know when we are finished
+    is_compressed: bool,          // whether or not we need to decompress the archive
+    filename: Option<String>,     // maybe a file name if !compressed
 }
 
 // List commits for a repository
@@ -65,8 +67,23 @@ pub async fn index(req: HttpRequest) -> HttpResponse {
     }
 }
 
-// List history for a branch or commit
-pub async fn commit_history(req: HttpRequest) -> HttpResponse {
+#[derive(Deserialize, Debug)]
+pub struct CommitHistoryQuery {
+    pub page: Option<usize>,
+    pub page_size: Option<usize>,
+    pub path: Option<String>,
+    pub author: Option<String>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub grep: Option<String>,
+}
+
+// List history for a branch or commit, filtered like `oxen log`. Paginated if `page` or `page_size` is given,
+// otherwise the whole list comes back as a single page.
+pub async fn commit_history(
+    req: HttpRequest,
+    query: web::Query<CommitHistoryQuery>,
+) -> HttpResponse {
     let app_data = req.app_data::<OxenAppData>().unwrap();
     let namespace: Option<&str> = req.match_info().get("namespace");
     let repo_name: Option<&str> = req.match_info().get("repo_name");
@@ -75,8 +92,26 @@ pub async fn commit_history(req: HttpRequest) -> HttpResponse {
     if let (Some(namespace), Some(repo_name), Some(commit_or_branch)) =
         (namespace, repo_name, commit_or_branch)
     {
+        let opts = match parse_log_opts(commit_or_branch, &query) {
+            Ok(opts) => opts,
+            Err(err) => {
+                let msg = format!("{err}");
+                return HttpResponse::BadRequest().json(StatusMessage::error(&msg));
+            }
+        };
+        let page = query.page.unwrap_or(1).max(1);
+        let page_size = match (query.page, query.page_size) {
+            (None, None) => None,
+            (_, Some(page_size)) => Some(page_size),
+            (Some(_), None) => Some(constants::DEFAULT_PAGE_SIZE),
+        };
+        if page_size == Some(0) {
+            let msg = "page_size must be greater than 0";
+            return HttpResponse::BadRequest().json(StatusMessage::error(msg));
+        }
+
         let repo_dir = app_data.path.join(namespace).join(repo_name);
-        match p_index_commit_or_branch_history(&repo_dir, commit_or_branch) {
+        match p_index_commit_or_branch_history(&repo_dir, &opts, page, page_size) {
             Ok(response) => HttpResponse::Ok().json(response),
             Err(err) => {
                 let msg = format!("api err: {err}");
@@ -289,14 +324,59 @@ fn p_index(repo_dir: &Path) -> Result<ListCommitResponse, OxenError> {
     Ok(ListCommitResponse::success(commits))
 }
 
+fn parse_log_opts(
+    commit_or_branch: &str,
+    query: &CommitHistoryQuery,
+) -> Result<LogOpts, OxenError> {
+    Ok(LogOpts {
+        path: query.path.as_ref().map(PathBuf::from),
+        author: query.author.to_owned(),
+        since: query
+            .since
+            .as_deref()
+            .map(LogOpts::parse_since)
+            .transpose()?,
+        until: query
+            .until
+            .as_deref()
+            .map(LogOpts::parse_until)
+            .transpose()?,
+        grep: query.grep.to_owned(),
+        ..LogOpts::from_revision(commit_or_branch)
+    })
+}
+
+/// Without a `page_size` all the commits are returned in one page
 fn p_index_commit_or_branch_history(
     repo_dir: &Path,
-    commit_or_branch: &str,
-) -> Result<ListCommitResponse, OxenError> {
+    opts: &LogOpts,
+    page: usize,
+    page_size: Option<usize>,
+) -> Result<PaginatedCommitsResponse, OxenError> {
     let repo = LocalRepository::new(repo_dir)?;
-    let commits = command::log_commit_or_branch_history(&repo, commit_or_branch)?;
-    log::debug!("controllers::commits: : {:#?}", commits);
-    Ok(ListCommitResponse::success(commits))
+    let commits = command::log_with_opts(&repo, opts)?;
+    log::debug!(
+        "controllers::commits: {} commits for {:?}",
+        commits.len(),
+        opts
+    );
+
+    let total_entries = commits.len();
+    let page_size = page_size.unwrap_or(total_entries.max(1));
+    let commits: Vec<Commit> = commits
+        .into_iter()
+        .skip((page - 1) * page_size)
+        .take(page_size)
+        .collect();
+    Ok(PaginatedCommitsResponse {
+        status: String::from(STATUS_SUCCESS),
+        status_message: String::from(MSG_RESOURCE_FOUND),
+        commits,
+        page_size,
+        page_number: page,
+        total_pages: total_entries.div_ceil(page_size),
+        total_entries,
+    })
 }
 
 pub async fn download_commit_db(req: HttpRequest) -> HttpResponse {
@@ -346,6 +426,9 @@ pub async fn download_commit_db(req: HttpRequest) -> HttpResponse {
 
 fn compress_commit(repository: &LocalRepository, commit: &Commit) -> Result<Vec<u8>, OxenError> {
     // Tar and gzip the commit db directory
+    if HistoryPack::is_packed(repository, &commit.id) {
+        HistoryPack::new(repository).unpack_commit(&commit.id)?;
+    }
     // zip up the rocksdb in history dir, and post to server
     let commit_dir = util::fs::oxen_hidden_dir(&repository.path)
         .join(HISTORY_DIR)
@@ -423,6 +506,10 @@ pub async fn upload_chunk(
     let repo_name: &str = req.match_info().get("repo_name").unwrap();
     let commit_id: &str = req.match_info().get("commit_id").unwrap();
 
+    if let Err(err) = UploadSessionStore::validate_hash(&query.hash) {
+        return Ok(HttpResponse::BadRequest().json(StatusMessage::error(&err.to_string())));
+    }
+
     match api::local::repositories::get_by_namespace_and_name(&app_data.path, namespace, repo_name)
     {
         Ok(Some(repo)) => {
@@ -436,67 +523,65 @@ pub async fn upload_chunk(
 
                     log::debug!("upload_raw got chunk {chunk_num}/{total_chunks} of upload {id} of total size {size}");
 
-                    // Create a tmp dir for this upload
-                    let tmp_dir = hidden_dir.join("tmp").join("chunked").join(id);
-                    let chunk_file = tmp_dir.join(format!("chunk_{chunk_num:016}"));
-
-                    // mkdir if !exists
-                    if !tmp_dir.exists() {
-                        if let Err(err) = std::fs::create_dir_all(&tmp_dir) {
-                            log::error!("Could not complete chunk upload, mkdir failed: {:?}", err);
-                            return Ok(HttpResponse::InternalServerError()
-                                .json(StatusMessage::internal_server_error()));
-                        }
-                    }
-
                     // Read bytes from body
                     let mut bytes = web::BytesMut::new();
                     while let Some(item) = chunk.next().await {
                         bytes.extend_from_slice(&item.unwrap());
                     }
 
-                    // Write to tmp file
-                    log::debug!("upload_raw writing file {:?}", chunk_file);
-                    match std::fs::File::create(&chunk_file) {
-                        Ok(mut f) => {
-                            match f.write_all(&bytes) {
-                                Ok(_) => {
-                                    // Successfully wrote chunk
-                                    log::debug!(
-                                        "upload_raw successfully wrote chunk {:?}",
-                                        chunk_file
-                                    );
-
-                                    check_if_upload_complete_and_unpack(
-                                        hidden_dir,
-                                        tmp_dir,
-                                        total_chunks,
-                                        size,
-                                        query.is_compressed,
-                                        query.filename.to_owned(),
-                                    );
+                    let store = UploadSessionStore::new(&repo);
+                    // Starting a new upload is a good time to clean up the ones clients gave up on
+                    if let Ok(None) = store.get(&id) {
+                        if let Err(err) = store.expire_stale(app_data.upload_ttl) {
+                            log::error!("Could not expire stale uploads: {}", err);
+                        }
+                    }
+
+                    let upload = UploadSession::new(
+                        &id,
+                        commit_id,
+                        query.content_hash.as_deref(),
+                        total_chunks,
+                        size,
+                    );
+                    match store.put_chunk(&upload, chunk_num, &bytes) {
+                        Ok(session) => {
+                            log::debug!(
+                                "upload_raw successfully wrote chunk {} of {}",
+                                chunk_num,
+                                id
+                            );
 
-                                    Ok(HttpResponse::Ok().json(CommitResponse {
-                                        status: String::from(STATUS_SUCCESS),
-                                        status_message: String::from(MSG_RESOURCE_CREATED),
-                                        commit: commit.to_owned(),
-                                    }))
+                            if session.is_complete() {
+                                let result = store.read_all(&session);
+                                // Cleanup tmp files
+                                if let Err(err) = store.remove(&id) {
+                                    log::error!("Could not remove upload {}: {}", id, err);
                                 }
-                                Err(err) => {
-                                    log::error!(
-                                        "Could not complete chunk upload, file create failed: {:?}",
-                                        err
-                                    );
-                                    Ok(HttpResponse::InternalServerError()
-                                        .json(StatusMessage::internal_server_error()))
+
+                                match result {
+                                    Ok(buffer) => unpack_upload(
+                                        &hidden_dir,
+                                        buffer,
+                                        query.is_compressed,
+                                        query.filename.to_owned(),
+                                    ),
+                                    Err(err) => {
+                                        log::error!("Could not read upload {}: {}", id, err);
+                                        return Ok(HttpResponse::InternalServerError()
+                                            .json(StatusMessage::internal_server_error()));
+                                    }
                                 }
                             }
+
+                            Ok(HttpResponse::Ok().json(CommitResponse {
+                                status: String::from(STATUS_SUCCESS),
+                                status_message: String::from(MSG_RESOURCE_CREATED),
+                                commit: commit.to_owned(),
+                            }))
                         }
                         Err(err) => {
-                            log::error!(
-                                "Could not complete chunk upload, file create failed: {:?}",
-                                err
-                            );
+                            log::error!("Could not complete chunk upload: {:?}", err);
                             Ok(HttpResponse::InternalServerError()
                                 .json(StatusMessage::internal_server_error()))
                         }
@@ -524,92 +609,86 @@ pub async fn upload_chunk(
     }
 }
 
-fn check_if_upload_complete_and_unpack(
-    hidden_dir: PathBuf,
-    tmp_dir: PathBuf,
-    total_chunks: usize,
-    total_size: usize,
+fn unpack_upload(
+    hidden_dir: &Path,
+    buffer: Vec<u8>,
     is_compressed: bool,
     filename: Option<String>,
 ) {
-    let mut files = util::fs::list_files_in_dir(&tmp_dir);
-
-    if total_chunks < files.len() {
-        return;
-    }
-    files.sort();
+    log::debug!("Decompressing {} bytes to {:?}", buffer.len(), hidden_dir);
+
+    // TODO: better error handling...
+    // Combine into actual file data
+    if is_compressed {
+        // Unpack tarball to our hidden dir
+        let mut archive = Archive::new(GzDecoder::new(&buffer[..]));
+        unpack_entry_tarball(hidden_dir, &mut archive);
+    } else {
+        // just write buffer to disk
+        match filename {
+            Some(filename) => {
+                // TODO: better error handling...
+
+                log::debug!("Got filename {}", filename);
+                let full_path = hidden_dir.join(filename);
+                log::debug!("Unpack to {:?}", full_path);
+                if let Some(parent) = full_path.parent() {
+                    if !parent.exists() {
+                        std::fs::create_dir_all(parent).expect("Could not create parent dir");
+                    }
+                }
 
-    let mut uploaded_size: u64 = 0;
-    for file in files.iter() {
-        match std::fs::metadata(file) {
-            Ok(metadata) => {
-                uploaded_size += metadata.len();
+                let mut f = std::fs::File::create(&full_path).expect("Could write file");
+                match f.write_all(&buffer) {
+                    Ok(_) => {
+                        log::debug!("Unpack successful! {:?}", full_path);
+                    }
+                    Err(err) => {
+                        log::error!("Could not write all data to disk {:?}", err);
+                    }
+                }
             }
-            Err(err) => {
-                log::warn!("Err getting metadata on {:?}\n{:?}", file, err);
+            None => {
+                log::error!("Must supply filename if !compressed");
             }
         }
     }
+}
 
-    log::debug!(
-        "upload_raw checking if complete... {} == {}",
-        total_size,
-        uploaded_size
-    );
-
-    if total_size == (uploaded_size as usize) {
-        // std::thread::spawn(move || {
-        // Get tar.gz bytes for history/COMMIT_ID data
-        log::debug!("Decompressing {} bytes to {:?}", total_size, hidden_dir);
-
-        let mut buffer: Vec<u8> = Vec::new();
-        for file in files.iter() {
-            log::debug!("Reading file bytes {:?}", file);
-            let mut f = std::fs::File::open(file).unwrap();
-
-            f.read_to_end(&mut buffer).unwrap();
-        }
+/// Controller to see which pieces of a chunked upload have arrived, so the client can resume it
+pub async fn upload_session(req: HttpRequest) -> HttpResponse {
+    let app_data = req.app_data::<OxenAppData>().unwrap();
+    let namespace: &str = req.match_info().get("namespace").unwrap();
+    let repo_name: &str = req.match_info().get("repo_name").unwrap();
+    let hash: &str = req.match_info().get("hash").unwrap();
 
-        // TODO: better error handling...
-        // Combine into actual file data
-        if is_compressed {
-            // Unpack tarball to our hidden dir
-            let mut archive = Archive::new(GzDecoder::new(&buffer[..]));
-            unpack_entry_tarball(&hidden_dir, &mut archive);
-        } else {
-            // just write buffer to disk
-            match filename {
-                Some(filename) => {
-                    // TODO: better error handling...
-
-                    log::debug!("Got filename {}", filename);
-                    let full_path = hidden_dir.join(filename);
-                    log::debug!("Unpack to {:?}", full_path);
-                    if let Some(parent) = full_path.parent() {
-                        if !parent.exists() {
-                            std::fs::create_dir_all(parent).expect("Could not create parent dir");
-                        }
-                    }
+    if let Err(err) = UploadSessionStore::validate_hash(hash) {
+        return HttpResponse::BadRequest().json(StatusMessage::error(&err.to_string()));
+    }
 
-                    let mut f = std::fs::File::create(&full_path).expect("Could write file");
-                    match f.write_all(&buffer) {
-                        Ok(_) => {
-                            log::debug!("Unpack successful! {:?}", full_path);
-                        }
-                        Err(err) => {
-                            log::error!("Could not write all data to disk {:?}", err);
-                        }
-                    }
-                }
-                None => {
-                    log::error!("Must supply filename if !compressed");
-                }
+    match api::local::repositories::get_by_namespace_and_name(&app_data.path, namespace, repo_name)
+    {
+        Ok(Some(repo)) => match UploadSessionStore::new(&repo).get(hash) {
+            Ok(Some(session)) => HttpResponse::Ok().json(UploadSessionResponse {
+                status: String::from(STATUS_SUCCESS),
+                status_message: String::from(MSG_RESOURCE_FOUND),
+                missing_chunks: session.missing_chunks(),
+                session,
+            }),
+            Ok(None) => HttpResponse::NotFound().json(StatusMessage::resource_not_found()),
+            Err(err) => {
+                log::error!("Could not get upload session {}: {}", hash, err);
+                HttpResponse::InternalServerError().json(StatusMessage::internal_server_error())
             }
+        },
+        Ok(None) => {
+            log::debug!("404 could not get repo {}", repo_name);
+            HttpResponse::NotFound().json(StatusMessage::resource_not_found())
+        }
+        Err(err) => {
+            log::error!("Err get_by_name: {}", err);
+            HttpResponse::InternalServerError().json(StatusMessage::internal_server_error())
         }
-
-        // Cleanup tmp files
-        std::fs::remove_dir_all(tmp_dir).unwrap();
-        // });
     }
 }
 
@@ -800,20 +879,23 @@ fn create_commit(repo_dir: &Path, commit: &Commit) -> Result<(), OxenError> {
 mod tests {
 
     use actix_web::body::to_bytes;
-    use actix_web::{web, App};
+    use actix_web::{http, web, App, HttpResponse};
     use flate2::write::GzEncoder;
     use flate2::Compression;
     use std::path::Path;
     use std::thread;
 
     use liboxen::command;
-    use liboxen::constants::OXEN_HIDDEN_DIR;
+    use liboxen::constants::{self, OXEN_HIDDEN_DIR};
     use liboxen::error::OxenError;
     use liboxen::util;
-    use liboxen::view::{CommitResponse, ListCommitResponse};
+    use liboxen::view::{
+        CommitResponse, ListCommitResponse, PaginatedCommitsResponse, UploadSessionResponse,
+    };
 
     use crate::app_data::OxenAppData;
     use crate::controllers;
+    use crate::controllers::commits::CommitHistoryQuery;
     use crate::test::{self, init_test_env};
 
     #[actix_web::test]
@@ -903,7 +985,8 @@ mod tests {
             branch_name,
         );
 
-        let resp = controllers::commits::commit_history(req).await;
+        let query: web::Query<CommitHistoryQuery> = web::Query::from_query("").unwrap();
+        let resp = controllers::commits::commit_history(req, query).await;
         let body = to_bytes(resp.into_body()).await.unwrap();
         let text = std::str::from_utf8(&body).unwrap();
         let list: ListCommitResponse = serde_json::from_str(text)?;
@@ -951,7 +1034,8 @@ mod tests {
             og_branch.name,
         );
 
-        let resp = controllers::commits::commit_history(req).await;
+        let query: web::Query<CommitHistoryQuery> = web::Query::from_query("").unwrap();
+        let resp = controllers::commits::commit_history(req, query).await;
         let body = to_bytes(resp.into_body()).await.unwrap();
         let text = std::str::from_utf8(&body).unwrap();
         let list: ListCommitResponse = serde_json::from_str(text)?;
@@ -964,6 +1048,121 @@ mod tests {
         Ok(())
     }
 
+    async fn history_page(sync_dir: &Path, branch_name: &str, query: &str) -> HttpResponse {
+        let namespace = "Testing-Namespace";
+        let repo_name = "Testing-Name";
+        let uri = format!("/oxen/{namespace}/{repo_name}/commits/{branch_name}/history?{query}");
+        let req = test::repo_request_with_param(
+            sync_dir,
+            &uri,
+            namespace,
+            repo_name,
+            "commit_or_branch",
+            branch_name.to_string(),
+        );
+        let query: web::Query<CommitHistoryQuery> = web::Query::from_query(query).unwrap();
+        controllers::commits::commit_history(req, query).await
+    }
+
+    #[actix_web::test]
+    async fn test_controllers_commits_history_filters_and_pages() -> Result<(), OxenError> {
+        let sync_dir = test::get_sync_dir()?;
+
+        let namespace = "Testing-Namespace";
+        let repo_name = "Testing-Name";
+        let repo = test::create_local_repo(&sync_dir, namespace, repo_name)?;
+        let branch = command::current_branch(&repo)?.unwrap();
+
+        let hello_file = repo.path.join("hello.txt");
+        util::fs::write_to_path(&hello_file, "Hello")?;
+        command::add(&repo, &hello_file)?;
+        command::commit(&repo, "Adding hello")?;
+
+        let world_file = repo.path.join("world.txt");
+        util::fs::write_to_path(&world_file, "World")?;
+        command::add(&repo, &world_file)?;
+        command::commit(&repo, "Adding world")?;
+
+        util::fs::write_to_path(&hello_file, "Hello again")?;
+        command::add(&repo, &hello_file)?;
+        let last = command::commit(&repo, "Editing hello")?.unwrap();
+
+        let resp = history_page(&sync_dir, &branch.name, "path=hello.txt").await;
+        let body = to_bytes(resp.into_body()).await.unwrap();
+        let text = std::str::from_utf8(&body).unwrap();
+        let page: PaginatedCommitsResponse = serde_json::from_str(text)?;
+        assert_eq!(page.total_entries, 2);
+        assert_eq!(page.commits[0].id, last.id);
+        // Clients that read the history before it was paginated still can
+        let list: ListCommitResponse = serde_json::from_str(text)?;
+        assert_eq!(list.commits.len(), 2);
+
+        // Plus the initial commit
+        let resp = history_page(&sync_dir, &branch.name, "page=2&page_size=3").await;
+        let body = to_bytes(resp.into_body()).await.unwrap();
+        let page: PaginatedCommitsResponse =
+            serde_json::from_str(std::str::from_utf8(&body).unwrap())?;
+        assert_eq!(page.total_entries, 4);
+        assert_eq!(page.total_pages, 2);
+        assert_eq!(page.page_number, 2);
+        assert_eq!(page.commits.len(), 1);
+
+        let resp = history_page(&sync_dir, &branch.name, "grep=Adding&since=2000-01-01").await;
+        let body = to_bytes(resp.into_body()).await.unwrap();
+        let page: PaginatedCommitsResponse =
+            serde_json::from_str(std::str::from_utf8(&body).unwrap())?;
+        assert_eq!(page.total_entries, 2);
+
+        let resp = history_page(&sync_dir, &branch.name, "since=not-a-date").await;
+        assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+
+        // cleanup
+        std::fs::remove_dir_all(sync_dir)?;
+
+        Ok(())
+    }
+
+    #[actix_web::test]
+    async fn test_controllers_commits_history_without_page_params_lists_all(
+    ) -> Result<(), OxenError> {
+        let sync_dir = test::get_sync_dir()?;
+
+        let namespace = "Testing-Namespace";
+        let repo_name = "Testing-Name";
+        let repo = test::create_local_repo(&sync_dir, namespace, repo_name)?;
+        let branch = command::current_branch(&repo)?.unwrap();
+
+        // More than fit in a default page, plus the initial commit
+        let hello_file = repo.path.join("hello.txt");
+        let num_commits = constants::DEFAULT_PAGE_SIZE + 2;
+        for i in 0..num_commits {
+            util::fs::write_to_path(&hello_file, &format!("Hello {i}"))?;
+            command::add(&repo, &hello_file)?;
+            command::commit(&repo, &format!("Hello {i}"))?;
+        }
+
+        let resp = history_page(&sync_dir, &branch.name, "").await;
+        let body = to_bytes(resp.into_body()).await.unwrap();
+        let page: PaginatedCommitsResponse =
+            serde_json::from_str(std::str::from_utf8(&body).unwrap())?;
+        assert_eq!(page.total_entries, num_commits + 1);
+        assert_eq!(page.commits.len(), num_commits + 1);
+        assert_eq!(page.total_pages, 1);
+
+        // A page without a size still gets the default size
+        let resp = history_page(&sync_dir, &branch.name, "page=2").await;
+        let body = to_bytes(resp.into_body()).await.unwrap();
+        let page: PaginatedCommitsResponse =
+            serde_json::from_str(std::str::from_utf8(&body).unwrap())?;
+        assert_eq!(page.page_size, constants::DEFAULT_PAGE_SIZE);
+        assert_eq!(page.commits.len(), 3);
+
+        // cleanup
+        std::fs::remove_dir_all(sync_dir)?;
+
+        Ok(())
+    }
+
     #[actix_web::test]
     async fn test_controllers_commits_upload() -> Result<(), OxenError> {
         let sync_dir = test::get_sync_dir()?;
@@ -998,9 +1197,7 @@ mod tests {
         let uri = format!("/oxen/{}/{}/commits/{}", namespace, repo_name, commit.id);
         let app = actix_web::test::init_service(
             App::new()
-                .app_data(OxenAppData {
-                    path: sync_dir.clone(),
-                })
+                .app_data(OxenAppData::new(sync_dir.clone()))
                 .route(
                     "/oxen/{namespace}/{repo_name}/commits/{commit_id}",
                     web::post().to(controllers::commits::upload),
@@ -1047,4 +1244,103 @@ mod tests {
 
         Ok(())
     }
+
+    #[actix_web::test]
+    async fn test_controllers_commits_resume_chunked_upload() -> Result<(), OxenError> {
+        let sync_dir = test::get_sync_dir()?;
+
+        let namespace = "Testing-Namespace";
+        let repo_name = "Testing-Name";
+        let repo = test::create_local_repo(&sync_dir, namespace, repo_name)?;
+        let hello_file = repo.path.join("hello.txt");
+        util::fs::write_to_path(&hello_file, "Hello")?;
+        command::add(&repo, &hello_file)?;
+        let commit = command::commit(&repo, "First commit")?.unwrap();
+
+        let app = actix_web::test::init_service(
+            App::new()
+                .app_data(OxenAppData::new(sync_dir.clone()))
+                .route(
+                    "/oxen/{namespace}/{repo_name}/commits/{commit_id}/upload_chunk",
+                    web::post().to(controllers::commits::upload_chunk),
+                )
+                .route(
+                    "/oxen/{namespace}/{repo_name}/uploads/{hash}",
+                    web::get().to(controllers::commits::upload_session),
+                ),
+        )
+        .await;
+
+        let hash = "abc123";
+        let content_hash = util::hasher::hash_buffer(b"helloworld");
+        let session_uri = format!("/oxen/{namespace}/{repo_name}/uploads/{hash}");
+        let chunk_uri = |chunk_num: usize| {
+            format!(
+                "/oxen/{}/{}/commits/{}/upload_chunk?chunk_num={}&total_size=10&hash={}&content_hash={}&total_chunks=2&is_compressed=false&filename=uploaded.txt",
+                namespace, repo_name, commit.id, chunk_num, hash, content_hash
+            )
+        };
+
+        // Nothing has been sent yet
+        let req = actix_web::test::TestRequest::get()
+            .uri(&session_uri)
+            .to_request();
+        let resp = actix_web::test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 404);
+
+        // Send the second half, as if the connection dropped before the first
+        let req = actix_web::test::TestRequest::post()
+            .uri(&chunk_uri(1))
+            .set_payload("world")
+            .to_request();
+        let resp = actix_web::test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 200);
+
+        let req = actix_web::test::TestRequest::get()
+            .uri(&session_uri)
+            .to_request();
+        let resp = actix_web::test::call_service(&app, req).await;
+        let bytes = actix_http::body::to_bytes(resp.into_body()).await.unwrap();
+        let body = std::str::from_utf8(&bytes).unwrap();
+        let resp: UploadSessionResponse = serde_json::from_str(body)?;
+        assert_eq!(resp.missing_chunks, vec![0]);
+
+        // Resume with the missing piece, which completes the upload
+        let req = actix_web::test::TestRequest::post()
+            .uri(&chunk_uri(0))
+            .set_payload("hello")
+            .to_request();
+        let resp = actix_web::test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 200);
+
+        let uploaded_file = sync_dir
+            .join(namespace)
+            .join(repo_name)
+            .join(OXEN_HIDDEN_DIR)
+            .join("uploaded.txt");
+        assert_eq!(util::fs::read_from_path(&uploaded_file)?, "helloworld");
+
+        let req = actix_web::test::TestRequest::get()
+            .uri(&session_uri)
+            .to_request();
+        let resp = actix_web::test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 404);
+
+        // A hash that is a path does not get anywhere near the file system
+        let req = actix_web::test::TestRequest::post()
+            .uri(&format!(
+                "/oxen/{}/{}/commits/{}/upload_chunk?chunk_num=0&total_size=5&hash=..%2F..%2F..&total_chunks=1&is_compressed=false&filename=uploaded.txt",
+                namespace, repo_name, commit.id
+            ))
+            .set_payload("hello")
+            .to_request();
+        let resp = actix_web::test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 400);
+        assert!(repo.path.join(OXEN_HIDDEN_DIR).exists());
+
+        // cleanup
+        std::fs::remove_dir_all(sync_dir)?;
+
+        Ok(())
+    }
 }
@@ -0,0 +1,251 @@
+use crate::app_data::OxenAppData;
+
+use liboxen::api;
+use liboxen::df::DFDiffOpts;
+use liboxen::error::OxenError;
+use liboxen::index::differ;
+use liboxen::model::{Commit, LocalRepository, TreeDiff};
+use liboxen::util;
+use liboxen::view::http::{MSG_RESOURCE_FOUND, STATUS_SUCCESS};
+use liboxen::view::{CompareResponse, JsonDataFrameDiff, StatusMessage};
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde::Deserialize;
+
+use std::path::{Path, PathBuf};
+
+#[derive(Deserialize, Debug)]
+pub struct CompareQuery {
+    pub primary_key: Option<String>,
+    pub ignore_cols: Option<String>,
+}
+
+pub async fn show(req: HttpRequest, query: web::Query<CompareQuery>) -> HttpResponse {
+    let app_data = req.app_data::<OxenAppData>().unwrap();
+
+    let namespace: &str = req.match_info().get("namespace").unwrap();
+    let name: &str = req.match_info().get("repo_name").unwrap();
+    let base_head: &str = req.match_info().get("base_head").unwrap();
+    let resource: PathBuf = req.match_info().query("resource").parse().unwrap();
+
+    let (base_ref, head_ref) = match util::resource::parse_commit_range(base_head) {
+        Ok(range) => range,
+        Err(err) => {
+            log::debug!("compare::show invalid range {}: {}", base_head, err);
+            return HttpResponse::BadRequest().json(StatusMessage::error(&err.to_string()));
+        }
+    };
+
+    log::debug!(
+        "compare::show repo [{}] base [{}] head [{}] resource [{:?}]",
+        name,
+        base_ref,
+        head_ref,
+        resource
+    );
+    match api::local::repositories::get_by_namespace_and_name(&app_data.path, namespace, name) {
+        Ok(Some(repo)) => {
+            let (base, head) = match (get_commit(&repo, &base_ref), get_commit(&repo, &head_ref)) {
+                (Some(base), Some(head)) => (base, head),
+                _ => {
+                    log::debug!("compare::show could not find {}", base_head);
+                    return HttpResponse::NotFound().json(StatusMessage::resource_not_found());
+                }
+            };
+
+            let opts = DFDiffOpts {
+                primary_key: query.primary_key.clone(),
+                ignore_columns: query
+                    .ignore_cols
+                    .as_ref()
+                    .map(|cols| cols.split(',').map(|c| String::from(c.trim())).collect())
+                    .unwrap_or_default(),
+            };
+            match compare_commits(&repo, &base, &head, &resource, &opts) {
+                Ok((compare, df_diff)) => HttpResponse::Ok().json(CompareResponse {
+                    status: String::from(STATUS_SUCCESS),
+                    status_message: String::from(MSG_RESOURCE_FOUND),
+                    compare,
+                    df_diff,
+                }),
+                Err(err) => {
+                    log::error!("unable to compare {}. Err: {}", base_head, err);
+                    HttpResponse::InternalServerError().json(StatusMessage::internal_server_error())
+                }
+            }
+        }
+        Ok(None) => {
+            log::debug!("compare::show could not find repo with name {}", name);
+            HttpResponse::NotFound().json(StatusMessage::resource_not_found())
+        }
+        Err(err) => {
+            log::error!("unable to get repo {}. Err: {}", name, err);
+            HttpResponse::InternalServerError().json(StatusMessage::internal_server_error())
+        }
+    }
+}
+
+fn compare_commits(
+    repo: &LocalRepository,
+    base: &Commit,
+    head: &Commit,
+    resource: &Path,
+    opts: &DFDiffOpts,
+) -> Result<(TreeDiff, Option<JsonDataFrameDiff>), OxenError> {
+    let compare = differ::diff_commits(repo, base, head, Some(resource), opts)?;
+
+    // Give back the structured diff if we are looking at a single tabular file
+    let modified = compare.modified.iter().find(|entry| entry.path == resource);
+    let df_diff = match modified {
+        Some(entry) if util::fs::is_tabular(&entry.path) => {
+            let base_entry = entry.base_entry.as_ref().unwrap();
+            let head_entry = entry.head_entry.as_ref().unwrap();
+            let diff = differ::diff_tabular_entries(repo, base_entry, head_entry, opts)?;
+            Some(JsonDataFrameDiff::from_diff(diff))
+        }
+        _ => None,
+    };
+
+    Ok((compare, df_diff))
+}
+
+fn get_commit(repo: &LocalRepository, commit_or_branch: &str) -> Option<Commit> {
+    util::resource::maybe_get_commit(repo, commit_or_branch)
+        .ok()
+        .flatten()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use actix_web::{http, web};
+
+    use actix_web::body::to_bytes;
+
+    use liboxen::command;
+    use liboxen::constants::DEFAULT_BRANCH_NAME;
+    use liboxen::error::OxenError;
+    use liboxen::util;
+    use liboxen::view::http::STATUS_SUCCESS;
+    use liboxen::view::CompareResponse;
+
+    use crate::controllers;
+    use crate::controllers::compare::CompareQuery;
+    use crate::test;
+
+    #[actix_web::test]
+    async fn test_controllers_compare_branches() -> Result<(), OxenError> {
+        let sync_dir = test::get_sync_dir()?;
+
+        let namespace = "Testing-Namespace";
+        let name = "Testing-Compare";
+        let repo = test::create_local_repo(&sync_dir, namespace, name)?;
+
+        let path = repo.path.join("labels.txt");
+        util::fs::write_to_path(&path, "cat\ndog")?;
+        command::add(&repo, &path)?;
+        command::commit(&repo, "Adding labels")?;
+
+        command::create_checkout_branch(&repo, "add-fish")?;
+        util::fs::write_to_path(&path, "cat\ndog\nfish")?;
+        let new_path = repo.path.join("new.txt");
+        util::fs::write_to_path(&new_path, "new")?;
+        command::add(&repo, &repo.path)?;
+        command::commit(&repo, "Adding fish")?;
+
+        let base_head = format!("{DEFAULT_BRANCH_NAME}..add-fish");
+        let uri = format!("/oxen/{namespace}/{name}/compare/{base_head}");
+        let req =
+            test::repo_request_with_param(&sync_dir, &uri, namespace, name, "base_head", base_head);
+
+        let query: web::Query<CompareQuery> = web::Query::from_query("").unwrap();
+        let resp = controllers::compare::show(req, query).await;
+        assert_eq!(resp.status(), http::StatusCode::OK);
+        let body = to_bytes(resp.into_body()).await.unwrap();
+        let text = std::str::from_utf8(&body).unwrap();
+        let response: CompareResponse = serde_json::from_str(text)?;
+        assert_eq!(response.status, STATUS_SUCCESS);
+        assert_eq!(response.compare.added.len(), 1);
+        assert_eq!(response.compare.modified.len(), 1);
+        assert_eq!(response.compare.removed.len(), 0);
+
+        // cleanup
+        std::fs::remove_dir_all(sync_dir)?;
+
+        Ok(())
+    }
+
+    #[actix_web::test]
+    async fn test_controllers_compare_unknown_ref() -> Result<(), OxenError> {
+        let sync_dir = test::get_sync_dir()?;
+
+        let namespace = "Testing-Namespace";
+        let name = "Testing-Compare-Missing";
+        test::create_local_repo(&sync_dir, namespace, name)?;
+
+        let base_head = format!("{DEFAULT_BRANCH_NAME}..does-not-exist");
+        let uri = format!("/oxen/{namespace}/{name}/compare/{base_head}");
+        let req =
+            test::repo_request_with_param(&sync_dir, &uri, namespace, name, "base_head", base_head);
+
+        let query: web::Query<CompareQuery> = web::Query::from_query("").unwrap();
+        let resp = controllers::compare::show(req, query).await;
+        assert_eq!(resp.status(), http::StatusCode::NOT_FOUND);
+
+        // cleanup
+        std::fs::remove_dir_all(sync_dir)?;
+
+        Ok(())
+    }
+
+    #[actix_web::test]
+    async fn test_controllers_compare_tabular_file_with_primary_key() -> Result<(), OxenError> {
+        let sync_dir = test::get_sync_dir()?;
+
+        let namespace = "Testing-Namespace";
+        let name = "Testing-Compare-Tabular";
+        let repo = test::create_local_repo(&sync_dir, namespace, name)?;
+
+        let path = repo.path.join("labels.csv");
+        util::fs::write_to_path(&path, "id,label,annotator\n1,cat,ox\n2,dog,ox\n")?;
+        command::add(&repo, &path)?;
+        command::commit(&repo, "Adding labels")?;
+
+        command::create_checkout_branch(&repo, "relabel")?;
+        util::fs::write_to_path(&path, "id,label,annotator\n1,lion,bull\n2,dog,bull\n")?;
+        command::add(&repo, &path)?;
+        command::commit(&repo, "Relabel cat")?;
+
+        let base_head = format!("{DEFAULT_BRANCH_NAME}..relabel");
+        let uri = format!("/oxen/{namespace}/{name}/compare/{base_head}/labels.csv");
+        let req = actix_web::test::TestRequest::with_uri(&uri)
+            .app_data(crate::app_data::OxenAppData::new(sync_dir.to_path_buf()))
+            .param("namespace", namespace)
+            .param("repo_name", name)
+            .param("base_head", base_head)
+            .param("resource", "labels.csv")
+            .to_http_request();
+        let query: web::Query<CompareQuery> =
+            web::Query::from_query("primary_key=id&ignore_cols=annotator").unwrap();
+
+        let resp = controllers::compare::show(req, query).await;
+        assert_eq!(resp.status(), http::StatusCode::OK);
+        let body = to_bytes(resp.into_body()).await.unwrap();
+        let text = std::str::from_utf8(&body).unwrap();
+        let response: CompareResponse = serde_json::from_str(text)?;
+        let df_diff = response.df_diff.unwrap();
+        assert!(df_diff.added_rows.is_none());
+        assert!(df_diff.removed_rows.is_none());
+        assert_eq!(df_diff.modified_rows.len(), 1);
+        let row = &df_diff.modified_rows[0];
+        assert_eq!(row.key, "1");
+        assert_eq!(row.cells.len(), 1);
+        assert_eq!(row.cells[0].before, "cat");
+        assert_eq!(row.cells[0].after, "lion");
+
+        // cleanup
+        std::fs::remove_dir_all(sync_dir)?;
+
+        Ok(())
+    }
+}
@@ -3,7 +3,8 @@ use crate::app_data::OxenAppData;
 use liboxen::{api, constants};
 
 use actix_web::{web, HttpRequest, HttpResponse};
-use liboxen::df::{tabular, DFOpts};
+use liboxen::df::{self, tabular, DFOpts};
+use liboxen::index::schema_field_indexer;
 use liboxen::model::Schema;
 use liboxen::view::http::{MSG_RESOURCE_FOUND, STATUS_SUCCESS};
 use liboxen::view::json_data_frame::JsonDataSize;
@@ -27,6 +28,13 @@ pub struct DFOptsQuery {
     pub page_size: Option<usize>,
 }
 
+#[derive(Deserialize, Debug)]
+pub struct DFSqlQuery {
+    pub query: String,
+    pub page: Option<usize>,
+    pub page_size: Option<usize>,
+}
+
 pub async fn get(req: HttpRequest, query: web::Query<DFOptsQuery>) -> HttpResponse {
     let app_data = req.app_data::<OxenAppData>().unwrap();
 
@@ -58,7 +66,29 @@ pub async fn get(req: HttpRequest, query: web::Query<DFOptsQuery>) -> HttpRespon
 
                             log::debug!("Got filter {:?}", filter);
                             let lazy_cp = lazy_df.clone();
-                            let mut df = tabular::transform_df(lazy_cp, filter).unwrap();
+                            let commit = match api::local::commits::get_by_id(&repo, &commit_id) {
+                                Ok(Some(commit)) => commit,
+                                _ => {
+                                    log::error!("df::get could not get commit {}", commit_id);
+                                    return HttpResponse::NotFound()
+                                        .json(StatusMessage::resource_not_found());
+                                }
+                            };
+                            // Equality filters on indexed fields take rows from the index
+                            let lookup = |field: &str, value: &str| {
+                                schema_field_indexer::lookup(
+                                    &repo, &commit, &filepath, field, value,
+                                )
+                            };
+                            let mut df =
+                                match tabular::transform_df_with_index(lazy_cp, filter, lookup) {
+                                    Ok(df) => df,
+                                    Err(err) => {
+                                        log::debug!("df::get invalid opts: {}", err);
+                                        return HttpResponse::BadRequest()
+                                            .json(StatusMessage::error(&err.to_string()));
+                                    }
+                                };
                             let full_df = lazy_df.collect().unwrap();
                             let page_size = query.page_size.unwrap_or(constants::DEFAULT_PAGE_SIZE);
                             let page = query.page.unwrap_or(constants::DEFAULT_PAGE_NUM);
@@ -110,6 +140,55 @@ pub async fn get(req: HttpRequest, query: web::Query<DFOptsQuery>) -> HttpRespon
     }
 }
 
+pub async fn sql(req: HttpRequest, query: web::Query<DFSqlQuery>) -> HttpResponse {
+    let app_data = req.app_data::<OxenAppData>().unwrap();
+
+    let namespace: &str = req.match_info().get("namespace").unwrap();
+    let name: &str = req.match_info().get("repo_name").unwrap();
+
+    log::debug!("df::sql repo name [{}] query [{}]", name, query.query);
+    match api::local::repositories::get_by_namespace_and_name(&app_data.path, namespace, name) {
+        Ok(Some(repo)) => {
+            let page_size = query.page_size.unwrap_or(constants::DEFAULT_PAGE_SIZE);
+            let page = query.page.unwrap_or(constants::DEFAULT_PAGE_NUM);
+
+            let full_df = match df::sql::query(&repo, &query.query) {
+                Ok(full_df) => full_df,
+                Err(err) => {
+                    log::debug!("df::sql invalid query {}: {}", query.query, err);
+                    return HttpResponse::BadRequest().json(StatusMessage::error(&err.to_string()));
+                }
+            };
+
+            let mut df = full_df.slice((page.saturating_sub(1) * page_size) as i64, page_size);
+            let total_pages = (full_df.height() / page_size) + 1;
+
+            let response = JsonDataFrameSliceResponse {
+                status: String::from(STATUS_SUCCESS),
+                status_message: String::from(MSG_RESOURCE_FOUND),
+                df: JsonDataFrame::from_df(&mut df),
+                full_size: JsonDataSize {
+                    width: full_df.width(),
+                    height: full_df.height(),
+                },
+                page_number: page,
+                page_size,
+                total_pages,
+                total_entries: full_df.height(),
+            };
+            HttpResponse::Ok().json(response)
+        }
+        Ok(None) => {
+            log::debug!("df::sql could not find repo with name {}", name);
+            HttpResponse::NotFound().json(StatusMessage::resource_not_found())
+        }
+        Err(err) => {
+            log::error!("df::sql Err: {}", err);
+            HttpResponse::InternalServerError().json(StatusMessage::internal_server_error())
+        }
+    }
+}
+
 /// Provide some default vals for opts
 fn parse_opts(query: &web::Query<DFOptsQuery>, filter_ops: &mut DFOpts) -> DFOpts {
     // Default to 0..10 unless they ask for "all"
@@ -142,3 +221,176 @@ fn parse_opts(query: &web::Query<DFOptsQuery>, filter_ops: &mut DFOpts) -> DFOpt
 
     filter_ops.clone()
 }
+
+#[cfg(test)]
+mod tests {
+
+    use actix_web::body::to_bytes;
+    use actix_web::{http, web};
+
+    use liboxen::command;
+    use liboxen::error::OxenError;
+    use liboxen::util;
+    use liboxen::view::http::STATUS_SUCCESS;
+    use liboxen::view::JsonDataFrameSliceResponse;
+
+    use crate::controllers;
+    use crate::controllers::df::{DFOptsQuery, DFSqlQuery};
+    use crate::test;
+
+    #[actix_web::test]
+    async fn test_controllers_df_sql_join() -> Result<(), OxenError> {
+        let sync_dir = test::get_sync_dir()?;
+
+        let namespace = "Testing-Namespace";
+        let name = "Testing-DF-SQL";
+        let repo = test::create_local_repo(&sync_dir, namespace, name)?;
+
+        let labels = repo.path.join("labels.csv");
+        let images = repo.path.join("images.csv");
+        util::fs::write_to_path(&labels, "id,label\n1,cat\n2,dog\n3,cat\n")?;
+        util::fs::write_to_path(&images, "id,file\n1,a.jpg\n2,b.jpg\n3,c.jpg\n")?;
+        command::add(&repo, &repo.path)?;
+        command::commit(&repo, "Adding data")?;
+
+        let uri = format!("/oxen/{namespace}/{name}/sql");
+        let req = test::repo_request(&sync_dir, &uri, namespace, name);
+        let query: web::Query<DFSqlQuery> = web::Query::from_query(
+            "query=SELECT%20file%20FROM%20%27images.csv%40main%27%20JOIN%20%27labels.csv%40main%27%20ON%20id%20%3D%20id%20WHERE%20label%20%3D%20%27cat%27&page_size=1",
+        )
+        .unwrap();
+
+        let resp = controllers::df::sql(req, query).await;
+        assert_eq!(resp.status(), http::StatusCode::OK);
+        let body = to_bytes(resp.into_body()).await.unwrap();
+        let text = std::str::from_utf8(&body).unwrap();
+        let response: JsonDataFrameSliceResponse = serde_json::from_str(text)?;
+        assert_eq!(response.status, STATUS_SUCCESS);
+        assert_eq!(response.full_size.height, 2);
+        assert_eq!(response.df.size.height, 1);
+        assert_eq!(response.df.size.width, 1);
+
+        // cleanup
+        std::fs::remove_dir_all(sync_dir)?;
+
+        Ok(())
+    }
+
+    #[actix_web::test]
+    async fn test_controllers_df_sql_invalid_query() -> Result<(), OxenError> {
+        let sync_dir = test::get_sync_dir()?;
+
+        let namespace = "Testing-Namespace";
+        let name = "Testing-DF-SQL-Invalid";
+        test::create_local_repo(&sync_dir, namespace, name)?;
+
+        let uri = format!("/oxen/{namespace}/{name}/sql");
+        let req = test::repo_request(&sync_dir, &uri, namespace, name);
+        let query: web::Query<DFSqlQuery> =
+            web::Query::from_query("query=SELECT%20*%20FROM%20%27missing.csv%40main%27").unwrap();
+
+        let resp = controllers::df::sql(req, query).await;
+        assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+
+        // cleanup
+        std::fs::remove_dir_all(sync_dir)?;
+
+        Ok(())
+    }
+
+    #[actix_web::test]
+    async fn test_controllers_df_get_filter() -> Result<(), OxenError> {
+        let sync_dir = test::get_sync_dir()?;
+
+        let namespace = "Testing-Namespace";
+        let name = "Testing-DF-Filter";
+        let repo = test::create_local_repo(&sync_dir, namespace, name)?;
+
+        let labels = repo.path.join("labels.csv");
+        util::fs::write_to_path(&labels, "id,label\n1,cat\n2,dog\n3,fish\n4,cat\n")?;
+        command::add(&repo, &labels)?;
+        command::commit(&repo, "Adding labels")?;
+
+        let uri = format!("/oxen/{namespace}/{name}/df/main/labels.csv");
+        let req = test::repo_request_with_param(
+            &sync_dir,
+            &uri,
+            namespace,
+            name,
+            "resource",
+            "main/labels.csv",
+        );
+        let query: web::Query<DFOptsQuery> = web::Query::from_query(
+            "filter=(label%20in%20%5Bcat%2C%20fish%5D)%20%26%26%20id%20%3E%201",
+        )
+        .unwrap();
+
+        let resp = controllers::df::get(req, query).await;
+        assert_eq!(resp.status(), http::StatusCode::OK);
+        let body = to_bytes(resp.into_body()).await.unwrap();
+        let text = std::str::from_utf8(&body).unwrap();
+        let response: JsonDataFrameSliceResponse = serde_json::from_str(text)?;
+        assert_eq!(response.df.size.height, 2);
+
+        // Parse errors come back as a bad request instead of a panic
+        let req = test::repo_request_with_param(
+            &sync_dir,
+            &uri,
+            namespace,
+            name,
+            "resource",
+            "main/labels.csv",
+        );
+        let query: web::Query<DFOptsQuery> =
+            web::Query::from_query("filter=(label%20%3D%3D%20cat").unwrap();
+        let resp = controllers::df::get(req, query).await;
+        assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+
+        // cleanup
+        std::fs::remove_dir_all(sync_dir)?;
+
+        Ok(())
+    }
+
+    #[actix_web::test]
+    async fn test_controllers_df_get_filter_indexed_field() -> Result<(), OxenError> {
+        let sync_dir = test::get_sync_dir()?;
+
+        let namespace = "Testing-Namespace";
+        let name = "Testing-DF-Index";
+        let repo = test::create_local_repo(&sync_dir, namespace, name)?;
+
+        let labels = repo.path.join("labels.csv");
+        util::fs::write_to_path(&labels, "id,label\n1,cat\n2,dog\n3,fish\n4,cat\n")?;
+        command::add(&repo, &labels)?;
+        let commit = command::commit(&repo, "Adding labels")?.unwrap();
+        let schema = command::schema_list(&repo, Some(&commit.id))?
+            .pop()
+            .unwrap();
+        command::schema_create_index(&repo, &schema.hash, "label")?;
+
+        let uri = format!("/oxen/{namespace}/{name}/df/main/labels.csv");
+        let req = test::repo_request_with_param(
+            &sync_dir,
+            &uri,
+            namespace,
+            name,
+            "resource",
+            "main/labels.csv",
+        );
+        let query: web::Query<DFOptsQuery> =
+            web::Query::from_query("filter=label%20%3D%3D%20cat%20%26%26%20id%20%3E%201").unwrap();
+
+        let resp = controllers::df::get(req, query).await;
+        assert_eq!(resp.status(), http::StatusCode::OK);
+        let body = to_bytes(resp.into_body()).await.unwrap();
+        let text = std::str::from_utf8(&body).unwrap();
+        let response: JsonDataFrameSliceResponse = serde_json::from_str(text)?;
+        assert_eq!(response.df.size.height, 1);
+
+        // cleanup
+        std::fs::remove_dir_all(sync_dir)?;
+
+        Ok(())
+    }
+}
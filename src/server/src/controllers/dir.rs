@@ -176,9 +176,7 @@ mod tests {
         let uri = format!("/oxen/{}/{}/dir/{}/train/", namespace, name, commit.id);
         let app = actix_web::test::init_service(
             App::new()
-                .app_data(OxenAppData {
-                    path: sync_dir.clone(),
-                })
+                .app_data(OxenAppData::new(sync_dir.clone()))
                 .route(
                     "/oxen/{namespace}/{repo_name}/dir/{resource:.*}",
                     web::get().to(controllers::dir::get),
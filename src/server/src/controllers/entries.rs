@@ -2,13 +2,15 @@ use crate::app_data::OxenAppData;
 use crate::view::PaginatedLinesResponse;
 
 use liboxen::api;
-use liboxen::constants::AVG_CHUNK_SIZE;
+use liboxen::constants::{AVG_CHUNK_SIZE, TMP_DIR};
 use liboxen::error::OxenError;
-use liboxen::index::CommitDirReader;
+use liboxen::index::{ChunkStore, CommitDirReader};
 use liboxen::model::{Commit, CommitEntry, LocalRepository, RemoteEntry};
 use liboxen::util;
 use liboxen::view::http::{MSG_RESOURCE_CREATED, MSG_RESOURCE_FOUND, STATUS_SUCCESS};
-use liboxen::view::{PaginatedEntries, RemoteEntryResponse, StatusMessage};
+use liboxen::view::{
+    CommitEntries, CommitEntriesResponse, PaginatedEntries, RemoteEntryResponse, StatusMessage,
+};
 
 use actix_web::{web, HttpRequest, HttpResponse};
 use flate2::read::GzDecoder;
@@ -119,6 +121,48 @@ pub async fn download_data_from_version_paths(
     }
 }
 
+/// Given a list of entries, respond with the ones this repo does not have the versions of, so a resumed push can skip the rest
+pub async fn list_missing_versions(req: HttpRequest, body: String) -> HttpResponse {
+    let app_data = req.app_data::<OxenAppData>().unwrap();
+    let namespace: &str = req.match_info().get("namespace").unwrap();
+    let name: &str = req.match_info().get("repo_name").unwrap();
+
+    let data: CommitEntries = match serde_json::from_str(&body) {
+        Ok(data) => data,
+        Err(err) => {
+            log::error!(
+                "entries::list_missing_versions could not parse body: {}",
+                err
+            );
+            return HttpResponse::BadRequest().json(StatusMessage::error("Invalid body."));
+        }
+    };
+
+    match api::local::repositories::get_by_namespace_and_name(&app_data.path, namespace, name) {
+        Ok(Some(repo)) => {
+            let store = ChunkStore::new(&repo);
+            let entries = data
+                .entries
+                .into_iter()
+                .filter(|entry| !store.has_version(entry))
+                .collect();
+            HttpResponse::Ok().json(CommitEntriesResponse {
+                status: String::from(STATUS_SUCCESS),
+                status_message: String::from(MSG_RESOURCE_FOUND),
+                entries,
+            })
+        }
+        Ok(None) => {
+            log::debug!("404 could not get repo {}", name);
+            HttpResponse::NotFound().json(StatusMessage::resource_not_found())
+        }
+        Err(err) => {
+            log::error!("Unable to get repository {}. Err: {}", name, err);
+            HttpResponse::InternalServerError().json(StatusMessage::internal_server_error())
+        }
+    }
+}
+
 /// Download a chunk of a larger file
 pub async fn download_chunk(req: HttpRequest, query: web::Query<ChunkQuery>) -> HttpResponse {
     let app_data = req.app_data::<OxenAppData>().unwrap();
@@ -228,6 +272,7 @@ fn compress_entries(
     entries: &[RemoteEntry],
 ) -> Result<Vec<u8>, OxenError> {
     let entry_reader = CommitDirReader::new(repo, commit)?;
+    let store = ChunkStore::new(repo);
 
     let enc = GzEncoder::new(Vec::new(), Compression::default());
     let mut tar = tar::Builder::new(enc);
@@ -235,7 +280,7 @@ fn compress_entries(
     for entry in entries.iter() {
         let filename = &entry.filename;
         if let Some(entry) = entry_reader.get_entry(Path::new(filename))? {
-            let version_path = util::fs::version_path(repo, &entry);
+            let version_path = store.version_file(&entry)?;
             tar.append_path_with_name(version_path, filename)?;
         } else {
             log::error!(
@@ -337,7 +382,7 @@ pub async fn list_lines_in_file(req: HttpRequest, query: web::Query<PageNumQuery
                         let start = page * page_size;
                         let (lines, total_entries) =
                             liboxen::util::fs::read_lines_paginated_ret_size(
-                                &version_path,
+                                version_path.path(),
                                 start,
                                 page_size,
                             );
@@ -437,27 +482,33 @@ async fn p_create_entry(
     mut body: web::Payload,
     data: web::Query<CommitEntry>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    // Write entry to versions dir
-    let version_path = util::fs::version_path(repository, &data);
-
-    if let Some(parent) = version_path.parent() {
-        if !parent.exists() {
-            std::fs::create_dir_all(parent)?;
-        }
+    // Write to a tmp file, then store it in the versions dir, as chunks if it is large
+    let tmp_dir = util::fs::oxen_hidden_dir(&repository.path).join(TMP_DIR);
+    if !tmp_dir.exists() {
+        std::fs::create_dir_all(&tmp_dir)?;
     }
+    let tmp_path = tmp_dir.join(uuid::Uuid::new_v4().to_string());
 
-    let mut file = File::create(&version_path)?;
+    let mut file = File::create(&tmp_path)?;
     let mut total_bytes = 0;
     while let Some(item) = body.next().await {
         total_bytes += file.write(&item?)?;
     }
+    drop(file);
     log::debug!(
         "Wrote {} bytes to for {:?} to {:?}",
         total_bytes,
         data.path,
-        version_path,
+        tmp_path,
     );
 
+    let result = ChunkStore::new(repository).put_version_file(&data, &tmp_path);
+    std::fs::remove_file(&tmp_path)?;
+    if let Err(err) = result {
+        log::error!("Could not store version of {:?}: {}", data.path, err);
+        return Ok(HttpResponse::BadRequest().json(StatusMessage::error(&err.to_string())));
+    }
+
     Ok(HttpResponse::Ok().json(RemoteEntryResponse {
         status: String::from(STATUS_SUCCESS),
         status_message: String::from(MSG_RESOURCE_CREATED),
@@ -467,16 +518,18 @@ async fn p_create_entry(
 
 #[cfg(test)]
 mod tests {
+    use actix_web::body::to_bytes;
     use actix_web::{web, App};
     use flate2::read::GzDecoder;
     use std::path::{Path, PathBuf};
     use tar::Archive;
 
+    use liboxen::api;
     use liboxen::command;
     use liboxen::error::OxenError;
     use liboxen::model::CommitEntry;
     use liboxen::util;
-    use liboxen::view::RemoteEntryResponse;
+    use liboxen::view::{CommitEntries, CommitEntriesResponse, RemoteEntryResponse};
 
     use crate::app_data::OxenAppData;
     use crate::controllers;
@@ -510,9 +563,7 @@ mod tests {
         );
         let app = actix_web::test::init_service(
             App::new()
-                .app_data(OxenAppData {
-                    path: sync_dir.clone(),
-                })
+                .app_data(OxenAppData::new(sync_dir.clone()))
                 .route(
                     "/oxen/{namespace}/{repo_name}/entries",
                     web::post().to(controllers::entries::create),
@@ -577,9 +628,7 @@ mod tests {
         println!("Hit uri {uri}");
         let app = actix_web::test::init_service(
             App::new()
-                .app_data(OxenAppData {
-                    path: sync_dir.clone(),
-                })
+                .app_data(OxenAppData::new(sync_dir.clone()))
                 .route(
                     "/oxen/{namespace}/{repo_name}/commits/{commit_id}/download_page",
                     web::get().to(controllers::entries::download_page),
@@ -606,4 +655,43 @@ mod tests {
 
         Ok(())
     }
+
+    #[actix_web::test]
+    async fn test_controllers_entries_list_missing_versions() -> Result<(), OxenError> {
+        let sync_dir = test::get_sync_dir()?;
+
+        let namespace = "Testing-Namespace";
+        let repo_name = "Testing-Name";
+        let repo = test::create_local_repo(&sync_dir, namespace, repo_name)?;
+        let hello_file = repo.path.join("hello.txt");
+        util::fs::write_to_path(&hello_file, "Hello")?;
+        command::add(&repo, &hello_file)?;
+        let commit = command::commit(&repo, "First commit")?.unwrap();
+        let entry =
+            api::local::entries::get_entry_for_commit(&repo, &commit, Path::new("hello.txt"))?
+                .unwrap();
+
+        // The same contents at a new path in a new commit still need their own version file
+        let mut copied_entry = entry.clone();
+        copied_entry.path = PathBuf::from("hello_copy.txt");
+        copied_entry.commit_id = String::from("not-a-commit");
+
+        let body = serde_json::to_string(&CommitEntries {
+            entries: vec![entry, copied_entry],
+        })?;
+        let uri = format!("/oxen/{namespace}/{repo_name}/versions/missing");
+        let req = test::repo_request(&sync_dir, &uri, namespace, repo_name);
+        let resp = controllers::entries::list_missing_versions(req, body).await;
+
+        let body = to_bytes(resp.into_body()).await.unwrap();
+        let text = std::str::from_utf8(&body).unwrap();
+        let resp: CommitEntriesResponse = serde_json::from_str(text)?;
+        assert_eq!(resp.entries.len(), 1);
+        assert_eq!(resp.entries[0].path, PathBuf::from("hello_copy.txt"));
+
+        // cleanup
+        std::fs::remove_dir_all(sync_dir)?;
+
+        Ok(())
+    }
 }
@@ -95,7 +95,7 @@ pub async fn meta_data_legacy(req: HttpRequest) -> HttpResponse {
                             status_message: String::from(MSG_RESOURCE_FOUND),
                             meta: FileMetaData {
                                 size: meta.len(),
-                                data_type: util::fs::file_datatype(&version_path),
+                                data_type: util::fs::file_datatype(version_path.path()),
                                 resource: ResourceVersion {
                                     path: String::from(filepath.to_str().unwrap()),
                                     version: commit_id,
@@ -176,7 +176,7 @@ pub async fn meta_data(req: HttpRequest) -> HttpResponse {
                                 is_dir: false,
                                 size: meta.len(),
                                 latest_commit: Some(latest_commit),
-                                datatype: util::fs::file_datatype(&version_path),
+                                datatype: util::fs::file_datatype(version_path.path()),
                                 resource,
                             },
                         };
@@ -0,0 +1,215 @@
+use crate::app_data::OxenAppData;
+
+use actix_web::{HttpRequest, HttpResponse};
+
+use liboxen::api;
+use liboxen::model::ProtectedBranch;
+use liboxen::view::http::{
+    MSG_RESOURCE_CREATED, MSG_RESOURCE_DELETED, MSG_RESOURCE_FOUND, STATUS_SUCCESS,
+};
+use liboxen::view::{ListProtectedBranchesResponse, ProtectedBranchResponse, StatusMessage};
+
+pub async fn index(req: HttpRequest) -> HttpResponse {
+    let app_data = req.app_data::<OxenAppData>().unwrap();
+    let namespace: &str = req.match_info().get("namespace").unwrap();
+    let name: &str = req.match_info().get("repo_name").unwrap();
+    match api::local::repositories::get_by_namespace_and_name(&app_data.path, namespace, name) {
+        Ok(Some(repository)) => match api::local::branch_policies::get(&repository) {
+            Ok(policy) => HttpResponse::Ok().json(ListProtectedBranchesResponse {
+                status: String::from(STATUS_SUCCESS),
+                status_message: String::from(MSG_RESOURCE_FOUND),
+                protected_branches: policy.protected_branches,
+            }),
+            Err(err) => {
+                log::error!("Unable to read branch policy. Err: {}", err);
+                HttpResponse::InternalServerError().json(StatusMessage::internal_server_error())
+            }
+        },
+        Ok(None) => {
+            log::debug!("404 protected_branches::index could not get repo {}", name);
+            HttpResponse::NotFound().json(StatusMessage::resource_not_found())
+        }
+        Err(err) => {
+            log::error!(
+                "Err protected_branches::index could not get repo {} {:?}",
+                name,
+                err
+            );
+            HttpResponse::InternalServerError().json(StatusMessage::internal_server_error())
+        }
+    }
+}
+
+pub async fn create(req: HttpRequest, body: String) -> HttpResponse {
+    let app_data = req.app_data::<OxenAppData>().unwrap();
+    let namespace: &str = req.match_info().get("namespace").unwrap();
+    let name: &str = req.match_info().get("repo_name").unwrap();
+    let data: Result<ProtectedBranch, serde_json::Error> = serde_json::from_str(&body);
+    match data {
+        Ok(protection) => match api::local::repositories::get_by_namespace_and_name(
+            &app_data.path,
+            namespace,
+            name,
+        ) {
+            Ok(Some(repository)) => {
+                match api::local::branch_policies::protect(&repository, protection.clone()) {
+                    Ok(_) => HttpResponse::Ok().json(ProtectedBranchResponse {
+                        status: String::from(STATUS_SUCCESS),
+                        status_message: String::from(MSG_RESOURCE_CREATED),
+                        protected_branch: protection,
+                    }),
+                    Err(err) => {
+                        log::debug!("Could not protect {}: {}", protection.pattern, err);
+                        HttpResponse::BadRequest().json(StatusMessage::error(&err.to_string()))
+                    }
+                }
+            }
+            Ok(None) => {
+                log::debug!("404 protected_branches::create could not get repo {}", name);
+                HttpResponse::NotFound().json(StatusMessage::resource_not_found())
+            }
+            Err(err) => {
+                log::error!(
+                    "Err protected_branches::create could not get repo {} {:?}",
+                    name,
+                    err
+                );
+                HttpResponse::InternalServerError().json(StatusMessage::internal_server_error())
+            }
+        },
+        Err(_) => HttpResponse::BadRequest().json(StatusMessage::error("Invalid body.")),
+    }
+}
+
+pub async fn delete(req: HttpRequest) -> HttpResponse {
+    let app_data = req.app_data::<OxenAppData>().unwrap();
+    let namespace: Option<&str> = req.match_info().get("namespace");
+    let name: Option<&str> = req.match_info().get("repo_name");
+    let pattern: Option<&str> = req.match_info().get("pattern");
+    if let (Some(namespace), Some(name), Some(pattern)) = (namespace, name, pattern) {
+        match api::local::repositories::get_by_namespace_and_name(&app_data.path, namespace, name) {
+            Ok(Some(repository)) => {
+                match api::local::branch_policies::unprotect(&repository, pattern) {
+                    Ok(Some(protected_branch)) => {
+                        HttpResponse::Ok().json(ProtectedBranchResponse {
+                            status: String::from(STATUS_SUCCESS),
+                            status_message: String::from(MSG_RESOURCE_DELETED),
+                            protected_branch,
+                        })
+                    }
+                    Ok(None) => {
+                        log::debug!("{} is not protected in repo {}", pattern, name);
+                        HttpResponse::NotFound().json(StatusMessage::resource_not_found())
+                    }
+                    Err(err) => {
+                        log::error!("Could not unprotect {}: {}", pattern, err);
+                        HttpResponse::InternalServerError()
+                            .json(StatusMessage::internal_server_error())
+                    }
+                }
+            }
+            Ok(None) => {
+                log::debug!("404 protected_branches::delete could not get repo {}", name);
+                HttpResponse::NotFound().json(StatusMessage::resource_not_found())
+            }
+            Err(err) => {
+                log::error!("protected_branches::delete could not find repo: {}", err);
+                HttpResponse::InternalServerError().json(StatusMessage::internal_server_error())
+            }
+        }
+    } else {
+        let msg = "Must supply `namespace`, `repo_name` and `pattern` params";
+        HttpResponse::BadRequest().json(StatusMessage::error(msg))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use actix_web::http::{self};
+
+    use actix_web::body::to_bytes;
+
+    use liboxen::api;
+    use liboxen::constants::DEFAULT_BRANCH_NAME;
+    use liboxen::error::OxenError;
+    use liboxen::view::http::{STATUS_ERROR, STATUS_SUCCESS};
+    use liboxen::view::{ListProtectedBranchesResponse, StatusMessage};
+
+    use crate::controllers;
+    use crate::test;
+
+    #[actix_web::test]
+    async fn test_controllers_protected_branches_block_delete() -> Result<(), OxenError> {
+        let sync_dir = test::get_sync_dir()?;
+
+        let namespace = "Testing-Namespace";
+        let name = "Testing-Protected-Branches";
+        let repo = test::create_local_repo(&sync_dir, namespace, name)?;
+
+        let uri = format!("/oxen/{namespace}/{name}/protected_branches");
+        let data = r#"{"pattern": "release/*"}"#;
+        let req = test::repo_request(&sync_dir, &uri, namespace, name);
+        let resp = controllers::protected_branches::create(req, String::from(data)).await;
+        assert_eq!(resp.status(), http::StatusCode::OK);
+
+        let req = test::repo_request(&sync_dir, &uri, namespace, name);
+        let resp = controllers::protected_branches::index(req).await;
+        assert_eq!(resp.status(), http::StatusCode::OK);
+        let body = to_bytes(resp.into_body()).await.unwrap();
+        let text = std::str::from_utf8(&body).unwrap();
+        let list: ListProtectedBranchesResponse = serde_json::from_str(text)?;
+        assert_eq!(list.status, STATUS_SUCCESS);
+        assert_eq!(list.protected_branches.len(), 1);
+        // Deletion and force pushes are blocked unless the policy says otherwise
+        assert!(list.protected_branches[0].prevent_deletion);
+        assert!(list.protected_branches[0].prevent_force_push);
+        assert!(!list.protected_branches[0].require_validation);
+
+        // Protected branches cannot be deleted
+        api::local::branches::create(&repo, "release/1.0")?;
+        let branch_uri = format!("/oxen/{namespace}/{name}/branches/release/1.0");
+        let req = test::repo_request_with_param(
+            &sync_dir,
+            &branch_uri,
+            namespace,
+            name,
+            "branch_name",
+            "release/1.0",
+        );
+        let resp = controllers::branches::delete(req).await;
+        assert_eq!(resp.status(), http::StatusCode::FORBIDDEN);
+        let body = to_bytes(resp.into_body()).await.unwrap();
+        let text = std::str::from_utf8(&body).unwrap();
+        let status: StatusMessage = serde_json::from_str(text)?;
+        assert_eq!(status.status, STATUS_ERROR);
+        assert!(status.status_message.contains("cannot be deleted"));
+        assert!(api::local::branches::get_by_name(&repo, "release/1.0")?.is_some());
+
+        // Until the protection is removed
+        let req =
+            test::repo_request_with_param(&sync_dir, &uri, namespace, name, "pattern", "release/*");
+        let resp = controllers::protected_branches::delete(req).await;
+        assert_eq!(resp.status(), http::StatusCode::OK);
+
+        let req = test::repo_request_with_param(
+            &sync_dir,
+            &branch_uri,
+            namespace,
+            name,
+            "branch_name",
+            "release/1.0",
+        );
+        let resp = controllers::branches::delete(req).await;
+        assert_eq!(resp.status(), http::StatusCode::OK);
+        assert!(api::local::branches::get_by_name(&repo, "release/1.0")?.is_none());
+
+        // Other branches were never protected
+        assert!(api::local::branches::get_by_name(&repo, DEFAULT_BRANCH_NAME)?.is_some());
+
+        // cleanup
+        std::fs::remove_dir_all(sync_dir)?;
+
+        Ok(())
+    }
+}
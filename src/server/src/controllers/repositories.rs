@@ -1,4 +1,6 @@
 use crate::app_data::OxenAppData;
+use crate::auth::access_control::{AccessControl, RequiredAccess, Role};
+use crate::auth::access_keys::JWTClaim;
 
 use liboxen::api;
 use liboxen::util;
@@ -13,7 +15,7 @@ use liboxen::view::{ListRepositoryResponse, RepositoryResponse, RepositoryView,
 use liboxen::model::{LocalRepository, RepositoryNew};
 
 use actix_files::NamedFile;
-use actix_web::{HttpRequest, HttpResponse};
+use actix_web::{HttpMessage, HttpRequest, HttpResponse};
 use std::path::{Path, PathBuf};
 
 pub async fn index(req: HttpRequest) -> HttpResponse {
@@ -121,22 +123,40 @@ pub async fn create(req: HttpRequest, body: String) -> HttpResponse {
     // println!("controllers::repositories::create body:\n{}", body);
     let data: Result<RepositoryNew, serde_json::Error> = serde_json::from_str(&body);
     match data {
-        Ok(data) => match api::local::repositories::create_empty(&app_data.path, &data) {
-            Ok(_) => HttpResponse::Ok().json(RepositoryResponse {
-                status: String::from(STATUS_SUCCESS),
-                status_message: String::from(MSG_RESOURCE_CREATED),
-                repository: RepositoryView {
+        Ok(data) => {
+            for name in [&data.namespace, &data.name] {
+                if let Err(err) = AccessControl::validate_name(name) {
+                    return HttpResponse::BadRequest().json(StatusMessage::error(&err.to_string()));
+                }
+            }
+
+            // Only set when the server runs with auth
+            if let Some(claim) = req.extensions().get::<JWTClaim>() {
+                let required = RequiredAccess {
                     namespace: data.namespace.clone(),
-                    name: data.name.clone(),
-                },
-            }),
+                    repo_name: Some(data.name.clone()),
+                    role: Role::Write,
+                };
+                if let Some(missing) = claim.missing_scope(&required) {
+                    return HttpResponse::Forbidden().json(StatusMessage::error(&missing));
+                }
 
-            Err(err) => {
-                println!("Err api::local::repositories::create: {err:?}");
-                log::error!("Err api::local::repositories::create: {:?}", err);
-                HttpResponse::InternalServerError().json(StatusMessage::internal_server_error())
+                let access = AccessControl::new(&app_data.path);
+                match access.missing_create_permission(claim.email(), &data.namespace) {
+                    Ok(None) => {}
+                    Ok(Some(missing)) => {
+                        return HttpResponse::Forbidden().json(StatusMessage::error(&missing))
+                    }
+                    Err(err) => {
+                        log::error!("Err could not check access: {:?}", err);
+                        return HttpResponse::InternalServerError()
+                            .json(StatusMessage::internal_server_error());
+                    }
+                }
             }
-        },
+
+            create_empty(app_data, &data)
+        }
         Err(err) => {
             log::error!(
                 "Err api::local::repositories::create parse error: {:?}",
@@ -147,6 +167,25 @@ pub async fn create(req: HttpRequest, body: String) -> HttpResponse {
     }
 }
 
+fn create_empty(app_data: &OxenAppData, data: &RepositoryNew) -> HttpResponse {
+    match api::local::repositories::create_empty(&app_data.path, data) {
+        Ok(_) => HttpResponse::Ok().json(RepositoryResponse {
+            status: String::from(STATUS_SUCCESS),
+            status_message: String::from(MSG_RESOURCE_CREATED),
+            repository: RepositoryView {
+                namespace: data.namespace.clone(),
+                name: data.name.clone(),
+            },
+        }),
+
+        Err(err) => {
+            println!("Err api::local::repositories::create: {err:?}");
+            log::error!("Err api::local::repositories::create: {:?}", err);
+            HttpResponse::InternalServerError().json(StatusMessage::internal_server_error())
+        }
+    }
+}
+
 pub async fn delete(req: HttpRequest) -> HttpResponse {
     let app_data = req.app_data::<OxenAppData>().unwrap();
 
@@ -155,10 +194,18 @@ pub async fn delete(req: HttpRequest) -> HttpResponse {
     if let (Some(name), Some(namespace)) = (name, namespace) {
         match api::local::repositories::get_by_namespace_and_name(&app_data.path, namespace, name) {
             Ok(Some(repository)) => match api::local::repositories::delete(repository) {
-                Ok(_) => HttpResponse::Ok().json(StatusMessage {
-                    status: String::from(STATUS_SUCCESS),
-                    status_message: String::from(MSG_RESOURCE_DELETED),
-                }),
+                Ok(_) => {
+                    if let Err(err) =
+                        AccessControl::new(&app_data.path).remove_repo(namespace, name)
+                    {
+                        log::error!("Could not remove collaborators of {}: {}", name, err);
+                    }
+
+                    HttpResponse::Ok().json(StatusMessage {
+                        status: String::from(STATUS_SUCCESS),
+                        status_message: String::from(MSG_RESOURCE_DELETED),
+                    })
+                }
                 Err(err) => {
                     log::error!("Error deleting repository: {}", err);
                     HttpResponse::InternalServerError().json(StatusMessage::internal_server_error())
@@ -265,15 +312,17 @@ mod tests {
     use actix_web::http::{self};
 
     use actix_web::body::to_bytes;
+    use actix_web::HttpMessage;
 
     use liboxen::constants;
     use liboxen::error::OxenError;
-    use liboxen::model::{Commit, RepositoryNew};
+    use liboxen::model::{Commit, RepositoryNew, User};
 
     use liboxen::view::http::STATUS_SUCCESS;
-    use liboxen::view::{ListRepositoryResponse, RepositoryResponse};
+    use liboxen::view::{ListRepositoryResponse, RepositoryResponse, StatusMessage};
     use time::OffsetDateTime;
 
+    use crate::auth::access_keys::AccessKeyManager;
     use crate::controllers;
     use crate::test;
 
@@ -382,4 +431,54 @@ mod tests {
 
         Ok(())
     }
+
+    #[actix_web::test]
+    async fn test_controllers_respositories_create_needs_namespace_owner() -> Result<(), OxenError>
+    {
+        let sync_dir = test::get_sync_dir()?;
+        let keygen = AccessKeyManager::new(&sync_dir)?;
+        let mut claims = vec![];
+        for (name, email) in [("Ox", "ox@oxen.ai"), ("Bessie", "bessie@oxen.ai")] {
+            let user = User {
+                name: String::from(name),
+                email: String::from(email),
+            };
+            let (_user, token) = keygen.create(&user)?;
+            claims.push(keygen.get_claim(&token)?.unwrap());
+        }
+
+        // The first user to create a repository in the namespace owns it
+        let namespace = "Testing-Namespace";
+        for (claim, name, status) in [
+            (&claims[0], "Testing-1", http::StatusCode::OK),
+            (&claims[1], "Testing-2", http::StatusCode::FORBIDDEN),
+            (&claims[0], "Testing-3", http::StatusCode::OK),
+        ] {
+            let repo_new = RepositoryNew {
+                name: String::from(name),
+                namespace: String::from(namespace),
+                root_commit: None,
+            };
+            let data = serde_json::to_string(&repo_new)?;
+            let req = test::request(&sync_dir, "/api/repos");
+            req.extensions_mut().insert(claim.clone());
+
+            let resp = controllers::repositories::create(req, data).await;
+            assert_eq!(resp.status(), status);
+            if status == http::StatusCode::FORBIDDEN {
+                let body = to_bytes(resp.into_body()).await.unwrap();
+                let text = std::str::from_utf8(&body).unwrap();
+                let response: StatusMessage = serde_json::from_str(text)?;
+                assert_eq!(
+                    response.status_message,
+                    "bessie@oxen.ai does not have owner access to namespace Testing-Namespace"
+                );
+            }
+        }
+
+        // cleanup
+        std::fs::remove_dir_all(sync_dir)?;
+
+        Ok(())
+    }
 }
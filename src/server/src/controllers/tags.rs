@@ -0,0 +1,347 @@
+use crate::app_data::OxenAppData;
+use crate::auth::access_control::{AccessControl, RequiredAccess, Role};
+use crate::auth::access_keys::JWTClaim;
+
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use serde::Deserialize;
+
+use liboxen::api;
+use liboxen::error::OxenError;
+use liboxen::model::Tag;
+use liboxen::view::http::{
+    MSG_RESOURCE_CREATED, MSG_RESOURCE_DELETED, MSG_RESOURCE_FOUND, STATUS_SUCCESS,
+};
+use liboxen::view::{ListTagsResponse, StatusMessage, TagResponse};
+
+#[derive(Deserialize, Debug)]
+pub struct TagDeleteQuery {
+    #[serde(default)]
+    pub force: bool,
+}
+
+pub async fn index(req: HttpRequest) -> HttpResponse {
+    let app_data = req.app_data::<OxenAppData>().unwrap();
+    let namespace: &str = req.match_info().get("namespace").unwrap();
+    let name: &str = req.match_info().get("repo_name").unwrap();
+    match api::local::repositories::get_by_namespace_and_name(&app_data.path, namespace, name) {
+        Ok(Some(repository)) => match api::local::tags::list(&repository) {
+            Ok(tags) => HttpResponse::Ok().json(ListTagsResponse {
+                status: String::from(STATUS_SUCCESS),
+                status_message: String::from(MSG_RESOURCE_FOUND),
+                tags,
+            }),
+            Err(err) => {
+                log::error!("Unable to list tags. Err: {}", err);
+                HttpResponse::InternalServerError().json(StatusMessage::internal_server_error())
+            }
+        },
+        Ok(None) => {
+            log::debug!("404 tags::index could not get repo {}", name);
+            HttpResponse::NotFound().json(StatusMessage::resource_not_found())
+        }
+        Err(err) => {
+            log::error!("Err tags::index could not get repo {} {:?}", name, err);
+            HttpResponse::InternalServerError().json(StatusMessage::internal_server_error())
+        }
+    }
+}
+
+pub async fn show(req: HttpRequest) -> HttpResponse {
+    let app_data = req.app_data::<OxenAppData>().unwrap();
+    let namespace: Option<&str> = req.match_info().get("namespace");
+    let name: Option<&str> = req.match_info().get("repo_name");
+    let tag_name: Option<&str> = req.match_info().get("tag_name");
+    if let (Some(namespace), Some(name), Some(tag_name)) = (namespace, name, tag_name) {
+        match api::local::repositories::get_by_namespace_and_name(&app_data.path, namespace, name) {
+            Ok(Some(repository)) => match api::local::tags::get_by_name(&repository, tag_name) {
+                Ok(Some(tag)) => HttpResponse::Ok().json(TagResponse {
+                    status: String::from(STATUS_SUCCESS),
+                    status_message: String::from(MSG_RESOURCE_FOUND),
+                    tag,
+                }),
+                Ok(None) => {
+                    log::debug!("tag {} does not exist for repo: {}", tag_name, name);
+                    HttpResponse::NotFound().json(StatusMessage::resource_not_found())
+                }
+                Err(err) => {
+                    log::error!("Err getting tag {}: {}", tag_name, err);
+                    HttpResponse::InternalServerError().json(StatusMessage::internal_server_error())
+                }
+            },
+            Ok(None) => {
+                log::debug!("404 tags::show could not get repo {}", name);
+                HttpResponse::NotFound().json(StatusMessage::resource_not_found())
+            }
+            Err(err) => {
+                log::error!("Err tags::show could not get repo {} {:?}", name, err);
+                HttpResponse::InternalServerError().json(StatusMessage::internal_server_error())
+            }
+        }
+    } else {
+        let msg = "Must supply `namespace`, `repo_name` and `tag_name` params";
+        HttpResponse::BadRequest().json(StatusMessage::error(msg))
+    }
+}
+
+/// Tags are immutable, creating one that exists for the same commit returns it and for another commit is a conflict
+pub async fn create(req: HttpRequest, body: String) -> HttpResponse {
+    let app_data = req.app_data::<OxenAppData>().unwrap();
+    let namespace: &str = req.match_info().get("namespace").unwrap();
+    let name: &str = req.match_info().get("repo_name").unwrap();
+    let data: Result<Tag, serde_json::Error> = serde_json::from_str(&body);
+    let tag = match data {
+        Ok(tag) => tag,
+        Err(_) => return HttpResponse::BadRequest().json(StatusMessage::error("Invalid body.")),
+    };
+
+    match api::local::repositories::get_by_namespace_and_name(&app_data.path, namespace, name) {
+        Ok(Some(repository)) => match api::local::tags::save_if_absent(&repository, &tag) {
+            Ok(Some(existing)) if existing.commit_id == tag.commit_id => {
+                HttpResponse::Ok().json(TagResponse {
+                    status: String::from(STATUS_SUCCESS),
+                    status_message: String::from(MSG_RESOURCE_FOUND),
+                    tag: existing,
+                })
+            }
+            Ok(Some(_)) => {
+                let err = OxenError::tag_already_exists(&tag.name);
+                HttpResponse::Conflict().json(StatusMessage::error(&err.to_string()))
+            }
+            Ok(None) => HttpResponse::Ok().json(TagResponse {
+                status: String::from(STATUS_SUCCESS),
+                status_message: String::from(MSG_RESOURCE_CREATED),
+                tag,
+            }),
+            Err(err) => {
+                log::debug!("Could not create tag {}: {}", tag.name, err);
+                HttpResponse::BadRequest().json(StatusMessage::error(&err.to_string()))
+            }
+        },
+        Ok(None) => {
+            log::debug!("404 tags::create could not get repo {}", name);
+            HttpResponse::NotFound().json(StatusMessage::resource_not_found())
+        }
+        Err(err) => {
+            log::error!("Err tags::create could not get repo {} {:?}", name, err);
+            HttpResponse::InternalServerError().json(StatusMessage::internal_server_error())
+        }
+    }
+}
+
+/// Only a force delete by an admin of the repository removes a tag
+pub async fn delete(req: HttpRequest, query: web::Query<TagDeleteQuery>) -> HttpResponse {
+    let app_data = req.app_data::<OxenAppData>().unwrap();
+    let namespace: Option<&str> = req.match_info().get("namespace");
+    let name: Option<&str> = req.match_info().get("repo_name");
+    let tag_name: Option<&str> = req.match_info().get("tag_name");
+    if let (Some(namespace), Some(name), Some(tag_name)) = (namespace, name, tag_name) {
+        if !query.force {
+            let msg =
+                format!("Tag '{tag_name}' is immutable, an admin can delete it with force=true");
+            return HttpResponse::Forbidden().json(StatusMessage::error(&msg));
+        }
+
+        // Only set when the server runs with auth
+        if let Some(claim) = req.extensions().get::<JWTClaim>() {
+            let required = RequiredAccess {
+                namespace: String::from(namespace),
+                repo_name: Some(String::from(name)),
+                role: Role::Admin,
+            };
+            if let Some(missing) = claim.missing_scope(&required) {
+                return HttpResponse::Forbidden().json(StatusMessage::error(&missing));
+            }
+
+            let access = AccessControl::new(&app_data.path);
+            match access.missing_permission(claim.email(), &required) {
+                Ok(None) => {}
+                Ok(Some(missing)) => {
+                    return HttpResponse::Forbidden().json(StatusMessage::error(&missing))
+                }
+                Err(err) => {
+                    log::error!("Err could not check access: {:?}", err);
+                    return HttpResponse::InternalServerError()
+                        .json(StatusMessage::internal_server_error());
+                }
+            }
+        }
+
+        match api::local::repositories::get_by_namespace_and_name(&app_data.path, namespace, name) {
+            Ok(Some(repository)) => match api::local::tags::delete(&repository, tag_name) {
+                Ok(Some(tag)) => HttpResponse::Ok().json(TagResponse {
+                    status: String::from(STATUS_SUCCESS),
+                    status_message: String::from(MSG_RESOURCE_DELETED),
+                    tag,
+                }),
+                Ok(None) => {
+                    log::debug!("tag {} does not exist for repo: {}", tag_name, name);
+                    HttpResponse::NotFound().json(StatusMessage::resource_not_found())
+                }
+                Err(err) => {
+                    log::error!("Could not delete tag {}: {}", tag_name, err);
+                    HttpResponse::InternalServerError().json(StatusMessage::internal_server_error())
+                }
+            },
+            Ok(None) => {
+                log::debug!("404 tags::delete could not get repo {}", name);
+                HttpResponse::NotFound().json(StatusMessage::resource_not_found())
+            }
+            Err(err) => {
+                log::error!("tags::delete could not find repo: {}", err);
+                HttpResponse::InternalServerError().json(StatusMessage::internal_server_error())
+            }
+        }
+    } else {
+        let msg = "Must supply `namespace`, `repo_name` and `tag_name` params";
+        HttpResponse::BadRequest().json(StatusMessage::error(msg))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use actix_web::body::to_bytes;
+    use actix_web::http::{self};
+    use actix_web::web;
+    use actix_web::HttpMessage;
+
+    use liboxen::api;
+    use liboxen::command;
+    use liboxen::error::OxenError;
+    use liboxen::model::{Tag, User};
+    use liboxen::util;
+    use liboxen::view::ListTagsResponse;
+
+    use crate::auth::access_control::{AccessControl, Role};
+    use crate::auth::access_keys::AccessKeyManager;
+    use crate::controllers;
+    use crate::controllers::tags::TagDeleteQuery;
+    use crate::test;
+
+    #[actix_web::test]
+    async fn test_controllers_tags_are_immutable() -> Result<(), OxenError> {
+        let sync_dir = test::get_sync_dir()?;
+
+        let namespace = "Testing-Namespace";
+        let name = "Testing-Tags";
+        let repo = test::create_local_repo(&sync_dir, namespace, name)?;
+        let path = repo.path.join("labels.txt");
+        util::fs::write_to_path(&path, "cat\ndog")?;
+        command::add(&repo, &path)?;
+        let first = command::commit(&repo, "Adding labels")?.unwrap();
+        util::fs::write_to_path(&path, "cat\ndog\nox")?;
+        command::add(&repo, &path)?;
+        let second = command::commit(&repo, "Adding ox")?.unwrap();
+
+        let tag = Tag {
+            name: String::from("v1.3"),
+            commit_id: first.id.clone(),
+            tagger: String::from("Ox"),
+            email: String::from("ox@oxen.ai"),
+            message: String::from("First release"),
+            timestamp: first.timestamp,
+        };
+        let uri = format!("/oxen/{namespace}/{name}/tags");
+        let req = test::repo_request(&sync_dir, &uri, namespace, name);
+        let resp = controllers::tags::create(req, serde_json::to_string(&tag)?).await;
+        assert_eq!(resp.status(), http::StatusCode::OK);
+
+        // Pushing the same tag again is fine
+        let req = test::repo_request(&sync_dir, &uri, namespace, name);
+        let resp = controllers::tags::create(req, serde_json::to_string(&tag)?).await;
+        assert_eq!(resp.status(), http::StatusCode::OK);
+
+        // Moving it is not
+        let moved = Tag {
+            commit_id: second.id.clone(),
+            ..tag.clone()
+        };
+        let req = test::repo_request(&sync_dir, &uri, namespace, name);
+        let resp = controllers::tags::create(req, serde_json::to_string(&moved)?).await;
+        assert_eq!(resp.status(), http::StatusCode::CONFLICT);
+
+        let req = test::repo_request(&sync_dir, &uri, namespace, name);
+        let resp = controllers::tags::index(req).await;
+        let body = to_bytes(resp.into_body()).await.unwrap();
+        let list: ListTagsResponse = serde_json::from_str(std::str::from_utf8(&body).unwrap())?;
+        assert_eq!(list.tags, vec![tag.clone()]);
+
+        // Deleting needs force
+        let tag_uri = format!("/oxen/{namespace}/{name}/tags/v1.3");
+        let req =
+            test::repo_request_with_param(&sync_dir, &tag_uri, namespace, name, "tag_name", "v1.3");
+        let query: web::Query<TagDeleteQuery> = web::Query::from_query("").unwrap();
+        let resp = controllers::tags::delete(req, query).await;
+        assert_eq!(resp.status(), http::StatusCode::FORBIDDEN);
+        assert!(api::local::tags::get_by_name(&repo, "v1.3")?.is_some());
+
+        let req =
+            test::repo_request_with_param(&sync_dir, &tag_uri, namespace, name, "tag_name", "v1.3");
+        let query: web::Query<TagDeleteQuery> = web::Query::from_query("force=true").unwrap();
+        let resp = controllers::tags::delete(req, query).await;
+        assert_eq!(resp.status(), http::StatusCode::OK);
+        assert!(api::local::tags::get_by_name(&repo, "v1.3")?.is_none());
+
+        // cleanup
+        std::fs::remove_dir_all(sync_dir)?;
+
+        Ok(())
+    }
+
+    #[actix_web::test]
+    async fn test_controllers_tags_delete_needs_admin() -> Result<(), OxenError> {
+        let sync_dir = test::get_sync_dir()?;
+
+        let namespace = "Testing-Namespace";
+        let name = "Testing-Tags";
+        let repo = test::create_local_repo(&sync_dir, namespace, name)?;
+        let path = repo.path.join("labels.txt");
+        util::fs::write_to_path(&path, "cat\ndog")?;
+        command::add(&repo, &path)?;
+        let commit = command::commit(&repo, "Adding labels")?.unwrap();
+        let tag = Tag {
+            name: String::from("v1.3"),
+            commit_id: commit.id.clone(),
+            tagger: String::from("Ox"),
+            email: String::from("ox@oxen.ai"),
+            message: String::from("First release"),
+            timestamp: commit.timestamp,
+        };
+        api::local::tags::save(&repo, &tag)?;
+
+        let keygen = AccessKeyManager::new(&sync_dir)?;
+        let mut claims = vec![];
+        for (name, email) in [("Ox", "ox@oxen.ai"), ("Bessie", "bessie@oxen.ai")] {
+            let user = User {
+                name: String::from(name),
+                email: String::from(email),
+            };
+            let (_user, token) = keygen.create(&user)?;
+            claims.push(keygen.get_claim(&token)?.unwrap());
+        }
+        let access = AccessControl::new(&sync_dir);
+        access.add_owner(namespace, "ox@oxen.ai")?;
+        access.grant(namespace, name, "bessie@oxen.ai", Role::Write)?;
+
+        // A writer can not delete it even with force, whatever the validator decided
+        let tag_uri = format!("/oxen/{namespace}/{name}/tags/v1.3");
+        for (claim, status) in [
+            (&claims[1], http::StatusCode::FORBIDDEN),
+            (&claims[0], http::StatusCode::OK),
+        ] {
+            let req = test::repo_request_with_param(
+                &sync_dir, &tag_uri, namespace, name, "tag_name", "v1.3",
+            );
+            req.extensions_mut().insert(claim.clone());
+            let query: web::Query<TagDeleteQuery> = web::Query::from_query("force=true").unwrap();
+            let resp = controllers::tags::delete(req, query).await;
+            assert_eq!(resp.status(), status);
+        }
+        assert!(api::local::tags::get_by_name(&repo, "v1.3")?.is_none());
+
+        // cleanup
+        std::fs::remove_dir_all(sync_dir)?;
+
+        Ok(())
+    }
+}
@@ -1,5 +1,8 @@
+use liboxen::api;
+use liboxen::command;
 use liboxen::config::UserConfig;
-use liboxen::model::User;
+use liboxen::constants::DEFAULT_UPLOAD_TTL_SECS;
+use liboxen::model::{ProtectedBranch, RepositoryNew, User};
 
 pub mod app_data;
 pub mod auth;
@@ -16,19 +19,47 @@ extern crate log;
 use actix_web::middleware::{Condition, Logger};
 use actix_web::{web, App, HttpServer};
 use actix_web_httpauth::middleware::HttpAuthentication;
+use bytesize::ByteSize;
 use clap::{Arg, Command};
 use env_logger::Env;
 use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::auth::access_control::{AccessControl, Role};
+use crate::auth::access_keys::TokenScope;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 const ADD_USER_USAGE: &str =
     "Usage: `oxen-server add-user -e <email> -n <name> -o user_config.toml`";
 
+const REVOKE_TOKEN_USAGE: &str =
+    "Usage: `oxen-server revoke-token <ID>` or `oxen-server revoke-token --email <email>`";
+
 const START_SERVER_USAGE: &str = "Usage: `oxen-server start -h 0.0.0.0 -p 3000`";
 
+const GC_USAGE: &str = "Usage: `oxen-server gc <namespace/repo_name> [--dry-run]`";
+
+const BRANCH_POLICY_USAGE: &str =
+    "Usage: `oxen-server branch-policy <namespace/repo_name> <list|protect|unprotect> [PATTERN]`";
+
+const GRANT_USAGE: &str =
+    "Usage: `oxen-server grant <namespace[/repo_name]> -e <email> [-r read|write|admin]`";
+
+const REVOKE_USAGE: &str = "Usage: `oxen-server revoke <namespace[/repo_name]> -e <email>`";
+
+const MIRROR_USAGE: &str =
+    "Usage: `oxen-server mirror <URL>... [--interval <SECONDS>] [--once]`, with URLs like http://upstream:3000/namespace/repo_name";
+
 const INVALID_PORT_MSG: &str = "Port must a valid number between 0-65535";
 
+const INVALID_EXPIRES_IN_MSG: &str = "--expires-in must be a number of days";
+
+const INVALID_UPLOAD_TTL_MSG: &str = "UPLOAD_TTL_SECS must be a number of seconds";
+
+const INVALID_INTERVAL_MSG: &str = "--interval must be a number of seconds";
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     env_logger::init_from_env(Env::default().default_filter_or("info,debug"));
@@ -38,6 +69,11 @@ async fn main() -> std::io::Result<()> {
         Err(_) => String::from("/tmp/oxen_sync"),
     };
 
+    let upload_ttl = match std::env::var("UPLOAD_TTL_SECS") {
+        Ok(secs) => secs.parse::<u64>().expect(INVALID_UPLOAD_TTL_MSG),
+        Err(_) => DEFAULT_UPLOAD_TTL_SECS,
+    };
+
     let command = Command::new("oxen-server")
         .version(VERSION)
         .about("Oxen Server")
@@ -101,6 +137,165 @@ async fn main() -> std::io::Result<()> {
                         .default_missing_value("always")
                         .help("Where to write the output config file to give to the user")
                         .takes_value(true),
+                )
+                .arg(
+                    Arg::new("expires-in")
+                        .long("expires-in")
+                        .help("Number of days until the token stops working, tokens do not expire by default")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::new("scope")
+                        .long("scope")
+                        .help("Limit the token to read, namespace:<namespace> or push:<namespace>/<repo_name>")
+                        .takes_value(true)
+                        .multiple_occurrences(true),
+                ),
+        )
+        .subcommand(
+            Command::new("list-tokens")
+                .about("List the tokens that have not been revoked")
+                .arg(
+                    Arg::new("email")
+                        .long("email")
+                        .short('e')
+                        .help("Only list the tokens of this user")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            Command::new("revoke-token")
+                .about(REVOKE_TOKEN_USAGE)
+                .arg(Arg::new("ID").help("Id of the token from `oxen-server list-tokens`"))
+                .arg(
+                    Arg::new("email")
+                        .long("email")
+                        .short('e')
+                        .help("Revoke all the tokens of this user")
+                        .takes_value(true)
+                        .conflicts_with("ID"),
+                ),
+        )
+        .subcommand(
+            Command::new("gc")
+                .about(GC_USAGE)
+                .arg(
+                    Arg::new("REPO")
+                        .help("The repository to collect, as namespace/repo_name")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("dry-run")
+                        .long("dry-run")
+                        .short('n')
+                        .help("Report what would be removed without removing anything")
+                        .takes_value(false),
+                ),
+        )
+        .subcommand(
+            Command::new("mirror")
+                .about(MIRROR_USAGE)
+                .arg(
+                    Arg::new("URL")
+                        .help("Upstream repositories to copy into the sync dir, with the same commit ids")
+                        .required(true)
+                        .multiple_values(true),
+                )
+                .arg(
+                    Arg::new("interval")
+                        .long("interval")
+                        .short('i')
+                        .default_value("60")
+                        .help("Seconds to wait between pulls")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::new("once")
+                        .long("once")
+                        .help("Pull once and exit")
+                        .takes_value(false),
+                ),
+        )
+        .subcommand(
+            Command::new("branch-policy")
+                .about(BRANCH_POLICY_USAGE)
+                .subcommand_required(true)
+                .arg(
+                    Arg::new("REPO")
+                        .help("The repository to manage, as namespace/repo_name")
+                        .required(true),
+                )
+                .subcommand(Command::new("list").about("List the protected branch patterns"))
+                .subcommand(
+                    Command::new("protect")
+                        .about("Protect the branches matching a name or glob like release/*")
+                        .arg(Arg::new("PATTERN").required(true))
+                        .arg(
+                            Arg::new("allow-deletion")
+                                .long("allow-deletion")
+                                .help("Allow the matching branches to be deleted")
+                                .takes_value(false),
+                        )
+                        .arg(
+                            Arg::new("allow-force-push")
+                                .long("allow-force-push")
+                                .help("Allow force pushes to the matching branches")
+                                .takes_value(false),
+                        )
+                        .arg(
+                            Arg::new("require-validation")
+                                .long("require-validation")
+                                .help("Only accept pushes of commits that passed validation")
+                                .takes_value(false),
+                        ),
+                )
+                .subcommand(
+                    Command::new("unprotect")
+                        .about("Remove the protection for a pattern")
+                        .arg(Arg::new("PATTERN").required(true)),
+                ),
+        )
+        .subcommand(
+            Command::new("grant")
+                .about(GRANT_USAGE)
+                .arg(
+                    Arg::new("TARGET")
+                        .help("A namespace to make the user an owner of, or namespace/repo_name to make them a collaborator on")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("email")
+                        .long("email")
+                        .short('e')
+                        .help("Email of the user to grant access to")
+                        .required(true)
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::new("role")
+                        .long("role")
+                        .short('r')
+                        .help("Role of a repository collaborator")
+                        .possible_values(["read", "write", "admin"])
+                        .default_value("read")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            Command::new("revoke")
+                .about(REVOKE_USAGE)
+                .arg(
+                    Arg::new("TARGET")
+                        .help("A namespace to remove the user as an owner of, or namespace/repo_name to remove them as a collaborator")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("email")
+                        .long("email")
+                        .short('e')
+                        .help("Email of the user to revoke access from")
+                        .required(true)
+                        .takes_value(true),
                 ),
         );
     let matches = command.get_matches();
@@ -115,7 +310,8 @@ async fn main() -> std::io::Result<()> {
                     println!("Syncing to directory: {sync_dir}");
                     let enable_auth = sub_matches.is_present("auth");
 
-                    let data = app_data::OxenAppData::from(&sync_dir);
+                    let data = app_data::OxenAppData::from(&sync_dir)
+                        .with_upload_ttl(Duration::from_secs(upload_ttl));
                     HttpServer::new(move || {
                         App::new()
                             .app_data(data.clone())
@@ -160,7 +356,26 @@ async fn main() -> std::io::Result<()> {
                             name: name.to_string(),
                             email: email.to_string(),
                         };
-                        match keygen.create(&new_user) {
+                        let scopes = match sub_matches.values_of("scope") {
+                            Some(scopes) => {
+                                match scopes
+                                    .map(TokenScope::from_str)
+                                    .collect::<Result<Vec<_>, _>>()
+                                {
+                                    Ok(scopes) => scopes,
+                                    Err(err) => {
+                                        eprintln!("Err: {err}");
+                                        return Ok(());
+                                    }
+                                }
+                            }
+                            None => vec![],
+                        };
+                        let expires_in = sub_matches.value_of("expires-in").map(|days| {
+                            let days = days.parse::<u64>().expect(INVALID_EXPIRES_IN_MSG);
+                            Duration::from_secs(days * 24 * 60 * 60)
+                        });
+                        match keygen.create_scoped(&new_user, &scopes, expires_in) {
                             Ok((user, token)) => {
                                 let cfg = UserConfig::from_user(&user);
                                 match cfg.save(Path::new(output)) {
@@ -185,6 +400,267 @@ async fn main() -> std::io::Result<()> {
 
             Ok(())
         }
+        Some(("list-tokens", sub_matches)) => {
+            let email = sub_matches.value_of("email");
+            match auth::access_keys::AccessKeyManager::new(Path::new(&sync_dir))
+                .and_then(|keygen| keygen.list())
+            {
+                Ok(claims) => {
+                    for claim in claims {
+                        if email.is_some() && email != Some(claim.email()) {
+                            continue;
+                        }
+
+                        let expires = match claim.expires_at() {
+                            Some(_) if claim.is_expired() => String::from("expired"),
+                            Some(expires_at) => format!("expires {expires_at}"),
+                            None => String::from("never expires"),
+                        };
+                        let scopes = claim
+                            .scopes()
+                            .iter()
+                            .map(|scope| scope.to_string())
+                            .collect::<Vec<String>>();
+                        let scopes = if scopes.is_empty() {
+                            String::from("all access")
+                        } else {
+                            scopes.join(", ")
+                        };
+                        println!(
+                            "{}\t{} <{}>\t{}\t{}",
+                            claim.id(),
+                            claim.name(),
+                            claim.email(),
+                            expires,
+                            scopes
+                        );
+                    }
+                }
+                Err(err) => {
+                    eprintln!("Err: {err}")
+                }
+            }
+
+            Ok(())
+        }
+        Some(("revoke-token", sub_matches)) => {
+            let keygen = match auth::access_keys::AccessKeyManager::new(Path::new(&sync_dir)) {
+                Ok(keygen) => keygen,
+                Err(err) => {
+                    eprintln!("Err: {err}");
+                    return Ok(());
+                }
+            };
+
+            match (sub_matches.value_of("ID"), sub_matches.value_of("email")) {
+                (Some(id), None) => match keygen.revoke(id) {
+                    Ok(true) => println!("Revoked token {id}"),
+                    Ok(false) => eprintln!("No token with id {id}"),
+                    Err(err) => eprintln!("Err: {err}"),
+                },
+                (None, Some(email)) => match keygen.revoke_user(email) {
+                    Ok(num_revoked) => println!("Revoked {num_revoked} tokens of {email}"),
+                    Err(err) => eprintln!("Err: {err}"),
+                },
+                _ => {
+                    eprintln!("{REVOKE_TOKEN_USAGE}")
+                }
+            }
+
+            Ok(())
+        }
+        Some(("gc", sub_matches)) => {
+            let repo_name = sub_matches.value_of("REPO").expect("required");
+            let dry_run = sub_matches.is_present("dry-run");
+            match repo_name.split_once('/') {
+                Some((namespace, name)) => {
+                    let path = Path::new(&sync_dir);
+                    match api::local::repositories::get_by_namespace_and_name(path, namespace, name)
+                    {
+                        Ok(Some(repo)) => match command::gc(&repo, dry_run) {
+                            Ok(stats) => {
+                                let verb = if dry_run { "Would remove" } else { "Removed" };
+                                println!(
+                                    "{} {} commits, {} version dirs, {} chunks and {} pack objects, reclaiming {}",
+                                    verb,
+                                    stats.num_commits,
+                                    stats.num_version_dirs,
+                                    stats.num_chunks,
+                                    stats.num_pack_objects,
+                                    ByteSize::b(stats.num_bytes)
+                                );
+                            }
+                            Err(err) => {
+                                eprintln!("Err: {err}")
+                            }
+                        },
+                        Ok(None) => {
+                            eprintln!("Repository {repo_name} does not exist in {sync_dir}")
+                        }
+                        Err(err) => {
+                            eprintln!("Err: {err}")
+                        }
+                    }
+                }
+                None => {
+                    eprintln!("{GC_USAGE}")
+                }
+            }
+
+            Ok(())
+        }
+        Some(("mirror", sub_matches)) => {
+            let urls: Vec<&str> = sub_matches.values_of("URL").expect("required").collect();
+            let interval = sub_matches
+                .value_of("interval")
+                .expect("has default")
+                .parse::<u64>()
+                .expect(INVALID_INTERVAL_MSG);
+            let once = sub_matches.is_present("once");
+
+            // Repositories live in sync_dir/namespace/repo_name, same as on the upstream server
+            let mut mirrors: Vec<(&str, String)> = vec![];
+            for url in urls {
+                match RepositoryNew::from_url(url) {
+                    Ok(repo) => mirrors.push((url, repo.namespace)),
+                    Err(err) => {
+                        eprintln!("Invalid url {url}: {err}\n{MIRROR_USAGE}");
+                        return Ok(());
+                    }
+                }
+            }
+
+            loop {
+                for (url, namespace) in mirrors.iter() {
+                    let dst = Path::new(&sync_dir).join(namespace);
+                    match command::mirror(url, &dst).await {
+                        Ok(branches) => {
+                            for branch in branches {
+                                log::info!(
+                                    "Mirrored {} {} -> {}",
+                                    url,
+                                    branch.name,
+                                    branch.commit_id
+                                );
+                            }
+                        }
+                        Err(err) => log::error!("Could not mirror {}: {}", url, err),
+                    }
+                }
+
+                if once {
+                    break;
+                }
+                actix_web::rt::time::sleep(Duration::from_secs(interval)).await;
+            }
+
+            Ok(())
+        }
+        Some(("branch-policy", sub_matches)) => {
+            let repo_name = sub_matches.value_of("REPO").expect("required");
+            let (namespace, name) = match repo_name.split_once('/') {
+                Some((namespace, name)) => (namespace, name),
+                None => {
+                    eprintln!("{BRANCH_POLICY_USAGE}");
+                    return Ok(());
+                }
+            };
+
+            let path = Path::new(&sync_dir);
+            let repo =
+                match api::local::repositories::get_by_namespace_and_name(path, namespace, name) {
+                    Ok(Some(repo)) => repo,
+                    Ok(None) => {
+                        eprintln!("Repository {repo_name} does not exist in {sync_dir}");
+                        return Ok(());
+                    }
+                    Err(err) => {
+                        eprintln!("Err: {err}");
+                        return Ok(());
+                    }
+                };
+
+            match sub_matches.subcommand() {
+                Some(("list", _)) => match api::local::branch_policies::get(&repo) {
+                    Ok(policy) => {
+                        for protection in policy.protected_branches {
+                            println!(
+                                "{}\tprevent_deletion={} prevent_force_push={} require_validation={}",
+                                protection.pattern,
+                                protection.prevent_deletion,
+                                protection.prevent_force_push,
+                                protection.require_validation
+                            );
+                        }
+                    }
+                    Err(err) => {
+                        eprintln!("Err: {err}")
+                    }
+                },
+                Some(("protect", policy_matches)) => {
+                    let pattern = policy_matches.value_of("PATTERN").expect("required");
+                    let mut protection = ProtectedBranch::new(pattern);
+                    protection.prevent_deletion = !policy_matches.is_present("allow-deletion");
+                    protection.prevent_force_push = !policy_matches.is_present("allow-force-push");
+                    protection.require_validation = policy_matches.is_present("require-validation");
+                    match api::local::branch_policies::protect(&repo, protection) {
+                        Ok(_) => println!("Protected {pattern} in {repo_name}"),
+                        Err(err) => eprintln!("Err: {err}"),
+                    }
+                }
+                Some(("unprotect", policy_matches)) => {
+                    let pattern = policy_matches.value_of("PATTERN").expect("required");
+                    match api::local::branch_policies::unprotect(&repo, pattern) {
+                        Ok(Some(_)) => println!("Removed protection {pattern} from {repo_name}"),
+                        Ok(None) => eprintln!("{pattern} is not protected in {repo_name}"),
+                        Err(err) => eprintln!("Err: {err}"),
+                    }
+                }
+                _ => {
+                    eprintln!("{BRANCH_POLICY_USAGE}")
+                }
+            }
+
+            Ok(())
+        }
+        Some(("grant", sub_matches)) => {
+            let target = sub_matches.value_of("TARGET").expect("required");
+            let email = sub_matches.value_of("email").expect("required");
+            let access = AccessControl::new(Path::new(&sync_dir));
+            let result = match target.split_once('/') {
+                Some((namespace, name)) => {
+                    let role = sub_matches.value_of("role").expect("has default");
+                    Role::from_str(role)
+                        .and_then(|role| access.grant(namespace, name, email, role))
+                        .map(|_| format!("Granted {email} {role} access to {target}"))
+                }
+                None => access
+                    .add_owner(target, email)
+                    .map(|_| format!("{email} now owns namespace {target}")),
+            };
+            match result {
+                Ok(msg) => println!("{msg}"),
+                Err(err) => eprintln!("Err: {err}"),
+            }
+
+            Ok(())
+        }
+        Some(("revoke", sub_matches)) => {
+            let target = sub_matches.value_of("TARGET").expect("required");
+            let email = sub_matches.value_of("email").expect("required");
+            let access = AccessControl::new(Path::new(&sync_dir));
+            let result = match target.split_once('/') {
+                Some((namespace, name)) => access.revoke(namespace, name, email),
+                None => access.remove_owner(target, email),
+            };
+            match result {
+                Ok(true) => println!("Revoked access to {target} from {email}"),
+                Ok(false) => eprintln!("{email} did not have access to {target}"),
+                Err(err) => eprintln!("Err: {err}"),
+            }
+
+            Ok(())
+        }
         _ => unreachable!(), // If all subcommands are defined above, anything else is unreachabe!()
     }
 }
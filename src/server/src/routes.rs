@@ -42,6 +42,10 @@ pub fn config(cfg: &mut web::ServiceConfig) {
         "/{namespace}/{repo_name}/commits/{commit_id}/upload_chunk",
         web::post().to(controllers::commits::upload_chunk),
     )
+    .route(
+        "/{namespace}/{repo_name}/uploads/{hash}",
+        web::get().to(controllers::commits::upload_session),
+    )
     .route(
         "/{namespace}/{repo_name}/commits/{commit_or_branch:.*}/history",
         web::get().to(controllers::commits::commit_history),
@@ -79,6 +83,45 @@ pub fn config(cfg: &mut web::ServiceConfig) {
         "/{namespace}/{repo_name}/branches/{branch_name:.*}",
         web::put().to(controllers::branches::update),
     )
+    // ----- Protected Branches ----- //
+    .route(
+        "/{namespace}/{repo_name}/protected_branches",
+        web::get().to(controllers::protected_branches::index),
+    )
+    .route(
+        "/{namespace}/{repo_name}/protected_branches",
+        web::post().to(controllers::protected_branches::create),
+    )
+    .route(
+        "/{namespace}/{repo_name}/protected_branches/{pattern:.*}",
+        web::delete().to(controllers::protected_branches::delete),
+    )
+    // ----- Tags ----- //
+    .route(
+        "/{namespace}/{repo_name}/tags",
+        web::get().to(controllers::tags::index),
+    )
+    .route(
+        "/{namespace}/{repo_name}/tags",
+        web::post().to(controllers::tags::create),
+    )
+    .route(
+        "/{namespace}/{repo_name}/tags/{tag_name:.*}",
+        web::get().to(controllers::tags::show),
+    )
+    .route(
+        "/{namespace}/{repo_name}/tags/{tag_name:.*}",
+        web::delete().to(controllers::tags::delete),
+    )
+    // ----- Compare ----- //
+    .route(
+        "/{namespace}/{repo_name}/compare/{base_head}",
+        web::get().to(controllers::compare::show),
+    )
+    .route(
+        "/{namespace}/{repo_name}/compare/{base_head}/{resource:.*}",
+        web::get().to(controllers::compare::show),
+    )
     // ----- Stats ----- //
     .route(
         "/{namespace}/{repo_name}/stats",
@@ -107,11 +150,36 @@ pub fn config(cfg: &mut web::ServiceConfig) {
         "/{namespace}/{repo_name}/chunk/{resource:.*}", // Download a chunk of a larger versioned file
         web::get().to(controllers::entries::download_chunk),
     )
+    // ----- Chunks (content defined pieces of large versioned files) ----- //
+    .route(
+        "/{namespace}/{repo_name}/chunks/missing",
+        web::post().to(controllers::chunks::list_missing),
+    )
+    .route(
+        "/{namespace}/{repo_name}/chunks/{hash}",
+        web::get().to(controllers::chunks::download),
+    )
+    .route(
+        "/{namespace}/{repo_name}/chunks/{hash}",
+        web::post().to(controllers::chunks::upload),
+    )
+    .route(
+        "/{namespace}/{repo_name}/manifests",
+        web::post().to(controllers::chunks::create_manifest),
+    )
+    .route(
+        "/{namespace}/{repo_name}/manifests/{hash}",
+        web::get().to(controllers::chunks::show_manifest),
+    )
     // ----- Versions ----- //
     .route(
         "/{namespace}/{repo_name}/versions", // Download tar.gz set of version files
         web::get().to(controllers::entries::download_data_from_version_paths),
     )
+    .route(
+        "/{namespace}/{repo_name}/versions/missing",
+        web::post().to(controllers::entries::list_missing_versions),
+    )
     // POST to versions will be depreciated...
     .route(
         "/{namespace}/{repo_name}/versions", // Download tar.gz set of version files
@@ -127,6 +195,10 @@ pub fn config(cfg: &mut web::ServiceConfig) {
         "/{namespace}/{repo_name}/df/{resource:.*}",
         web::get().to(controllers::df::get),
     )
+    .route(
+        "/{namespace}/{repo_name}/sql",
+        web::get().to(controllers::df::sql),
+    )
 
     // .route(
     //     "/{namespace}/{repo_name}/commits/{commit_id}/entries",
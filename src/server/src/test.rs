@@ -61,9 +61,7 @@ where
 
 pub fn request(sync_dir: &Path, uri: &str) -> actix_web::HttpRequest {
     actix_web::test::TestRequest::with_uri(uri)
-        .app_data(OxenAppData {
-            path: sync_dir.to_path_buf(),
-        })
+        .app_data(OxenAppData::new(sync_dir.to_path_buf()))
         .to_http_request()
 }
 
@@ -73,9 +71,7 @@ pub fn namespace_request(
     repo_namespace: impl Into<Cow<'static, str>>,
 ) -> actix_web::HttpRequest {
     actix_web::test::TestRequest::with_uri(uri)
-        .app_data(OxenAppData {
-            path: sync_dir.to_path_buf(),
-        })
+        .app_data(OxenAppData::new(sync_dir.to_path_buf()))
         .param("namespace", repo_namespace)
         .to_http_request()
 }
@@ -87,9 +83,7 @@ pub fn repo_request(
     repo_name: impl Into<Cow<'static, str>>,
 ) -> actix_web::HttpRequest {
     actix_web::test::TestRequest::with_uri(uri)
-        .app_data(OxenAppData {
-            path: sync_dir.to_path_buf(),
-        })
+        .app_data(OxenAppData::new(sync_dir.to_path_buf()))
         .param("namespace", repo_namespace)
         .param("repo_name", repo_name)
         .to_http_request()
@@ -104,9 +98,7 @@ pub fn repo_request_with_param(
     val: impl Into<Cow<'static, str>>,
 ) -> actix_web::HttpRequest {
     actix_web::test::TestRequest::with_uri(uri)
-        .app_data(OxenAppData {
-            path: sync_dir.to_path_buf(),
-        })
+        .app_data(OxenAppData::new(sync_dir.to_path_buf()))
         .param("namespace", repo_namespace)
         .param("repo_name", repo_name)
         .param(key, val)
@@ -120,9 +112,7 @@ pub fn request_with_param(
     val: impl Into<Cow<'static, str>>,
 ) -> actix_web::HttpRequest {
     actix_web::test::TestRequest::with_uri(uri)
-        .app_data(OxenAppData {
-            path: sync_dir.to_path_buf(),
-        })
+        .app_data(OxenAppData::new(sync_dir.to_path_buf()))
         .param(key, val)
         .to_http_request()
 }
@@ -133,9 +123,7 @@ pub fn request_with_json(
     data: impl Serialize,
 ) -> actix_web::HttpRequest {
     actix_web::test::TestRequest::with_uri(uri)
-        .app_data(OxenAppData {
-            path: sync_dir.to_path_buf(),
-        })
+        .app_data(OxenAppData::new(sync_dir.to_path_buf()))
         .set_json(data)
         .to_http_request()
 }
@@ -148,9 +136,7 @@ pub fn request_with_payload_and_entry(
     data: impl Into<actix_web::web::Bytes>,
 ) -> (actix_web::HttpRequest, actix_web::dev::Payload) {
     actix_web::test::TestRequest::with_uri(uri)
-        .app_data(OxenAppData {
-            path: sync_dir.to_path_buf(),
-        })
+        .app_data(OxenAppData::new(sync_dir.to_path_buf()))
         .param("filename", filename)
         .param("hash", hash)
         .set_payload(data)
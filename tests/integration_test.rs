@@ -2,10 +2,10 @@ use liboxen::api;
 use liboxen::command;
 use liboxen::constants;
 use liboxen::df::tabular;
-use liboxen::df::DFOpts;
+use liboxen::df::{DFDiffOpts, DFOpts};
 use liboxen::error::OxenError;
-use liboxen::index::CommitDirReader;
-use liboxen::model::StagedEntryStatus;
+use liboxen::index::{ChunkStore, CommitDirReader};
+use liboxen::model::{LocalRepository, SparseSpec, StagedEntryStatus, Tag};
 use liboxen::opts::RestoreOpts;
 use liboxen::test;
 use liboxen::util;
@@ -95,6 +95,60 @@ fn test_command_add_one_file_top_level() -> Result<(), OxenError> {
     })
 }
 
+#[test]
+fn test_command_status_respects_oxenignore() -> Result<(), OxenError> {
+    test::run_training_data_repo_test_no_commits(|repo| {
+        let ignore_file = repo.path.join(constants::OXEN_IGNORE_FILE);
+        test::write_txt_file_to_path(&ignore_file, "large_files/\nREADME.md\n")?;
+
+        let repo_status = command::status(&repo)?;
+        repo_status.print_stdout();
+
+        // labels.txt
+        // .oxenignore
+        assert_eq!(repo_status.untracked_files.len(), 2);
+        // train/
+        // test/
+        // nlp/
+        // annotations/
+        assert_eq!(repo_status.untracked_dirs.len(), 4);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_command_add_dir_skips_oxenignore_unless_forced() -> Result<(), OxenError> {
+    test::run_training_data_repo_test_no_commits(|repo| {
+        let train_dir = repo.path.join("train");
+        let ignore_file = train_dir.join(constants::OXEN_IGNORE_FILE);
+        test::write_txt_file_to_path(&ignore_file, "cat_*.jpg\n")?;
+
+        command::add(&repo, &train_dir)?;
+
+        let repo_status = command::status(&repo)?;
+        repo_status.print_stdout();
+        // dog_1.jpg, dog_2.jpg, dog_3.jpg, hidden files like the .oxenignore are skipped when adding a dir
+        assert_eq!(repo_status.added_files.len(), 3);
+        // README.md, labels.txt and train/.oxenignore
+        assert_eq!(repo_status.untracked_files.len(), 3);
+
+        // Adding an ignored file directly is an error
+        let cat_file = train_dir.join("cat_1.jpg");
+        assert!(command::add(&repo, &cat_file).is_err());
+
+        // Unless we force it
+        command::add_force(&repo, &cat_file)?;
+        let repo_status = command::status(&repo)?;
+        assert_eq!(repo_status.added_files.len(), 4);
+        assert!(repo_status
+            .added_files
+            .contains_key(&PathBuf::from("train").join("cat_1.jpg")));
+
+        Ok(())
+    })
+}
+
 #[test]
 fn test_command_status_shows_intermediate_directory_if_file_added() -> Result<(), OxenError> {
     test::run_training_data_repo_test_no_commits(|repo| {
@@ -1575,6 +1629,74 @@ async fn test_pull_multiple_data_frames_multiple_schemas() -> Result<(), OxenErr
     .await
 }
 
+// Large files are stored as chunks, and an edit should only move the chunks that changed
+#[tokio::test]
+async fn test_command_push_pull_large_file_in_chunks() -> Result<(), OxenError> {
+    test::run_empty_local_repo_test_async(|mut repo| async move {
+        // Write a file that is a few chunks long
+        let num_bytes = (constants::AVG_CHUNK_SIZE * 3) as usize;
+        let mut data: Vec<u8> = (0..num_bytes).map(|_| rand::random::<u8>()).collect();
+        let filename = "model.bin";
+        let file_path = repo.path.join(filename);
+        std::fs::write(&file_path, &data)?;
+
+        command::add(&repo, &file_path)?;
+        command::commit(&repo, "Adding model")?.unwrap();
+
+        // Only the chunks are kept in the versions dir, not a full copy
+        let commit = command::head_commit(&repo)?;
+        let reader = CommitDirReader::new(&repo, &commit)?;
+        let entry = reader.get_entry(Path::new(filename))?.unwrap();
+        let store = ChunkStore::new(&repo);
+        assert!(!util::fs::version_path(&repo, &entry).exists());
+        let og_manifest = store.get_manifest(&entry.hash)?.unwrap();
+        assert!(og_manifest.chunks.len() > 1);
+
+        // Set the proper remote
+        let remote = test::repo_remote_url_from(&repo.dirname());
+        command::add_remote(&mut repo, constants::DEFAULT_REMOTE_NAME, &remote)?;
+        let remote_repo = test::create_remote_repo(&repo).await?;
+        command::push(&repo).await?;
+
+        test::run_empty_dir_test_async(|new_repo_dir| async move {
+            let shallow = false;
+            let cloned_repo =
+                command::clone(&remote_repo.remote.url, &new_repo_dir, shallow).await?;
+            let cloned_path = cloned_repo.path.join(filename);
+            assert_eq!(std::fs::read(&cloned_path)?, data);
+
+            // Edit a few bytes in the middle and push again
+            let middle = num_bytes / 2;
+            for byte in data[middle..middle + 32].iter_mut() {
+                *byte = byte.wrapping_add(1);
+            }
+            std::fs::write(&file_path, &data)?;
+            command::add(&repo, &file_path)?;
+            command::commit(&repo, "Tweak the model")?.unwrap();
+            command::push(&repo).await?;
+
+            // Most of the chunks are shared with the first version
+            let commit = command::head_commit(&repo)?;
+            let reader = CommitDirReader::new(&repo, &commit)?;
+            let entry = reader.get_entry(Path::new(filename))?.unwrap();
+            let new_manifest = store.get_manifest(&entry.hash)?.unwrap();
+            let cloned_store = ChunkStore::new(&cloned_repo);
+            let missing = cloned_store.missing_chunks(&new_manifest.chunk_hashes())?;
+            assert!(missing.len() < new_manifest.chunks.len());
+
+            command::pull(&cloned_repo).await?;
+            assert_eq!(std::fs::read(&cloned_path)?, data);
+            assert!(command::status(&cloned_repo)?.is_clean());
+
+            api::remote::repositories::delete(&remote_repo).await?;
+
+            Ok(new_repo_dir)
+        })
+        .await
+    })
+    .await
+}
+
 // Make sure we can push again after pulling on the other side, then pull again
 #[tokio::test]
 async fn test_push_pull_push_pull_on_branch() -> Result<(), OxenError> {
@@ -1752,6 +1874,266 @@ async fn test_push_branch_with_with_no_new_commits() -> Result<(), OxenError> {
     .await
 }
 
+#[tokio::test]
+async fn test_push_skips_versions_remote_has() -> Result<(), OxenError> {
+    test::run_training_data_repo_test_no_commits_async(|mut repo| async move {
+        let train_path = repo.path.join("train");
+        let og_num_files = util::fs::rcount_files_in_dir(&train_path);
+        command::add(&repo, &train_path)?;
+        let commit = command::commit(&repo, "Adding train dir")?.unwrap();
+
+        // Set the proper remote
+        let remote = test::repo_remote_url_from(&repo.dirname());
+        command::add_remote(&mut repo, constants::DEFAULT_REMOTE_NAME, &remote)?;
+
+        // Create Remote
+        let remote_repo = test::create_remote_repo(&repo).await?;
+
+        // Push it
+        command::push(&repo).await?;
+
+        // A push that is resumed after this point has nothing left to send
+        let entries = api::local::entries::list_all(&repo, &commit)?;
+        let missing = api::remote::entries::list_missing_versions(&remote_repo, &entries).await?;
+        assert!(missing.is_empty());
+
+        // Copies of the same contents in a new commit still get pushed
+        let copy_path = repo.path.join("train_copy");
+        util::fs::copy_dir_all(&train_path, &copy_path)?;
+        command::add(&repo, &copy_path)?;
+        command::commit(&repo, "Adding a copy of the train dir")?.unwrap();
+        command::push(&repo).await?;
+
+        test::run_empty_dir_test_async(|new_repo_dir| async move {
+            let shallow = true;
+            let cloned_repo =
+                command::clone(&remote_repo.remote.url, &new_repo_dir, shallow).await?;
+            command::pull(&cloned_repo).await?;
+
+            let cloned_copy_dir = cloned_repo.path.join("train_copy");
+            assert_eq!(
+                og_num_files,
+                util::fs::rcount_files_in_dir(&cloned_copy_dir)
+            );
+
+            api::remote::repositories::delete(&remote_repo).await?;
+
+            Ok(new_repo_dir)
+        })
+        .await
+    })
+    .await
+}
+
+#[tokio::test]
+async fn test_sparse_clone_then_pull_paths() -> Result<(), OxenError> {
+    test::run_training_data_repo_test_fully_committed_async(|mut repo| async move {
+        let og_num_train_files = util::fs::rcount_files_in_dir(&repo.path.join("train"));
+
+        // Set the proper remote
+        let remote = test::repo_remote_url_from(&repo.dirname());
+        command::add_remote(&mut repo, constants::DEFAULT_REMOTE_NAME, &remote)?;
+
+        // Create Remote
+        let remote_repo = test::create_remote_repo(&repo).await?;
+
+        // Push it
+        command::push(&repo).await?;
+
+        test::run_empty_dir_test_async(|new_repo_dir| async move {
+            let spec = SparseSpec::new(
+                vec![String::from("annotations/")],
+                vec![String::from("annotations/test/")],
+            );
+            let shallow = false;
+            let mut cloned_repo = command::clone_sparse(
+                &remote_repo.remote.url,
+                &new_repo_dir,
+                shallow,
+                spec.clone(),
+            )
+            .await?;
+
+            // Only the matching entries are pulled
+            assert!(cloned_repo
+                .path
+                .join("annotations")
+                .join("train")
+                .join("bounding_box.csv")
+                .exists());
+            assert!(!cloned_repo.path.join("annotations").join("test").exists());
+            assert!(!cloned_repo.path.join("train").exists());
+
+            // The rest are known but absent, not removed
+            let status = command::status(&cloned_repo)?;
+            assert!(status.is_clean());
+
+            // The spec is saved in the config
+            let saved_repo = LocalRepository::from_dir(&cloned_repo.path)?;
+            assert_eq!(saved_repo.sparse, Some(spec));
+
+            // Committing keeps the absent entries
+            let head = command::head_commit(&cloned_repo)?;
+            let og_num_entries = api::local::entries::count_for_commit(&cloned_repo, &head)?;
+            let new_file = cloned_repo.path.join("annotations").join("new.txt");
+            util::fs::write_to_path(&new_file, "new annotations")?;
+            command::add(&cloned_repo, &new_file)?;
+            let commit = command::commit(&cloned_repo, "Adding new annotations")?.unwrap();
+            let num_entries = api::local::entries::count_for_commit(&cloned_repo, &commit)?;
+            assert_eq!(num_entries, og_num_entries + 1);
+
+            // Widen the sparse tree to pull the train dir as well
+            let paths = vec![String::from("train/")];
+            command::pull_paths(
+                &mut cloned_repo,
+                constants::DEFAULT_REMOTE_NAME,
+                constants::DEFAULT_BRANCH_NAME,
+                &paths,
+            )
+            .await?;
+            let cloned_num_train_files =
+                util::fs::rcount_files_in_dir(&cloned_repo.path.join("train"));
+            assert_eq!(og_num_train_files, cloned_num_train_files);
+            assert!(!cloned_repo.path.join("annotations").join("test").exists());
+
+            let saved_repo = LocalRepository::from_dir(&cloned_repo.path)?;
+            assert_eq!(
+                saved_repo.sparse.unwrap().include,
+                vec![String::from("annotations/"), String::from("train/")]
+            );
+
+            api::remote::repositories::delete(&remote_repo).await?;
+
+            Ok(new_repo_dir)
+        })
+        .await
+    })
+    .await
+}
+
+#[tokio::test]
+async fn test_fetch_records_remote_tracking_ref() -> Result<(), OxenError> {
+    test::run_training_data_repo_test_fully_committed_async(|mut repo| async move {
+        // Set the proper remote
+        let remote = test::repo_remote_url_from(&repo.dirname());
+        command::add_remote(&mut repo, constants::DEFAULT_REMOTE_NAME, &remote)?;
+
+        // Create Remote
+        let remote_repo = test::create_remote_repo(&repo).await?;
+
+        // Push it
+        command::push(&repo).await?;
+
+        test::run_empty_dir_test_async(|new_repo_dir| async move {
+            let shallow = false;
+            let cloned_repo =
+                command::clone(&remote_repo.remote.url, &new_repo_dir, shallow).await?;
+            let og_head = command::head_commit(&cloned_repo)?;
+
+            // Add a commit on the original side and push it
+            let new_file = repo.path.join("fetch_me.txt");
+            util::fs::write_to_path(&new_file, "fetch me")?;
+            command::add(&repo, &new_file)?;
+            let new_commit = command::commit(&repo, "Adding fetch_me.txt")?.unwrap();
+            command::push(&repo).await?;
+
+            let fetched =
+                command::fetch(&cloned_repo, constants::DEFAULT_REMOTE_NAME, None).await?;
+            assert_eq!(fetched.len(), 1);
+            assert_eq!(fetched[0].name, "origin/main");
+            assert_eq!(fetched[0].commit_id, new_commit.id);
+
+            // Nothing moved locally
+            assert_eq!(command::head_commit(&cloned_repo)?.id, og_head.id);
+            assert!(!cloned_repo.path.join("fetch_me.txt").exists());
+            assert!(command::status(&cloned_repo)?.is_clean());
+
+            // But the history is there to look at
+            let history = command::log_commit_or_branch_history(&cloned_repo, "origin/main")?;
+            assert_eq!(history.first().unwrap().id, new_commit.id);
+            let diff = command::diff_commits(
+                &cloned_repo,
+                "main",
+                "origin/main",
+                None,
+                &DFDiffOpts::default(),
+            )?;
+            assert_eq!(diff.added.len(), 1);
+            assert_eq!(diff.added[0].path, PathBuf::from("fetch_me.txt"));
+
+            // Fetched commits are reachable until they are merged
+            command::gc(&cloned_repo, false)?;
+            let history = command::log_commit_or_branch_history(&cloned_repo, "origin/main")?;
+            assert_eq!(history.first().unwrap().id, new_commit.id);
+
+            api::remote::repositories::delete(&remote_repo).await?;
+
+            Ok(new_repo_dir)
+        })
+        .await
+    })
+    .await
+}
+
+#[tokio::test]
+async fn test_push_rejects_non_fast_forward_unless_forced() -> Result<(), OxenError> {
+    test::run_training_data_repo_test_fully_committed_async(|mut repo| async move {
+        // Set the proper remote
+        let remote = test::repo_remote_url_from(&repo.dirname());
+        command::add_remote(&mut repo, constants::DEFAULT_REMOTE_NAME, &remote)?;
+
+        // Create Remote
+        let remote_repo = test::create_remote_repo(&repo).await?;
+
+        // Push it
+        command::push(&repo).await?;
+
+        test::run_empty_dir_test_async(|new_repo_dir| async move {
+            let shallow = false;
+            let cloned_repo =
+                command::clone(&remote_repo.remote.url, &new_repo_dir, shallow).await?;
+
+            // Someone else pushes first
+            let their_file = cloned_repo.path.join("theirs.txt");
+            util::fs::write_to_path(&their_file, "theirs")?;
+            command::add(&cloned_repo, &their_file)?;
+            command::commit(&cloned_repo, "Adding theirs.txt")?;
+            command::push(&cloned_repo).await?;
+
+            // Our push would drop their commit
+            let our_file = repo.path.join("ours.txt");
+            util::fs::write_to_path(&our_file, "ours")?;
+            command::add(&repo, &our_file)?;
+            let our_commit = command::commit(&repo, "Adding ours.txt")?.unwrap();
+            let result = command::push(&repo).await;
+            assert!(result.is_err());
+            assert!(result
+                .unwrap_err()
+                .to_string()
+                .contains("Fetch and merge first"));
+
+            // Forcing it overwrites the remote branch
+            command::force_push_remote_branch(
+                &repo,
+                constants::DEFAULT_REMOTE_NAME,
+                constants::DEFAULT_BRANCH_NAME,
+            )
+            .await?;
+            let remote_branch =
+                api::remote::branches::get_by_name(&remote_repo, constants::DEFAULT_BRANCH_NAME)
+                    .await?
+                    .unwrap();
+            assert_eq!(remote_branch.commit_id, our_commit.id);
+
+            api::remote::repositories::delete(&remote_repo).await?;
+
+            Ok(new_repo_dir)
+        })
+        .await
+    })
+    .await
+}
+
 #[tokio::test]
 async fn test_delete_remote_branch() -> Result<(), OxenError> {
     test::run_training_data_repo_test_fully_committed_async(|mut repo| async move {
@@ -1785,6 +2167,181 @@ async fn test_delete_remote_branch() -> Result<(), OxenError> {
     .await
 }
 
+#[tokio::test]
+async fn test_push_all_remotes() -> Result<(), OxenError> {
+    test::run_training_data_repo_test_fully_committed_async(|mut repo| async move {
+        let remote = test::repo_remote_url_from(&repo.dirname());
+        command::add_remote(&mut repo, constants::DEFAULT_REMOTE_NAME, &remote)?;
+        let remote_repo = test::create_remote_repo(&repo).await?;
+
+        let backup_name = format!("{}-backup", repo.dirname());
+        let backup = test::repo_remote_url_from(&backup_name);
+        command::add_remote(&mut repo, "backup", &backup)?;
+        let backup_repo = command::create_remote(
+            &repo,
+            constants::DEFAULT_NAMESPACE,
+            &backup_name,
+            test::test_host(),
+        )
+        .await?;
+
+        let pushed = command::push_all_remotes(&repo, constants::DEFAULT_BRANCH_NAME).await?;
+        assert_eq!(pushed.len(), 2);
+
+        let head = command::head_commit(&repo)?;
+        for remote_repo in [&remote_repo, &backup_repo] {
+            let branch =
+                api::remote::branches::get_by_name(remote_repo, constants::DEFAULT_BRANCH_NAME)
+                    .await?
+                    .unwrap();
+            assert_eq!(branch.commit_id, head.id);
+        }
+
+        // A remote that cannot be pushed to does not stop the others
+        let new_file = repo.path.join("all_remotes.txt");
+        util::fs::write_to_path(&new_file, "all remotes")?;
+        command::add(&repo, &new_file)?;
+        let new_commit = command::commit(&repo, "Adding all_remotes.txt")?.unwrap();
+        api::remote::repositories::delete(&backup_repo).await?;
+
+        let result = command::push_all_remotes(&repo, constants::DEFAULT_BRANCH_NAME).await;
+        assert!(result.unwrap_err().to_string().contains("backup"));
+        let branch =
+            api::remote::branches::get_by_name(&remote_repo, constants::DEFAULT_BRANCH_NAME)
+                .await?
+                .unwrap();
+        assert_eq!(branch.commit_id, new_commit.id);
+
+        api::remote::repositories::delete(&remote_repo).await?;
+
+        Ok(())
+    })
+    .await
+}
+
+#[tokio::test]
+async fn test_mirror_keeps_branches_and_commit_ids() -> Result<(), OxenError> {
+    test::run_training_data_repo_test_fully_committed_async(|mut repo| async move {
+        let remote = test::repo_remote_url_from(&repo.dirname());
+        command::add_remote(&mut repo, constants::DEFAULT_REMOTE_NAME, &remote)?;
+        let remote_repo = test::create_remote_repo(&repo).await?;
+        command::push(&repo).await?;
+
+        let branch_name = "feature/mirror";
+        command::create_checkout_branch(&repo, branch_name)?;
+        let new_file = repo.path.join("mirror_me.txt");
+        util::fs::write_to_path(&new_file, "mirror me")?;
+        command::add(&repo, &new_file)?;
+        let branch_commit = command::commit(&repo, "Adding mirror_me.txt")?.unwrap();
+        command::create_tag(&repo, "v0.1", None, "Mirrored release")?;
+        command::push_remote_branch(&repo, constants::DEFAULT_REMOTE_NAME, branch_name).await?;
+
+        test::run_empty_dir_test_async(|mirror_dir| async move {
+            let branches = command::mirror(&remote_repo.remote.url, &mirror_dir).await?;
+            assert_eq!(branches.len(), 2);
+
+            let mirror = LocalRepository::from_dir(&mirror_dir.join(&remote_repo.name))?;
+            let mirrored = api::local::branches::get_by_name(&mirror, branch_name)?.unwrap();
+            assert_eq!(mirrored.commit_id, branch_commit.id);
+
+            // The versions of the whole history are there, without a working directory
+            let history = command::log_commit_or_branch_history(&mirror, branch_name)?;
+            let og_history = command::log_commit_or_branch_history(&repo, branch_name)?;
+            assert_eq!(history.len(), og_history.len());
+            let store = ChunkStore::new(&mirror);
+            for commit in history {
+                for entry in CommitDirReader::new(&mirror, &commit)?.list_entries()? {
+                    assert!(store.has_version(&entry));
+                }
+            }
+            assert!(!mirror.path.join("mirror_me.txt").exists());
+
+            // Updates and deleted branches come through on the next run
+            command::checkout(&repo, constants::DEFAULT_BRANCH_NAME)?;
+            let new_file = repo.path.join("later.txt");
+            util::fs::write_to_path(&new_file, "later")?;
+            command::add(&repo, &new_file)?;
+            let later_commit = command::commit(&repo, "Adding later.txt")?.unwrap();
+            command::push(&repo).await?;
+            api::remote::branches::delete(&remote_repo, branch_name).await?;
+
+            let branches = command::mirror(&remote_repo.remote.url, &mirror_dir).await?;
+            assert_eq!(branches.len(), 1);
+            let main = api::local::branches::get_by_name(&mirror, constants::DEFAULT_BRANCH_NAME)?
+                .unwrap();
+            assert_eq!(main.commit_id, later_commit.id);
+            assert!(api::local::branches::get_by_name(&mirror, branch_name)?.is_none());
+
+            // Tags keep their commit even when no branch has it anymore
+            let tag = api::local::tags::get_by_name(&mirror, "v0.1")?.unwrap();
+            assert_eq!(tag.commit_id, branch_commit.id);
+
+            api::remote::repositories::delete(&remote_repo).await?;
+
+            Ok(mirror_dir)
+        })
+        .await
+    })
+    .await
+}
+
+#[tokio::test]
+async fn test_push_pull_tags() -> Result<(), OxenError> {
+    test::run_training_data_repo_test_fully_committed_async(|mut repo| async move {
+        let remote = test::repo_remote_url_from(&repo.dirname());
+        command::add_remote(&mut repo, constants::DEFAULT_REMOTE_NAME, &remote)?;
+        let remote_repo = test::create_remote_repo(&repo).await?;
+
+        let first = command::create_tag(&repo, "v1.0", None, "First release")?;
+        command::push(&repo).await?;
+        let remote_tags = api::remote::tags::list(&remote_repo).await?;
+        assert_eq!(remote_tags, vec![first.clone()]);
+
+        // A tag on a commit the remote does not have yet goes up with the branch
+        let new_file = repo.path.join("v1.1.txt");
+        util::fs::write_to_path(&new_file, "v1.1")?;
+        command::add(&repo, &new_file)?;
+        command::commit(&repo, "Adding v1.1.txt")?.unwrap();
+        let second = command::create_tag(&repo, "v1.1", None, "")?;
+        let result =
+            command::push_remote_branch(&repo, constants::DEFAULT_REMOTE_NAME, "v1.1").await;
+        assert!(result.is_err());
+        command::push(&repo).await?;
+        let remote_tag = api::remote::tags::get_by_name(&remote_repo, "v1.1").await?;
+        assert_eq!(remote_tag, Some(second.clone()));
+
+        // Tags cannot be moved on the remote
+        let moved = Tag {
+            commit_id: second.commit_id.clone(),
+            ..first.clone()
+        };
+        assert!(api::remote::tags::create(&remote_repo, &moved)
+            .await
+            .is_err());
+
+        test::run_empty_dir_test_async(|new_repo_dir| async move {
+            let cloned_repo = command::clone(&remote_repo.remote.url, &new_repo_dir, false).await?;
+            assert_eq!(command::list_tags(&cloned_repo)?.len(), 2);
+
+            command::checkout(&cloned_repo, "v1.0")?;
+            assert_eq!(command::head_commit(&cloned_repo)?.id, first.commit_id);
+            assert!(!cloned_repo.path.join("v1.1.txt").exists());
+
+            // Only an admin force delete removes a tag from the remote
+            command::delete_remote_tag(&cloned_repo, constants::DEFAULT_REMOTE_NAME, "v1.0")
+                .await?;
+            let remote_tags = api::remote::tags::list(&remote_repo).await?;
+            assert_eq!(remote_tags, vec![second]);
+
+            api::remote::repositories::delete(&remote_repo).await?;
+
+            Ok(new_repo_dir)
+        })
+        .await
+    })
+    .await
+}
+
 #[tokio::test]
 async fn test_should_not_push_branch_that_does_not_exist() -> Result<(), OxenError> {
     test::run_training_data_repo_test_fully_committed_async(|mut repo| async move {
@@ -2571,101 +3128,129 @@ fn test_stage_and_commit_schema() -> Result<(), OxenError> {
 }
 
 #[test]
-fn test_command_merge_dataframe_conflict_both_added_rows_checkout_theirs() -> Result<(), OxenError>
-{
+fn test_command_merge_dataframe_both_added_rows_merges_rows() -> Result<(), OxenError> {
     test::run_training_data_repo_test_fully_committed(|repo| {
         let og_branch = command::current_branch(&repo)?.unwrap();
 
-        // Add a more rows on this branch
-        let branch_name = "ox-add-rows";
-        command::create_checkout_branch(&repo, branch_name)?;
-
         let bbox_filename = Path::new("annotations")
             .join("train")
             .join("bounding_box.csv");
         let bbox_file = repo.path.join(&bbox_filename);
-        let bbox_file =
-            test::append_line_txt_file(bbox_file, "train/cat_3.jpg,cat,41.0,31.5,410,427")?;
-        let their_branch_contents = util::fs::read_from_path(&bbox_file)?;
-        let their_df = tabular::read_df(&bbox_file, DFOpts::empty())?;
-        println!("their df {their_df}");
 
+        // Add a more rows on this branch
+        let branch_name = "ox-add-rows";
+        command::create_checkout_branch(&repo, branch_name)?;
+
+        // Add in a line in this branch
+        let row_from_branch = "train/cat_3.jpg,cat,41.0,31.5,410,427";
+        let bbox_file = test::append_line_txt_file(bbox_file, row_from_branch)?;
+
+        // Add the changes
         command::add(&repo, &bbox_file)?;
         command::commit(&repo, "Adding new annotation as an Ox on a branch.")?;
 
         // Add a more rows on the main branch
         command::checkout(&repo, og_branch.name)?;
 
-        let bbox_file =
-            test::append_line_txt_file(bbox_file, "train/dog_4.jpg,dog,52.0,62.5,256,429")?;
+        let row_from_main = "train/dog_4.jpg,dog,52.0,62.5,256,429";
+        let bbox_file = test::append_line_txt_file(bbox_file, row_from_main)?;
 
         command::add(&repo, &bbox_file)?;
         command::commit(&repo, "Adding new annotation on main branch")?;
 
-        // Try to merge in the changes
-        command::merge(&repo, branch_name)?;
+        // Rows added on different sides merge without a conflict
+        let commit = command::merge(&repo, branch_name)?;
+        assert!(commit.is_some());
 
-        // We should have a conflict....
         let status = command::status(&repo)?;
-        assert_eq!(status.merge_conflicts.len(), 1);
-
-        // Run command::checkout_theirs() and make sure their changes get kept
-        command::checkout_theirs(&repo, &bbox_filename)?;
-        let restored_df = tabular::read_df(&bbox_file, DFOpts::empty())?;
-        println!("restored df {restored_df}");
-
-        let file_contents = util::fs::read_from_path(&bbox_file)?;
+        assert_eq!(status.merge_conflicts.len(), 0);
 
-        assert_eq!(file_contents, their_branch_contents);
+        // 6 original rows, plus one from each branch, with main's row before the branch's
+        let df = tabular::read_df(&bbox_file, DFOpts::empty())?;
+        assert_eq!(df.height(), 8);
+        let files: Vec<&str> = df
+            .column("file")
+            .unwrap()
+            .utf8()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+        assert_eq!(files[6], "train/dog_4.jpg");
+        assert_eq!(files[7], "train/cat_3.jpg");
 
         Ok(())
     })
 }
 
-#[test]
-fn test_command_merge_dataframe_conflict_both_added_rows_combine_uniq() -> Result<(), OxenError> {
-    test::run_training_data_repo_test_fully_committed(|repo| {
-        let og_branch = command::current_branch(&repo)?.unwrap();
+// Writes a csv with a unique id column, then edits the first row differently on a branch and on main
+fn populate_edited_row_merge_conflict(
+    repo: &LocalRepository,
+    branch_name: &str,
+) -> Result<(PathBuf, String), OxenError> {
+    let og_branch = command::current_branch(repo)?.unwrap();
+    let labels_file = repo.path.join("annotations").join("labels.csv");
+    test::write_txt_file_to_path(&labels_file, "id,label\n1,cat\n2,dog\n3,fish\n")?;
+    command::add(repo, &labels_file)?;
+    command::commit(repo, "Adding labels.csv")?;
 
-        let bbox_filename = Path::new("annotations")
-            .join("train")
-            .join("bounding_box.csv");
-        let bbox_file = repo.path.join(&bbox_filename);
+    command::create_checkout_branch(repo, branch_name)?;
+    let labels_file = test::modify_txt_file(labels_file, "id,label\n1,tiger\n2,dog\n3,fish\n")?;
+    let their_branch_contents = util::fs::read_from_path(&labels_file)?;
+    command::add(repo, &labels_file)?;
+    command::commit(repo, "Changing cat to tiger on a branch.")?;
 
-        // Add a more rows on this branch
-        let branch_name = "ox-add-rows";
-        command::create_checkout_branch(&repo, branch_name)?;
+    command::checkout(repo, og_branch.name)?;
+    let labels_file = test::modify_txt_file(labels_file, "id,label\n1,lion\n2,dog\n3,fish\n")?;
+    command::add(repo, &labels_file)?;
+    command::commit(repo, "Changing cat to lion on main branch")?;
 
-        // Add in a line in this branch
-        let row_from_branch = "train/cat_3.jpg,cat,41.0,31.5,410,427";
-        let bbox_file = test::append_line_txt_file(bbox_file, row_from_branch)?;
+    Ok((labels_file, their_branch_contents))
+}
 
-        // Add the changes
-        command::add(&repo, &bbox_file)?;
-        command::commit(&repo, "Adding new annotation as an Ox on a branch.")?;
+#[test]
+fn test_command_merge_dataframe_conflict_edited_row_checkout_theirs() -> Result<(), OxenError> {
+    test::run_training_data_repo_test_fully_committed(|repo| {
+        let branch_name = "ox-edit-row";
+        let (labels_file, their_branch_contents) =
+            populate_edited_row_merge_conflict(&repo, branch_name)?;
 
-        // Add a more rows on the main branch
-        command::checkout(&repo, og_branch.name)?;
+        // Try to merge in the changes
+        let commit = command::merge_with_primary_key(&repo, branch_name, "id")?;
+        assert!(commit.is_none());
 
-        let row_from_main = "train/dog_4.jpg,dog,52.0,62.5,256,429";
-        let bbox_file = test::append_line_txt_file(bbox_file, row_from_main)?;
+        // We should have a conflict on the one row both sides edited
+        let status = command::status(&repo)?;
+        assert_eq!(status.merge_conflicts.len(), 1);
+        assert_eq!(status.merge_conflicts[0].row_conflicts.len(), 1);
 
-        command::add(&repo, &bbox_file)?;
-        command::commit(&repo, "Adding new annotation on main branch")?;
+        // Run command::checkout_theirs() and make sure their changes get kept
+        let labels_filename = util::fs::path_relative_to_dir(&labels_file, &repo.path)?;
+        command::checkout_theirs(&repo, &labels_filename)?;
+        let file_contents = util::fs::read_from_path(&labels_file)?;
+        assert_eq!(file_contents, their_branch_contents);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_command_merge_dataframe_conflict_edited_row_combine_uniq() -> Result<(), OxenError> {
+    test::run_training_data_repo_test_fully_committed(|repo| {
+        let branch_name = "ox-edit-row";
+        let (labels_file, _) = populate_edited_row_merge_conflict(&repo, branch_name)?;
 
         // Try to merge in the changes
-        command::merge(&repo, branch_name)?;
+        command::merge_with_primary_key(&repo, branch_name, "id")?;
 
         // We should have a conflict....
         let status = command::status(&repo)?;
         assert_eq!(status.merge_conflicts.len(), 1);
 
-        // Run command::checkout_theirs() and make sure their changes get kept
-        command::checkout_combine(&repo, bbox_filename)?;
-        let df = tabular::read_df(&bbox_file, DFOpts::empty())?;
-
-        // This doesn't guarantee order, but let's make sure we have 7 annotations now
-        assert_eq!(df.height(), 8);
+        // Combining keeps both versions of the edited row
+        let labels_filename = util::fs::path_relative_to_dir(&labels_file, &repo.path)?;
+        command::checkout_combine(&repo, labels_filename)?;
+        let df = tabular::read_df(&labels_file, DFOpts::empty())?;
+        assert_eq!(df.height(), 4);
 
         Ok(())
     })
@@ -2738,7 +3323,7 @@ fn test_diff_tabular_add_col() -> Result<(), OxenError> {
         // Perform df transform
         command::df(bbox_file, opts)?;
 
-        let diff = command::diff(&repo, None, &bbox_filename);
+        let diff = command::diff(&repo, None, &bbox_filename, &DFDiffOpts::empty());
         assert!(diff.is_ok());
         let diff = diff.unwrap();
         assert_eq!(
@@ -2787,7 +3372,7 @@ fn test_diff_tabular_add_row() -> Result<(), OxenError> {
         // Perform df transform
         command::df(bbox_file, opts)?;
 
-        match command::diff(&repo, None, &bbox_filename) {
+        match command::diff(&repo, None, &bbox_filename, &DFDiffOpts::empty()) {
             Ok(diff) => {
                 println!("{diff}");
 
@@ -2816,6 +3401,32 @@ shape: (1, 6)
     })
 }
 
+#[test]
+fn test_diff_tabular_modified_row_with_primary_key() -> Result<(), OxenError> {
+    test::run_empty_local_repo_test(|repo| {
+        let filename = Path::new("labels.csv");
+        let path = repo.path.join(filename);
+        test::write_txt_file_to_path(&path, "id,label,score\n1,cat,0.5\n2,dog,0.7\n")?;
+        command::add(&repo, &path)?;
+        command::commit(&repo, "Adding labels")?;
+
+        test::modify_txt_file(&path, "id,label,score\n1,lion,0.5\n2,dog,0.7\n")?;
+
+        // Without a key the edit is a removed plus an added row
+        let diff = command::diff(&repo, None, filename, &DFDiffOpts::empty())?;
+        assert!(diff.contains("Added Rows"));
+        assert!(diff.contains("Removed Rows"));
+
+        let diff = command::diff(&repo, None, filename, &DFDiffOpts::from_primary_key("id"))?;
+        assert_eq!(
+            diff,
+            "Modified Rows\n\n1\n-  label: cat\n+  label: lion\n\n"
+        );
+
+        Ok(())
+    })
+}
+
 #[test]
 fn test_diff_tabular_remove_row() -> Result<(), OxenError> {
     test::run_training_data_repo_test_fully_committed(|repo| {
@@ -2835,7 +3446,7 @@ train/cat_2.jpg,cat,30.5,44.0,333,396
 ",
         )?;
 
-        match command::diff(&repo, None, &bbox_filename) {
+        match command::diff(&repo, None, &bbox_filename, &DFDiffOpts::empty()) {
             Ok(diff) => {
                 println!("{diff}");
 